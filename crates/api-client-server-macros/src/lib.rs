@@ -81,14 +81,12 @@ fn strip_api_handler_attr(attrs: &[Attribute]) -> Vec<Attribute> {
 
 /// Extract the inner type `T` from `MyAppResult<T>` (or any single-generic wrapper).
 fn extract_inner_type(ty: &Type) -> &Type {
-    if let Type::Path(type_path) = ty {
-        if let Some(segment) = type_path.path.segments.last() {
-            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
-                    return inner;
-                }
-            }
-        }
+    if let Type::Path(type_path) = ty
+        && let Some(segment) = type_path.path.segments.last()
+        && let syn::PathArguments::AngleBracketed(args) = &segment.arguments
+        && let Some(syn::GenericArgument::Type(inner)) = args.args.first()
+    {
+        return inner;
     }
     ty
 }
@@ -107,7 +105,7 @@ fn parse_handler(method: &ImplItemFn) -> Option<ParsedHandler> {
         .find(|a| a.path().is_ident("api_handler"))?;
 
     let meta = &api_attr.meta;
-    let args = ApiHandlerArgs::from_meta(&meta).expect("Failed to parse #[api_handler] arguments");
+    let args = ApiHandlerArgs::from_meta(meta).expect("Failed to parse #[api_handler] arguments");
 
     let fn_name = method.sig.ident.clone();
     let visibility = method.vis.clone();
@@ -115,20 +113,20 @@ fn parse_handler(method: &ImplItemFn) -> Option<ParsedHandler> {
     // Parse parameters (skip &self)
     let mut params = Vec::new();
     for arg in method.sig.inputs.iter().skip(1) {
-        if let FnArg::Typed(PatType { pat, ty, attrs, .. }) = arg {
-            if let Pat::Ident(pat_ident) = pat.as_ref() {
-                let kind = take_param_attr(attrs).unwrap_or_else(|| {
-                    panic!(
-                        "Parameter `{}` in `{}` must be annotated with #[body], #[path], or #[query]",
-                        pat_ident.ident, fn_name
-                    )
-                });
-                params.push(HandlerParam {
-                    name: pat_ident.ident.clone(),
-                    ty: ty.clone(),
-                    kind,
-                });
-            }
+        if let FnArg::Typed(PatType { pat, ty, attrs, .. }) = arg
+            && let Pat::Ident(pat_ident) = pat.as_ref()
+        {
+            let kind = take_param_attr(attrs).unwrap_or_else(|| {
+                panic!(
+                    "Parameter `{}` in `{}` must be annotated with #[body], #[path], or #[query]",
+                    pat_ident.ident, fn_name
+                )
+            });
+            params.push(HandlerParam {
+                name: pat_ident.ident.clone(),
+                ty: ty.clone(),
+                kind,
+            });
         }
     }
 
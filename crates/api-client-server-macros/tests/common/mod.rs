@@ -57,6 +57,7 @@ pub struct ListUsersResponse {
 
 // ── Result type (user-defined, must impl IntoResponse) ─────────────────
 
+#[allow(dead_code)]
 pub enum MyAppResult<T> {
     Ok(T),
     NotFound(String),
@@ -1,6 +1,6 @@
-use std::fmt::Display;
+use std::fmt;
 
-use chrono::NaiveDateTime;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
 use reqwasm::http::Request;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -95,7 +95,7 @@ pub enum Update<T> {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AlbumID(pub i32);
 
-impl Display for AlbumID {
+impl fmt::Display for AlbumID {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
@@ -107,10 +107,10 @@ impl From<i32> for AlbumID {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PhotoID(pub i32);
 
-impl Display for PhotoID {
+impl fmt::Display for PhotoID {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
@@ -122,6 +122,82 @@ impl From<i32> for PhotoID {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobID(pub i32);
+
+impl fmt::Display for JobID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<i32> for JobID {
+    fn from(id: i32) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayID(pub i32);
+
+impl fmt::Display for DisplayID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<i32> for DisplayID {
+    fn from(id: i32) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuggestionID(pub i32);
+
+impl fmt::Display for SuggestionID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<i32> for SuggestionID {
+    fn from(id: i32) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagID(pub i32);
+
+impl fmt::Display for TagID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<i32> for TagID {
+    fn from(id: i32) -> Self {
+        Self(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleID(pub i32);
+
+impl fmt::Display for ScheduleID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<i32> for ScheduleID {
+    fn from(id: i32) -> Self {
+        Self(id)
+    }
+}
+
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct UploadPhotoRequest {
     title: Option<String>,
@@ -131,25 +207,136 @@ pub struct UploadPhotoRequest {
     albums: Vec<AlbumID>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Longest title, artist or copyright a photo can have, in characters
+pub const MAX_PHOTO_FIELD_LEN: usize = 200;
+
+/// Longest notes a photo can have, in characters
+pub const MAX_PHOTO_NOTES_LEN: usize = 5000;
+
+/// Change a photo's details. Fields left out stay as they are. Text is trimmed, and setting it
+/// to nothing but whitespace removes it.
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct UpdatePhotoRequest {
     pub title: Option<Update<String>>,
     pub artist: Option<Update<String>>,
     pub copyright: Option<Update<String>>,
+    #[serde(default)]
+    pub notes: Option<Update<String>>,
     pub date_taken: Option<Update<NaiveDateTime>>,
     pub mat_preset: Option<String>,
 }
 
+/// What a bulk request does to each of its photos
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum BulkPhotoOperation {
+    /// Add to the end of each album. Photos already in an album keep their place.
+    AddToAlbums {
+        albums: Vec<AlbumID>,
+    },
+    RemoveFromAlbums {
+        albums: Vec<AlbumID>,
+    },
+    /// Move the photos to the trash
+    Delete,
+    SetMatPreset {
+        mat_preset: String,
+    },
+    /// Make the same change to every photo's details, e.g. set the artist
+    Update {
+        details: UpdatePhotoRequest,
+    },
+    /// Attaching a tag that doesn't exist yet creates it
+    AttachTags {
+        tags: Vec<String>,
+    },
+    DetachTags {
+        tags: Vec<String>,
+    },
+}
+
+/// Do the same thing to many photos at once. Either every change is made or, if the request
+/// can't be carried out, none of them are.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkPhotoRequest {
+    pub photos: Vec<PhotoID>,
+    pub operation: BulkPhotoOperation,
+}
+
+/// How a bulk request went for one of its photos
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkPhotoResult {
+    pub photo: PhotoID,
+    /// Why the photo was skipped. `None` when the operation was done.
+    pub error: Option<String>,
+}
+
+impl BulkPhotoResult {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateAlbumRequest {
     pub name: String,
     pub notes: Option<String>,
+    /// Make a virtual album instead of one photos are added to by hand
+    #[serde(default)]
+    pub virtual_album: Option<VirtualAlbum>,
+    /// Make a smart album holding every photo that matches the rule
+    #[serde(default)]
+    pub smart_rule: Option<SmartRule>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateAlbumRequest {
     pub name: Option<String>,
     pub notes: Option<Update<String>>,
+    /// Set to turn the album into a smart album or change its rule, remove to manage its
+    /// photos by hand again
+    #[serde(default)]
+    pub smart_rule: Option<Update<SmartRule>>,
+}
+
+/// New order for the photos of an album managed by hand
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReorderAlbumRequest {
+    /// These photos go first, in this order. Photos left out follow in their current order.
+    pub photos: Vec<PhotoID>,
+}
+
+/// Either end of an album
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlbumEnd {
+    Top,
+    Bottom,
+}
+
+/// Move one photo to the start or end of its album
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveAlbumPhotoRequest {
+    pub to: AlbumEnd,
+}
+
+/// What to sort the photos of an album by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlbumPhotoSort {
+    /// Oldest first. Photos without a date go last.
+    DateTaken,
+    /// A to Z, ignoring case. Untitled photos go last.
+    Title,
+}
+
+/// Put an album's photos in order once. Photos added later still go at the end.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SortAlbumRequest {
+    pub by: AlbumPhotoSort,
+    /// Newest or Z first. Photos without a value still go last.
+    #[serde(default)]
+    pub descending: bool,
 }
 
 /// Response struct for an album request
@@ -159,25 +346,783 @@ pub struct Album {
     pub name: String,
     pub notes: Option<String>,
     pub photos: Vec<PhotoID>,
+    /// Set for albums whose photos are picked by date rather than added by hand
+    pub virtual_album: Option<VirtualAlbum>,
+    /// Set for smart albums, which hold every photo matching the rule
+    pub smart_rule: Option<SmartRule>,
+}
+
+impl Album {
+    /// Zip of this album's originals, relative to the server root
+    pub fn export_url(&self) -> String {
+        format!("/api/albums/{}/export", self.id)
+    }
+}
+
+/// Whether a photo is wider or taller as displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhotoOrientation {
+    Landscape,
+    Portrait,
+    Square,
+}
+
+impl PhotoOrientation {
+    pub const ALL: &[PhotoOrientation] = &[Self::Landscape, Self::Portrait, Self::Square];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Landscape => "Landscape",
+            Self::Portrait => "Portrait",
+            Self::Square => "Square",
+        }
+    }
+}
+
+/// Saved filter for a smart album. A photo has to match every condition that is set, so the
+/// default rule matches the whole library.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmartRule {
+    /// Part of the artist's name, ignoring case
+    pub artist: Option<String>,
+    /// Taken on or after this date
+    pub taken_after: Option<NaiveDate>,
+    /// Taken on or before this date
+    pub taken_before: Option<NaiveDate>,
+    pub orientation: Option<PhotoOrientation>,
+    pub mat_preset: Option<String>,
+    /// Part of the camera make or model, ignoring case
+    pub camera: Option<String>,
+    /// Added to the library within this many days
+    pub added_within_days: Option<u32>,
+    /// Tags the photo must all have, ignoring case
+    pub tags: Vec<String>,
+}
+
+impl SmartRule {
+    /// Whether `photo` meets every condition of the rule. `now` is in UTC, like `added_at`.
+    pub fn matches(&self, photo: &Photo, now: NaiveDateTime) -> bool {
+        let contains = |haystack: Option<String>, needle: &str| {
+            haystack.is_some_and(|h| h.to_lowercase().contains(&needle.to_lowercase()))
+        };
+
+        if let Some(artist) = &self.artist
+            && !contains(photo.artist.clone(), artist)
+        {
+            return false;
+        }
+        if let Some(camera) = &self.camera
+            && !contains(photo.exif.camera(), camera)
+        {
+            return false;
+        }
+        if let Some(after) = self.taken_after
+            && photo.date_taken.is_none_or(|t| t.date() < after)
+        {
+            return false;
+        }
+        if let Some(before) = self.taken_before
+            && photo.date_taken.is_none_or(|t| t.date() > before)
+        {
+            return false;
+        }
+        if let Some(orientation) = self.orientation
+            && photo.orientation() != Some(orientation)
+        {
+            return false;
+        }
+        if let Some(preset) = &self.mat_preset
+            && photo.mat_preset != *preset
+        {
+            return false;
+        }
+        if let Some(days) = self.added_within_days
+            && photo.added_at < now - Duration::days(days as i64)
+        {
+            return false;
+        }
+        if !TagMatch::All.matches(&self.tags, &photo.tags) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Photos within this many days either side of today's date count as "this week"
+const THIS_WEEK_DAYS: i64 = 3;
+
+/// An album whose photos are picked by when they were taken, worked out again every time the
+/// album is read. Photos without a `date_taken` are never included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum VirtualAlbum {
+    /// Taken on today's date in an earlier year
+    OnThisDay,
+    /// Taken within a few days of today's date `years_ago` years back, or in any earlier year
+    ThisWeek { years_ago: Option<u32> },
+    /// Taken in a month (1-12) of any year
+    TakenInMonth { month: u32 },
+}
+
+impl VirtualAlbum {
+    /// Whether a photo taken at `taken` belongs in the album on `today`
+    pub fn contains(&self, taken: NaiveDateTime, today: NaiveDate) -> bool {
+        let taken = taken.date();
+        match *self {
+            Self::OnThisDay => {
+                taken.year() < today.year() && anniversary(taken, today.year()) == today
+            }
+            Self::ThisWeek { years_ago } => {
+                // Check the anniversaries either side too, so the week spans New Year
+                let years = today.year() - taken.year();
+                (years - 1..=years + 1).any(|ago| {
+                    let when = anniversary(taken, taken.year() + ago);
+                    let near = (when - today).num_days().abs() <= THIS_WEEK_DAYS;
+                    near && ago >= 1 && years_ago.is_none_or(|n| ago == n as i32)
+                })
+            }
+            Self::TakenInMonth { month } => taken.month() == month,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match *self {
+            Self::OnThisDay => "On this day in past years".to_string(),
+            Self::ThisWeek { years_ago: None } => "This week in past years".to_string(),
+            Self::ThisWeek { years_ago: Some(1) } => "This week last year".to_string(),
+            Self::ThisWeek { years_ago: Some(n) } => format!("This week {n} years ago"),
+            Self::TakenInMonth { month } => {
+                let name = MONTH_NAMES.get((month as usize).checked_sub(1).unwrap_or(12));
+                format!("Taken in {}", name.copied().unwrap_or("an unknown month"))
+            }
+        }
+    }
+}
+
+/// The same day and month as `date` in `year`. 29 February falls on the 28th in other years.
+fn anniversary(date: NaiveDate, year: i32) -> NaiveDate {
+    date.with_year(year)
+        .or_else(|| NaiveDate::from_ymd_opt(year, 2, 28))
+        .unwrap()
 }
 
 /// Response struct for a photo request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Photo {
     pub id: PhotoID,
+    /// Websize rendition, for showing the photo on screen
     pub url: String,
+    /// Small preview for grids and lists
+    pub thumbnail_url: String,
+    /// The original as uploaded, in its own format
+    pub full_url: String,
     pub title: Option<String>,
     pub notes: Option<String>,
     pub artist: Option<String>,
     pub copyright: Option<String>,
     pub date_taken: Option<NaiveDateTime>,
     pub mat_preset: String,
+    /// Pixel size of the photo as displayed, after EXIF orientation has been applied
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub exif: PhotoExif,
+    /// When the photo was imported into the library, in UTC
+    pub added_at: NaiveDateTime,
+    /// Tag names, sorted
+    pub tags: Vec<String>,
+}
+
+impl Photo {
+    /// `None` until the photo's dimensions are known
+    pub fn orientation(&self) -> Option<PhotoOrientation> {
+        let (width, height) = (self.width?, self.height?);
+        Some(match width.cmp(&height) {
+            std::cmp::Ordering::Greater => PhotoOrientation::Landscape,
+            std::cmp::Ordering::Less => PhotoOrientation::Portrait,
+            std::cmp::Ordering::Equal => PhotoOrientation::Square,
+        })
+    }
+}
+
+/// Which rendition of a photo `/api/images/{id}` serves
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageVariant {
+    Thumbnail,
+    #[default]
+    Web,
+    Full,
+}
+
+impl ImageVariant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Thumbnail => "thumbnail",
+            Self::Web => "web",
+            Self::Full => "full",
+        }
+    }
+
+    /// URL of this rendition of a photo. The web rendition is the default and needs no query.
+    /// `key` is the photo's image key, see [`ImageQuery::v`].
+    pub fn url(&self, id: PhotoID, key: Option<&str>) -> String {
+        let mut query = vec![];
+        if *self != Self::Web {
+            query.push(format!("variant={}", self.as_str()));
+        }
+        if let Some(key) = key {
+            query.push(format!("v={key}"));
+        }
+        if query.is_empty() {
+            format!("/api/images/{id}")
+        } else {
+            format!("/api/images/{id}?{}", query.join("&"))
+        }
+    }
+}
+
+/// Query parameters for `/api/images/{id}`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImageQuery {
+    pub variant: Option<ImageVariant>,
+    /// Digest of the photo's image files. A URL with the current one always gets the same
+    /// bytes, so it can be cached for good
+    pub v: Option<String>,
+}
+
+/// Query parameters for `/api/backup`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BackupQuery {
+    /// Leave out the websize and thumbnail renditions. They're rebuilt from the originals on
+    /// restore, so the archive is smaller but restoring takes longer
+    #[serde(default)]
+    pub originals_only: bool,
+}
+
+impl BackupQuery {
+    /// URL to download a backup from, relative to the server root
+    pub fn url(&self) -> String {
+        if self.originals_only {
+            "/api/backup?originals_only=true".to_string()
+        } else {
+            "/api/backup".to_string()
+        }
+    }
+}
+
+/// Camera and capture details read from a photo's EXIF data. Anything the camera didn't
+/// record, or recorded in a form that couldn't be read, is `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhotoExif {
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens: Option<String>,
+    /// In millimetres
+    pub focal_length: Option<f64>,
+    /// The f-number, e.g. 2.8 for f/2.8
+    pub aperture: Option<f64>,
+    /// Shutter speed in seconds
+    pub exposure_time: Option<f64>,
+    pub iso: Option<u32>,
+    /// Decimal degrees, positive north
+    pub latitude: Option<f64>,
+    /// Decimal degrees, positive east
+    pub longitude: Option<f64>,
+    /// Size of the original as recorded by the camera, before any orientation is applied
+    pub pixel_width: Option<u32>,
+    pub pixel_height: Option<u32>,
+    /// UTC offset of `Photo::date_taken`, e.g. "+02:00"
+    pub date_taken_offset: Option<String>,
+}
+
+impl PhotoExif {
+    /// Make and model for display, e.g. "Canon EOS R5". Most cameras already repeat the
+    /// make in the model name, so it is only prepended when missing.
+    pub fn camera(&self) -> Option<String> {
+        match (&self.camera_make, &self.camera_model) {
+            (Some(make), Some(model)) => {
+                let first_word = make.split_whitespace().next().unwrap_or(make);
+                if model.to_lowercase().starts_with(&first_word.to_lowercase()) {
+                    Some(model.clone())
+                } else {
+                    Some(format!("{make} {model}"))
+                }
+            }
+            (Some(make), None) => Some(make.clone()),
+            (None, Some(model)) => Some(model.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// Shutter speed the way cameras show it: "1/250s" for fast exposures, "2s" for long ones.
+    pub fn shutter_speed(&self) -> Option<String> {
+        let t = self.exposure_time.filter(|t| *t > 0.0)?;
+        if t >= 1.0 {
+            Some(format!("{}s", (t * 10.0).round() / 10.0))
+        } else {
+            Some(format!("1/{}s", (1.0 / t).round()))
+        }
+    }
+
+    /// One line of exposure settings, e.g. "50mm · f/1.8 · 1/250s · ISO 100".
+    pub fn exposure_summary(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            self.focal_length.map(|f| format!("{}mm", f.round())),
+            self.aperture
+                .map(|a| format!("f/{}", (a * 10.0).round() / 10.0)),
+            self.shutter_speed(),
+            self.iso.map(|iso| format!("ISO {iso}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" · "))
+        }
+    }
+}
+
+/// Lifecycle of a background job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Processing,
+    Done,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queued => "queued",
+            Self::Processing => "processing",
+            Self::Done => "done",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// Parse the status as stored in the database. Unknown values are treated as failed.
+    pub fn from_db(status: &str) -> Self {
+        match status {
+            "queued" => Self::Queued,
+            "processing" => Self::Processing,
+            "done" => Self::Done,
+            _ => Self::Failed,
+        }
+    }
+
+    /// Whether the job has finished, successfully or not
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Done | Self::Failed)
+    }
+}
+
+/// What a background job does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobKind {
+    /// Import an uploaded photo
+    #[default]
+    Upload,
+    /// Run the classifier on a photo to suggest tags
+    Classify,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Upload => "upload",
+            Self::Classify => "classify",
+        }
+    }
+
+    /// Parse the kind as stored in the database. Unknown values are treated as uploads.
+    pub fn from_db(kind: &str) -> Self {
+        match kind {
+            "classify" => Self::Classify,
+            _ => Self::Upload,
+        }
+    }
+}
+
+/// Response struct for a background job (e.g. an accepted upload)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: JobID,
+    pub kind: JobKind,
+    pub filename: String,
+    pub status: JobStatus,
+    pub error: Option<String>,
+    /// The imported photo once an upload is done, the photo already in the library when an
+    /// upload failed as a duplicate of it, or the photo being classified
+    pub photo: Option<PhotoID>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Body of the 409 response returned when an upload matches a photo already in the library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateError {
+    pub error: String,
+    /// The photo already in the library
+    pub existing: PhotoID,
+    /// Hamming distance between the perceptual hashes. 0 is an exact match.
+    pub distance: u32,
+}
+
+/// Query parameters for finding near-duplicate photos
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DuplicatesQuery {
+    /// Maximum Hamming distance between hashes. Defaults to the server's duplicate threshold.
+    pub max_distance: Option<u32>,
+}
+
+/// Photos in the library that look like the same shot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    pub photos: Vec<Photo>,
+    /// Largest Hamming distance between any two photos in the group
+    pub distance: u32,
+}
+
+/// Merge near-duplicates into the photo being kept
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergePhotosRequest {
+    /// Photos to fold into the kept one. Their album memberships and tags move over and they
+    /// go to the trash.
+    pub duplicates: Vec<PhotoID>,
+}
+
+/// Whether a photo needs every tag asked for or just one of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMatch {
+    #[default]
+    All,
+    Any,
+}
+
+impl TagMatch {
+    /// Whether a photo tagged `have` matches the tags `wanted`, ignoring case.
+    /// Asking for no tags matches every photo.
+    pub fn matches(&self, wanted: &[String], have: &[String]) -> bool {
+        if wanted.is_empty() {
+            return true;
+        }
+        let has = |tag: &String| have.iter().any(|h| h.eq_ignore_ascii_case(tag));
+        match self {
+            Self::All => wanted.iter().all(has),
+            Self::Any => wanted.iter().any(has),
+        }
+    }
+}
+
+/// What `/api/photos` sorts by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhotoSort {
+    /// When the photo was added to the library
+    #[default]
+    Created,
+    /// EXIF date taken. Photos without one come last.
+    Taken,
+    /// Title, ignoring case. Untitled photos come last.
+    Title,
+    /// When the photo was last on a display. Photos never shown come last.
+    LastShown,
+}
+
+impl PhotoSort {
+    /// Newest first for dates, A to Z for titles
+    pub fn default_order(&self) -> SortOrder {
+        match self {
+            Self::Title => SortOrder::Asc,
+            Self::Created | Self::Taken | Self::LastShown => SortOrder::Desc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Query parameters for `/api/photos`. Every filter is optional and they combine with AND.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhotoQuery {
+    /// Comma separated tag names to filter by
+    pub tags: Option<String>,
+    #[serde(default)]
+    pub tag_match: TagMatch,
+    /// Only photos in this album, including virtual and smart albums
+    pub album: Option<i32>,
+    /// Only photos that haven't been added to any album
+    #[serde(default)]
+    pub without_album: bool,
+    /// Taken on or after this date
+    pub taken_after: Option<NaiveDate>,
+    /// Taken on or before this date
+    pub taken_before: Option<NaiveDate>,
+    /// Text to look for in the title, artist, copyright or notes, ignoring case
+    pub search: Option<String>,
+    #[serde(default)]
+    pub sort: PhotoSort,
+    /// Defaults to the sort's own order, see [`PhotoSort::default_order`]
+    pub order: Option<SortOrder>,
+    /// Number of photos to skip
+    pub offset: Option<u32>,
+    /// Most photos to return. All of them when missing.
+    pub limit: Option<u32>,
+}
+
+impl PhotoQuery {
+    /// Photos with every one of `tags`
+    pub fn tagged(tags: &[&str]) -> Self {
+        Self {
+            tags: Some(tags.join(",")),
+            ..Default::default()
+        }
+    }
+
+    /// The same query, `limit` photos starting at `offset`
+    pub fn page(self, offset: u32, limit: u32) -> Self {
+        Self {
+            offset: Some(offset),
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    pub fn order(&self) -> SortOrder {
+        self.order.unwrap_or_else(|| self.sort.default_order())
+    }
+
+    /// The search text, if there is any besides whitespace
+    pub fn search_text(&self) -> Option<&str> {
+        self.search
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
+
+    /// Tag names asked for, without blanks or repeats
+    pub fn tag_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for tag in self.tags.iter().flat_map(|tags| tags.split(',')) {
+            let tag = tag.trim();
+            if !tag.is_empty() && !names.iter().any(|n| n.eq_ignore_ascii_case(tag)) {
+                names.push(tag.to_string());
+            }
+        }
+        names
+    }
+}
+
+/// A deleted photo waiting in the trash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedPhoto {
+    pub photo: Photo,
+    pub deleted_at: NaiveDateTime,
+    /// When the photo and its files will be deleted for good
+    pub purge_at: NaiveDateTime,
+}
+
+/// One page of `/api/photos`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhotoPage {
+    pub photos: Vec<Photo>,
+    /// Number of photos matching the filters, across every page
+    pub total: u32,
+    /// Number of matching photos before this page
+    pub offset: u32,
+}
+
+impl PhotoPage {
+    /// Offset of the next page, or None if this is the last one
+    pub fn next_offset(&self) -> Option<u32> {
+        let next = self.offset + self.photos.len() as u32;
+        (!self.photos.is_empty() && next < self.total).then_some(next)
+    }
+}
+
+/// Query parameters for `/api/search`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchQuery {
+    /// Words to look for. Each one matches the start of a word, so "grand" finds "grandma".
+    #[serde(default)]
+    pub q: String,
+}
+
+/// A photo whose title, artist or notes matched a search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhotoSearchResult {
+    pub photo: Photo,
+    /// The text around the match, HTML-escaped, with the matching words in `<mark>`
+    pub snippet: String,
+}
+
+/// An album whose name or notes matched a search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlbumSearchResult {
+    pub album: Album,
+    /// The text around the match, HTML-escaped, with the matching words in `<mark>`
+    pub snippet: String,
+}
+
+/// Results of `/api/search`, best matches first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub photos: Vec<PhotoSearchResult>,
+    pub albums: Vec<AlbumSearchResult>,
+}
+
+impl SearchResults {
+    pub fn is_empty(&self) -> bool {
+        self.photos.is_empty() && self.albums.is_empty()
+    }
+}
+
+/// A keyword photos can be filtered and grouped by
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: TagID,
+    pub name: String,
+    /// Number of photos with this tag
+    pub photo_count: u32,
+}
+
+/// Create or rename a tag
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagRequest {
+    pub name: String,
+}
+
+/// Fold other tags into the one being kept
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeTagsRequest {
+    /// Tags to merge. Their photos get the kept tag and they are deleted.
+    pub tags: Vec<TagID>,
+}
+
+/// What a classifier found in a photo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionKind {
+    /// Something in the photo, e.g. "dog" or "bicycle"
+    #[default]
+    Tag,
+    /// A face, labelled with who it is if the model knows
+    Face,
+    /// The kind of scene, e.g. "beach" or "night"
+    Scene,
+}
+
+impl SuggestionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tag => "tag",
+            Self::Face => "face",
+            Self::Scene => "scene",
+        }
+    }
+
+    /// Parse the kind as stored in the database. Unknown values are treated as plain tags.
+    pub fn from_db(kind: &str) -> Self {
+        match kind {
+            "face" => Self::Face,
+            "scene" => Self::Scene,
+            _ => Self::Tag,
+        }
+    }
+}
+
+/// A tag the classifier proposed for a photo, waiting for review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSuggestion {
+    pub id: SuggestionID,
+    pub photo: PhotoID,
+    /// Name of the tag added to the photo if the suggestion is accepted
+    pub label: String,
+    pub kind: SuggestionKind,
+    /// How sure the classifier is, from 0 to 1
+    pub confidence: f32,
+    pub created_at: NaiveDateTime,
+}
+
+/// Query parameters for `/api/suggestions`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SuggestionsQuery {
+    /// Only suggestions for this photo
+    pub photo: Option<i32>,
+}
+
+/// Add or remove tags on many photos at once
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagPhotosRequest {
+    pub photos: Vec<PhotoID>,
+    /// Tag names. Attaching a tag that doesn't exist yet creates it.
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateSettingsRequest {
     pub current_album_id: Option<Update<AlbumID>>,
     pub interval_seconds: Option<i32>,
+    pub rotation_mode: Option<RotationMode>,
+}
+
+/// How /api/next picks the next photo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationMode {
+    /// In album order, or upload order for the whole library
+    #[default]
+    Sequential,
+    /// Every photo once per cycle in a random order, reshuffled for the next cycle
+    Shuffle,
+    /// Random, favoring photos that haven't been shown for a while
+    WeightedRandom,
+}
+
+impl RotationMode {
+    pub const ALL: &[RotationMode] = &[
+        RotationMode::Sequential,
+        RotationMode::Shuffle,
+        RotationMode::WeightedRandom,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sequential => "sequential",
+            Self::Shuffle => "shuffle",
+            Self::WeightedRandom => "weighted_random",
+        }
+    }
+
+    /// Parse the mode as stored in the database. Unknown values fall back to sequential.
+    pub fn from_db(mode: &str) -> Self {
+        match mode {
+            "shuffle" => Self::Shuffle,
+            "weighted_random" => Self::WeightedRandom,
+            _ => Self::Sequential,
+        }
+    }
+
+    /// Human readable name for the admin UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Sequential => "In order",
+            Self::Shuffle => "Shuffle",
+            Self::WeightedRandom => "Weighted random",
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Copy, Clone)]
@@ -213,27 +1158,204 @@ pub struct Next {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CurrentAlbum {
-    /// Current album to serve images from
-    pub album: AlbumID,
-    /// Index of currently displayed image in album
-    pub index: usize,
+pub struct CurrentAlbum {
+    /// Current album to serve images from
+    pub album: AlbumID,
+    /// Index of currently displayed image in album
+    pub index: usize,
+}
+
+/// Query parameters for `/api/next`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NextQuery {
+    /// Token of a registered display. Without one the shared rotation in the settings is used
+    pub display: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateDisplayRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateDisplayRequest {
+    pub name: Option<String>,
+    pub current_album_id: Option<Update<AlbumID>>,
+    pub interval_seconds: Option<i32>,
+    pub rotation_mode: Option<RotationMode>,
+    pub mat_preset: Option<Update<String>>,
+}
+
+/// A registered picture frame. Each display keeps its own place in its own album, so several
+/// frames can show different things without skipping each other's photos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Display {
+    pub id: DisplayID,
+    pub name: String,
+    /// Identifies the frame to `/api/next`
+    pub token: String,
+    pub current_album: Option<CurrentAlbum>,
+    /// Number of seconds until next image
+    pub interval: Interval,
+    pub mode: RotationMode,
+    /// Mat shown around every photo on this display instead of each photo's own
+    pub mat_preset: Option<String>,
+    /// When the frame last asked for a photo
+    pub last_seen_at: Option<NaiveDateTime>,
+}
+
+impl Display {
+    /// Viewer page for this display, relative to the server root
+    pub fn viewer_url(&self) -> String {
+        format!("/?display={}", self.token)
+    }
+}
+
+/// What plays while a schedule is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum ScheduleAction {
+    Album {
+        album: AlbumID,
+    },
+    /// Show nothing, e.g. overnight
+    Blank,
+}
+
+/// A rule choosing what plays at certain times. When several are active the one with the
+/// highest priority wins. When none are, the album picked in the settings (or on the display)
+/// plays.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: ScheduleID,
+    pub name: String,
+    pub action: ScheduleAction,
+    /// Only applies to this display. `None` applies to every frame
+    pub display: Option<DisplayID>,
+    pub priority: i32,
+    pub enabled: bool,
+    /// Days of the week the schedule is active. Empty means every day
+    pub days: Vec<Weekday>,
+    /// Months (1-12) the schedule is active. Empty means all year
+    pub months: Vec<u32>,
+    /// Time of day the schedule starts. `None` means midnight
+    pub start_time: Option<NaiveTime>,
+    /// Time of day the schedule stops. `None` means midnight. An end before the start runs
+    /// past midnight, and the early hours count as part of the day the schedule started.
+    pub end_time: Option<NaiveTime>,
+}
+
+impl Schedule {
+    pub fn is_active_at(&self, at: NaiveDateTime) -> bool {
+        if !self.enabled {
+            return false;
+        }
+
+        let time = at.time();
+        let (in_window, started_yesterday) = match (self.start_time, self.end_time) {
+            (None, None) => (true, false),
+            (Some(start), None) => (time >= start, false),
+            (None, Some(end)) => (time < end, false),
+            (Some(start), Some(end)) if start <= end => (start <= time && time < end, false),
+            (Some(start), Some(end)) => (time >= start || time < end, time < end),
+        };
+        if !in_window {
+            return false;
+        }
+
+        let day = if started_yesterday {
+            at.date() - Duration::days(1)
+        } else {
+            at.date()
+        };
+        (self.days.is_empty() || self.days.contains(&day.weekday()))
+            && (self.months.is_empty() || self.months.contains(&day.month()))
+    }
+
+    /// The schedule in effect for `display` (or the shared rotation) at `at`. Ties in priority
+    /// go to the oldest schedule.
+    pub fn active(
+        schedules: &[Schedule],
+        display: Option<DisplayID>,
+        at: NaiveDateTime,
+    ) -> Option<&Schedule> {
+        schedules
+            .iter()
+            .filter(|s| s.display.is_none() || s.display == display)
+            .filter(|s| s.is_active_at(at))
+            .max_by_key(|s| (s.priority, std::cmp::Reverse(s.id.0)))
+    }
+
+    /// Request that saves this schedule unchanged, for editing a copy
+    pub fn to_request(&self) -> ScheduleRequest {
+        ScheduleRequest {
+            name: self.name.clone(),
+            action: self.action,
+            display: self.display,
+            priority: self.priority,
+            enabled: self.enabled,
+            days: self.days.clone(),
+            months: self.months.clone(),
+            start_time: self.start_time,
+            end_time: self.end_time,
+        }
+    }
+
+    /// Short description of when the schedule is active, e.g. "Mon, Tue · 07:00-09:00"
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.days.is_empty() {
+            let days: Vec<String> = self.days.iter().map(|d| d.to_string()).collect();
+            parts.push(days.join(", "));
+        }
+        if !self.months.is_empty() {
+            let months: Vec<&str> = self
+                .months
+                .iter()
+                .filter_map(|&m| MONTH_NAMES.get((m as usize).checked_sub(1)?).copied())
+                .collect();
+            parts.push(months.join(", "));
+        }
+        let hm = |t: NaiveTime| format!("{:02}:{:02}", t.hour(), t.minute());
+        match (self.start_time, self.end_time) {
+            (None, None) => {}
+            (Some(start), None) => parts.push(format!("from {}", hm(start))),
+            (None, Some(end)) => parts.push(format!("until {}", hm(end))),
+            (Some(start), Some(end)) => parts.push(format!("{}-{}", hm(start), hm(end))),
+        }
+        if parts.is_empty() {
+            "Always".to_string()
+        } else {
+            parts.join(" · ")
+        }
+    }
+}
+
+/// Short month names, January first
+pub const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Body for creating or replacing a schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleRequest {
+    pub name: String,
+    pub action: ScheduleAction,
+    pub display: Option<DisplayID>,
+    pub priority: i32,
+    pub enabled: bool,
+    pub days: Vec<Weekday>,
+    pub months: Vec<u32>,
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RotationSettings {
     pub current_album: Option<CurrentAlbum>,
     /// Number of seconds until next image
     pub interval: Interval,
-}
-
-impl Default for RotationSettings {
-    fn default() -> Self {
-        Self {
-            current_album: None,
-            interval: Interval::default(),
-        }
-    }
+    pub mode: RotationMode,
 }
 
 #[derive(Debug, Clone)]
@@ -368,16 +1490,21 @@ impl Client {
     // Next (for viewer)
     // ─────────────────────────────────────────────────────────────────────────
 
-    pub async fn get_next(&self) -> Result<Next, ApiError> {
-        self.get("/api/next").await
+    /// Next photo for the display with `display_token`, or for the shared rotation
+    pub async fn get_next(&self, display_token: Option<&str>) -> Result<Next, ApiError> {
+        match display_token {
+            Some(token) => self.get(format!("/api/next?display={token}")).await,
+            None => self.get("/api/next").await,
+        }
     }
 
     // ─────────────────────────────────────────────────────────────────────────
     // Photos
     // ─────────────────────────────────────────────────────────────────────────
 
-    pub async fn get_photos(&self) -> Result<Vec<Photo>, ApiError> {
-        self.get("/api/photos").await
+    pub async fn get_photos(&self, query: &PhotoQuery) -> Result<PhotoPage, ApiError> {
+        let query = serde_urlencoded::to_string(query).expect("PhotoQuery serializes");
+        self.get(format!("/api/photos?{query}")).await
     }
 
     pub async fn get_photo(&self, id: PhotoID) -> Result<Photo, ApiError> {
@@ -396,6 +1523,140 @@ impl Client {
         self.delete(format!("/api/photos/{id}")).await
     }
 
+    pub async fn bulk_photos(
+        &self,
+        req: &BulkPhotoRequest,
+    ) -> Result<Vec<BulkPhotoResult>, ApiError> {
+        self.post("/api/photos/bulk", req).await
+    }
+
+    pub async fn get_duplicates(
+        &self,
+        max_distance: Option<u32>,
+    ) -> Result<Vec<DuplicateGroup>, ApiError> {
+        match max_distance {
+            Some(max_distance) => {
+                self.get(format!(
+                    "/api/photos/duplicates?max_distance={max_distance}"
+                ))
+                .await
+            }
+            None => self.get("/api/photos/duplicates").await,
+        }
+    }
+
+    pub async fn merge_photos(
+        &self,
+        keep: PhotoID,
+        req: &MergePhotosRequest,
+    ) -> Result<Photo, ApiError> {
+        self.post(format!("/api/photos/{keep}/merge"), req).await
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Trash
+    // ─────────────────────────────────────────────────────────────────────────
+
+    pub async fn get_trash(&self) -> Result<Vec<TrashedPhoto>, ApiError> {
+        self.get("/api/trash").await
+    }
+
+    pub async fn restore_photo(&self, id: PhotoID) -> Result<Photo, ApiError> {
+        self.post(format!("/api/trash/{id}/restore"), &()).await
+    }
+
+    pub async fn purge_photo(&self, id: PhotoID) -> Result<(), ApiError> {
+        self.delete(format!("/api/trash/{id}")).await
+    }
+
+    /// Delete everything in the trash for good. Returns the number of photos deleted.
+    pub async fn empty_trash(&self) -> Result<u32, ApiError> {
+        self.delete("/api/trash").await
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Tags
+    // ─────────────────────────────────────────────────────────────────────────
+
+    pub async fn get_tags(&self) -> Result<Vec<Tag>, ApiError> {
+        self.get("/api/tags").await
+    }
+
+    pub async fn create_tag(&self, req: &TagRequest) -> Result<Tag, ApiError> {
+        self.post("/api/tags", req).await
+    }
+
+    pub async fn rename_tag(&self, id: TagID, req: &TagRequest) -> Result<Tag, ApiError> {
+        self.put(format!("/api/tags/{id}"), req).await
+    }
+
+    pub async fn merge_tags(&self, keep: TagID, req: &MergeTagsRequest) -> Result<Tag, ApiError> {
+        self.post(format!("/api/tags/{keep}/merge"), req).await
+    }
+
+    pub async fn delete_tag(&self, id: TagID) -> Result<(), ApiError> {
+        self.delete(format!("/api/tags/{id}")).await
+    }
+
+    pub async fn attach_tags(&self, req: &TagPhotosRequest) -> Result<(), ApiError> {
+        self.post("/api/tags/attach", req).await
+    }
+
+    pub async fn detach_tags(&self, req: &TagPhotosRequest) -> Result<(), ApiError> {
+        self.post("/api/tags/detach", req).await
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Jobs
+    // ─────────────────────────────────────────────────────────────────────────
+
+    pub async fn get_jobs(&self) -> Result<Vec<Job>, ApiError> {
+        self.get("/api/jobs").await
+    }
+
+    pub async fn get_job(&self, id: JobID) -> Result<Job, ApiError> {
+        self.get(format!("/api/jobs/{id}")).await
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Search
+    // ─────────────────────────────────────────────────────────────────────────
+
+    pub async fn search(&self, q: &str) -> Result<SearchResults, ApiError> {
+        let query = serde_urlencoded::to_string(SearchQuery { q: q.to_string() })
+            .expect("SearchQuery serializes");
+        self.get(format!("/api/search?{query}")).await
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Tag Suggestions
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Queue a job that runs the classifier on a photo
+    pub async fn classify_photo(&self, id: PhotoID) -> Result<Job, ApiError> {
+        self.post(format!("/api/photos/{id}/classify"), &()).await
+    }
+
+    pub async fn get_suggestions(
+        &self,
+        photo: Option<PhotoID>,
+    ) -> Result<Vec<TagSuggestion>, ApiError> {
+        match photo {
+            Some(photo) => self.get(format!("/api/suggestions?photo={photo}")).await,
+            None => self.get("/api/suggestions").await,
+        }
+    }
+
+    pub async fn accept_suggestion(&self, id: SuggestionID) -> Result<(), ApiError> {
+        self.post(format!("/api/suggestions/{id}/accept"), &())
+            .await
+    }
+
+    pub async fn reject_suggestion(&self, id: SuggestionID) -> Result<(), ApiError> {
+        self.post(format!("/api/suggestions/{id}/reject"), &())
+            .await
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Albums
     // ─────────────────────────────────────────────────────────────────────────
@@ -440,6 +1701,31 @@ impl Client {
         self.delete(format!("/api/albums/{album_id}/photos/{photo_id}")).await
     }
 
+    pub async fn reorder_album(
+        &self,
+        id: AlbumID,
+        req: &ReorderAlbumRequest,
+    ) -> Result<(), ApiError> {
+        self.put(format!("/api/albums/{id}/order"), req).await
+    }
+
+    pub async fn move_album_photo(
+        &self,
+        album_id: AlbumID,
+        photo_id: PhotoID,
+        req: &MoveAlbumPhotoRequest,
+    ) -> Result<(), ApiError> {
+        self.post(
+            format!("/api/albums/{album_id}/photos/{photo_id}/move"),
+            req,
+        )
+        .await
+    }
+
+    pub async fn sort_album(&self, id: AlbumID, req: &SortAlbumRequest) -> Result<(), ApiError> {
+        self.post(format!("/api/albums/{id}/sort"), req).await
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Settings
     // ─────────────────────────────────────────────────────────────────────────
@@ -451,6 +1737,58 @@ impl Client {
     pub async fn update_settings(&self, updates: &UpdateSettingsRequest) -> Result<(), ApiError> {
         self.put("/api/settings", updates).await
     }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Displays
+    // ─────────────────────────────────────────────────────────────────────────
+
+    pub async fn get_displays(&self) -> Result<Vec<Display>, ApiError> {
+        self.get("/api/displays").await
+    }
+
+    pub async fn get_display(&self, id: DisplayID) -> Result<Display, ApiError> {
+        self.get(format!("/api/displays/{id}")).await
+    }
+
+    pub async fn create_display(&self, req: &CreateDisplayRequest) -> Result<Display, ApiError> {
+        self.post("/api/displays", req).await
+    }
+
+    pub async fn update_display(
+        &self,
+        id: DisplayID,
+        updates: &UpdateDisplayRequest,
+    ) -> Result<Display, ApiError> {
+        self.put(format!("/api/displays/{id}"), updates).await
+    }
+
+    pub async fn delete_display(&self, id: DisplayID) -> Result<(), ApiError> {
+        self.delete(format!("/api/displays/{id}")).await
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Schedules
+    // ─────────────────────────────────────────────────────────────────────────
+
+    pub async fn get_schedules(&self) -> Result<Vec<Schedule>, ApiError> {
+        self.get("/api/schedules").await
+    }
+
+    pub async fn create_schedule(&self, req: &ScheduleRequest) -> Result<Schedule, ApiError> {
+        self.post("/api/schedules", req).await
+    }
+
+    pub async fn update_schedule(
+        &self,
+        id: ScheduleID,
+        req: &ScheduleRequest,
+    ) -> Result<Schedule, ApiError> {
+        self.put(format!("/api/schedules/{id}"), req).await
+    }
+
+    pub async fn delete_schedule(&self, id: ScheduleID) -> Result<(), ApiError> {
+        self.delete(format!("/api/schedules/{id}")).await
+    }
 }
 
 #[cfg(test)]
@@ -558,6 +1896,588 @@ mod tests {
         assert!(json.contains("\"shadow\":"));
     }
 
+    #[test]
+    fn test_job_status_round_trips_through_db_strings() {
+        for status in [
+            JobStatus::Queued,
+            JobStatus::Processing,
+            JobStatus::Done,
+            JobStatus::Failed,
+        ] {
+            assert_eq!(JobStatus::from_db(status.as_str()), status);
+        }
+    }
+
+    #[test]
+    fn test_job_status_serializes_lowercase() {
+        assert_eq!(
+            serde_json::to_string(&JobStatus::Processing).unwrap(),
+            "\"processing\""
+        );
+    }
+
+    #[test]
+    fn test_photo_exif_camera_skips_repeated_make() {
+        let exif = PhotoExif {
+            camera_make: Some("Canon".to_string()),
+            camera_model: Some("Canon EOS R5".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(exif.camera().as_deref(), Some("Canon EOS R5"));
+    }
+
+    #[test]
+    fn test_photo_exif_camera_joins_make_and_model() {
+        let exif = PhotoExif {
+            camera_make: Some("NIKON CORPORATION".to_string()),
+            camera_model: Some("Z 6".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(exif.camera().as_deref(), Some("NIKON CORPORATION Z 6"));
+
+        let exif = PhotoExif {
+            camera_make: Some("NIKON CORPORATION".to_string()),
+            camera_model: Some("NIKON Z 6".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(exif.camera().as_deref(), Some("NIKON Z 6"));
+    }
+
+    #[test]
+    fn test_photo_exif_shutter_speed() {
+        let speed = |t| {
+            PhotoExif {
+                exposure_time: Some(t),
+                ..Default::default()
+            }
+            .shutter_speed()
+        };
+        assert_eq!(speed(0.004).as_deref(), Some("1/250s"));
+        assert_eq!(speed(1.0 / 3.0).as_deref(), Some("1/3s"));
+        assert_eq!(speed(2.0).as_deref(), Some("2s"));
+        assert_eq!(speed(1.3).as_deref(), Some("1.3s"));
+        assert_eq!(speed(0.0), None);
+    }
+
+    #[test]
+    fn test_photo_exif_exposure_summary() {
+        let exif = PhotoExif {
+            focal_length: Some(50.0),
+            aperture: Some(1.8),
+            exposure_time: Some(0.004),
+            iso: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(
+            exif.exposure_summary().as_deref(),
+            Some("50mm · f/1.8 · 1/250s · ISO 100")
+        );
+
+        let partial = PhotoExif {
+            iso: Some(400),
+            ..Default::default()
+        };
+        assert_eq!(partial.exposure_summary().as_deref(), Some("ISO 400"));
+
+        assert_eq!(PhotoExif::default().exposure_summary(), None);
+    }
+
+    #[test]
+    fn test_image_variant_urls() {
+        let id = PhotoID(7);
+        assert_eq!(ImageVariant::Web.url(id, None), "/api/images/7");
+        assert_eq!(
+            ImageVariant::Thumbnail.url(id, None),
+            "/api/images/7?variant=thumbnail"
+        );
+        assert_eq!(
+            ImageVariant::Full.url(id, None),
+            "/api/images/7?variant=full"
+        );
+
+        assert_eq!(
+            ImageVariant::Web.url(id, Some("0a1b")),
+            "/api/images/7?v=0a1b"
+        );
+        assert_eq!(
+            ImageVariant::Full.url(id, Some("0a1b")),
+            "/api/images/7?variant=full&v=0a1b"
+        );
+    }
+
+    #[test]
+    fn test_backup_query_url_round_trips() {
+        for originals_only in [false, true] {
+            let url = BackupQuery { originals_only }.url();
+            let query = url.split_once('?').map(|(_, q)| q).unwrap_or_default();
+            let parsed: BackupQuery = serde_urlencoded::from_str(query).unwrap();
+            assert_eq!(parsed.originals_only, originals_only);
+        }
+    }
+
+    #[test]
+    fn test_image_variant_serializes_lowercase() {
+        for variant in [
+            ImageVariant::Thumbnail,
+            ImageVariant::Web,
+            ImageVariant::Full,
+        ] {
+            let json = serde_json::to_string(&variant).unwrap();
+            assert_eq!(json, format!("\"{}\"", variant.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_rotation_mode_round_trips_through_db_strings() {
+        for mode in RotationMode::ALL {
+            assert_eq!(RotationMode::from_db(mode.as_str()), *mode);
+            let json = serde_json::to_string(mode).unwrap();
+            assert_eq!(json, format!("\"{}\"", mode.as_str()));
+        }
+        assert_eq!(RotationMode::from_db("bogus"), RotationMode::Sequential);
+    }
+
+    #[test]
+    fn test_display_viewer_url_carries_token() {
+        let display = Display {
+            id: DisplayID(3),
+            name: "Kitchen".to_string(),
+            token: "abc123".to_string(),
+            current_album: None,
+            interval: Interval::from_seconds(60),
+            mode: RotationMode::default(),
+            mat_preset: None,
+            last_seen_at: None,
+        };
+        assert_eq!(display.viewer_url(), "/?display=abc123");
+    }
+
+    #[test]
+    fn test_album_export_url() {
+        let album = Album {
+            id: AlbumID(4),
+            name: "Holidays".to_string(),
+            notes: None,
+            photos: vec![],
+            virtual_album: None,
+            smart_rule: None,
+        };
+        assert_eq!(album.export_url(), "/api/albums/4/export");
+    }
+
+    fn schedule(priority: i32) -> Schedule {
+        Schedule {
+            id: ScheduleID(priority),
+            name: format!("Schedule {priority}"),
+            action: ScheduleAction::Blank,
+            display: None,
+            priority,
+            enabled: true,
+            days: vec![],
+            months: vec![],
+            start_time: None,
+            end_time: None,
+        }
+    }
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn hm(time: &str) -> Option<NaiveTime> {
+        Some(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+    }
+
+    #[test]
+    fn test_schedule_weekday_mornings() {
+        let s = Schedule {
+            days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            start_time: hm("07:00"),
+            end_time: hm("09:00"),
+            ..schedule(0)
+        };
+        // 2026-03-02 is a Monday
+        assert!(s.is_active_at(at("2026-03-02", "07:00")));
+        assert!(s.is_active_at(at("2026-03-02", "08:59")));
+        assert!(!s.is_active_at(at("2026-03-02", "09:00")));
+        assert!(!s.is_active_at(at("2026-03-02", "06:59")));
+        assert!(!s.is_active_at(at("2026-03-07", "08:00")));
+    }
+
+    #[test]
+    fn test_schedule_months() {
+        let s = Schedule {
+            months: vec![12],
+            ..schedule(0)
+        };
+        assert!(s.is_active_at(at("2026-12-01", "00:00")));
+        assert!(s.is_active_at(at("2026-12-31", "23:59")));
+        assert!(!s.is_active_at(at("2027-01-01", "00:00")));
+    }
+
+    #[test]
+    fn test_schedule_overnight_counts_as_the_starting_day() {
+        let s = Schedule {
+            days: vec![Weekday::Fri],
+            start_time: hm("22:00"),
+            end_time: hm("06:00"),
+            ..schedule(0)
+        };
+        // 2026-03-06 is a Friday
+        assert!(s.is_active_at(at("2026-03-06", "23:00")));
+        assert!(s.is_active_at(at("2026-03-07", "05:59")));
+        assert!(!s.is_active_at(at("2026-03-07", "06:00")));
+        assert!(!s.is_active_at(at("2026-03-06", "05:00")));
+        assert!(!s.is_active_at(at("2026-03-07", "23:00")));
+    }
+
+    #[test]
+    fn test_schedule_open_ended_times() {
+        let after = Schedule {
+            start_time: hm("22:00"),
+            ..schedule(0)
+        };
+        assert!(after.is_active_at(at("2026-03-02", "22:30")));
+        assert!(!after.is_active_at(at("2026-03-02", "01:00")));
+
+        let before = Schedule {
+            end_time: hm("06:00"),
+            ..schedule(0)
+        };
+        assert!(before.is_active_at(at("2026-03-02", "01:00")));
+        assert!(!before.is_active_at(at("2026-03-02", "22:30")));
+    }
+
+    #[test]
+    fn test_schedule_active_picks_highest_priority() {
+        let now = at("2026-03-02", "12:00");
+        let disabled = Schedule {
+            enabled: false,
+            ..schedule(9)
+        };
+        let schedules = vec![schedule(1), schedule(5), disabled];
+        let active = Schedule::active(&schedules, None, now).unwrap();
+        assert_eq!(active.priority, 5);
+        assert!(Schedule::active(&[], None, now).is_none());
+    }
+
+    #[test]
+    fn test_schedule_active_for_display() {
+        let now = at("2026-03-02", "12:00");
+        let kitchen = Schedule {
+            display: Some(DisplayID(1)),
+            ..schedule(5)
+        };
+        let schedules = vec![schedule(1), kitchen];
+        assert_eq!(
+            Schedule::active(&schedules, Some(DisplayID(1)), now)
+                .unwrap()
+                .priority,
+            5
+        );
+        assert_eq!(
+            Schedule::active(&schedules, Some(DisplayID(2)), now)
+                .unwrap()
+                .priority,
+            1
+        );
+        assert_eq!(Schedule::active(&schedules, None, now).unwrap().priority, 1);
+    }
+
+    #[test]
+    fn test_schedule_summary() {
+        let s = Schedule {
+            days: vec![Weekday::Sat, Weekday::Sun],
+            months: vec![12],
+            start_time: hm("22:00"),
+            ..schedule(0)
+        };
+        assert_eq!(s.summary(), "Sat, Sun · Dec · from 22:00");
+        assert_eq!(schedule(0).summary(), "Always");
+    }
+
+    fn date(ymd: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(ymd, "%Y-%m-%d").unwrap()
+    }
+
+    fn taken(ymd: &str) -> NaiveDateTime {
+        date(ymd).and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_virtual_album_on_this_day() {
+        let album = VirtualAlbum::OnThisDay;
+        let today = date("2026-06-15");
+        assert!(album.contains(taken("2019-06-15"), today));
+        assert!(!album.contains(taken("2026-06-15"), today));
+        assert!(!album.contains(taken("2019-06-16"), today));
+    }
+
+    #[test]
+    fn test_virtual_album_on_this_day_leap_day() {
+        let album = VirtualAlbum::OnThisDay;
+        assert!(album.contains(taken("2024-02-29"), date("2026-02-28")));
+        assert!(album.contains(taken("2024-02-29"), date("2028-02-29")));
+        assert!(!album.contains(taken("2023-02-28"), date("2028-02-29")));
+    }
+
+    #[test]
+    fn test_virtual_album_this_week() {
+        let any_year = VirtualAlbum::ThisWeek { years_ago: None };
+        let today = date("2026-06-15");
+        assert!(any_year.contains(taken("2020-06-12"), today));
+        assert!(any_year.contains(taken("2020-06-18"), today));
+        assert!(!any_year.contains(taken("2020-06-19"), today));
+        assert!(!any_year.contains(taken("2026-06-14"), today));
+
+        let two_years = VirtualAlbum::ThisWeek { years_ago: Some(2) };
+        assert!(two_years.contains(taken("2024-06-16"), today));
+        assert!(!two_years.contains(taken("2023-06-16"), today));
+    }
+
+    #[test]
+    fn test_virtual_album_this_week_spans_new_year() {
+        let album = VirtualAlbum::ThisWeek { years_ago: Some(1) };
+        // Early January 2026 looks back to late December 2024 and early January 2025
+        assert!(album.contains(taken("2024-12-31"), date("2026-01-01")));
+        assert!(album.contains(taken("2025-01-03"), date("2026-01-01")));
+        assert!(!album.contains(taken("2025-12-31"), date("2026-01-01")));
+    }
+
+    #[test]
+    fn test_virtual_album_taken_in_month() {
+        let album = VirtualAlbum::TakenInMonth { month: 12 };
+        let today = date("2026-06-15");
+        assert!(album.contains(taken("2026-12-25"), today));
+        assert!(album.contains(taken("1999-12-01"), today));
+        assert!(!album.contains(taken("2026-11-30"), today));
+        assert_eq!(album.describe(), "Taken in Dec");
+    }
+
+    fn photo() -> Photo {
+        Photo {
+            id: PhotoID(1),
+            url: ImageVariant::Web.url(PhotoID(1), None),
+            thumbnail_url: ImageVariant::Thumbnail.url(PhotoID(1), None),
+            full_url: ImageVariant::Full.url(PhotoID(1), None),
+            title: None,
+            notes: None,
+            artist: Some("Ansel Adams".to_string()),
+            copyright: None,
+            date_taken: Some(taken("2020-07-04")),
+            mat_preset: "classic".to_string(),
+            width: Some(3000),
+            height: Some(2000),
+            exif: PhotoExif {
+                camera_make: Some("FUJIFILM".to_string()),
+                camera_model: Some("X-T4".to_string()),
+                ..Default::default()
+            },
+            added_at: taken("2026-06-10"),
+            tags: vec!["Beach".to_string(), "family".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_smart_rule_default_matches_everything() {
+        assert!(SmartRule::default().matches(&photo(), taken("2026-06-15")));
+    }
+
+    #[test]
+    fn test_smart_rule_text_fields_ignore_case() {
+        let now = taken("2026-06-15");
+        let rule = SmartRule {
+            artist: Some("adams".to_string()),
+            camera: Some("fujifilm x-t".to_string()),
+            ..Default::default()
+        };
+        assert!(rule.matches(&photo(), now));
+
+        let rule = SmartRule {
+            artist: Some("Weston".to_string()),
+            ..Default::default()
+        };
+        assert!(!rule.matches(&photo(), now));
+    }
+
+    #[test]
+    fn test_smart_rule_date_range_is_inclusive() {
+        let now = taken("2026-06-15");
+        let rule = SmartRule {
+            taken_after: Some(date("2020-07-04")),
+            taken_before: Some(date("2020-07-04")),
+            ..Default::default()
+        };
+        assert!(rule.matches(&photo(), now));
+
+        let undated = Photo {
+            date_taken: None,
+            ..photo()
+        };
+        assert!(!rule.matches(&undated, now));
+
+        let rule = SmartRule {
+            taken_after: Some(date("2020-07-05")),
+            ..Default::default()
+        };
+        assert!(!rule.matches(&photo(), now));
+    }
+
+    #[test]
+    fn test_smart_rule_orientation_and_mat() {
+        let now = taken("2026-06-15");
+        let landscape = SmartRule {
+            orientation: Some(PhotoOrientation::Landscape),
+            mat_preset: Some("classic".to_string()),
+            ..Default::default()
+        };
+        assert!(landscape.matches(&photo(), now));
+
+        let portrait = SmartRule {
+            orientation: Some(PhotoOrientation::Portrait),
+            ..Default::default()
+        };
+        assert!(!portrait.matches(&photo(), now));
+
+        let unknown_size = Photo {
+            width: None,
+            ..photo()
+        };
+        assert!(!landscape.matches(&unknown_size, now));
+    }
+
+    #[test]
+    fn test_smart_rule_added_within_days() {
+        let rule = SmartRule {
+            added_within_days: Some(7),
+            ..Default::default()
+        };
+        assert!(rule.matches(&photo(), taken("2026-06-15")));
+        assert!(!rule.matches(&photo(), taken("2026-06-30")));
+    }
+
+    #[test]
+    fn test_smart_rule_tags() {
+        let now = taken("2026-06-15");
+        let rule = SmartRule {
+            tags: vec!["beach".to_string(), "Family".to_string()],
+            ..Default::default()
+        };
+        assert!(rule.matches(&photo(), now));
+
+        let rule = SmartRule {
+            tags: vec!["beach".to_string(), "snow".to_string()],
+            ..Default::default()
+        };
+        assert!(!rule.matches(&photo(), now));
+    }
+
+    #[test]
+    fn test_tag_match() {
+        let have = vec!["beach".to_string(), "family".to_string()];
+        let wanted = vec!["Beach".to_string(), "snow".to_string()];
+        assert!(!TagMatch::All.matches(&wanted, &have));
+        assert!(TagMatch::Any.matches(&wanted, &have));
+        assert!(TagMatch::All.matches(&[], &have));
+        assert!(TagMatch::Any.matches(&[], &[]));
+        assert!(!TagMatch::Any.matches(&wanted, &[]));
+    }
+
+    #[test]
+    fn test_photo_query_tag_names() {
+        let query = PhotoQuery {
+            tags: Some(" beach, ,family,Beach ".to_string()),
+            tag_match: TagMatch::Any,
+            ..Default::default()
+        };
+        assert_eq!(query.tag_names(), vec!["beach", "family"]);
+        assert!(PhotoQuery::default().tag_names().is_empty());
+    }
+
+    #[test]
+    fn test_photo_query_round_trips_through_url() {
+        let query = PhotoQuery::tagged(&["beach", "sun & sand"]);
+        let encoded = serde_urlencoded::to_string(&query).unwrap();
+        let decoded: PhotoQuery = serde_urlencoded::from_str(&encoded).unwrap();
+        assert_eq!(decoded.tag_names(), vec!["beach", "sun & sand"]);
+        assert_eq!(decoded.tag_match, TagMatch::All);
+    }
+
+    #[test]
+    fn test_photo_query_filters_round_trip_through_url() {
+        let query = PhotoQuery {
+            album: Some(3),
+            without_album: true,
+            taken_after: NaiveDate::from_ymd_opt(2020, 1, 1),
+            search: Some("Yosemite".to_string()),
+            sort: PhotoSort::LastShown,
+            order: Some(SortOrder::Asc),
+            ..Default::default()
+        }
+        .page(40, 20);
+        let encoded = serde_urlencoded::to_string(&query).unwrap();
+        assert!(encoded.contains("sort=last_shown"));
+        let decoded: PhotoQuery = serde_urlencoded::from_str(&encoded).unwrap();
+        assert_eq!(decoded, query);
+        assert_eq!(decoded.order(), SortOrder::Asc);
+    }
+
+    #[test]
+    fn test_photo_query_defaults() {
+        let query: PhotoQuery = serde_urlencoded::from_str("search=%20%20").unwrap();
+        assert_eq!(query.sort, PhotoSort::Created);
+        assert_eq!(query.order(), SortOrder::Desc);
+        assert!(query.search_text().is_none());
+        assert!(!query.without_album);
+        assert!(query.limit.is_none());
+
+        let query = PhotoQuery {
+            sort: PhotoSort::Title,
+            ..Default::default()
+        };
+        assert_eq!(query.order(), SortOrder::Asc);
+    }
+
+    #[test]
+    fn test_photo_page_next_offset() {
+        let page = |offset, count, total| PhotoPage {
+            photos: vec![photo(); count],
+            total,
+            offset,
+        };
+        assert_eq!(page(0, 2, 5).next_offset(), Some(2));
+        assert_eq!(page(2, 2, 5).next_offset(), Some(4));
+        assert_eq!(page(4, 1, 5).next_offset(), None);
+        assert_eq!(page(10, 0, 5).next_offset(), None);
+    }
+
+    #[test]
+    fn test_bulk_photo_operation_is_tagged_by_kind() {
+        let json = r#"{"photos":[1,2],"operation":{"kind":"add_to_albums","albums":[3]}}"#;
+        let req: BulkPhotoRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.photos, vec![PhotoID(1), PhotoID(2)]);
+        assert!(matches!(
+            req.operation,
+            BulkPhotoOperation::AddToAlbums { ref albums } if albums == &[AlbumID(3)]
+        ));
+
+        let json = serde_json::to_string(&BulkPhotoOperation::Delete).unwrap();
+        assert_eq!(json, r#"{"kind":"delete"}"#);
+    }
+
+    #[test]
+    fn test_smart_rule_deserializes_with_missing_fields() {
+        let rule: SmartRule = serde_json::from_str(r#"{"artist":"Adams"}"#).unwrap();
+        assert_eq!(rule.artist.as_deref(), Some("Adams"));
+        assert!(rule.orientation.is_none());
+    }
+
     #[test]
     fn test_mat_style_deserialization() {
         let json = r##"{"name":"custom","background_color":"#000","padding":"1rem","shadow":null,"inner_border":null}"##;
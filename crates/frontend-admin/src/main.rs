@@ -1,11 +1,19 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
 use leptos::{prelude::*, task::spawn_local};
 use pictureframe_common::{
-    Album, AlbumID, Client, CreateAlbumRequest, MatStyle, Photo, RotationSettings, Update,
-    UpdatePhotoRequest, UpdateSettingsRequest,
+    Album, AlbumEnd, AlbumID, AlbumPhotoSort, BackupQuery, BulkPhotoOperation, BulkPhotoRequest,
+    Client, CreateAlbumRequest, CreateDisplayRequest, Display, DisplayID, DuplicateError,
+    DuplicateGroup, Job, JobStatus, MAX_PHOTO_FIELD_LEN, MAX_PHOTO_NOTES_LEN, MONTH_NAMES,
+    MatStyle, MergePhotosRequest, MoveAlbumPhotoRequest, Photo, PhotoID, PhotoOrientation,
+    PhotoPage, PhotoQuery, PhotoSort, ReorderAlbumRequest, RotationMode, RotationSettings,
+    Schedule, ScheduleAction, ScheduleID, ScheduleRequest, SearchResults, SmartRule,
+    SortAlbumRequest, SortOrder, SuggestionID, Tag, TagMatch, TagPhotosRequest, TagSuggestion,
+    TrashedPhoto, Update, UpdateAlbumRequest, UpdateDisplayRequest, UpdatePhotoRequest,
+    UpdateSettingsRequest, VirtualAlbum,
 };
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{FormData, HtmlInputElement, Request, RequestInit, Response};
+use web_sys::{FormData, Request, RequestInit, Response};
 
 fn main() {
     _ = console_log::init_with_level(log::Level::Debug);
@@ -16,7 +24,13 @@ fn main() {
 #[derive(Clone, Copy, PartialEq)]
 enum Tab {
     Photos,
+    Search,
     Albums,
+    Duplicates,
+    Trash,
+    Suggestions,
+    Displays,
+    Schedules,
     Settings,
 }
 
@@ -28,6 +42,9 @@ fn App() -> impl IntoView {
 
     // Shared state for photos and albums
     let (photos, set_photos) = signal(Vec::<Photo>::new());
+    // Bumped whenever the library changes, so views showing photos ask for them again
+    let (photos_changed, set_photos_changed) = signal(0u32);
+    let (tags, set_tags) = signal(Vec::<Tag>::new());
     let (albums, set_albums) = signal(Vec::<Album>::new());
     let (settings, set_settings) = signal(None::<RotationSettings>);
 
@@ -35,16 +52,35 @@ fn App() -> impl IntoView {
     let refresh_photos = {
         let client = client.clone();
         move || {
+            *set_photos_changed.write() += 1;
             let client = client.clone();
             spawn_local(async move {
-                match client.get_photos().await {
-                    Ok(p) => set_photos.set(p),
-                    Err(e) => log::error!("Failed to fetch photos: {:?}", e),
+                match client.get_tags().await {
+                    Ok(t) => set_tags.set(t),
+                    Err(e) => log::error!("Failed to fetch tags: {:?}", e),
                 }
             });
         }
     };
 
+    // Only the tabs that pick photos from the whole library need all of it
+    Effect::new({
+        let client = client.clone();
+        move |_| {
+            photos_changed.track();
+            if !matches!(active_tab.get(), Tab::Albums | Tab::Suggestions) {
+                return;
+            }
+            let client = client.clone();
+            spawn_local(async move {
+                match client.get_photos(&PhotoQuery::default()).await {
+                    Ok(p) => set_photos.set(p.photos),
+                    Err(e) => log::error!("Failed to fetch photos: {:?}", e),
+                }
+            });
+        }
+    });
+
     let refresh_albums = {
         let client = client.clone();
         move || {
@@ -90,7 +126,13 @@ fn App() -> impl IntoView {
             // Tab navigation
             <nav style="display: flex; gap: 0.5rem; margin-bottom: 1.5rem; border-bottom: 2px solid #e0e0e0; padding-bottom: 0.5rem;">
                 <TabButton tab=Tab::Photos active=active_tab set_active=set_active_tab label="Photos" />
+                <TabButton tab=Tab::Search active=active_tab set_active=set_active_tab label="Search" />
                 <TabButton tab=Tab::Albums active=active_tab set_active=set_active_tab label="Albums" />
+                <TabButton tab=Tab::Duplicates active=active_tab set_active=set_active_tab label="Duplicates" />
+                <TabButton tab=Tab::Trash active=active_tab set_active=set_active_tab label="Trash" />
+                <TabButton tab=Tab::Suggestions active=active_tab set_active=set_active_tab label="Suggested Tags" />
+                <TabButton tab=Tab::Displays active=active_tab set_active=set_active_tab label="Displays" />
+                <TabButton tab=Tab::Schedules active=active_tab set_active=set_active_tab label="Schedules" />
                 <TabButton tab=Tab::Settings active=active_tab set_active=set_active_tab label="Settings" />
             </nav>
 
@@ -98,11 +140,16 @@ fn App() -> impl IntoView {
             {move || match active_tab.get() {
                 Tab::Photos => view! {
                     <PhotosTab
-                        photos=photos
+                        photos_changed=photos_changed
+                        tags=tags
+                        albums=albums
                         client=client.clone()
                         on_refresh=refresh_photos.clone()
                     />
                 }.into_any(),
+                Tab::Search => view! {
+                    <SearchTab client=client.clone() />
+                }.into_any(),
                 Tab::Albums => view! {
                     <AlbumsTab
                         albums=albums
@@ -111,6 +158,51 @@ fn App() -> impl IntoView {
                         on_refresh_albums=refresh_albums.clone()
                     />
                 }.into_any(),
+                Tab::Duplicates => view! {
+                    <DuplicatesTab
+                        client=client.clone()
+                        on_merge={
+                            let refresh_photos = refresh_photos.clone();
+                            let refresh_albums = refresh_albums.clone();
+                            move || {
+                                refresh_photos();
+                                refresh_albums();
+                            }
+                        }
+                    />
+                }.into_any(),
+                Tab::Trash => view! {
+                    <TrashTab
+                        client=client.clone()
+                        on_restore={
+                            let refresh_photos = refresh_photos.clone();
+                            let refresh_albums = refresh_albums.clone();
+                            move || {
+                                refresh_photos();
+                                refresh_albums();
+                            }
+                        }
+                    />
+                }.into_any(),
+                Tab::Suggestions => view! {
+                    <SuggestionsTab
+                        photos=photos
+                        client=client.clone()
+                        on_accept=refresh_photos.clone()
+                    />
+                }.into_any(),
+                Tab::Displays => view! {
+                    <DisplaysTab
+                        albums=albums
+                        client=client.clone()
+                    />
+                }.into_any(),
+                Tab::Schedules => view! {
+                    <SchedulesTab
+                        albums=albums
+                        client=client.clone()
+                    />
+                }.into_any(),
                 Tab::Settings => view! {
                     <SettingsTab
                         settings=settings
@@ -152,21 +244,33 @@ fn TabButton(
 // ─────────────────────────────────────────────────────────────────────────────
 
 #[component]
-fn PhotosTab<F>(photos: ReadSignal<Vec<Photo>>, client: Client, on_refresh: F) -> impl IntoView
+fn PhotosTab<F>(
+    photos_changed: ReadSignal<u32>,
+    tags: ReadSignal<Vec<Tag>>,
+    albums: ReadSignal<Vec<Album>>,
+    client: Client,
+    on_refresh: F,
+) -> impl IntoView
 where
     F: Fn() + Clone + Send + 'static,
 {
     let (upload_status, set_upload_status) = signal(None::<String>);
+    // Filters and sort for the grid. The offset is kept apart so a new filter starts at page one
+    let query = RwSignal::new(PhotoQuery::default());
+    let (offset, set_offset) = signal(0u32);
+    let (page, set_page) = signal(None::<PhotoPage>);
     let (upload_error, set_upload_error) = signal(None::<String>);
+    // Photos picked for a bulk action. Kept when changing page or filters
+    let selection = RwSignal::new(Vec::<PhotoID>::new());
     let file_input_ref = NodeRef::<leptos::html::Input>::new();
 
     let handle_file_change = {
+        let client = client.clone();
         let on_refresh = on_refresh.clone();
         move |_| {
             let Some(input) = file_input_ref.get() else {
                 return;
             };
-            let input: HtmlInputElement = input.into();
             let Some(files) = input.files() else {
                 return;
             };
@@ -183,18 +287,46 @@ where
             set_upload_error.set(None);
             let on_refresh = on_refresh.clone();
 
+            let client = client.clone();
             spawn_local(async move {
                 let mut errors: Vec<String> = Vec::new();
+                let mut jobs: Vec<Job> = Vec::new();
                 let total = files.len();
 
                 for (i, file) in files.into_iter().enumerate() {
                     set_upload_status.set(Some(format!("Uploading {}/{}...", i + 1, total)));
 
-                    if let Err(e) = upload_photo(file).await {
-                        log::error!("Upload failed: {}", e);
-                        errors.push(e);
+                    match upload_photo(file).await {
+                        Ok(job) => jobs.push(job),
+                        Err(e) => {
+                            log::error!("Upload failed: {}", e);
+                            errors.push(e);
+                        }
+                    }
+                }
+
+                // The server processes uploads in the background. Poll until they're all finished
+                let queued = jobs.len();
+                while jobs.iter().any(|job| !job.status.is_finished()) {
+                    let finished = jobs.iter().filter(|job| job.status.is_finished()).count();
+                    set_upload_status.set(Some(format!("Processing {}/{}...", finished, queued)));
+                    sleep(JOB_POLL_INTERVAL_MS).await;
+
+                    for job in jobs.iter_mut().filter(|job| !job.status.is_finished()) {
+                        match client.get_job(job.id).await {
+                            Ok(updated) => *job = updated,
+                            Err(e) => log::error!("Failed to get job {}: {}", job.id, e),
+                        }
                     }
                 }
+                for job in jobs.iter().filter(|job| job.status == JobStatus::Failed) {
+                    let error = job
+                        .error
+                        .clone()
+                        .unwrap_or_else(|| "Processing failed".to_string());
+                    log::error!("Processing {} failed: {}", job.filename, error);
+                    errors.push(format!("{}: {}", job.filename, error));
+                }
 
                 set_upload_status.set(None);
 
@@ -214,7 +346,6 @@ where
 
                 // Clear the input so the same files can be selected again
                 if let Some(input) = file_input_ref.get() {
-                    let input: HtmlInputElement = input.into();
                     input.set_value("");
                 }
             });
@@ -223,22 +354,52 @@ where
 
     let trigger_upload = move |_| {
         if let Some(input) = file_input_ref.get() {
-            let input: HtmlInputElement = input.into();
             input.click();
         }
     };
 
     let is_uploading = move || upload_status.get().is_some();
 
+    // Ask the server again whenever the filters, the page or the library change
+    Effect::new({
+        let client = client.clone();
+        move |_| {
+            let query = query.get().page(offset.get(), PHOTOS_PER_PAGE);
+            photos_changed.track();
+            let client = client.clone();
+            spawn_local(async move {
+                match client.get_photos(&query).await {
+                    // Deleting the last photo on the last page leaves nothing to show
+                    Ok(p) if p.photos.is_empty() && p.offset > 0 => {
+                        set_offset.set(p.offset.saturating_sub(PHOTOS_PER_PAGE));
+                    }
+                    Ok(p) => set_page.set(Some(p)),
+                    Err(e) => log::error!("Failed to get photos: {:?}", e),
+                }
+            });
+        }
+    });
+    let shown = move || page.get().map(|p| p.photos).unwrap_or_default();
+    let total = move || page.get().map_or(0, |p| p.total);
+    // Sorting doesn't hide any photos
+    let unfiltered = move || {
+        let q = query.get();
+        q == PhotoQuery {
+            sort: q.sort,
+            order: q.order,
+            ..Default::default()
+        }
+    };
+
     view! {
         <div>
             <div style="display: flex; justify-content: space-between; align-items: center; margin-bottom: 1rem;">
-                <h2 style="margin: 0;">"Photos (" {move || photos.get().len()} ")"</h2>
+                <h2 style="margin: 0;">"Photos (" {total} ")"</h2>
                 <div style="display: flex; gap: 0.5rem;">
                     // Hidden file input (multiple files allowed)
                     <input
                         type="file"
-                        accept="image/jpeg"
+                        accept="image/jpeg,image/png,image/webp,image/tiff,image/heic,image/heif,.heic,.heif"
                         multiple=true
                         node_ref=file_input_ref
                         style="display: none;"
@@ -273,20 +434,157 @@ where
                 </div>
             })}
 
+            // Filters
+            <div style="display: flex; flex-wrap: wrap; gap: 0.5rem; align-items: center; margin-bottom: 1rem;">
+                <input
+                    type="search"
+                    placeholder="Search titles, artists and notes..."
+                    style="flex: 1; min-width: 12rem; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                    on:change=move |ev| edit_query(query, set_offset, |q| q.search = non_empty(event_target_value(&ev)))
+                />
+                <input
+                    type="text"
+                    placeholder="Filter by tags, comma separated..."
+                    list="tag-names"
+                    style="flex: 1; min-width: 12rem; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                    on:change=move |ev| edit_query(query, set_offset, |q| q.tags = non_empty(event_target_value(&ev)))
+                />
+                <select
+                    style="padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                    on:change=move |ev| {
+                        let tag_match = if event_target_value(&ev) == "any" { TagMatch::Any } else { TagMatch::All };
+                        edit_query(query, set_offset, |q| q.tag_match = tag_match)
+                    }
+                >
+                    <option value="all">"Every tag"</option>
+                    <option value="any">"Any tag"</option>
+                </select>
+            </div>
+            <div style="display: flex; flex-wrap: wrap; gap: 0.5rem; align-items: center; margin-bottom: 1rem; font-size: 0.9rem;">
+                <select
+                    style="padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        edit_query(query, set_offset, |q| {
+                            q.without_album = value == "none";
+                            q.album = value.parse().ok();
+                        })
+                    }
+                >
+                    <option value="">"In any album or none"</option>
+                    <option value="none">"Not in any album"</option>
+                    {move || albums.get().into_iter().map(|album| view! {
+                        <option value=album.id.0.to_string()>{album.name}</option>
+                    }).collect::<Vec<_>>()}
+                </select>
+                <label>"Taken from"</label>
+                <input
+                    type="date"
+                    style="padding: 0.4rem; border: 1px solid #ccc; border-radius: 4px;"
+                    on:change=move |ev| edit_query(query, set_offset, |q| q.taken_after = parse_date_input(&event_target_value(&ev)))
+                />
+                <label>"to"</label>
+                <input
+                    type="date"
+                    style="padding: 0.4rem; border: 1px solid #ccc; border-radius: 4px;"
+                    on:change=move |ev| edit_query(query, set_offset, |q| q.taken_before = parse_date_input(&event_target_value(&ev)))
+                />
+                <label style="margin-left: auto;">"Sort by"</label>
+                <select
+                    style="padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                    on:change=move |ev| {
+                        let sort = match event_target_value(&ev).as_str() {
+                            "taken" => PhotoSort::Taken,
+                            "title" => PhotoSort::Title,
+                            "last_shown" => PhotoSort::LastShown,
+                            _ => PhotoSort::Created,
+                        };
+                        edit_query(query, set_offset, |q| {
+                            q.sort = sort;
+                            q.order = None;
+                        })
+                    }
+                >
+                    <option value="created">"Date added"</option>
+                    <option value="taken">"Date taken"</option>
+                    <option value="title">"Title"</option>
+                    <option value="last_shown">"Last shown"</option>
+                </select>
+                <select
+                    style="padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                    prop:value=move || if query.get().order() == SortOrder::Asc { "asc" } else { "desc" }
+                    on:change=move |ev| {
+                        let order = if event_target_value(&ev) == "asc" { SortOrder::Asc } else { SortOrder::Desc };
+                        edit_query(query, set_offset, |q| q.order = Some(order))
+                    }
+                >
+                    <option value="asc">"Ascending"</option>
+                    <option value="desc">"Descending"</option>
+                </select>
+            </div>
+            <datalist id="tag-names">
+                {move || tags.get().into_iter().map(|tag| view! {
+                    <option value=tag.name />
+                }).collect::<Vec<_>>()}
+            </datalist>
+
+            <BulkActions
+                selection=selection
+                shown=Signal::derive(move || shown().iter().map(|p| p.id).collect())
+                albums=albums
+                client=client.clone()
+                on_done=on_refresh.clone()
+            />
+
             <div style="display: grid; grid-template-columns: repeat(auto-fill, minmax(200px, 1fr)); gap: 1rem;">
-                {move || photos.get().into_iter().map(|photo| {
+                {move || shown().into_iter().map(|photo| {
                     let client = client.clone();
                     let on_refresh = on_refresh.clone();
-                    view! { <PhotoCard photo=photo client=client on_delete=on_refresh /> }
+                    view! { <PhotoCard photo=photo client=client selection=selection on_delete=on_refresh /> }
                 }).collect::<Vec<_>>()}
             </div>
 
-            {move || if photos.get().is_empty() {
+            // Paging
+            {move || page.get().filter(|p| !p.photos.is_empty()).map(|p| {
+                let previous = p.offset.checked_sub(PHOTOS_PER_PAGE).or((p.offset > 0).then_some(0));
+                let next = p.next_offset();
+                view! {
+                    <div style="display: flex; justify-content: center; align-items: center; gap: 1rem; margin-top: 1rem;">
+                        <button
+                            style="padding: 0.25rem 0.75rem; border: 1px solid #ccc; border-radius: 4px; background: white; cursor: pointer;"
+                            disabled=previous.is_none()
+                            on:click=move |_| if let Some(previous) = previous { set_offset.set(previous) }
+                        >
+                            "Previous"
+                        </button>
+                        <span style="color: #666; font-size: 0.9rem;">
+                            {format!("{}-{} of {}", p.offset + 1, p.offset + p.photos.len() as u32, p.total)}
+                        </span>
+                        <button
+                            style="padding: 0.25rem 0.75rem; border: 1px solid #ccc; border-radius: 4px; background: white; cursor: pointer;"
+                            disabled=next.is_none()
+                            on:click=move |_| if let Some(next) = next { set_offset.set(next) }
+                        >
+                            "Next"
+                        </button>
+                    </div>
+                }
+            })}
+
+            {move || if page.get().is_none() {
+                None
+            } else if total() == 0 && unfiltered() {
                 Some(view! {
                     <p style="color: #666; text-align: center; padding: 2rem;">
                         "No photos yet. Click \"Upload Photo\" to add your first photo."
                     </p>
                 })
+            } else if total() == 0 {
+                Some(view! {
+                    <p style="color: #666; text-align: center; padding: 2rem;">
+                        "No photos match these filters."
+                    </p>
+                })
             } else {
                 None
             }}
@@ -294,8 +592,302 @@ where
     }
 }
 
+/// Bar of actions for the selected photos. Each action is one request, applied to every
+/// selected photo at once.
+#[component]
+fn BulkActions<F>(
+    selection: RwSignal<Vec<PhotoID>>,
+    /// Photos on the current page, for "Select page"
+    shown: Signal<Vec<PhotoID>>,
+    albums: ReadSignal<Vec<Album>>,
+    client: Client,
+    on_done: F,
+) -> impl IntoView
+where
+    F: Fn() + Clone + Send + 'static,
+{
+    let (busy, set_busy) = signal(false);
+    let (status, set_status) = signal(None::<String>);
+    let (album, set_album) = signal(None::<AlbumID>);
+    let (mat_preset, set_mat_preset) = signal(MatStyle::preset_names()[0].to_string());
+    let (tag, set_tag) = signal(String::new());
+    let (field, set_field) = signal("artist".to_string());
+    let (value, set_value) = signal(String::new());
+
+    let run = move |operation: BulkPhotoOperation| {
+        let photos = selection.get_untracked();
+        if photos.is_empty() || busy.get_untracked() {
+            return;
+        }
+        let deleting = matches!(operation, BulkPhotoOperation::Delete);
+        set_busy.set(true);
+        set_status.set(None);
+        let client = client.clone();
+        let on_done = on_done.clone();
+        spawn_local(async move {
+            let req = BulkPhotoRequest { photos, operation };
+            match client.bulk_photos(&req).await {
+                Ok(results) => {
+                    let skipped: Vec<_> = results.iter().filter_map(|r| r.error.clone()).collect();
+                    if let Some(first) = skipped.first() {
+                        set_status.set(Some(format!(
+                            "{} of {} skipped: {}",
+                            skipped.len(),
+                            results.len(),
+                            first
+                        )));
+                    }
+                    if deleting {
+                        selection.set(Vec::new());
+                    }
+                    on_done();
+                }
+                Err(e) => set_status.set(Some(e.to_string())),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    // Albums photos can be added to by hand
+    let manual_albums = move || {
+        albums
+            .get()
+            .into_iter()
+            .filter(|a| a.virtual_album.is_none() && a.smart_rule.is_none())
+            .collect::<Vec<_>>()
+    };
+    let button_style = "padding: 0.25rem 0.5rem; border: 1px solid #ccc; border-radius: 4px; background: white; cursor: pointer; font-size: 0.85rem;";
+    let input_style =
+        "padding: 0.25rem; border: 1px solid #ccc; border-radius: 4px; font-size: 0.85rem;";
+
+    view! {
+        <div style="display: flex; flex-wrap: wrap; gap: 0.5rem; align-items: center; margin-bottom: 1rem; padding: 0.5rem; background: #f5f5f5; border-radius: 4px; font-size: 0.9rem;">
+            <strong>{move || format!("{} selected", selection.get().len())}</strong>
+            <button
+                style=button_style
+                on:click=move |_| {
+                    let mut selected = selection.get();
+                    selected.extend(shown.get().into_iter().filter(|id| !selected.contains(id)).collect::<Vec<_>>());
+                    selection.set(selected);
+                }
+            >
+                "Select page"
+            </button>
+            <button style=button_style on:click=move |_| selection.set(Vec::new())>"Clear"</button>
+
+            {move || (!selection.get().is_empty()).then(|| {
+                let run = run.clone();
+                // Click handlers for the actions that need the picked album or the typed tag
+                let with_album = |make: fn(Vec<AlbumID>) -> BulkPhotoOperation| {
+                    let run = run.clone();
+                    move |_| {
+                        if let Some(album) = album.get_untracked() {
+                            run(make(vec![album]));
+                        }
+                    }
+                };
+                let with_tag = |make: fn(Vec<String>) -> BulkPhotoOperation| {
+                    let run = run.clone();
+                    move |_| {
+                        let tag = tag.get_untracked();
+                        if !tag.trim().is_empty() {
+                            run(make(vec![tag]));
+                        }
+                    }
+                };
+                view! {
+                    <select
+                        style=input_style
+                        on:change=move |ev| set_album.set(event_target_value(&ev).parse().ok().map(AlbumID))
+                    >
+                        <option value="" selected=move || album.get().is_none()>"Album..."</option>
+                        {manual_albums().into_iter().map(|a| view! {
+                            <option value=a.id.0.to_string() selected=move || album.get() == Some(a.id)>{a.name}</option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                    <button style=button_style disabled=move || busy.get() || album.get().is_none() on:click=with_album(|albums| BulkPhotoOperation::AddToAlbums { albums })>
+                        "Add to album"
+                    </button>
+                    <button style=button_style disabled=move || busy.get() || album.get().is_none() on:click=with_album(|albums| BulkPhotoOperation::RemoveFromAlbums { albums })>
+                        "Remove from album"
+                    </button>
+
+                    <select style=input_style on:change=move |ev| set_mat_preset.set(event_target_value(&ev))>
+                        {MatStyle::preset_names().iter().map(|&name| view! {
+                            <option value=name selected=move || mat_preset.get() == name>{name}</option>
+                        }).collect::<Vec<_>>()}
+                    </select>
+                    <button
+                        style=button_style
+                        disabled=move || busy.get()
+                        on:click={
+                            let run = run.clone();
+                            move |_| run(BulkPhotoOperation::SetMatPreset { mat_preset: mat_preset.get_untracked() })
+                        }
+                    >
+                        "Set mat"
+                    </button>
+
+                    <input
+                        type="text"
+                        placeholder="Tag"
+                        list="tag-names"
+                        style=format!("{input_style} width: 6rem;")
+                        prop:value=move || tag.get()
+                        on:input=move |ev| set_tag.set(event_target_value(&ev))
+                    />
+                    <button style=button_style disabled=move || busy.get() on:click=with_tag(|tags| BulkPhotoOperation::AttachTags { tags })>"Tag"</button>
+                    <button style=button_style disabled=move || busy.get() on:click=with_tag(|tags| BulkPhotoOperation::DetachTags { tags })>"Untag"</button>
+
+                    <select style=input_style on:change=move |ev| set_field.set(event_target_value(&ev))>
+                        <option value="artist">"Artist"</option>
+                        <option value="copyright">"Copyright"</option>
+                        <option value="title">"Title"</option>
+                    </select>
+                    <input
+                        type="text"
+                        placeholder="Blank to remove"
+                        style=format!("{input_style} width: 8rem;")
+                        prop:value=move || value.get()
+                        on:input=move |ev| set_value.set(event_target_value(&ev))
+                    />
+                    <button
+                        style=button_style
+                        disabled=move || busy.get()
+                        on:click={
+                            let run = run.clone();
+                            move |_| {
+                                let value = value.get_untracked();
+                                let update = Some(if value.trim().is_empty() { Update::Remove } else { Update::Set(value) });
+                                let mut details = UpdatePhotoRequest::default();
+                                match field.get_untracked().as_str() {
+                                    "title" => details.title = update,
+                                    "copyright" => details.copyright = update,
+                                    _ => details.artist = update,
+                                }
+                                run(BulkPhotoOperation::Update { details });
+                            }
+                        }
+                    >
+                        "Set"
+                    </button>
+
+                    <button
+                        style="padding: 0.25rem 0.5rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.85rem;"
+                        disabled=move || busy.get()
+                        on:click=move |_| run(BulkPhotoOperation::Delete)
+                    >
+                        "Move to trash"
+                    </button>
+                }
+            })}
+
+            {move || status.get().map(|s| view! { <span style="color: #c62828;">{s}</span> })}
+        </div>
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Search Tab
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[component]
+fn SearchTab(client: Client) -> impl IntoView {
+    let (text, set_text) = signal(String::new());
+    let (results, set_results) = signal(SearchResults::default());
+
+    let handle_input = move |ev| {
+        let q = event_target_value(&ev);
+        set_text.set(q.clone());
+        let client = client.clone();
+        spawn_local(async move {
+            match client.search(&q).await {
+                // Answers can arrive out of order while typing; keep the one for the latest text
+                Ok(r) if text.get_untracked() == q => set_results.set(r),
+                Ok(_) => {}
+                Err(e) => log::error!("Failed to search: {:?}", e),
+            }
+        });
+    };
+
+    view! {
+        <div>
+            <input
+                type="search"
+                placeholder="Search titles, artists, notes and album names..."
+                style="width: 100%; box-sizing: border-box; padding: 0.75rem; font-size: 1rem; border: 1px solid #ccc; border-radius: 4px; margin-bottom: 1rem;"
+                on:input=handle_input
+            />
+
+            {move || (!results.get().albums.is_empty()).then(|| view! {
+                <h3>"Albums"</h3>
+                <ul style="list-style: none; padding: 0;">
+                    {results.get().albums.into_iter().map(|result| view! {
+                        <li style="padding: 0.5rem 0; border-bottom: 1px solid #eee;">
+                            <strong>{result.album.name}</strong>
+                            <span style="color: #888; font-size: 0.85rem;">
+                                {format!(" ({} photos)", result.album.photos.len())}
+                            </span>
+                            <div style="font-size: 0.9rem; color: #555;" inner_html=result.snippet></div>
+                        </li>
+                    }).collect::<Vec<_>>()}
+                </ul>
+            })}
+
+            {move || (!results.get().photos.is_empty()).then(|| view! {
+                <h3>"Photos"</h3>
+                <div style="display: grid; grid-template-columns: repeat(auto-fill, minmax(180px, 1fr)); gap: 1rem;">
+                    {results.get().photos.into_iter().map(|result| view! {
+                        <div style="border: 1px solid #e0e0e0; border-radius: 8px; overflow: hidden; background: white;">
+                            <img
+                                src=result.photo.thumbnail_url.clone()
+                                style="width: 100%; height: 120px; object-fit: cover; display: block;"
+                                loading="lazy"
+                            />
+                            <div style="padding: 0.5rem; font-size: 0.85rem;" inner_html=result.snippet></div>
+                        </div>
+                    }).collect::<Vec<_>>()}
+                </div>
+            })}
+
+            {move || (results.get().is_empty() && !text.get().trim().is_empty()).then(|| view! {
+                <p style="color: #666; text-align: center; padding: 2rem;">"Nothing found."</p>
+            })}
+        </div>
+    }
+}
+
+/// Photos shown on each page of the Photos tab
+const PHOTOS_PER_PAGE: u32 = 48;
+
+/// Change one filter of the photo grid and go back to the first page
+fn edit_query(
+    query: RwSignal<PhotoQuery>,
+    set_offset: WriteSignal<u32>,
+    edit: impl FnOnce(&mut PhotoQuery),
+) {
+    let mut updated = query.get_untracked();
+    edit(&mut updated);
+    set_offset.set(0);
+    query.set(updated);
+}
+
+/// How often to check on uploads that are still being processed
+const JOB_POLL_INTERVAL_MS: i32 = 1000;
+
+/// Wait for `ms` milliseconds without blocking the UI.
+async fn sleep(ms: i32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        if let Some(window) = web_sys::window() {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms);
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
 /// Upload a photo file to the server via multipart form data.
-async fn upload_photo(file: web_sys::File) -> Result<(), String> {
+/// Returns the job that will process the photo in the background.
+async fn upload_photo(file: web_sys::File) -> Result<Job, String> {
     let form_data = FormData::new().map_err(|e| format!("Failed to create FormData: {:?}", e))?;
     form_data
         .append_with_blob_and_filename("file", &file, &file.name())
@@ -324,10 +916,24 @@ async fn upload_photo(file: web_sys::File) -> Result<(), String> {
             .map_err(|_| "Failed to read response body")?
             .as_string()
             .unwrap_or_default();
+        if status == 409
+            && let Ok(duplicate) = serde_json::from_str::<DuplicateError>(&body)
+        {
+            return Err(format!(
+                "{} is already in the library (photo {})",
+                file.name(),
+                duplicate.existing
+            ));
+        }
         return Err(format!("Server error ({}): {}", status, body));
     }
 
-    Ok(())
+    let body = JsFuture::from(resp.text().map_err(|_| "Failed to get response text")?)
+        .await
+        .map_err(|_| "Failed to read response body")?
+        .as_string()
+        .unwrap_or_default();
+    serde_json::from_str(&body).map_err(|e| format!("Unexpected upload response: {}", e))
 }
 
 /// Visual preview of a mat style - shows a small square with the mat's background color,
@@ -418,7 +1024,13 @@ where
 }
 
 #[component]
-fn PhotoCard<F>(photo: Photo, client: Client, on_delete: F) -> impl IntoView
+fn PhotoCard<F>(
+    photo: Photo,
+    client: Client,
+    /// Photos picked for a bulk action
+    selection: RwSignal<Vec<PhotoID>>,
+    on_delete: F,
+) -> impl IntoView
 where
     F: Fn() + Clone + Send + 'static,
 {
@@ -426,6 +1038,33 @@ where
     let current_preset = photo.mat_preset.clone();
     let (deleting, set_deleting) = signal(false);
     let (updating_mat, set_updating_mat) = signal(false);
+    let (new_tag, set_new_tag) = signal(String::new());
+    let (editing, set_editing) = signal(false);
+
+    // Add or remove one tag on this photo
+    let edit_tag = {
+        let client = client.clone();
+        let on_refresh = on_delete.clone();
+        move |tag: String, attach: bool| {
+            let client = client.clone();
+            let on_refresh = on_refresh.clone();
+            spawn_local(async move {
+                let req = TagPhotosRequest {
+                    photos: vec![photo_id],
+                    tags: vec![tag],
+                };
+                let result = if attach {
+                    client.attach_tags(&req).await
+                } else {
+                    client.detach_tags(&req).await
+                };
+                match result {
+                    Ok(_) => on_refresh(),
+                    Err(e) => log::error!("Failed to update tags: {:?}", e),
+                }
+            });
+        }
+    };
 
     let handle_delete = {
         let client = client.clone();
@@ -447,6 +1086,19 @@ where
         }
     };
 
+    // Queue the classifier; its suggestions show up in the Suggested Tags tab
+    let handle_classify = {
+        let client = client.clone();
+        move |_| {
+            let client = client.clone();
+            spawn_local(async move {
+                if let Err(e) = client.classify_photo(photo_id).await {
+                    log::error!("Failed to classify photo: {:?}", e);
+                }
+            });
+        }
+    };
+
     let handle_mat_change = {
         let client = client.clone();
         let on_refresh = on_delete.clone();
@@ -462,6 +1114,7 @@ where
                     title: None,
                     artist: None,
                     copyright: None,
+                    notes: None,
                     date_taken: None,
                     mat_preset: Some(preset),
                 };
@@ -477,7 +1130,7 @@ where
     // Get mat style for the thumbnail preview
     let mat_style = MatStyle::from_preset(&photo.mat_preset);
     let thumbnail_container_style = format!(
-        "background: {}; padding: 8px;{}",
+        "position: relative; background: {}; padding: 8px;{}",
         mat_style.background_color,
         mat_style.shadow.as_ref().map(|s| format!(" box-shadow: {};", s)).unwrap_or_default()
     );
@@ -487,18 +1140,96 @@ where
             // Photo thumbnail with mat preview effect
             <div style=thumbnail_container_style>
                 <img
-                    src=photo.url.clone()
+                    src=photo.thumbnail_url.clone()
                     style="width: 100%; height: 134px; object-fit: cover; display: block;"
                     loading="lazy"
                 />
+                <input
+                    type="checkbox"
+                    title="Select"
+                    style="position: absolute; top: 12px; left: 12px; width: 1.1rem; height: 1.1rem; cursor: pointer;"
+                    prop:checked=move || selection.get().contains(&photo_id)
+                    on:change=move |_| selection.set(toggled(selection.get(), photo_id))
+                />
             </div>
             <div style="padding: 0.75rem;">
-                <div style="font-weight: 500; margin-bottom: 0.25rem; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
-                    {photo.title.clone().unwrap_or_else(|| format!("Photo {}", photo.id.0))}
-                </div>
-                {photo.artist.map(|a| view! {
-                    <div style="font-size: 0.85rem; color: #666;">{a}</div>
+                {
+                    let photo = photo.clone();
+                    let client = client.clone();
+                    let on_saved = on_delete.clone();
+                    move || if editing.get() {
+                        let on_saved = on_saved.clone();
+                        view! {
+                            <PhotoDetailsEditor
+                                photo=photo.clone()
+                                client=client.clone()
+                                on_saved=move || {
+                                    set_editing.set(false);
+                                    on_saved();
+                                }
+                                on_cancel=move || set_editing.set(false)
+                            />
+                        }.into_any()
+                    } else {
+                        view! {
+                            <div style="font-weight: 500; margin-bottom: 0.25rem; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                {photo.title.clone().unwrap_or_else(|| format!("Photo {}", photo.id.0))}
+                            </div>
+                            {photo.artist.clone().map(|a| view! {
+                                <div style="font-size: 0.85rem; color: #666;">{a}</div>
+                            })}
+                            {photo.date_taken.map(|d| view! {
+                                <div style="font-size: 0.8rem; color: #888;">{d.format("%-d %b %Y").to_string()}</div>
+                            })}
+                            {photo.notes.clone().map(|n| view! {
+                                <div style="font-size: 0.8rem; color: #555; margin-top: 0.25rem; white-space: pre-wrap;">{n}</div>
+                            })}
+                        }.into_any()
+                    }
+                }
+                {photo.exif.camera().map(|c| view! {
+                    <div style="font-size: 0.8rem; color: #888; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">{c}</div>
                 })}
+                {photo.exif.exposure_summary().map(|e| view! {
+                    <div style="font-size: 0.8rem; color: #888;">{e}</div>
+                })}
+
+                // Tags
+                <div style="display: flex; flex-wrap: wrap; gap: 4px; margin-top: 0.5rem;">
+                    {photo.tags.iter().map(|tag| {
+                        let edit_tag = edit_tag.clone();
+                        let tag = tag.clone();
+                        view! {
+                            <span style="font-size: 0.75rem; padding: 2px 6px; background: #e3f2fd; border-radius: 10px;">
+                                {tag.clone()}
+                                <button
+                                    style="margin-left: 4px; border: none; background: none; cursor: pointer; padding: 0; color: #666;"
+                                    on:click=move |_| edit_tag(tag.clone(), false)
+                                >
+                                    "×"
+                                </button>
+                            </span>
+                        }
+                    }).collect::<Vec<_>>()}
+                    <input
+                        type="text"
+                        placeholder="Add tag"
+                        list="tag-names"
+                        style="width: 6rem; font-size: 0.75rem; padding: 2px 4px; border: 1px solid #ccc; border-radius: 4px;"
+                        prop:value=move || new_tag.get()
+                        on:input=move |ev| set_new_tag.set(event_target_value(&ev))
+                        on:keydown={
+                            let edit_tag = edit_tag.clone();
+                            move |ev: leptos::ev::KeyboardEvent| {
+                                let tag = new_tag.get();
+                                if ev.key() == "Enter" && !tag.trim().is_empty() {
+                                    set_new_tag.set(String::new());
+                                    edit_tag(tag, true);
+                                }
+                            }
+                        }
+                    />
+                </div>
 
                 // Visual mat preset picker
                 <MatPresetPicker
@@ -507,13 +1238,35 @@ where
                     disabled=Signal::derive(move || updating_mat.get())
                 />
 
-                <button
-                    style="margin-top: 0.5rem; padding: 0.25rem 0.5rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
-                    on:click=handle_delete
-                    disabled=move || deleting.get()
-                >
-                    {move || if deleting.get() { "Deleting..." } else { "Delete" }}
-                </button>
+                <div style="display: flex; gap: 0.5rem; align-items: center; margin-top: 0.5rem;">
+                    <button
+                        style="padding: 0.25rem 0.5rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                        on:click=handle_delete
+                        disabled=move || deleting.get()
+                    >
+                        {move || if deleting.get() { "Deleting..." } else { "Move to trash" }}
+                    </button>
+                    <button
+                        style="padding: 0.25rem 0.5rem; background: #607D8B; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                        on:click=move |_| set_editing.set(true)
+                        disabled=move || editing.get()
+                    >
+                        "Edit details"
+                    </button>
+                    <button
+                        style="padding: 0.25rem 0.5rem; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                        on:click=handle_classify
+                    >
+                        "Suggest tags"
+                    </button>
+                    <a
+                        href=photo.full_url.clone()
+                        download=""
+                        style="font-size: 0.8rem; color: #2196F3;"
+                    >
+                        "Download original"
+                    </a>
+                </div>
             </div>
         </div>
     }
@@ -523,9 +1276,293 @@ where
 // Albums Tab
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Value of the album type select in the create album form. `None` is an album managed by hand
+/// Inline form for a photo's title, artist, copyright, notes and date taken
 #[component]
-fn AlbumsTab<F>(
-    albums: ReadSignal<Vec<Album>>,
+fn PhotoDetailsEditor<S, C>(
+    photo: Photo,
+    client: Client,
+    on_saved: S,
+    on_cancel: C,
+) -> impl IntoView
+where
+    S: Fn() + Clone + Send + 'static,
+    C: Fn() + Clone + Send + 'static,
+{
+    let title = RwSignal::new(photo.title.clone().unwrap_or_default());
+    let artist = RwSignal::new(photo.artist.clone().unwrap_or_default());
+    let copyright = RwSignal::new(photo.copyright.clone().unwrap_or_default());
+    let notes = RwSignal::new(photo.notes.clone().unwrap_or_default());
+    let date_taken = RwSignal::new(format_datetime_input(photo.date_taken));
+    let (saving, set_saving) = signal(false);
+    let (error, set_error) = signal(None::<String>);
+
+    let handle_save = move |_| {
+        let updates = UpdatePhotoRequest {
+            title: text_update(&photo.title, title.get()),
+            artist: text_update(&photo.artist, artist.get()),
+            copyright: text_update(&photo.copyright, copyright.get()),
+            notes: text_update(&photo.notes, notes.get()),
+            date_taken: if date_taken.get() == format_datetime_input(photo.date_taken) {
+                None
+            } else {
+                Some(parse_datetime_input(&date_taken.get()).map_or(Update::Remove, Update::Set))
+            },
+            mat_preset: None,
+        };
+        set_saving.set(true);
+        set_error.set(None);
+        let client = client.clone();
+        let on_saved = on_saved.clone();
+        let id = photo.id;
+        spawn_local(async move {
+            match client.update_photo(id, &updates).await {
+                Ok(_) => on_saved(),
+                Err(e) => set_error.set(Some(e.to_string())),
+            }
+            set_saving.set(false);
+        });
+    };
+
+    let input_style = "width: 100%; box-sizing: border-box; padding: 0.25rem; margin-bottom: 0.25rem; border: 1px solid #ccc; border-radius: 4px; font-size: 0.85rem;";
+    view! {
+        <div>
+            <input type="text" placeholder="Title" maxlength=MAX_PHOTO_FIELD_LEN style=input_style bind:value=title />
+            <input type="text" placeholder="Artist" maxlength=MAX_PHOTO_FIELD_LEN style=input_style bind:value=artist />
+            <input type="text" placeholder="Copyright" maxlength=MAX_PHOTO_FIELD_LEN style=input_style bind:value=copyright />
+            <textarea placeholder="Notes" maxlength=MAX_PHOTO_NOTES_LEN rows="3" style=input_style bind:value=notes></textarea>
+            <label style="font-size: 0.8rem; color: #666;">"Taken"</label>
+            <input type="datetime-local" style=input_style bind:value=date_taken />
+            {move || error.get().map(|e| view! {
+                <div style="color: #c62828; font-size: 0.8rem; margin-bottom: 0.25rem;">{e}</div>
+            })}
+            <div style="display: flex; gap: 0.5rem;">
+                <button
+                    style="padding: 0.25rem 0.5rem; background: #4CAF50; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                    on:click=handle_save
+                    disabled=move || saving.get()
+                >
+                    {move || if saving.get() { "Saving..." } else { "Save" }}
+                </button>
+                <button
+                    style="padding: 0.25rem 0.5rem; background: #eee; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                    on:click=move |_| on_cancel()
+                >
+                    "Cancel"
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/// The change to send for an edited text field, or None if it wasn't changed.
+/// Clearing the field removes the value.
+fn text_update(original: &Option<String>, edited: String) -> Option<Update<String>> {
+    if edited.trim() == original.as_deref().unwrap_or_default().trim() {
+        None
+    } else if edited.trim().is_empty() {
+        Some(Update::Remove)
+    } else {
+        Some(Update::Set(edited))
+    }
+}
+
+/// Parse the value of an `<input type="datetime-local">`, which may leave out the seconds
+fn parse_datetime_input(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+        .ok()
+}
+
+fn format_datetime_input(date: Option<NaiveDateTime>) -> String {
+    date.map(|d| d.format("%Y-%m-%dT%H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn parse_album_kind(value: &str) -> Option<VirtualAlbum> {
+    match value {
+        "on_this_day" => Some(VirtualAlbum::OnThisDay),
+        "this_week" => Some(VirtualAlbum::ThisWeek { years_ago: None }),
+        _ => {
+            let month = value.strip_prefix("month:")?.parse().ok()?;
+            Some(VirtualAlbum::TakenInMonth { month })
+        }
+    }
+}
+
+/// The ways the photos of an album can be put in a new order
+#[derive(Clone)]
+enum AlbumOrderChange {
+    Reorder(Vec<PhotoID>),
+    Move(PhotoID, AlbumEnd),
+    Sort(AlbumPhotoSort),
+}
+
+/// `order` with photo `from` moved into the place of photo `to`. The photos in between
+/// shift along one place to make room.
+fn moved(order: &[i32], from: i32, to: i32) -> Vec<PhotoID> {
+    let mut order = order.to_vec();
+    let from_index = order.iter().position(|id| *id == from);
+    let to_index = order.iter().position(|id| *id == to);
+    if let (Some(from_index), Some(to_index)) = (from_index, to_index) {
+        let photo = order.remove(from_index);
+        order.insert(to_index, photo);
+    }
+    order.into_iter().map(PhotoID).collect()
+}
+
+/// Change one condition of a smart rule
+fn edit_rule(rule: RwSignal<SmartRule>, edit: impl FnOnce(&mut SmartRule)) {
+    let mut updated = rule.get_untracked();
+    edit(&mut updated);
+    rule.set(updated);
+}
+
+/// Text inputs left blank mean "any"
+fn non_empty(value: String) -> Option<String> {
+    (!value.trim().is_empty()).then_some(value)
+}
+
+/// Parse the value of an `<input type="date">`. Empty means no date.
+fn parse_date_input(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+fn format_date_input(date: Option<NaiveDate>) -> String {
+    date.map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Form for the conditions of a smart album, with a live count of the photos they match
+#[component]
+fn SmartRuleEditor(rule: RwSignal<SmartRule>, photos: ReadSignal<Vec<Photo>>) -> impl IntoView {
+    let input_style = "padding: 0.4rem; border: 1px solid #ccc; border-radius: 4px;";
+    let matching = move || {
+        let rule = rule.get();
+        let now = chrono::Utc::now().naive_utc();
+        photos.get().iter().filter(|p| rule.matches(p, now)).count()
+    };
+
+    view! {
+        <div style="padding: 0.75rem; background: #f9f9f9; border-radius: 4px; margin-bottom: 1rem;">
+            <div style="display: grid; grid-template-columns: auto 1fr; gap: 0.5rem 0.75rem; align-items: center; font-size: 0.9rem;">
+                <label>"Artist contains"</label>
+                <input
+                    type="text"
+                    style=input_style
+                    prop:value=move || rule.get().artist.unwrap_or_default()
+                    on:input=move |ev| {
+                        let artist = non_empty(event_target_value(&ev));
+                        edit_rule(rule, |r| r.artist = artist);
+                    }
+                />
+
+                <label>"Camera contains"</label>
+                <input
+                    type="text"
+                    style=input_style
+                    prop:value=move || rule.get().camera.unwrap_or_default()
+                    on:input=move |ev| {
+                        let camera = non_empty(event_target_value(&ev));
+                        edit_rule(rule, |r| r.camera = camera);
+                    }
+                />
+
+                <label>"Tagged with all of"</label>
+                <input
+                    type="text"
+                    placeholder="Comma separated"
+                    list="tag-names"
+                    style=input_style
+                    prop:value=move || rule.get().tags.join(", ")
+                    on:change=move |ev| {
+                        let tags = PhotoQuery { tags: Some(event_target_value(&ev)), ..Default::default() }.tag_names();
+                        edit_rule(rule, |r| r.tags = tags);
+                    }
+                />
+
+                <label>"Taken between"</label>
+                <div style="display: flex; gap: 0.5rem; align-items: center;">
+                    <input
+                        type="date"
+                        style=input_style
+                        prop:value=move || format_date_input(rule.get().taken_after)
+                        on:change=move |ev| {
+                            let date = parse_date_input(&event_target_value(&ev));
+                            edit_rule(rule, |r| r.taken_after = date);
+                        }
+                    />
+                    "and"
+                    <input
+                        type="date"
+                        style=input_style
+                        prop:value=move || format_date_input(rule.get().taken_before)
+                        on:change=move |ev| {
+                            let date = parse_date_input(&event_target_value(&ev));
+                            edit_rule(rule, |r| r.taken_before = date);
+                        }
+                    />
+                </div>
+
+                <label>"Orientation"</label>
+                <select
+                    style=input_style
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        let orientation = PhotoOrientation::ALL.iter().copied().find(|o| o.label() == value);
+                        edit_rule(rule, |r| r.orientation = orientation);
+                    }
+                >
+                    <option value="any" selected=move || rule.get().orientation.is_none()>"Any"</option>
+                    {PhotoOrientation::ALL.iter().copied().map(|orientation| view! {
+                        <option value=orientation.label() selected=move || rule.get().orientation == Some(orientation)>
+                            {orientation.label()}
+                        </option>
+                    }).collect::<Vec<_>>()}
+                </select>
+
+                <label>"Mat style"</label>
+                <select
+                    style=input_style
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        let preset = MatStyle::preset_names().contains(&value.as_str()).then_some(value);
+                        edit_rule(rule, |r| r.mat_preset = preset);
+                    }
+                >
+                    <option value="" selected=move || rule.get().mat_preset.is_none()>"Any"</option>
+                    {MatStyle::preset_names().iter().map(|name| view! {
+                        <option value=*name selected=move || rule.get().mat_preset.as_deref() == Some(*name)>
+                            {*name}
+                        </option>
+                    }).collect::<Vec<_>>()}
+                </select>
+
+                <label>"Added in the last"</label>
+                <div style="display: flex; gap: 0.5rem; align-items: center;">
+                    <input
+                        type="number"
+                        min="1"
+                        style="width: 5rem; padding: 0.4rem; border: 1px solid #ccc; border-radius: 4px;"
+                        prop:value=move || rule.get().added_within_days.map(|d| d.to_string()).unwrap_or_default()
+                        on:input=move |ev| {
+                            let days = event_target_value(&ev).parse().ok().filter(|d| *d > 0);
+                            edit_rule(rule, |r| r.added_within_days = days);
+                        }
+                    />
+                    "days"
+                </div>
+            </div>
+            <p style="margin: 0.75rem 0 0 0; font-size: 0.85rem; color: #666;">
+                {matching} " photos match"
+            </p>
+        </div>
+    }
+}
+
+#[component]
+fn AlbumsTab<F>(
+    albums: ReadSignal<Vec<Album>>,
     photos: ReadSignal<Vec<Photo>>,
     client: Client,
     on_refresh_albums: F,
@@ -534,6 +1571,9 @@ where
     F: Fn() + Clone + Send + 'static,
 {
     let (new_album_name, set_new_album_name) = signal(String::new());
+    let (new_album_kind, set_new_album_kind) = signal(None::<VirtualAlbum>);
+    let (new_album_smart, set_new_album_smart) = signal(false);
+    let new_album_rule = RwSignal::new(SmartRule::default());
     let (creating, set_creating) = signal(false);
     let (selected_album, set_selected_album) = signal(None::<AlbumID>);
 
@@ -550,11 +1590,19 @@ where
             let on_refresh = on_refresh.clone();
             spawn_local(async move {
                 match client
-                    .create_album(&CreateAlbumRequest { name, notes: None })
+                    .create_album(&CreateAlbumRequest {
+                        name,
+                        notes: None,
+                        virtual_album: new_album_kind.get_untracked(),
+                        smart_rule: new_album_smart
+                            .get_untracked()
+                            .then(|| new_album_rule.get_untracked()),
+                    })
                     .await
                 {
                     Ok(_) => {
                         set_new_album_name.set(String::new());
+                        new_album_rule.set(SmartRule::default());
                         on_refresh();
                     }
                     Err(e) => log::error!("Failed to create album: {:?}", e),
@@ -579,6 +1627,24 @@ where
                     prop:value=move || new_album_name.get()
                     on:input=move |ev| set_new_album_name.set(event_target_value(&ev))
                 />
+                <select
+                    style="padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        set_new_album_smart.set(value == "smart");
+                        set_new_album_kind.set(parse_album_kind(&value));
+                    }
+                >
+                    <option value="manual">"Photos I pick"</option>
+                    <option value="smart">"Photos matching a rule"</option>
+                    <option value="on_this_day">{VirtualAlbum::OnThisDay.describe()}</option>
+                    <option value="this_week">{VirtualAlbum::ThisWeek { years_ago: None }.describe()}</option>
+                    {(1..=12).map(|month| view! {
+                        <option value=format!("month:{}", month)>
+                            {VirtualAlbum::TakenInMonth { month }.describe()}
+                        </option>
+                    }).collect::<Vec<_>>()}
+                </select>
                 <button
                     style="padding: 0.5rem 1rem; background: #4CAF50; color: white; border: none; border-radius: 4px; cursor: pointer;"
                     on:click=handle_create
@@ -587,6 +1653,9 @@ where
                     {move || if creating.get() { "Creating..." } else { "Create Album" }}
                 </button>
             </div>
+            <Show when=move || new_album_smart.get()>
+                <SmartRuleEditor rule=new_album_rule photos=photos />
+            </Show>
 
             // Album list
             <div style="display: flex; gap: 1rem;">
@@ -605,6 +1674,8 @@ where
                                 <div style="font-weight: 500;">{album.name.clone()}</div>
                                 <div style="font-size: 0.85rem; color: #666;">
                                     {album.photos.len()} " photos"
+                                    {album.virtual_album.map(|v| format!(" · {}", v.describe()))}
+                                    {album.smart_rule.is_some().then_some(" · Smart")}
                                 </div>
                             </div>
                         }
@@ -650,7 +1721,77 @@ where
 {
     let album_id = album.id;
     let album_photo_ids: Vec<i32> = album.photos.iter().map(|p| p.0).collect();
+    // Virtual and smart albums pick their own photos
+    let is_virtual = album.virtual_album.is_some() || album.smart_rule.is_some();
     let (deleting, set_deleting) = signal(false);
+    let rule = RwSignal::new(album.smart_rule.clone().unwrap_or_default());
+    let (saving_rule, set_saving_rule) = signal(false);
+    // Photo being dragged to a new place in the album
+    let (dragged, set_dragged) = signal(None::<i32>);
+
+    // Run one of the ordering requests, then show the new order
+    let arrange = {
+        let client = client.clone();
+        let on_refresh = on_refresh.clone();
+        move |change: AlbumOrderChange| {
+            let client = client.clone();
+            let on_refresh = on_refresh.clone();
+            spawn_local(async move {
+                let result = match change {
+                    AlbumOrderChange::Reorder(photos) => {
+                        client
+                            .reorder_album(album_id, &ReorderAlbumRequest { photos })
+                            .await
+                    }
+                    AlbumOrderChange::Move(photo, to) => {
+                        client
+                            .move_album_photo(album_id, photo, &MoveAlbumPhotoRequest { to })
+                            .await
+                    }
+                    AlbumOrderChange::Sort(by) => {
+                        client
+                            .sort_album(
+                                album_id,
+                                &SortAlbumRequest {
+                                    by,
+                                    descending: false,
+                                },
+                            )
+                            .await
+                    }
+                };
+                if let Err(e) = result {
+                    log::error!("Failed to reorder album: {:?}", e);
+                }
+                on_refresh();
+            });
+        }
+    };
+
+    let handle_save_rule = {
+        let client = client.clone();
+        let on_refresh = on_refresh.clone();
+        move |_| {
+            if saving_rule.get() {
+                return;
+            }
+            set_saving_rule.set(true);
+            let client = client.clone();
+            let on_refresh = on_refresh.clone();
+            spawn_local(async move {
+                let updates = UpdateAlbumRequest {
+                    name: None,
+                    notes: None,
+                    smart_rule: Some(Update::Set(rule.get_untracked())),
+                };
+                match client.update_album(album_id, &updates).await {
+                    Ok(_) => on_refresh(),
+                    Err(e) => log::error!("Failed to save smart rule: {:?}", e),
+                }
+                set_saving_rule.set(false);
+            });
+        }
+    };
 
     let handle_delete_album = {
         let client = client.clone();
@@ -672,41 +1813,143 @@ where
         }
     };
 
+    let export_url = album.export_url();
+
     view! {
         <div>
             <div style="display: flex; justify-content: space-between; align-items: center; margin-bottom: 1rem;">
                 <h3 style="margin: 0;">{album.name}</h3>
+                <div style="display: flex; gap: 0.5rem;">
+                    <a
+                        href=export_url
+                        download
+                        title="Every original in album order, with a manifest of titles, artists and dates"
+                        style="padding: 0.25rem 0.5rem; background: #2196F3; color: white; border-radius: 4px; text-decoration: none; font-size: 0.85rem;"
+                    >
+                        "Download zip"
+                    </a>
+                    <button
+                        style="padding: 0.25rem 0.5rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.85rem;"
+                        on:click=handle_delete_album
+                        disabled=move || deleting.get()
+                    >
+                        {move || if deleting.get() { "Deleting..." } else { "Delete Album" }}
+                    </button>
+                </div>
+            </div>
+
+            {album.virtual_album.map(|v| view! {
+                <p style="font-size: 0.85rem; color: #666;">
+                    {v.describe()} ". Photos are picked by the date they were taken, so this album changes from day to day."
+                </p>
+            })}
+
+            {album.smart_rule.is_some().then(|| view! {
+                <p style="font-size: 0.85rem; color: #666;">
+                    "Holds every photo matching these conditions, including photos uploaded later."
+                </p>
+                <SmartRuleEditor rule=rule photos=photos />
                 <button
-                    style="padding: 0.25rem 0.5rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.85rem;"
-                    on:click=handle_delete_album
-                    disabled=move || deleting.get()
+                    style="padding: 0.25rem 0.75rem; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                    on:click=handle_save_rule
+                    disabled=move || saving_rule.get()
                 >
-                    {move || if deleting.get() { "Deleting..." } else { "Delete Album" }}
+                    {move || if saving_rule.get() { "Saving..." } else { "Save Rule" }}
                 </button>
-            </div>
+            })}
 
-            <h4 style="margin: 1rem 0 0.5rem 0;">"Photos in Album"</h4>
+            <div style="display: flex; align-items: center; gap: 0.5rem; margin: 1rem 0 0.5rem 0;">
+                <h4 style="margin: 0;">"Photos in Album"</h4>
+                <span style="font-size: 0.85rem; color: #666;" style:display=if is_virtual { "none" } else { "inline" }>
+                    "Drag to reorder, or sort by"
+                </span>
+                <button
+                    style="padding: 0.15rem 0.5rem; border: 1px solid #ccc; border-radius: 4px; background: white; cursor: pointer; font-size: 0.8rem;"
+                    style:display=if is_virtual { "none" } else { "inline" }
+                    on:click={
+                        let arrange = arrange.clone();
+                        move |_| arrange(AlbumOrderChange::Sort(AlbumPhotoSort::DateTaken))
+                    }
+                >
+                    "Date taken"
+                </button>
+                <button
+                    style="padding: 0.15rem 0.5rem; border: 1px solid #ccc; border-radius: 4px; background: white; cursor: pointer; font-size: 0.8rem;"
+                    style:display=if is_virtual { "none" } else { "inline" }
+                    on:click={
+                        let arrange = arrange.clone();
+                        move |_| arrange(AlbumOrderChange::Sort(AlbumPhotoSort::Title))
+                    }
+                >
+                    "Title"
+                </button>
+            </div>
             <div style="display: flex; flex-wrap: wrap; gap: 0.5rem; margin-bottom: 1.5rem;">
                 {
                     let album_photo_ids = album_photo_ids.clone();
                     let client = client.clone();
                     let on_refresh = on_refresh.clone();
+                    let arrange = arrange.clone();
                     move || {
                         let album_ids = album_photo_ids.clone();
-                        photos.get().into_iter()
-                            .filter(|p| album_ids.contains(&p.id.0))
+                        let library = photos.get();
+                        // In album order, not library order
+                        album_ids.iter()
+                            .filter_map(|id| library.iter().find(|p| p.id.0 == *id).cloned())
                             .map(|photo| {
                                 let photo_id = photo.id;
                                 let client = client.clone();
                                 let on_refresh = on_refresh.clone();
+                                let album_ids = album_ids.clone();
+                                let arrange_drop = arrange.clone();
+                                let arrange_top = arrange.clone();
+                                let arrange_bottom = arrange.clone();
                                 view! {
-                                    <div style="position: relative; width: 80px; height: 80px;">
+                                    <div
+                                        style="position: relative; width: 80px; height: 80px;"
+                                        style:cursor=if is_virtual { "default" } else { "grab" }
+                                        style:opacity=move || if dragged.get() == Some(photo_id.0) { "0.4" } else { "1" }
+                                        draggable=if is_virtual { "false" } else { "true" }
+                                        on:dragstart=move |_| set_dragged.set(Some(photo_id.0))
+                                        on:dragend=move |_| set_dragged.set(None)
+                                        // Allow dropping another photo here
+                                        on:dragover=move |ev| ev.prevent_default()
+                                        on:drop=move |ev| {
+                                            ev.prevent_default();
+                                            if let Some(from) = dragged.get_untracked()
+                                                && from != photo_id.0
+                                            {
+                                                arrange_drop(AlbumOrderChange::Reorder(moved(&album_ids, from, photo_id.0)));
+                                            }
+                                            set_dragged.set(None);
+                                        }
+                                    >
                                         <img
-                                            src=photo.url.clone()
-                                            style="width: 100%; height: 100%; object-fit: cover; border-radius: 4px;"
+                                            src=photo.thumbnail_url.clone()
+                                            style="width: 100%; height: 100%; object-fit: cover; border-radius: 4px; pointer-events: none;"
                                         />
+                                        <div
+                                            style="position: absolute; bottom: 2px; left: 2px; display: flex; gap: 2px;"
+                                            style:display=if is_virtual { "none" } else { "flex" }
+                                        >
+                                            <button
+                                                title="Move to top"
+                                                style="width: 20px; height: 20px; padding: 0; background: rgba(255,255,255,0.9); border: none; border-radius: 50%; cursor: pointer; font-size: 11px;"
+                                                on:click=move |_| arrange_top(AlbumOrderChange::Move(photo_id, AlbumEnd::Top))
+                                            >
+                                                "⤒"
+                                            </button>
+                                            <button
+                                                title="Move to bottom"
+                                                style="width: 20px; height: 20px; padding: 0; background: rgba(255,255,255,0.9); border: none; border-radius: 50%; cursor: pointer; font-size: 11px;"
+                                                on:click=move |_| arrange_bottom(AlbumOrderChange::Move(photo_id, AlbumEnd::Bottom))
+                                            >
+                                                "⤓"
+                                            </button>
+                                        </div>
                                         <button
                                             style="position: absolute; top: 2px; right: 2px; width: 20px; height: 20px; background: rgba(244,67,54,0.9); color: white; border: none; border-radius: 50%; cursor: pointer; font-size: 12px; line-height: 1;"
+                                            style:display=if is_virtual { "none" } else { "block" }
                                             on:click={
                                                 let client = client.clone();
                                                 let on_refresh = on_refresh.clone();
@@ -731,8 +1974,8 @@ where
                 }
             </div>
 
-            <h4 style="margin: 1rem 0 0.5rem 0;">"Add Photos"</h4>
-            <div style="display: flex; flex-wrap: wrap; gap: 0.5rem;">
+            <h4 style="margin: 1rem 0 0.5rem 0;" style:display=if is_virtual { "none" } else { "block" }>"Add Photos"</h4>
+            <div style="display: flex; flex-wrap: wrap; gap: 0.5rem;" style:display=if is_virtual { "none" } else { "flex" }>
                 {
                     let album_photo_ids = album_photo_ids.clone();
                     let client = client.clone();
@@ -764,7 +2007,7 @@ where
                                         }
                                     >
                                         <img
-                                            src=photo.url.clone()
+                                            src=photo.thumbnail_url.clone()
                                             style="width: 100%; height: 100%; object-fit: cover; border-radius: 4px; border: 2px dashed #ccc;"
                                         />
                                     </div>
@@ -778,113 +2021,1265 @@ where
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
-// Settings Tab
+// Duplicates Tab
 // ─────────────────────────────────────────────────────────────────────────────
 
 #[component]
-fn SettingsTab<F>(
-    settings: ReadSignal<Option<RotationSettings>>,
-    albums: ReadSignal<Vec<Album>>,
-    client: Client,
-    on_refresh: F,
-) -> impl IntoView
+fn DuplicatesTab<F>(client: Client, on_merge: F) -> impl IntoView
 where
     F: Fn() + Clone + Send + 'static,
 {
-    let (saving, set_saving) = signal(false);
+    let (groups, set_groups) = signal(Vec::<DuplicateGroup>::new());
+    let (max_distance, set_max_distance) = signal(None::<u32>);
+    let (loading, set_loading) = signal(false);
 
-    let handle_set_album = {
+    let refresh = {
         let client = client.clone();
-        let on_refresh = on_refresh.clone();
-        move |album_id: Option<AlbumID>| {
-            if saving.get() {
-                return;
-            }
-            set_saving.set(true);
+        move || {
             let client = client.clone();
-            let on_refresh = on_refresh.clone();
+            set_loading.set(true);
             spawn_local(async move {
-                let update = UpdateSettingsRequest {
-                    current_album_id: Some(match album_id {
-                        Some(id) => Update::Set(id),
-                        None => Update::Remove,
-                    }),
-                    interval_seconds: None,
-                };
-                match client.update_settings(&update).await {
-                    Ok(_) => on_refresh(),
-                    Err(e) => log::error!("Failed to update settings: {:?}", e),
+                match client.get_duplicates(max_distance.get_untracked()).await {
+                    Ok(g) => set_groups.set(g),
+                    Err(e) => log::error!("Failed to fetch duplicates: {:?}", e),
                 }
-                set_saving.set(false);
+                set_loading.set(false);
             });
         }
     };
 
-    let handle_set_interval = {
-        let client = client.clone();
-        let on_refresh = on_refresh.clone();
-        move |seconds: i32| {
-            if saving.get() {
-                return;
-            }
-            set_saving.set(true);
-            let client = client.clone();
-            let on_refresh = on_refresh.clone();
-            spawn_local(async move {
-                let update = UpdateSettingsRequest {
-                    current_album_id: None,
-                    interval_seconds: Some(seconds),
-                };
-                match client.update_settings(&update).await {
-                    Ok(_) => on_refresh(),
-                    Err(e) => log::error!("Failed to update settings: {:?}", e),
-                }
-                set_saving.set(false);
-            });
-        }
-    };
+    // Initial load
+    Effect::new({
+        let refresh = refresh.clone();
+        move |_| refresh()
+    });
 
     view! {
         <div>
-            <h2 style="margin-bottom: 1.5rem;">"Settings"</h2>
+            <div style="display: flex; justify-content: space-between; align-items: center; margin-bottom: 1rem;">
+                <h2 style="margin: 0;">"Duplicates (" {move || groups.get().len()} ")"</h2>
+                <div style="display: flex; gap: 0.5rem; align-items: center;">
+                    <label style="font-size: 0.9rem; color: #666;">"Max distance"</label>
+                    <input
+                        type="number"
+                        min="0"
+                        max="64"
+                        placeholder="default"
+                        style="width: 5rem; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                        on:input=move |ev| set_max_distance.set(event_target_value(&ev).parse().ok())
+                    />
+                    <button
+                        style="padding: 0.5rem 1rem; background: #4CAF50; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                        on:click={
+                            let refresh = refresh.clone();
+                            move |_| refresh()
+                        }
+                        disabled=move || loading.get()
+                    >
+                        {move || if loading.get() { "Searching..." } else { "Find Duplicates" }}
+                    </button>
+                </div>
+            </div>
 
-            {move || settings.get().map(|s| {
-                let current_album_id = s.current_album.as_ref().map(|c| c.album.0);
-                let interval = s.interval.seconds();
+            <p style="color: #666; margin-top: 0;">
+                "Pick the photo to keep in each group. Merging adds it to every album the others were in, then deletes the others."
+            </p>
 
+            {move || groups.get().into_iter().map(|group| {
+                let client = client.clone();
+                let on_merge = on_merge.clone();
+                let refresh = refresh.clone();
                 view! {
-                    <div style="max-width: 500px;">
-                        // Current album
-                        <div style="margin-bottom: 1.5rem;">
-                            <label style="display: block; font-weight: 500; margin-bottom: 0.5rem;">
-                                "Current Album"
-                            </label>
-                            <select
-                                style="width: 100%; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px; font-size: 1rem;"
-                                on:change={
-                                    let handle = handle_set_album.clone();
-                                    move |ev| {
-                                        let value = event_target_value(&ev);
-                                        let album_id = if value == "none" {
-                                            None
-                                        } else {
-                                            value.parse::<i32>().ok().map(AlbumID)
-                                        };
-                                        handle(album_id);
-                                    }
-                                }
-                                disabled=move || saving.get()
-                            >
-                                <option value="none" selected=current_album_id.is_none()>
-                                    "(All photos - no album selected)"
-                                </option>
-                                {albums.get().into_iter().map(|album| {
-                                    let is_selected = current_album_id == Some(album.id.0);
-                                    view! {
-                                        <option value=album.id.0.to_string() selected=is_selected>
-                                            {album.name} " (" {album.photos.len()} " photos)"
-                                        </option>
-                                    }
+                    <DuplicateGroupCard
+                        group=group
+                        client=client
+                        on_merge=move || {
+                            refresh();
+                            on_merge();
+                        }
+                    />
+                }
+            }).collect::<Vec<_>>()}
+
+            {move || if groups.get().is_empty() && !loading.get() {
+                Some(view! {
+                    <p style="color: #666; text-align: center; padding: 2rem;">
+                        "No duplicates found."
+                    </p>
+                })
+            } else {
+                None
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn DuplicateGroupCard<F>(group: DuplicateGroup, client: Client, on_merge: F) -> impl IntoView
+where
+    F: Fn() + Clone + Send + 'static,
+{
+    let (keep, set_keep) = signal(group.photos[0].id);
+    let (merging, set_merging) = signal(false);
+    let ids: Vec<PhotoID> = group.photos.iter().map(|p| p.id).collect();
+
+    let handle_merge = move |_| {
+        if merging.get() {
+            return;
+        }
+        set_merging.set(true);
+        let keep = keep.get();
+        let duplicates = ids.iter().copied().filter(|id| *id != keep).collect();
+        let client = client.clone();
+        let on_merge = on_merge.clone();
+        spawn_local(async move {
+            match client
+                .merge_photos(keep, &MergePhotosRequest { duplicates })
+                .await
+            {
+                Ok(_) => on_merge(),
+                Err(e) => log::error!("Failed to merge photos: {:?}", e),
+            }
+            set_merging.set(false);
+        });
+    };
+
+    view! {
+        <div style="border: 1px solid #e0e0e0; border-radius: 8px; padding: 1rem; margin-bottom: 1rem; background: white;">
+            <div style="display: flex; justify-content: space-between; align-items: center; margin-bottom: 0.75rem;">
+                <span style="color: #666; font-size: 0.9rem;">
+                    {format!("{} photos, distance {}", group.photos.len(), group.distance)}
+                </span>
+                <button
+                    style="padding: 0.25rem 0.75rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                    on:click=handle_merge
+                    disabled=move || merging.get()
+                >
+                    {move || if merging.get() { "Merging..." } else { "Keep Selected, Merge Others" }}
+                </button>
+            </div>
+            <div style="display: grid; grid-template-columns: repeat(auto-fill, minmax(160px, 1fr)); gap: 0.75rem;">
+                {group.photos.into_iter().map(|photo| {
+                    let photo_id = photo.id;
+                    let is_kept = move || keep.get() == photo_id;
+                    view! {
+                        <div
+                            style=move || if is_kept() {
+                                "border: 3px solid #4CAF50; border-radius: 4px; cursor: pointer;"
+                            } else {
+                                "border: 3px solid transparent; border-radius: 4px; cursor: pointer; opacity: 0.6;"
+                            }
+                            on:click=move |_| set_keep.set(photo_id)
+                        >
+                            <img
+                                src=photo.thumbnail_url.clone()
+                                style="width: 100%; height: 120px; object-fit: cover; display: block;"
+                                loading="lazy"
+                            />
+                            <div style="padding: 0.25rem; font-size: 0.8rem; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                {move || if is_kept() { "Keep: " } else { "" }}
+                                {photo.title.clone().unwrap_or_else(|| format!("Photo {}", photo_id.0))}
+                            </div>
+                        </div>
+                    }
+                }).collect::<Vec<_>>()}
+            </div>
+        </div>
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Trash Tab
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[component]
+fn TrashTab<F>(client: Client, on_restore: F) -> impl IntoView
+where
+    F: Fn() + Clone + Send + 'static,
+{
+    let (trash, set_trash) = signal(Vec::<TrashedPhoto>::new());
+    let (busy, set_busy) = signal(false);
+
+    let refresh = {
+        let client = client.clone();
+        move || {
+            let client = client.clone();
+            spawn_local(async move {
+                match client.get_trash().await {
+                    Ok(t) => set_trash.set(t),
+                    Err(e) => log::error!("Failed to fetch trash: {:?}", e),
+                }
+            });
+        }
+    };
+
+    // Initial load
+    Effect::new({
+        let refresh = refresh.clone();
+        move |_| refresh()
+    });
+
+    let handle_empty = {
+        let client = client.clone();
+        let refresh = refresh.clone();
+        move |_| {
+            if busy.get() {
+                return;
+            }
+            set_busy.set(true);
+            let client = client.clone();
+            let refresh = refresh.clone();
+            spawn_local(async move {
+                if let Err(e) = client.empty_trash().await {
+                    log::error!("Failed to empty trash: {:?}", e);
+                }
+                set_busy.set(false);
+                refresh();
+            });
+        }
+    };
+
+    view! {
+        <div>
+            <div style="display: flex; justify-content: space-between; align-items: center; margin-bottom: 1rem;">
+                <h2 style="margin: 0;">"Trash (" {move || trash.get().len()} ")"</h2>
+                <button
+                    style="padding: 0.5rem 1rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                    on:click=handle_empty
+                    disabled=move || busy.get() || trash.get().is_empty()
+                >
+                    "Empty trash"
+                </button>
+            </div>
+
+            <p style="color: #666; margin-top: 0;">
+                "Deleted photos wait here, still in their albums, until they're deleted for good."
+            </p>
+
+            <div style="display: grid; grid-template-columns: repeat(auto-fill, minmax(200px, 1fr)); gap: 1rem;">
+                {move || trash.get().into_iter().map(|trashed| {
+                    let photo_id = trashed.photo.id;
+                    // Restore or purge this photo, then show what's left
+                    let act = {
+                        let client = client.clone();
+                        let refresh = refresh.clone();
+                        let on_restore = on_restore.clone();
+                        move |restore: bool| {
+                            if busy.get() {
+                                return;
+                            }
+                            set_busy.set(true);
+                            let client = client.clone();
+                            let refresh = refresh.clone();
+                            let on_restore = on_restore.clone();
+                            spawn_local(async move {
+                                if restore {
+                                    match client.restore_photo(photo_id).await {
+                                        Ok(_) => on_restore(),
+                                        Err(e) => log::error!("Failed to restore photo: {:?}", e),
+                                    }
+                                } else if let Err(e) = client.purge_photo(photo_id).await {
+                                    log::error!("Failed to purge photo: {:?}", e);
+                                }
+                                set_busy.set(false);
+                                refresh();
+                            });
+                        }
+                    };
+                    let purge = act.clone();
+                    view! {
+                        <div style="border: 1px solid #e0e0e0; border-radius: 8px; overflow: hidden; background: white;">
+                            <img
+                                src=trashed.photo.thumbnail_url.clone()
+                                style="width: 100%; height: 134px; object-fit: cover; display: block; opacity: 0.6;"
+                                loading="lazy"
+                            />
+                            <div style="padding: 0.75rem;">
+                                <div style="font-weight: 500; margin-bottom: 0.25rem; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                    {trashed.photo.title.clone().unwrap_or_else(|| format!("Photo {}", photo_id.0))}
+                                </div>
+                                <div style="font-size: 0.8rem; color: #888;">
+                                    {format!("Deleted {}", trashed.deleted_at.format("%-d %b %Y"))}
+                                </div>
+                                <div style="font-size: 0.8rem; color: #888;">
+                                    {format!("Gone for good {}", trashed.purge_at.format("%-d %b %Y"))}
+                                </div>
+                                <div style="display: flex; gap: 0.5rem; margin-top: 0.5rem;">
+                                    <button
+                                        style="padding: 0.25rem 0.5rem; background: #4CAF50; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                                        on:click=move |_| act(true)
+                                        disabled=move || busy.get()
+                                    >
+                                        "Restore"
+                                    </button>
+                                    <button
+                                        style="padding: 0.25rem 0.5rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                                        on:click=move |_| purge(false)
+                                        disabled=move || busy.get()
+                                    >
+                                        "Delete forever"
+                                    </button>
+                                </div>
+                            </div>
+                        </div>
+                    }
+                }).collect::<Vec<_>>()}
+            </div>
+
+            {move || trash.get().is_empty().then(|| view! {
+                <p style="color: #666; text-align: center; padding: 2rem;">
+                    "The trash is empty."
+                </p>
+            })}
+        </div>
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Suggestions Tab
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[component]
+fn SuggestionsTab<F>(photos: ReadSignal<Vec<Photo>>, client: Client, on_accept: F) -> impl IntoView
+where
+    F: Fn() + Clone + Send + Sync + 'static,
+{
+    let (suggestions, set_suggestions) = signal(Vec::<TagSuggestion>::new());
+    let (loading, set_loading) = signal(false);
+
+    let refresh = {
+        let client = client.clone();
+        move || {
+            let client = client.clone();
+            set_loading.set(true);
+            spawn_local(async move {
+                match client.get_suggestions(None).await {
+                    Ok(s) => set_suggestions.set(s),
+                    Err(e) => log::error!("Failed to fetch suggestions: {:?}", e),
+                }
+                set_loading.set(false);
+            });
+        }
+    };
+
+    // Initial load
+    Effect::new({
+        let refresh = refresh.clone();
+        move |_| refresh()
+    });
+
+    let review = {
+        let client = client.clone();
+        move |id: SuggestionID, accept: bool| {
+            let client = client.clone();
+            let on_accept = on_accept.clone();
+            spawn_local(async move {
+                let result = if accept {
+                    client.accept_suggestion(id).await
+                } else {
+                    client.reject_suggestion(id).await
+                };
+                match result {
+                    Ok(()) => {
+                        set_suggestions.set(
+                            suggestions
+                                .get_untracked()
+                                .into_iter()
+                                .filter(|s| s.id != id)
+                                .collect(),
+                        );
+                        if accept {
+                            on_accept();
+                        }
+                    }
+                    Err(e) => log::error!("Failed to review suggestion: {:?}", e),
+                }
+            });
+        }
+    };
+
+    view! {
+        <div>
+            <div style="display: flex; justify-content: space-between; align-items: center; margin-bottom: 1rem;">
+                <h2 style="margin: 0;">"Suggested Tags (" {move || suggestions.get().len()} ")"</h2>
+                <button
+                    style="padding: 0.5rem 1rem; background: #4CAF50; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                    on:click={
+                        let refresh = refresh.clone();
+                        move |_| refresh()
+                    }
+                    disabled=move || loading.get()
+                >
+                    {move || if loading.get() { "Loading..." } else { "Refresh" }}
+                </button>
+            </div>
+
+            <p style="color: #666; margin-top: 0;">
+                "Tags the classifier proposed after import. Accepting one tags the photo; rejected tags aren't suggested again."
+            </p>
+
+            <div style="display: grid; grid-template-columns: repeat(auto-fill, minmax(200px, 1fr)); gap: 1rem;">
+                {move || suggestions.get().into_iter().map(|suggestion| {
+                    let id = suggestion.id;
+                    let photo = photos.with(|photos| photos.iter().find(|p| p.id == suggestion.photo).cloned());
+                    let (thumbnail, title) = match photo {
+                        Some(photo) => (
+                            photo.thumbnail_url,
+                            photo.title.unwrap_or_else(|| format!("Photo {}", suggestion.photo.0)),
+                        ),
+                        None => (String::new(), format!("Photo {}", suggestion.photo.0)),
+                    };
+                    let review_accept = review.clone();
+                    let review_reject = review.clone();
+                    view! {
+                        <div style="border: 1px solid #e0e0e0; border-radius: 8px; overflow: hidden; background: white;">
+                            <img
+                                src=thumbnail
+                                style="width: 100%; height: 140px; object-fit: cover; display: block;"
+                                loading="lazy"
+                            />
+                            <div style="padding: 0.5rem;">
+                                <div style="font-size: 0.8rem; color: #666; overflow: hidden; text-overflow: ellipsis; white-space: nowrap;">
+                                    {title}
+                                </div>
+                                <div style="font-weight: bold; margin: 0.25rem 0;">{suggestion.label.clone()}</div>
+                                <div style="font-size: 0.8rem; color: #666; margin-bottom: 0.5rem;">
+                                    {format!("{}, {:.0}% sure", suggestion.kind.as_str(), suggestion.confidence * 100.0)}
+                                </div>
+                                <div style="display: flex; gap: 0.5rem;">
+                                    <button
+                                        style="flex: 1; padding: 0.25rem; background: #4CAF50; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                                        on:click=move |_| review_accept(id, true)
+                                    >
+                                        "Accept"
+                                    </button>
+                                    <button
+                                        style="flex: 1; padding: 0.25rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                                        on:click=move |_| review_reject(id, false)
+                                    >
+                                        "Reject"
+                                    </button>
+                                </div>
+                            </div>
+                        </div>
+                    }
+                }).collect::<Vec<_>>()}
+            </div>
+
+            {move || if suggestions.get().is_empty() && !loading.get() {
+                Some(view! {
+                    <p style="color: #666; text-align: center; padding: 2rem;">
+                        "Nothing to review."
+                    </p>
+                })
+            } else {
+                None
+            }}
+        </div>
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Displays Tab
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[component]
+fn DisplaysTab(albums: ReadSignal<Vec<Album>>, client: Client) -> impl IntoView {
+    let (displays, set_displays) = signal(Vec::<Display>::new());
+    let (new_display_name, set_new_display_name) = signal(String::new());
+    let (creating, set_creating) = signal(false);
+
+    let refresh = {
+        let client = client.clone();
+        move || {
+            let client = client.clone();
+            spawn_local(async move {
+                match client.get_displays().await {
+                    Ok(d) => set_displays.set(d),
+                    Err(e) => log::error!("Failed to fetch displays: {:?}", e),
+                }
+            });
+        }
+    };
+
+    // Load displays on mount
+    Effect::new({
+        let refresh = refresh.clone();
+        move |_| refresh()
+    });
+
+    let handle_create = {
+        let client = client.clone();
+        let refresh = refresh.clone();
+        move |_| {
+            let name = new_display_name.get();
+            if name.trim().is_empty() || creating.get() {
+                return;
+            }
+            set_creating.set(true);
+            let client = client.clone();
+            let refresh = refresh.clone();
+            spawn_local(async move {
+                match client.create_display(&CreateDisplayRequest { name }).await {
+                    Ok(_) => {
+                        set_new_display_name.set(String::new());
+                        refresh();
+                    }
+                    Err(e) => log::error!("Failed to create display: {:?}", e),
+                }
+                set_creating.set(false);
+            });
+        }
+    };
+
+    view! {
+        <div>
+            <h2 style="margin-bottom: 0.5rem;">"Displays (" {move || displays.get().len()} ")"</h2>
+            <p style="font-size: 0.85rem; color: #666; margin-bottom: 1rem;">
+                "Each display keeps its own album, timing and order. Open its viewer link on the frame. Frames without a link follow the Settings tab."
+            </p>
+
+            // Create display form
+            <div style="display: flex; gap: 0.5rem; margin-bottom: 1.5rem;">
+                <input
+                    type="text"
+                    placeholder="New display name..."
+                    style="flex: 1; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                    prop:value=move || new_display_name.get()
+                    on:input=move |ev| set_new_display_name.set(event_target_value(&ev))
+                />
+                <button
+                    style="padding: 0.5rem 1rem; background: #4CAF50; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                    on:click=handle_create
+                    disabled=move || creating.get() || new_display_name.get().trim().is_empty()
+                >
+                    {move || if creating.get() { "Creating..." } else { "Add Display" }}
+                </button>
+            </div>
+
+            <div style="display: flex; flex-direction: column; gap: 1rem;">
+                {move || displays.get().into_iter().map(|display| {
+                    view! {
+                        <DisplayCard
+                            display=display
+                            albums=albums
+                            client=client.clone()
+                            on_refresh=refresh.clone()
+                        />
+                    }
+                }).collect::<Vec<_>>()}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn DisplayCard<F>(
+    display: Display,
+    albums: ReadSignal<Vec<Album>>,
+    client: Client,
+    on_refresh: F,
+) -> impl IntoView
+where
+    F: Fn() + Clone + Send + 'static,
+{
+    let display_id = display.id;
+    let current_album_id = display.current_album.as_ref().map(|c| c.album.0);
+    let interval = display.interval.seconds();
+    let mode = display.mode;
+    let mat_preset = display.mat_preset.clone();
+    let viewer_url = display.viewer_url();
+    let (saving, set_saving) = signal(false);
+
+    let handle_update = {
+        let client = client.clone();
+        let on_refresh = on_refresh.clone();
+        move |update: UpdateDisplayRequest| {
+            if saving.get() {
+                return;
+            }
+            set_saving.set(true);
+            let client = client.clone();
+            let on_refresh = on_refresh.clone();
+            spawn_local(async move {
+                match client.update_display(display_id, &update).await {
+                    Ok(_) => on_refresh(),
+                    Err(e) => log::error!("Failed to update display: {:?}", e),
+                }
+                set_saving.set(false);
+            });
+        }
+    };
+
+    let handle_delete = {
+        let client = client.clone();
+        let on_refresh = on_refresh.clone();
+        move |_| {
+            if saving.get() {
+                return;
+            }
+            set_saving.set(true);
+            let client = client.clone();
+            let on_refresh = on_refresh.clone();
+            spawn_local(async move {
+                match client.delete_display(display_id).await {
+                    Ok(_) => on_refresh(),
+                    Err(e) => log::error!("Failed to delete display: {:?}", e),
+                }
+                set_saving.set(false);
+            });
+        }
+    };
+
+    view! {
+        <div style="border: 1px solid #e0e0e0; border-radius: 8px; padding: 1rem; background: white;">
+            <div style="display: flex; justify-content: space-between; align-items: center; margin-bottom: 0.75rem;">
+                <div>
+                    <div style="font-weight: 500;">{display.name.clone()}</div>
+                    <div style="font-size: 0.85rem; color: #666;">
+                        <a href=viewer_url.clone() target="_blank" style="color: #2196F3;">{viewer_url.clone()}</a>
+                        " · "
+                        {match display.last_seen_at {
+                            Some(seen) => format!("Last seen {}", seen.format("%Y-%m-%d %H:%M")),
+                            None => "Never seen".to_string(),
+                        }}
+                    </div>
+                </div>
+                <button
+                    style="padding: 0.25rem 0.5rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                    on:click=handle_delete
+                    disabled=move || saving.get()
+                >
+                    "Delete"
+                </button>
+            </div>
+
+            // Album
+            <div style="display: flex; gap: 0.5rem; margin-bottom: 0.75rem;">
+                <select
+                    style="flex: 1; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                    on:change={
+                        let handle = handle_update.clone();
+                        move |ev| {
+                            let value = event_target_value(&ev);
+                            let album = match value.parse::<i32>() {
+                                Ok(id) => Update::Set(AlbumID(id)),
+                                Err(_) => Update::Remove,
+                            };
+                            handle(UpdateDisplayRequest {
+                                current_album_id: Some(album),
+                                ..Default::default()
+                            });
+                        }
+                    }
+                    disabled=move || saving.get()
+                >
+                    <option value="none" selected=current_album_id.is_none()>
+                        "(All photos)"
+                    </option>
+                    {move || albums.get().into_iter().map(|album| {
+                        let is_selected = current_album_id == Some(album.id.0);
+                        view! {
+                            <option value=album.id.0.to_string() selected=is_selected>
+                                {album.name}
+                            </option>
+                        }
+                    }).collect::<Vec<_>>()}
+                </select>
+
+                // Mat override
+                <select
+                    style="padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                    on:change={
+                        let handle = handle_update.clone();
+                        move |ev| {
+                            let value = event_target_value(&ev);
+                            let mat = if value == "photo" {
+                                Update::Remove
+                            } else {
+                                Update::Set(value)
+                            };
+                            handle(UpdateDisplayRequest {
+                                mat_preset: Some(mat),
+                                ..Default::default()
+                            });
+                        }
+                    }
+                    disabled=move || saving.get()
+                >
+                    <option value="photo" selected=mat_preset.is_none()>
+                        "Each photo's own mat"
+                    </option>
+                    {MatStyle::preset_names().iter().map(|&name| {
+                        let is_selected = mat_preset.as_deref() == Some(name);
+                        view! {
+                            <option value=name selected=is_selected>{name}</option>
+                        }
+                    }).collect::<Vec<_>>()}
+                </select>
+            </div>
+
+            // Interval and order
+            <div style="display: flex; gap: 0.5rem; flex-wrap: wrap;">
+                {[30, 60, 120, 180, 300, 600].into_iter().map(|secs| {
+                    let label = if secs < 60 {
+                        format!("{}s", secs)
+                    } else {
+                        format!("{}m", secs / 60)
+                    };
+                    let style = if interval == secs as u32 {
+                        "padding: 0.25rem 0.75rem; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                    } else {
+                        "padding: 0.25rem 0.75rem; background: #f0f0f0; color: #333; border: none; border-radius: 4px; cursor: pointer;"
+                    };
+                    let handle = handle_update.clone();
+                    view! {
+                        <button
+                            style=style
+                            on:click=move |_| handle(UpdateDisplayRequest {
+                                interval_seconds: Some(secs),
+                                ..Default::default()
+                            })
+                            disabled=move || saving.get()
+                        >
+                            {label}
+                        </button>
+                    }
+                }).collect::<Vec<_>>()}
+                <span style="width: 1rem;"></span>
+                {RotationMode::ALL.iter().map(|&option| {
+                    let style = if option == mode {
+                        "padding: 0.25rem 0.75rem; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                    } else {
+                        "padding: 0.25rem 0.75rem; background: #f0f0f0; color: #333; border: none; border-radius: 4px; cursor: pointer;"
+                    };
+                    let handle = handle_update.clone();
+                    view! {
+                        <button
+                            style=style
+                            on:click=move |_| handle(UpdateDisplayRequest {
+                                rotation_mode: Some(option),
+                                ..Default::default()
+                            })
+                            disabled=move || saving.get()
+                        >
+                            {option.label()}
+                        </button>
+                    }
+                }).collect::<Vec<_>>()}
+            </div>
+        </div>
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Schedules Tab
+// ─────────────────────────────────────────────────────────────────────────────
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// Parse the value of an `<input type="time">`. Empty means no time.
+fn parse_time_input(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").ok()
+}
+
+fn format_time_input(time: Option<NaiveTime>) -> String {
+    time.map(|t| t.format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Add `item` to `items`, or remove it if already there
+fn toggled<T: PartialEq>(mut items: Vec<T>, item: T) -> Vec<T> {
+    match items.iter().position(|i| *i == item) {
+        Some(index) => {
+            items.remove(index);
+        }
+        None => items.push(item),
+    }
+    items
+}
+
+#[component]
+fn SchedulesTab(albums: ReadSignal<Vec<Album>>, client: Client) -> impl IntoView {
+    let (schedules, set_schedules) = signal(Vec::<Schedule>::new());
+    let (displays, set_displays) = signal(Vec::<Display>::new());
+    let (saving, set_saving) = signal(false);
+
+    // Form state. `editing` is the schedule being changed, `None` while adding a new one
+    let (editing, set_editing) = signal(None::<ScheduleID>);
+    let (name, set_name) = signal(String::new());
+    let (album, set_album) = signal(None::<AlbumID>);
+    let (display, set_display) = signal(None::<DisplayID>);
+    let (priority, set_priority) = signal(0i32);
+    let (days, set_days) = signal(Vec::<Weekday>::new());
+    let (months, set_months) = signal(Vec::<u32>::new());
+    let (start_time, set_start_time) = signal(String::new());
+    let (end_time, set_end_time) = signal(String::new());
+
+    let reset_form = move || {
+        set_editing.set(None);
+        set_name.set(String::new());
+        set_album.set(None);
+        set_display.set(None);
+        set_priority.set(0);
+        set_days.set(vec![]);
+        set_months.set(vec![]);
+        set_start_time.set(String::new());
+        set_end_time.set(String::new());
+    };
+
+    let edit = move |schedule: Schedule| {
+        set_editing.set(Some(schedule.id));
+        set_name.set(schedule.name);
+        set_album.set(match schedule.action {
+            ScheduleAction::Album { album } => Some(album),
+            ScheduleAction::Blank => None,
+        });
+        set_display.set(schedule.display);
+        set_priority.set(schedule.priority);
+        set_days.set(schedule.days);
+        set_months.set(schedule.months);
+        set_start_time.set(format_time_input(schedule.start_time));
+        set_end_time.set(format_time_input(schedule.end_time));
+    };
+
+    let refresh = {
+        let client = client.clone();
+        move || {
+            let client = client.clone();
+            spawn_local(async move {
+                match client.get_schedules().await {
+                    Ok(s) => set_schedules.set(s),
+                    Err(e) => log::error!("Failed to fetch schedules: {:?}", e),
+                }
+                match client.get_displays().await {
+                    Ok(d) => set_displays.set(d),
+                    Err(e) => log::error!("Failed to fetch displays: {:?}", e),
+                }
+            });
+        }
+    };
+
+    // Load schedules on mount
+    Effect::new({
+        let refresh = refresh.clone();
+        move |_| refresh()
+    });
+
+    // Save a schedule: a new one when `id` is `None`
+    let save = {
+        let client = client.clone();
+        let refresh = refresh.clone();
+        move |id: Option<ScheduleID>, req: ScheduleRequest| {
+            if saving.get() {
+                return;
+            }
+            set_saving.set(true);
+            let client = client.clone();
+            let refresh = refresh.clone();
+            spawn_local(async move {
+                let result = match id {
+                    Some(id) => client.update_schedule(id, &req).await,
+                    None => client.create_schedule(&req).await,
+                };
+                match result {
+                    Ok(_) => {
+                        if editing.get_untracked() == id {
+                            reset_form();
+                        }
+                        refresh();
+                    }
+                    Err(e) => log::error!("Failed to save schedule: {:?}", e),
+                }
+                set_saving.set(false);
+            });
+        }
+    };
+
+    let handle_submit = {
+        let save = save.clone();
+        move |_| {
+            let req = ScheduleRequest {
+                name: name.get(),
+                action: match album.get() {
+                    Some(album) => ScheduleAction::Album { album },
+                    None => ScheduleAction::Blank,
+                },
+                display: display.get(),
+                priority: priority.get(),
+                enabled: true,
+                days: days.get(),
+                months: months.get(),
+                start_time: parse_time_input(&start_time.get()),
+                end_time: parse_time_input(&end_time.get()),
+            };
+            save(editing.get(), req);
+        }
+    };
+
+    let handle_delete = {
+        let client = client.clone();
+        let refresh = refresh.clone();
+        move |id: ScheduleID| {
+            let client = client.clone();
+            let refresh = refresh.clone();
+            spawn_local(async move {
+                match client.delete_schedule(id).await {
+                    Ok(_) => {
+                        if editing.get_untracked() == Some(id) {
+                            reset_form();
+                        }
+                        refresh();
+                    }
+                    Err(e) => log::error!("Failed to delete schedule: {:?}", e),
+                }
+            });
+        }
+    };
+
+    let chip_style = |selected: bool| {
+        if selected {
+            "padding: 0.25rem 0.5rem; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.85rem;"
+        } else {
+            "padding: 0.25rem 0.5rem; background: #f0f0f0; color: #333; border: none; border-radius: 4px; cursor: pointer; font-size: 0.85rem;"
+        }
+    };
+
+    view! {
+        <div>
+            <h2 style="margin-bottom: 0.5rem;">"Schedules (" {move || schedules.get().len()} ")"</h2>
+            <p style="font-size: 0.85rem; color: #666; margin-bottom: 1rem;">
+                "Schedules pick what plays at certain times. When several match, the highest priority wins. When none match, the album from Settings or the display plays."
+            </p>
+
+            // Schedule form
+            <div style="border: 1px solid #e0e0e0; border-radius: 8px; padding: 1rem; margin-bottom: 1.5rem; display: flex; flex-direction: column; gap: 0.75rem;">
+                <div style="display: flex; gap: 0.5rem;">
+                    <input
+                        type="text"
+                        placeholder="Schedule name..."
+                        style="flex: 1; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                        prop:value=move || name.get()
+                        on:input=move |ev| set_name.set(event_target_value(&ev))
+                    />
+                    <label style="display: flex; align-items: center; gap: 0.25rem; font-size: 0.9rem;">
+                        "Priority"
+                        <input
+                            type="number"
+                            style="width: 4rem; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                            prop:value=move || priority.get().to_string()
+                            on:input=move |ev| set_priority.set(event_target_value(&ev).parse().unwrap_or(0))
+                        />
+                    </label>
+                </div>
+                <div style="display: flex; gap: 0.5rem;">
+                    <select
+                        style="flex: 1; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                        on:change=move |ev| set_album.set(event_target_value(&ev).parse::<i32>().ok().map(AlbumID))
+                    >
+                        <option value="blank" selected=move || album.get().is_none()>"Show nothing"</option>
+                        {move || albums.get().into_iter().map(|a| {
+                            let id = a.id;
+                            view! {
+                                <option value=id.0.to_string() selected=move || album.get() == Some(id)>
+                                    "Play " {a.name}
+                                </option>
+                            }
+                        }).collect::<Vec<_>>()}
+                    </select>
+                    <select
+                        style="flex: 1; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px;"
+                        on:change=move |ev| set_display.set(event_target_value(&ev).parse::<i32>().ok().map(DisplayID))
+                    >
+                        <option value="all" selected=move || display.get().is_none()>"On every frame"</option>
+                        {move || displays.get().into_iter().map(|d| {
+                            let id = d.id;
+                            view! {
+                                <option value=id.0.to_string() selected=move || display.get() == Some(id)>
+                                    "Only on " {d.name}
+                                </option>
+                            }
+                        }).collect::<Vec<_>>()}
+                    </select>
+                </div>
+                <div style="display: flex; gap: 0.25rem; flex-wrap: wrap; align-items: center;">
+                    <span style="font-size: 0.9rem; width: 4rem;">"Days"</span>
+                    {WEEKDAYS.into_iter().map(|day| {
+                        let selected = move || days.get().contains(&day);
+                        view! {
+                            <button
+                                style=move || chip_style(selected())
+                                on:click=move |_| set_days.set(toggled(days.get(), day))
+                            >
+                                {day.to_string()}
+                            </button>
+                        }
+                    }).collect::<Vec<_>>()}
+                </div>
+                <div style="display: flex; gap: 0.25rem; flex-wrap: wrap; align-items: center;">
+                    <span style="font-size: 0.9rem; width: 4rem;">"Months"</span>
+                    {MONTH_NAMES.iter().enumerate().map(|(i, &label)| {
+                        let month = i as u32 + 1;
+                        let selected = move || months.get().contains(&month);
+                        view! {
+                            <button
+                                style=move || chip_style(selected())
+                                on:click=move |_| set_months.set(toggled(months.get(), month))
+                            >
+                                {label}
+                            </button>
+                        }
+                    }).collect::<Vec<_>>()}
+                </div>
+                <div style="display: flex; gap: 0.5rem; align-items: center; font-size: 0.9rem;">
+                    <span style="width: 4rem;">"Time"</span>
+                    <input
+                        type="time"
+                        style="padding: 0.25rem; border: 1px solid #ccc; border-radius: 4px;"
+                        prop:value=move || start_time.get()
+                        on:input=move |ev| set_start_time.set(event_target_value(&ev))
+                    />
+                    "to"
+                    <input
+                        type="time"
+                        style="padding: 0.25rem; border: 1px solid #ccc; border-radius: 4px;"
+                        prop:value=move || end_time.get()
+                        on:input=move |ev| set_end_time.set(event_target_value(&ev))
+                    />
+                    <span style="color: #666;">"Leave days, months or times empty to match any."</span>
+                </div>
+                <div style="display: flex; gap: 0.5rem;">
+                    <button
+                        style="padding: 0.5rem 1rem; background: #4CAF50; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                        on:click=handle_submit
+                        disabled=move || saving.get() || name.get().trim().is_empty()
+                    >
+                        {move || if editing.get().is_some() { "Save Schedule" } else { "Add Schedule" }}
+                    </button>
+                    {move || editing.get().map(|_| view! {
+                        <button
+                            style="padding: 0.5rem 1rem; background: #f0f0f0; color: #333; border: none; border-radius: 4px; cursor: pointer;"
+                            on:click=move |_| reset_form()
+                        >
+                            "Cancel"
+                        </button>
+                    })}
+                </div>
+            </div>
+
+            // Schedule list, highest priority first
+            <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                {move || schedules.get().into_iter().map(|schedule| {
+                    let id = schedule.id;
+                    let plays = match schedule.action {
+                        ScheduleAction::Album { album } => albums
+                            .get()
+                            .into_iter()
+                            .find(|a| a.id == album)
+                            .map(|a| format!("Plays {}", a.name))
+                            .unwrap_or_else(|| format!("Plays album {}", album)),
+                        ScheduleAction::Blank => "Shows nothing".to_string(),
+                    };
+                    let frame = schedule
+                        .display
+                        .and_then(|d| displays.get().into_iter().find(|x| x.id == d))
+                        .map(|d| format!(" on {}", d.name))
+                        .unwrap_or_default();
+                    let toggle = {
+                        let save = save.clone();
+                        let mut req = schedule.to_request();
+                        req.enabled = !schedule.enabled;
+                        move |_| save(Some(id), req.clone())
+                    };
+                    let handle_delete = handle_delete.clone();
+                    let opacity = if schedule.enabled { "1" } else { "0.5" };
+                    view! {
+                        <div style="display: flex; justify-content: space-between; align-items: center; padding: 0.75rem; background: #f5f5f5; border-radius: 4px;" style:opacity=opacity>
+                            <div>
+                                <div style="font-weight: 500;">
+                                    {schedule.name.clone()}
+                                    <span style="font-weight: normal; color: #666;">" · priority " {schedule.priority}</span>
+                                </div>
+                                <div style="font-size: 0.85rem; color: #666;">
+                                    {plays} {frame} " · " {schedule.summary()}
+                                </div>
+                            </div>
+                            <div style="display: flex; gap: 0.5rem;">
+                                <button
+                                    style="padding: 0.25rem 0.5rem; background: #f0f0f0; color: #333; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                                    on:click=toggle
+                                    disabled=move || saving.get()
+                                >
+                                    {if schedule.enabled { "Disable" } else { "Enable" }}
+                                </button>
+                                <button
+                                    style="padding: 0.25rem 0.5rem; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                                    on:click={
+                                        let schedule = schedule.clone();
+                                        move |_| edit(schedule.clone())
+                                    }
+                                >
+                                    "Edit"
+                                </button>
+                                <button
+                                    style="padding: 0.25rem 0.5rem; background: #f44336; color: white; border: none; border-radius: 4px; cursor: pointer; font-size: 0.8rem;"
+                                    on:click=move |_| handle_delete(id)
+                                >
+                                    "Delete"
+                                </button>
+                            </div>
+                        </div>
+                    }
+                }).collect::<Vec<_>>()}
+            </div>
+        </div>
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Settings Tab
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[component]
+fn SettingsTab<F>(
+    settings: ReadSignal<Option<RotationSettings>>,
+    albums: ReadSignal<Vec<Album>>,
+    client: Client,
+    on_refresh: F,
+) -> impl IntoView
+where
+    F: Fn() + Clone + Send + 'static,
+{
+    let (saving, set_saving) = signal(false);
+
+    let handle_set_album = {
+        let client = client.clone();
+        let on_refresh = on_refresh.clone();
+        move |album_id: Option<AlbumID>| {
+            if saving.get() {
+                return;
+            }
+            set_saving.set(true);
+            let client = client.clone();
+            let on_refresh = on_refresh.clone();
+            spawn_local(async move {
+                let update = UpdateSettingsRequest {
+                    current_album_id: Some(match album_id {
+                        Some(id) => Update::Set(id),
+                        None => Update::Remove,
+                    }),
+                    interval_seconds: None,
+                    rotation_mode: None,
+                };
+                match client.update_settings(&update).await {
+                    Ok(_) => on_refresh(),
+                    Err(e) => log::error!("Failed to update settings: {:?}", e),
+                }
+                set_saving.set(false);
+            });
+        }
+    };
+
+    let handle_set_interval = {
+        let client = client.clone();
+        let on_refresh = on_refresh.clone();
+        move |seconds: i32| {
+            if saving.get() {
+                return;
+            }
+            set_saving.set(true);
+            let client = client.clone();
+            let on_refresh = on_refresh.clone();
+            spawn_local(async move {
+                let update = UpdateSettingsRequest {
+                    current_album_id: None,
+                    interval_seconds: Some(seconds),
+                    rotation_mode: None,
+                };
+                match client.update_settings(&update).await {
+                    Ok(_) => on_refresh(),
+                    Err(e) => log::error!("Failed to update settings: {:?}", e),
+                }
+                set_saving.set(false);
+            });
+        }
+    };
+
+    let handle_set_mode = {
+        let client = client.clone();
+        let on_refresh = on_refresh.clone();
+        move |mode: RotationMode| {
+            if saving.get() {
+                return;
+            }
+            set_saving.set(true);
+            let client = client.clone();
+            let on_refresh = on_refresh.clone();
+            spawn_local(async move {
+                let update = UpdateSettingsRequest {
+                    current_album_id: None,
+                    interval_seconds: None,
+                    rotation_mode: Some(mode),
+                };
+                match client.update_settings(&update).await {
+                    Ok(_) => on_refresh(),
+                    Err(e) => log::error!("Failed to update settings: {:?}", e),
+                }
+                set_saving.set(false);
+            });
+        }
+    };
+
+    view! {
+        <div>
+            <h2 style="margin-bottom: 1.5rem;">"Settings"</h2>
+
+            {move || settings.get().map(|s| {
+                let current_album_id = s.current_album.as_ref().map(|c| c.album.0);
+                let interval = s.interval.seconds();
+                let mode = s.mode;
+
+                view! {
+                    <div style="max-width: 500px;">
+                        // Current album
+                        <div style="margin-bottom: 1.5rem;">
+                            <label style="display: block; font-weight: 500; margin-bottom: 0.5rem;">
+                                "Current Album"
+                            </label>
+                            <select
+                                style="width: 100%; padding: 0.5rem; border: 1px solid #ccc; border-radius: 4px; font-size: 1rem;"
+                                on:change={
+                                    let handle = handle_set_album.clone();
+                                    move |ev| {
+                                        let value = event_target_value(&ev);
+                                        let album_id = if value == "none" {
+                                            None
+                                        } else {
+                                            value.parse::<i32>().ok().map(AlbumID)
+                                        };
+                                        handle(album_id);
+                                    }
+                                }
+                                disabled=move || saving.get()
+                            >
+                                <option value="none" selected=current_album_id.is_none()>
+                                    "(All photos - no album selected)"
+                                </option>
+                                {albums.get().into_iter().map(|album| {
+                                    let is_selected = current_album_id == Some(album.id.0);
+                                    view! {
+                                        <option value=album.id.0.to_string() selected=is_selected>
+                                            {album.name} " (" {album.photos.len()} " photos)"
+                                        </option>
+                                    }
                                 }).collect::<Vec<_>>()}
                             </select>
                             <p style="font-size: 0.85rem; color: #666; margin-top: 0.25rem;">
@@ -924,6 +3319,35 @@ where
                             </div>
                         </div>
 
+                        // Rotation mode
+                        <div style="margin-bottom: 1.5rem;">
+                            <label style="display: block; font-weight: 500; margin-bottom: 0.5rem;">
+                                "Order"
+                            </label>
+                            <div style="display: flex; gap: 0.5rem; flex-wrap: wrap;">
+                                {RotationMode::ALL.iter().map(|&option| {
+                                    let style = if option == mode {
+                                        "padding: 0.5rem 1rem; background: #2196F3; color: white; border: none; border-radius: 4px; cursor: pointer;"
+                                    } else {
+                                        "padding: 0.5rem 1rem; background: #f0f0f0; color: #333; border: none; border-radius: 4px; cursor: pointer;"
+                                    };
+                                    let handle = handle_set_mode.clone();
+                                    view! {
+                                        <button
+                                            style=style
+                                            on:click=move |_| handle(option)
+                                            disabled=move || saving.get()
+                                        >
+                                            {option.label()}
+                                        </button>
+                                    }
+                                }).collect::<Vec<_>>()}
+                            </div>
+                            <p style="font-size: 0.85rem; color: #666; margin-top: 0.25rem;">
+                                "Shuffle shows every photo once before reshuffling. Weighted random favors photos that haven't been shown for a while."
+                            </p>
+                        </div>
+
                         {move || if saving.get() {
                             Some(view! { <p style="color: #2196F3;">"Saving..."</p> })
                         } else {
@@ -932,6 +3356,32 @@ where
                     </div>
                 }
             })}
+
+            // Backup
+            <div style="max-width: 500px; margin-top: 2rem; padding-top: 1.5rem; border-top: 1px solid #e0e0e0;">
+                <label style="display: block; font-weight: 500; margin-bottom: 0.5rem;">
+                    "Backup"
+                </label>
+                <div style="display: flex; gap: 0.5rem; flex-wrap: wrap;">
+                    <a
+                        href=BackupQuery { originals_only: false }.url()
+                        download
+                        style="padding: 0.5rem 1rem; background: #2196F3; color: white; border-radius: 4px; text-decoration: none;"
+                    >
+                        "Download backup"
+                    </a>
+                    <a
+                        href=BackupQuery { originals_only: true }.url()
+                        download
+                        style="padding: 0.5rem 1rem; background: #f0f0f0; color: #333; border-radius: 4px; text-decoration: none;"
+                    >
+                        "Originals only"
+                    </a>
+                </div>
+                <p style="font-size: 0.85rem; color: #666; margin-top: 0.25rem;">
+                    "One archive of every photo, album and setting. Restore it on another frame with `pictureframe restore`. Originals-only backups are smaller, and the missing sizes are rebuilt on restore."
+                </p>
+            </div>
         </div>
     }
 }
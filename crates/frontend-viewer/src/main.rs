@@ -1,5 +1,5 @@
 use leptos::{prelude::*, task::spawn_local};
-use pictureframe_common::{Client, Next, Photo};
+use pictureframe_common::{ApiError, Client, Next, Photo};
 use std::time::Duration;
 
 fn main() {
@@ -12,6 +12,8 @@ fn main() {
 #[component]
 fn PhotoOverlay(photo: Photo, #[prop(into)] visible: Signal<bool>) -> impl IntoView {
     let opacity = move || if visible.get() { "1" } else { "0" };
+    let camera = photo.exif.camera();
+    let exposure = photo.exif.exposure_summary();
 
     view! {
         <div style:opacity=opacity style="
@@ -31,14 +33,28 @@ fn PhotoOverlay(photo: Photo, #[prop(into)] visible: Signal<bool>) -> impl IntoV
                 {photo.date_taken.map(|d| view! { <span>{d.format("%B %d, %Y").to_string()}</span> })}
                 {photo.copyright.map(|c| view! { <span>"© "{c}</span> })}
             </div>
+            <div style="display: flex; gap: 1.5rem; margin-top: 0.35rem; font-size: 0.8rem; opacity: 0.75;">
+                {camera.map(|c| view! { <span>{c}</span> })}
+                {photo.exif.lens.map(|l| view! { <span>{l}</span> })}
+                {exposure.map(|e| view! { <span>{e}</span> })}
+            </div>
         </div>
     }
 }
 
+/// Token of the display this viewer runs on, from the `?display=` query parameter
+fn display_token() -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    web_sys::UrlSearchParams::new_with_str(&search)
+        .ok()?
+        .get("display")
+}
+
 #[component]
 fn App() -> impl IntoView {
     // Client uses relative URLs - works when served from same origin
     let client = Client::new("");
+    let display = display_token();
 
     // Current photo state
     let (current, set_current) = signal(None::<Next>);
@@ -53,8 +69,9 @@ fn App() -> impl IntoView {
         let _ = fetch_trigger.get();
 
         let client = client.clone();
+        let display = display.clone();
         spawn_local(async move {
-            match client.get_next().await {
+            match client.get_next(display.as_deref()).await {
                 Ok(next) => {
                     let interval_secs = next.interval.seconds() as u64;
                     set_current.set(Some(next));
@@ -75,6 +92,10 @@ fn App() -> impl IntoView {
                 }
                 Err(e) => {
                     log::error!("Failed to fetch image: {:?}", e);
+                    // Nothing to show, e.g. a schedule blanks the frame overnight
+                    if matches!(e, ApiError::Http { status: 404, .. }) {
+                        set_current.set(None);
+                    }
                     // Retry after 30 seconds on error
                     set_timeout(
                         move || set_fetch_trigger.update(|n| *n = n.wrapping_add(1)),
@@ -86,7 +107,7 @@ fn App() -> impl IntoView {
     });
 
     view! {
-        <style>"html, body { margin: 0; padding: 0; overflow: hidden; }"</style>
+        <style>"html, body { margin: 0; padding: 0; overflow: hidden; background: #000; }"</style>
         {move || current.get().map(|next| {
             let photo = next.photo.clone();
             let url = next.photo.url.clone();
@@ -0,0 +1,16 @@
+-- Background jobs (e.g. uploaded photos waiting to be processed)
+CREATE TABLE IF NOT EXISTS job (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    kind TEXT NOT NULL DEFAULT 'upload',
+    status TEXT NOT NULL DEFAULT 'queued',
+    filename TEXT NOT NULL,
+    staged_path TEXT NOT NULL,
+    error TEXT,
+    photo_id INTEGER,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (photo_id) REFERENCES photo(id) ON DELETE SET NULL
+);
+
+-- Index for workers picking the oldest queued job
+CREATE INDEX IF NOT EXISTS idx_job_status ON job(status, id);
@@ -0,0 +1,4 @@
+-- Add upright rendition dimensions and the original's EXIF orientation to photo table
+ALTER TABLE photo ADD COLUMN width INTEGER;
+ALTER TABLE photo ADD COLUMN height INTEGER;
+ALTER TABLE photo ADD COLUMN orientation INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,13 @@
+-- Add camera, exposure and location details from EXIF to photo table
+ALTER TABLE photo ADD COLUMN camera_make TEXT;
+ALTER TABLE photo ADD COLUMN camera_model TEXT;
+ALTER TABLE photo ADD COLUMN lens TEXT;
+ALTER TABLE photo ADD COLUMN focal_length REAL;
+ALTER TABLE photo ADD COLUMN aperture REAL;
+ALTER TABLE photo ADD COLUMN exposure_time REAL;
+ALTER TABLE photo ADD COLUMN iso INTEGER;
+ALTER TABLE photo ADD COLUMN latitude REAL;
+ALTER TABLE photo ADD COLUMN longitude REAL;
+ALTER TABLE photo ADD COLUMN pixel_width INTEGER;
+ALTER TABLE photo ADD COLUMN pixel_height INTEGER;
+ALTER TABLE photo ADD COLUMN date_taken_offset TEXT;
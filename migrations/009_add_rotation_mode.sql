@@ -0,0 +1,14 @@
+-- Add rotation modes: shuffle keeps its current cycle in shuffle_queue, weighted random
+-- favors photos with a low photo.last_shown
+ALTER TABLE settings ADD COLUMN rotation_mode TEXT NOT NULL DEFAULT 'sequential';
+ALTER TABLE settings ADD COLUMN show_count INTEGER NOT NULL DEFAULT 0;
+
+-- Value of settings.show_count when the photo was last displayed. NULL if it never has been
+ALTER TABLE photo ADD COLUMN last_shown INTEGER;
+
+-- Photos still to come in the current shuffle cycle, in the order they will be shown
+CREATE TABLE IF NOT EXISTS shuffle_queue (
+    position INTEGER PRIMARY KEY,
+    photo_id INTEGER NOT NULL,
+    FOREIGN KEY (photo_id) REFERENCES photo(id) ON DELETE CASCADE
+);
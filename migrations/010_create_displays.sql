@@ -0,0 +1,20 @@
+-- Displays: named frames that each keep their own place in their own album
+CREATE TABLE IF NOT EXISTS display (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL,
+    -- Passed by the viewer as ?display=<token>
+    token TEXT UNIQUE NOT NULL,
+    current_album_id INTEGER,
+    current_photo_index INTEGER NOT NULL DEFAULT 0,
+    interval_seconds INTEGER NOT NULL DEFAULT 180,
+    rotation_mode TEXT NOT NULL DEFAULT 'sequential',
+    -- Overrides the mat of every photo shown on this display. NULL keeps each photo's own
+    mat_preset TEXT,
+    last_seen_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (current_album_id) REFERENCES album(id) ON DELETE SET NULL
+);
+
+-- Each display shuffles independently. NULL is the shared rotation from settings
+ALTER TABLE shuffle_queue ADD COLUMN display_id INTEGER REFERENCES display(id) ON DELETE CASCADE;
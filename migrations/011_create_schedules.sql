@@ -0,0 +1,21 @@
+-- Schedules: rules choosing which album plays at certain times
+CREATE TABLE IF NOT EXISTS schedule (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL,
+    -- Album to play. NULL shows nothing while the schedule is active
+    album_id INTEGER,
+    -- Only applies to this display. NULL applies to every frame
+    display_id INTEGER,
+    priority INTEGER NOT NULL DEFAULT 0,
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    -- Bit 0 is Monday, bit 6 Sunday. 0 means every day
+    weekdays INTEGER NOT NULL DEFAULT 0,
+    -- Bit 0 is January, bit 11 December. 0 means all year
+    months INTEGER NOT NULL DEFAULT 0,
+    start_time TEXT,
+    end_time TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (album_id) REFERENCES album(id) ON DELETE CASCADE,
+    FOREIGN KEY (display_id) REFERENCES display(id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+-- Virtual albums pick their photos by date_taken when read instead of using album_photo.
+-- JSON serialized pictureframe_common::VirtualAlbum, NULL for albums managed by hand
+ALTER TABLE album ADD COLUMN virtual_album TEXT;
@@ -0,0 +1,3 @@
+-- Smart albums hold every photo matching a saved filter instead of using album_photo.
+-- JSON serialized pictureframe_common::SmartRule, NULL for other albums
+ALTER TABLE album ADD COLUMN smart_rule TEXT;
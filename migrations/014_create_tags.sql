@@ -0,0 +1,20 @@
+-- Tags: keywords photos can be filtered and grouped by
+CREATE TABLE IF NOT EXISTS tag (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- "Beach" and "beach" are the same tag
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Junction table for many-to-many relationship between photos and tags
+CREATE TABLE IF NOT EXISTS photo_tag (
+    photo_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (photo_id, tag_id),
+    FOREIGN KEY (photo_id) REFERENCES photo(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tag(id) ON DELETE CASCADE
+);
+
+-- Index for finding the photos with a tag
+CREATE INDEX IF NOT EXISTS idx_photo_tag_tag ON photo_tag(tag_id);
@@ -0,0 +1,19 @@
+-- Tags proposed by the classifier, kept until someone accepts or rejects them
+CREATE TABLE IF NOT EXISTS tag_suggestion (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    photo_id INTEGER NOT NULL,
+    label TEXT NOT NULL COLLATE NOCASE,
+    -- tag, face or scene
+    kind TEXT NOT NULL DEFAULT 'tag',
+    -- 0 to 1
+    confidence REAL NOT NULL,
+    -- pending, accepted or rejected. Reviewed suggestions are kept so classifying the photo
+    -- again doesn't bring them back
+    status TEXT NOT NULL DEFAULT 'pending',
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (photo_id, label),
+    FOREIGN KEY (photo_id) REFERENCES photo(id) ON DELETE CASCADE
+);
+
+-- Index for listing suggestions waiting for review
+CREATE INDEX IF NOT EXISTS idx_tag_suggestion_status ON tag_suggestion(status, photo_id);
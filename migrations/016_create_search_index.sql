@@ -0,0 +1,54 @@
+-- Full-text search over photos and albums. The indexes read their text from the photo and
+-- album tables (external content) and the triggers below keep them in step.
+CREATE VIRTUAL TABLE IF NOT EXISTS photo_search USING fts5(
+    title,
+    artist,
+    notes,
+    content = 'photo',
+    content_rowid = 'id',
+    tokenize = 'porter unicode61 remove_diacritics 2'
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS album_search USING fts5(
+    name,
+    notes,
+    content = 'album',
+    content_rowid = 'id',
+    tokenize = 'porter unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS photo_search_insert AFTER INSERT ON photo BEGIN
+    INSERT INTO photo_search (rowid, title, artist, notes)
+    VALUES (new.id, new.title, new.artist, new.notes);
+END;
+
+CREATE TRIGGER IF NOT EXISTS photo_search_delete AFTER DELETE ON photo BEGIN
+    INSERT INTO photo_search (photo_search, rowid, title, artist, notes)
+    VALUES ('delete', old.id, old.title, old.artist, old.notes);
+END;
+
+CREATE TRIGGER IF NOT EXISTS photo_search_update AFTER UPDATE OF title, artist, notes ON photo BEGIN
+    INSERT INTO photo_search (photo_search, rowid, title, artist, notes)
+    VALUES ('delete', old.id, old.title, old.artist, old.notes);
+    INSERT INTO photo_search (rowid, title, artist, notes)
+    VALUES (new.id, new.title, new.artist, new.notes);
+END;
+
+CREATE TRIGGER IF NOT EXISTS album_search_insert AFTER INSERT ON album BEGIN
+    INSERT INTO album_search (rowid, name, notes) VALUES (new.id, new.name, new.notes);
+END;
+
+CREATE TRIGGER IF NOT EXISTS album_search_delete AFTER DELETE ON album BEGIN
+    INSERT INTO album_search (album_search, rowid, name, notes)
+    VALUES ('delete', old.id, old.name, old.notes);
+END;
+
+CREATE TRIGGER IF NOT EXISTS album_search_update AFTER UPDATE OF name, notes ON album BEGIN
+    INSERT INTO album_search (album_search, rowid, name, notes)
+    VALUES ('delete', old.id, old.name, old.notes);
+    INSERT INTO album_search (rowid, name, notes) VALUES (new.id, new.name, new.notes);
+END;
+
+-- Index everything that was added before this migration
+INSERT INTO photo_search (photo_search) VALUES ('rebuild');
+INSERT INTO album_search (album_search) VALUES ('rebuild');
@@ -0,0 +1,5 @@
+-- Deleted photos go to the trash instead of being removed straight away. They keep their
+-- albums and tags so a restore puts them back as they were.
+ALTER TABLE photo ADD COLUMN deleted_at DATETIME;
+
+CREATE INDEX IF NOT EXISTS idx_photo_deleted_at ON photo(deleted_at);
@@ -0,0 +1,13 @@
+-- Each display keeps its own rotation history, so photos shown on one display aren't held
+-- back on another. settings.show_count and photo.last_shown still count every photo shown
+ALTER TABLE display ADD COLUMN show_count INTEGER NOT NULL DEFAULT 0;
+
+-- Value of display.show_count when the photo was last shown on the display
+CREATE TABLE IF NOT EXISTS display_photo (
+    display_id INTEGER NOT NULL,
+    photo_id INTEGER NOT NULL,
+    last_shown INTEGER NOT NULL,
+    PRIMARY KEY (display_id, photo_id),
+    FOREIGN KEY (display_id) REFERENCES display(id) ON DELETE CASCADE,
+    FOREIGN KEY (photo_id) REFERENCES photo(id) ON DELETE CASCADE
+);
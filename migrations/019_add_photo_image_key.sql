@@ -0,0 +1,5 @@
+-- Record the original's MIME type so serving it doesn't have to sniff the file, and a digest
+-- of the photo's image files that goes in their URLs so browsers can cache them for good.
+-- Both are NULL for photos imported before they were recorded until they're filled in.
+ALTER TABLE photo ADD COLUMN content_type TEXT;
+ALTER TABLE photo ADD COLUMN image_key TEXT;
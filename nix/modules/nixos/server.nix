@@ -10,6 +10,7 @@
     dist-dir = cfg.distDir;
     host = cfg.host;
     port = cfg.port;
+    image-backend = cfg.imageBackend;
   };
 in {
   options.services.pictureframeServer = {
@@ -45,6 +46,12 @@ in {
       default = 3000;
       description = "Port to listen on.";
     };
+
+    imageBackend = lib.mkOption {
+      type = lib.types.enum ["magick" "native"];
+      default = "magick";
+      description = "How to generate websize and thumbnail images. 'native' needs no ImageMagick but can't read HEIC.";
+    };
   };
 
   config = lib.mkIf cfg.enable {
@@ -62,7 +69,7 @@ in {
       wantedBy = ["multi-user.target"];
       after = ["network.target"];
 
-      path = [
+      path = lib.optionals (cfg.imageBackend == "magick") [
         pkgs.imagemagick # TODO: instead of duplicating, take runtimeInputs from nix/lib/default.nix
       ];
 
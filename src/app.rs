@@ -1,73 +1,849 @@
-use crate::models::{DbAlbum, DbPhoto, DbSettings};
-use crate::on_disk_photo::OnDiskPhoto;
+use crate::backup::Snapshot;
+use crate::classifier::Classifier;
+use crate::export;
+use crate::image_processor::{self, ImageProcessor};
+use crate::models::{
+    DbAlbum, DbDisplay, DbJob, DbPhoto, DbSchedule, DbSettings, DbTag, DbTagSuggestion,
+};
+use crate::on_disk_photo::{self, OnDiskPhoto};
+use crate::photo_format::PhotoFormat;
+use crate::rotation;
 use anyhow::{Result, bail};
 use api_macros::api;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
+use chrono::{Datelike, NaiveDateTime, Weekday};
 use pictureframe_common::{
-    Album, AlbumID, CreateAlbumRequest, CurrentAlbum, Interval, MatStyle, Next, Photo, PhotoID,
-    RotationSettings, Update, UpdateAlbumRequest, UpdatePhotoRequest, UpdateSettingsRequest,
+    Album, AlbumEnd, AlbumID, AlbumPhotoSort, AlbumSearchResult, BackupQuery, BulkPhotoOperation,
+    BulkPhotoRequest, BulkPhotoResult, CreateAlbumRequest, CreateDisplayRequest, CurrentAlbum,
+    Display, DisplayID, DuplicateError, DuplicateGroup, DuplicatesQuery, ImageQuery, ImageVariant,
+    Interval, Job, JobID, JobKind, JobStatus, MAX_PHOTO_FIELD_LEN, MAX_PHOTO_NOTES_LEN, MatStyle,
+    MergePhotosRequest, MergeTagsRequest, MoveAlbumPhotoRequest, Next, NextQuery, Photo, PhotoExif,
+    PhotoID, PhotoPage, PhotoQuery, PhotoSearchResult, PhotoSort, ReorderAlbumRequest,
+    RotationMode, RotationSettings, Schedule, ScheduleAction, ScheduleID, ScheduleRequest,
+    SearchQuery, SearchResults, SmartRule, SortAlbumRequest, SortOrder, SuggestionID,
+    SuggestionKind, SuggestionsQuery, Tag, TagID, TagMatch, TagPhotosRequest, TagRequest,
+    TagSuggestion, TrashedPhoto, Update, UpdateAlbumRequest, UpdateDisplayRequest,
+    UpdatePhotoRequest, UpdateSettingsRequest, VirtualAlbum,
 };
+use rand::distr::{Alphanumeric, SampleString};
 use serde::Serialize;
-use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::sqlite::{Sqlite, SqlitePool, SqlitePoolOptions};
+use sqlx::{FromRow, QueryBuilder, Row};
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 use std::{fs, path::PathBuf};
+use tokio::io::{AsyncWriteExt, DuplexStream};
+use tokio::sync::Notify;
+use tokio_util::io::{ReaderStream, SyncIoBridge};
 use tracing::{debug, error, info, warn};
 
-const DB_FILE_NAME: &str = "db.sqlite";
+pub(crate) const DB_FILE_NAME: &str = "db.sqlite";
+
+/// Maximum number of jobs returned by `GET /api/jobs`
+const MAX_JOBS_LISTED: i32 = 200;
+
+/// Bytes of a backup archive buffered between the thread writing it and the response
+const BACKUP_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Bytes of a download buffered between the thread writing it and the response
+const DOWNLOAD_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Length of the random token a display identifies itself with
+const DISPLAY_TOKEN_LEN: usize = 16;
+
+/// Tags with the number of photos that have them. Filter with `WHERE` before the `GROUP BY`.
+/// Photos in the trash aren't counted.
+const SELECT_TAGS: &str = "SELECT t.id, t.name, COUNT(pt.photo_id) AS photo_count FROM tag t LEFT JOIN photo_tag pt ON pt.tag_id = t.id AND pt.photo_id IN (SELECT id FROM photo WHERE deleted_at IS NULL)";
+
+/// Most photos, and most albums, returned by `GET /api/search`
+const MAX_SEARCH_RESULTS: i64 = 50;
+
+/// Number of words of context in a search result's snippet
+const SNIPPET_TOKENS: i32 = 12;
+
+/// No photo was taken before this year, so an earlier date taken is a mistake
+const EARLIEST_PHOTO_YEAR: i32 = 1826;
+
+/// Classifications less certain than this aren't worth anyone's time to review
+const MIN_SUGGESTION_CONFIDENCE: f32 = 0.3;
+
+/// Default Hamming distance under which two photo hashes are treated as the same shot
+pub const DEFAULT_DUPLICATE_THRESHOLD: u32 = 4;
+
+/// Default number of days a deleted photo stays in the trash before it's purged
+pub const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// Returned (wrapped in an `anyhow::Error`) when a photo being imported is already in the library.
+#[derive(Debug, Clone, Copy)]
+pub struct DuplicatePhoto {
+    pub existing: i32,
+    /// Hamming distance between the two hashes. 0 is an exact match.
+    pub distance: u32,
+    /// The existing photo was deleted and is waiting in the trash
+    pub in_trash: bool,
+}
+
+impl std::fmt::Display for DuplicatePhoto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.distance == 0 {
+            write!(f, "Photo is a duplicate of photo {}", self.existing)?;
+        } else {
+            write!(
+                f,
+                "Photo is a near-duplicate of photo {} (distance {})",
+                self.existing, self.distance
+            )?;
+        }
+        if self.in_trash {
+            write!(f, ", which is in the trash")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DuplicatePhoto {}
+
+/// Move a file that could not be imported into the fails dir and write a sidecar
+/// `<name>.error.txt` next to it explaining what went wrong.
+fn move_bad_file(path: &Path, fails_dir: &Path, reason: &str) -> Result<()> {
+    let name = path.file_name().unwrap().to_string_lossy().to_string();
+
+    // Don't clobber an earlier failure with the same file name
+    let mut new_path = fails_dir.join(&name);
+    let mut n = 1;
+    while new_path.exists() {
+        new_path = fails_dir.join(format!("{n}-{name}"));
+        n += 1;
+    }
+
+    // The import may have already consumed the original before failing
+    if path.exists() {
+        fs::rename(path, &new_path)?;
+    }
 
-fn move_bad_file(path: &Path, fails_dir: &Path) -> Result<()> {
-    let name = path.file_name().unwrap();
-    let new_path = fails_dir.join(name);
-    fs::rename(path, new_path)?;
+    let report_name = format!(
+        "{}.error.txt",
+        new_path.file_name().unwrap().to_string_lossy()
+    );
+    let report = format!(
+        "file: {}\nfailed at: {}\nerror: {reason}\n",
+        path.display(),
+        chrono::Local::now().to_rfc3339(),
+    );
+    fs::write(fails_dir.join(report_name), report)?;
     Ok(())
 }
 
-fn db_photo_to_photo(input: &DbPhoto) -> Photo {
+/// Tag names of each photo, keyed by photo ID
+type PhotoTags = HashMap<i32, Vec<String>>;
+
+fn db_photo_to_photo(input: &DbPhoto, tags: &PhotoTags) -> Photo {
     Photo {
         id: PhotoID::from(input.id),
-        url: format!("/api/images/{}", input.id),
+        url: ImageVariant::Web.url(PhotoID::from(input.id), input.image_key.as_deref()),
+        thumbnail_url: ImageVariant::Thumbnail
+            .url(PhotoID::from(input.id), input.image_key.as_deref()),
+        full_url: ImageVariant::Full.url(PhotoID::from(input.id), input.image_key.as_deref()),
         title: input.title.clone(),
         notes: input.notes.clone(),
         artist: input.artist.clone(),
         copyright: input.copyright.clone(),
         date_taken: input.date_taken,
         mat_preset: input.mat_preset.clone(),
+        width: input.width.map(|w| w as u32),
+        height: input.height.map(|h| h as u32),
+        exif: PhotoExif {
+            camera_make: input.camera_make.clone(),
+            camera_model: input.camera_model.clone(),
+            lens: input.lens.clone(),
+            focal_length: input.focal_length,
+            aperture: input.aperture,
+            exposure_time: input.exposure_time,
+            iso: input.iso.map(|iso| iso as u32),
+            latitude: input.latitude,
+            longitude: input.longitude,
+            pixel_width: input.pixel_width.map(|w| w as u32),
+            pixel_height: input.pixel_height.map(|h| h as u32),
+            date_taken_offset: input.date_taken_offset.clone(),
+        },
+        added_at: input.created_at,
+        tags: tags.get(&input.id).cloned().unwrap_or_default(),
     }
 }
 
-fn is_photo(path: &Path) -> bool {
-    let Some(ext) = path.extension() else {
-        return false;
+/// Append the `WHERE` clause for the filters in `query`. `album_photos` holds the photos of
+/// the album being filtered by, if any.
+fn push_photo_filters(
+    sql: &mut QueryBuilder<'_, Sqlite>,
+    query: &PhotoQuery,
+    album_photos: Option<&[i32]>,
+) {
+    sql.push(" WHERE deleted_at IS NULL");
+
+    let names = query.tag_names();
+    if !names.is_empty() {
+        let required = match query.tag_match {
+            TagMatch::All => names.len() as i64,
+            TagMatch::Any => 1,
+        };
+        sql.push(
+            " AND id IN (SELECT pt.photo_id FROM photo_tag pt JOIN tag t ON t.id = pt.tag_id WHERE t.name IN (",
+        );
+        let mut names_list = sql.separated(", ");
+        for name in names {
+            names_list.push_bind(name);
+        }
+        sql.push(") GROUP BY pt.photo_id HAVING COUNT(*) >= ")
+            .push_bind(required)
+            .push(")");
+    }
+    if let Some(ids) = album_photos {
+        sql.push(" AND id IN (SELECT value FROM json_each(")
+            .push_bind(serde_json::json!(ids).to_string())
+            .push("))");
+    }
+    if query.without_album {
+        sql.push(" AND id NOT IN (SELECT photo_id FROM album_photo)");
+    }
+    if let Some(after) = query.taken_after {
+        sql.push(" AND date(date_taken) >= ").push_bind(after);
+    }
+    if let Some(before) = query.taken_before {
+        sql.push(" AND date(date_taken) <= ").push_bind(before);
+    }
+    if let Some(text) = query.search_text() {
+        let pattern = format!(
+            "%{}%",
+            text.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        sql.push(" AND (");
+        for (i, column) in ["title", "artist", "copyright", "notes"].iter().enumerate() {
+            if i > 0 {
+                sql.push(" OR ");
+            }
+            sql.push(format!("{column} LIKE "))
+                .push_bind(pattern.clone())
+                .push(" ESCAPE '\\'");
+        }
+        sql.push(")");
+    }
+}
+
+fn db_tag_to_tag(input: &DbTag) -> Tag {
+    Tag {
+        id: TagID::from(input.id),
+        name: input.name.clone(),
+        photo_count: input.photo_count as u32,
+    }
+}
+
+fn db_suggestion_to_suggestion(input: &DbTagSuggestion) -> TagSuggestion {
+    TagSuggestion {
+        id: SuggestionID::from(input.id),
+        photo: PhotoID::from(input.photo_id),
+        label: input.label.clone(),
+        kind: SuggestionKind::from_db(&input.kind),
+        confidence: input.confidence as f32,
+        created_at: input.created_at,
+    }
+}
+
+/// FTS5 query matching every word of `text` as a prefix, or None if there are no words.
+/// Each word is quoted so punctuation in it can't be read as query syntax.
+fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Turn a snippet marked up with `char(2)` and `char(3)` into HTML with the matches in
+/// `<mark>`. Everything else is escaped, since titles and notes are user input.
+fn highlight_snippet(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+    for c in snippet.chars() {
+        match c {
+            '\u{2}' => html.push_str("<mark>"),
+            '\u{3}' => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+/// Trimmed tag name, or why it can't be used
+fn check_tag_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tag name cannot be empty".to_string());
+    }
+    // Tags are filtered by as a comma separated list
+    if name.contains(',') {
+        return Err(format!("Tag name can't contain commas: {}", name));
+    }
+    Ok(name.to_string())
+}
+
+fn db_job_to_job(input: &DbJob) -> Job {
+    Job {
+        id: JobID::from(input.id),
+        kind: JobKind::from_db(&input.kind),
+        filename: input.filename.clone(),
+        status: JobStatus::from_db(&input.status),
+        error: input.error.clone(),
+        photo: input.photo_id.map(PhotoID::from),
+        created_at: input.created_at,
+        updated_at: input.updated_at,
+    }
+}
+
+/// The rule of a virtual album. Albums managed by hand have none
+fn virtual_album(input: &DbAlbum) -> Option<VirtualAlbum> {
+    let json = input.virtual_album.as_deref()?;
+    match serde_json::from_str(json) {
+        Ok(virtual_album) => Some(virtual_album),
+        Err(e) => {
+            warn!(
+                "Ignoring invalid virtual album rule on album {}: {e}",
+                input.id
+            );
+            None
+        }
+    }
+}
+
+fn is_virtual(input: &DbAlbum) -> bool {
+    virtual_album(input).is_some()
+}
+
+/// The saved filter of a smart album
+fn smart_rule(input: &DbAlbum) -> Option<SmartRule> {
+    let json = input.smart_rule.as_deref()?;
+    match serde_json::from_str(json) {
+        Ok(rule) => Some(rule),
+        Err(e) => {
+            warn!("Ignoring invalid smart rule on album {}: {e}", input.id);
+            None
+        }
+    }
+}
+
+/// Virtual and smart albums work out their own photos, so photos can't be added by hand
+fn picks_own_photos(input: &DbAlbum) -> bool {
+    is_virtual(input) || smart_rule(input).is_some()
+}
+
+/// `NotFound` for the first photo that isn't in the library
+async fn check_photos_exist<T>(
+    tx: &mut sqlx::SqliteConnection,
+    photos: &[PhotoID],
+) -> Option<APIResult<T>> {
+    for photo in photos {
+        match sqlx::query_scalar::<_, i32>(
+            "SELECT 1 FROM photo WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(photo.0)
+        .fetch_optional(&mut *tx)
+        .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => return Some(APIResult::NotFound(format!("Photo {} not found", photo))),
+            Err(e) => return Some(APIResult::InternalError(format!("Database error: {}", e))),
+        }
+    }
+    None
+}
+
+/// Photos of an album managed by hand, in order
+async fn album_order(
+    tx: &mut sqlx::SqliteConnection,
+    album_id: i32,
+) -> Result<Vec<i32>, sqlx::Error> {
+    sqlx::query_scalar::<_, i32>(
+        "SELECT photo_id FROM album_photo WHERE album_id = ? ORDER BY position, created_at",
+    )
+    .bind(album_id)
+    .fetch_all(&mut *tx)
+    .await
+}
+
+/// Compare two optional values with missing ones last, whichever way round the rest go
+fn cmp_missing_last<T: Ord>(a: Option<T>, b: Option<T>, descending: bool) -> std::cmp::Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    }
+}
+
+/// Number the photos of an album 0, 1, 2... in the order given
+async fn write_album_order(
+    tx: &mut sqlx::SqliteConnection,
+    album_id: i32,
+    order: &[i32],
+) -> Result<(), sqlx::Error> {
+    for (position, photo_id) in order.iter().enumerate() {
+        sqlx::query("UPDATE album_photo SET position = ? WHERE album_id = ? AND photo_id = ?")
+            .bind(position as i32)
+            .bind(album_id)
+            .bind(photo_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Refuse virtual albums that could never hold a photo. The frame would show the whole library
+/// in their place.
+fn check_virtual_album(virtual_album: &VirtualAlbum) -> Result<(), String> {
+    match *virtual_album {
+        VirtualAlbum::TakenInMonth { month } if !(1..=12).contains(&month) => {
+            Err(format!("Invalid month: {}", month))
+        }
+        VirtualAlbum::ThisWeek { years_ago: Some(0) } => {
+            Err("Years ago must be at least 1".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_smart_rule(rule: &SmartRule) -> Result<(), String> {
+    if let Some(preset) = &rule.mat_preset
+        && !MatStyle::preset_names().contains(&preset.as_str())
+    {
+        return Err(format!("Unknown mat preset: {}", preset));
+    }
+    if let (Some(after), Some(before)) = (rule.taken_after, rule.taken_before)
+        && after > before
+    {
+        return Err("Taken after must not be later than taken before".to_string());
+    }
+    if rule.added_within_days == Some(0) {
+        return Err("Added within days must be at least 1".to_string());
+    }
+    for tag in &rule.tags {
+        check_tag_name(tag)?;
+    }
+    Ok(())
+}
+
+/// Check the new values in a photo update, or say why they can't be used
+fn check_photo_update(req: &UpdatePhotoRequest, now: NaiveDateTime) -> Result<(), String> {
+    let texts = [
+        ("Title", &req.title, MAX_PHOTO_FIELD_LEN),
+        ("Artist", &req.artist, MAX_PHOTO_FIELD_LEN),
+        ("Copyright", &req.copyright, MAX_PHOTO_FIELD_LEN),
+        ("Notes", &req.notes, MAX_PHOTO_NOTES_LEN),
+    ];
+    for (field, update, max) in texts {
+        if let Some(Update::Set(text)) = update
+            && text.trim().chars().count() > max
+        {
+            return Err(format!("{} must be at most {} characters", field, max));
+        }
+    }
+    if let Some(Update::Set(date)) = &req.date_taken {
+        // A day of slack for cameras set to a time zone ahead of ours
+        if date.year() < EARLIEST_PHOTO_YEAR || *date > now + chrono::Duration::days(1) {
+            return Err(format!(
+                "Date taken must be between {} and today",
+                EARLIEST_PHOTO_YEAR
+            ));
+        }
+    }
+    if let Some(preset) = &req.mat_preset
+        && !MatStyle::preset_names().contains(&preset.as_str())
+    {
+        return Err(format!("Unknown mat preset: {}", preset));
+    }
+    Ok(())
+}
+
+/// The value to store for a text field update. Blank text is stored as NULL.
+fn text_update(update: &Update<String>) -> Option<String> {
+    match update {
+        Update::Set(text) => Some(text.trim().to_string()).filter(|t| !t.is_empty()),
+        Update::Remove => None,
+    }
+}
+
+/// `UPDATE` statement applying a checked photo update to photo `id`
+fn photo_update_sql(req: &UpdatePhotoRequest, id: i32) -> QueryBuilder<'static, Sqlite> {
+    let mut sql = QueryBuilder::<Sqlite>::new("UPDATE photo SET updated_at = CURRENT_TIMESTAMP");
+    let texts = [
+        ("title", &req.title),
+        ("artist", &req.artist),
+        ("copyright", &req.copyright),
+        ("notes", &req.notes),
+    ];
+    for (column, update) in texts {
+        if let Some(update) = update {
+            sql.push(format!(", {column} = "))
+                .push_bind(text_update(update));
+        }
+    }
+    match &req.date_taken {
+        Some(Update::Set(date)) => {
+            sql.push(", date_taken = ").push_bind(*date);
+        }
+        Some(Update::Remove) => {
+            sql.push(", date_taken = NULL");
+        }
+        None => {}
+    }
+    if let Some(preset) = &req.mat_preset {
+        sql.push(", mat_preset = ").push_bind(preset.clone());
+    }
+    sql.push(" WHERE deleted_at IS NULL AND id = ")
+        .push_bind(id);
+    sql
+}
+
+/// Delete a photo in the trash and everything pointing at it. Returns the deleted photo, or
+/// `None` if it isn't in the trash, e.g. because it was restored in the meantime. Its files are
+/// left for the caller to remove once the transaction is committed.
+async fn purge_photo_rows(
+    tx: &mut sqlx::SqliteConnection,
+    id: i32,
+) -> Result<Option<DbPhoto>, sqlx::Error> {
+    let Some(photo) = sqlx::query_as::<_, DbPhoto>(
+        "DELETE FROM photo WHERE id = ? AND deleted_at IS NOT NULL RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(None);
     };
-    let Ok(ext) = ext.to_ascii_lowercase().into_string() else {
+    for sql in [
+        "DELETE FROM album_photo WHERE photo_id = ?",
+        "DELETE FROM photo_tag WHERE photo_id = ?",
+        "DELETE FROM tag_suggestion WHERE photo_id = ?",
+        "DELETE FROM display_photo WHERE photo_id = ?",
+    ] {
+        sqlx::query(sql).bind(id).execute(&mut *tx).await?;
+    }
+    Ok(Some(photo))
+}
+
+/// Give a photo a tag, creating the tag if it's new
+async fn tag_photo(
+    tx: &mut sqlx::SqliteConnection,
+    photo_id: i32,
+    name: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR IGNORE INTO tag (name) VALUES (?)")
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO photo_tag (photo_id, tag_id)
+        SELECT ?, id FROM tag WHERE name = ?
+        "#,
+    )
+    .bind(photo_id)
+    .bind(name)
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+async fn untag_photo(
+    tx: &mut sqlx::SqliteConnection,
+    photo_id: i32,
+    name: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        DELETE FROM photo_tag
+        WHERE photo_id = ? AND tag_id IN (SELECT id FROM tag WHERE name = ?)
+        "#,
+    )
+    .bind(photo_id)
+    .bind(name)
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+/// Do a checked bulk operation to one photo. Album removals leave gaps in the album order
+/// for the caller to close.
+async fn apply_bulk_operation(
+    tx: &mut sqlx::SqliteConnection,
+    operation: &BulkPhotoOperation,
+    photo_id: i32,
+) -> Result<(), sqlx::Error> {
+    match operation {
+        BulkPhotoOperation::AddToAlbums { albums } => {
+            for album in albums {
+                sqlx::query(
+                    r#"
+                    INSERT OR IGNORE INTO album_photo (album_id, photo_id, position)
+                    SELECT ?, ?, COALESCE(MAX(position), -1) + 1
+                    FROM album_photo WHERE album_id = ?
+                    "#,
+                )
+                .bind(album.0)
+                .bind(photo_id)
+                .bind(album.0)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        BulkPhotoOperation::RemoveFromAlbums { albums } => {
+            for album in albums {
+                sqlx::query("DELETE FROM album_photo WHERE album_id = ? AND photo_id = ?")
+                    .bind(album.0)
+                    .bind(photo_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        BulkPhotoOperation::Delete => {
+            sqlx::query("UPDATE photo SET deleted_at = CURRENT_TIMESTAMP WHERE id = ?")
+                .bind(photo_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        BulkPhotoOperation::SetMatPreset { mat_preset } => {
+            sqlx::query(
+                "UPDATE photo SET mat_preset = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            )
+            .bind(mat_preset)
+            .bind(photo_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        BulkPhotoOperation::Update { details } => {
+            photo_update_sql(details, photo_id)
+                .build()
+                .execute(&mut *tx)
+                .await?;
+        }
+        BulkPhotoOperation::AttachTags { tags } => {
+            for name in tags {
+                tag_photo(tx, photo_id, name).await?;
+            }
+        }
+        BulkPhotoOperation::DetachTags { tags } => {
+            for name in tags {
+                untag_photo(tx, photo_id, name).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn db_album_to_album(input: &DbAlbum, photos: &[DbPhoto]) -> Album {
+    Album {
+        id: AlbumID::from(input.id),
+        name: input.name.clone(),
+        notes: input.notes.clone(),
+        photos: photos.iter().map(|p| PhotoID::from(p.id)).collect(),
+        virtual_album: virtual_album(input),
+        smart_rule: smart_rule(input),
+    }
+}
+
+fn db_display_to_display(input: &DbDisplay) -> Display {
+    Display {
+        id: DisplayID::from(input.id),
+        name: input.name.clone(),
+        token: input.token.clone(),
+        current_album: input.current_album_id.map(|album_id| CurrentAlbum {
+            album: AlbumID::from(album_id),
+            index: input.current_photo_index as usize,
+        }),
+        interval: Interval::from(input.interval_seconds),
+        mode: RotationMode::from_db(&input.rotation_mode),
+        mat_preset: input.mat_preset.clone(),
+        last_seen_at: input.last_seen_at,
+    }
+}
+
+fn db_schedule_to_schedule(input: &DbSchedule) -> Schedule {
+    let days = (0..7)
+        .filter(|bit| input.weekdays & (1 << bit) != 0)
+        .filter_map(|bit| Weekday::try_from(bit as u8).ok())
+        .collect();
+    let months = (0..12)
+        .filter(|bit| input.months & (1 << bit) != 0)
+        .map(|bit| bit as u32 + 1)
+        .collect();
+    Schedule {
+        id: ScheduleID::from(input.id),
+        name: input.name.clone(),
+        action: match input.album_id {
+            Some(album_id) => ScheduleAction::Album {
+                album: AlbumID::from(album_id),
+            },
+            None => ScheduleAction::Blank,
+        },
+        display: input.display_id.map(DisplayID::from),
+        priority: input.priority,
+        enabled: input.enabled,
+        days,
+        months,
+        start_time: input.start_time,
+        end_time: input.end_time,
+    }
+}
+
+/// `schedule.album_id` for an action. NULL shows nothing
+fn schedule_album_id(action: &ScheduleAction) -> Option<i32> {
+    match action {
+        ScheduleAction::Album { album } => Some(album.0),
+        ScheduleAction::Blank => None,
+    }
+}
+
+/// Bit 0 is Monday, as stored in `schedule.weekdays`
+fn weekdays_mask(days: &[Weekday]) -> i32 {
+    days.iter()
+        .fold(0, |mask, day| mask | 1 << day.num_days_from_monday())
+}
+
+/// Bit 0 is January, as stored in `schedule.months`
+fn months_mask(months: &[u32]) -> i32 {
+    months.iter().fold(0, |mask, month| mask | 1 << (month - 1))
+}
+
+/// Cache-Control for an image URL with the photo's current image key. The key changes
+/// whenever the files do, so the bytes behind such a URL never change.
+const IMAGE_CACHE_IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Cache-Control for an image URL without the current key, e.g. one saved before the photo's
+/// renditions were regenerated. Caches may keep the image but must check with the server first.
+const IMAGE_CACHE_REVALIDATE: &str = "public, no-cache";
+
+/// Strong ETag for one rendition of a photo. Photos imported before image keys were recorded
+/// go without one until it's filled in.
+fn image_etag(hash: &str, variant: ImageVariant, image_key: Option<&str>) -> String {
+    match image_key {
+        Some(key) => format!("\"{hash}-{}-{key}\"", variant.as_str()),
+        None => format!("\"{hash}-{}\"", variant.as_str()),
+    }
+}
+
+/// Whether an `If-None-Match` header matches `etag`. Uses the weak comparison RFC 9110
+/// requires for this header, so `W/` prefixes are ignored.
+fn etag_matches(if_none_match: &axum::http::HeaderValue, etag: &axum::http::HeaderValue) -> bool {
+    let Ok(if_none_match) = if_none_match.to_str() else {
         return false;
     };
-    matches!(ext.as_str(), "jpg" | "jpeg")
+    let etag = etag.to_str().unwrap_or_default();
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+/// Build a JSON error response for the raw (non-macro) handlers.
+fn json_error(status: StatusCode, msg: impl std::fmt::Display) -> axum::response::Response {
+    (
+        status,
+        axum::Json(serde_json::json!({ "error": msg.to_string() })),
+    )
+        .into_response()
+}
+
+/// `Content-Disposition` for downloading a file. Browsers that understand `filename*` get the
+/// name as is, others an ASCII approximation.
+fn attachment(filename: &str) -> String {
+    let ascii: String = filename
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => '_',
+            c if c == ' ' || c.is_ascii_graphic() => c,
+            _ => '_',
+        })
+        .collect();
+    let encoded: String = filename
+        .bytes()
+        .map(|b| match b {
+            b if b.is_ascii_alphanumeric() || b"-._~".contains(&b) => (b as char).to_string(),
+            b => format!("%{b:02X}"),
+        })
+        .collect();
+    format!("attachment; filename=\"{ascii}\"; filename*=UTF-8''{encoded}")
+}
+
+/// Respond with a download that `write` produces on a blocking thread as the client reads it,
+/// so it's never held in memory as a whole. If `write` fails the client is left with a
+/// truncated file.
+fn stream_download<F>(
+    content_type: &'static str,
+    filename: &str,
+    write: F,
+) -> axum::response::Response
+where
+    F: FnOnce(SyncIoBridge<DuplexStream>) -> Result<()> + Send + 'static,
+{
+    use axum::body::Body;
+    use axum::http::header;
+
+    let (writer, reader) = tokio::io::duplex(DOWNLOAD_BUFFER_SIZE);
+    let writer = SyncIoBridge::new(writer);
+    let name = filename.to_string();
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = write(writer) {
+            error!("Failed to write {name}: {e:#}");
+        }
+    });
+
+    axum::response::Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_DISPOSITION, attachment(filename))
+        .body(Body::from_stream(ReaderStream::new(reader)))
+        .unwrap()
+}
+
+/// Dotfiles are skipped so editors and file shares can keep their temp files in the inbox.
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().starts_with('.'))
+        .unwrap_or(false)
 }
 
 #[derive(Debug)]
 pub struct App {
-    #[allow(dead_code)]
     data_dir: Option<PathBuf>,
-    magick_exec: Option<PathBuf>,
+    image_processor: Option<Arc<dyn ImageProcessor>>,
+    /// Suggests tags for newly imported photos. Without one photos are only tagged by hand
+    classifier: Option<Arc<dyn Classifier>>,
     pool: SqlitePool,
     inbox_dir: Option<PathBuf>,
     photos_dir: Option<PathBuf>,
     fails_dir: Option<PathBuf>,
+    staging_dir: Option<PathBuf>,
+    /// Wakes job workers when a new job is queued
+    job_notify: Notify,
+    /// Photos whose hashes are within this Hamming distance are considered duplicates
+    duplicate_threshold: u32,
+    /// Days a deleted photo stays in the trash before it's purged
+    trash_retention_days: u32,
 }
 
 impl App {
-    pub async fn new(data_dir: PathBuf) -> Result<Self> {
-        let Ok(magick_exec) = which::which("magick") else {
-            bail!("magick: command not found")
-        };
+    pub async fn new(data_dir: PathBuf, image_processor: Arc<dyn ImageProcessor>) -> Result<Self> {
+        info!("Using {} image processor", image_processor.name());
 
         let inbox_dir = data_dir.join("inbox");
         let photos_dir = data_dir.join("photos");
         let fails_dir = data_dir.join("fails");
+        let staging_dir = data_dir.join("staging");
 
-        for dir in [&data_dir, &inbox_dir, &fails_dir, &photos_dir] {
+        for dir in [&data_dir, &inbox_dir, &fails_dir, &photos_dir, &staging_dir] {
             if !dir.is_dir() {
                 fs::create_dir_all(dir)?;
             }
@@ -86,11 +862,16 @@ impl App {
 
         Ok(Self {
             data_dir: Some(data_dir),
-            magick_exec: Some(magick_exec),
+            image_processor: Some(image_processor),
+            classifier: None,
             pool,
             inbox_dir: Some(inbox_dir),
             photos_dir: Some(photos_dir),
             fails_dir: Some(fails_dir),
+            staging_dir: Some(staging_dir),
+            job_notify: Notify::new(),
+            duplicate_threshold: DEFAULT_DUPLICATE_THRESHOLD,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
         })
     }
 
@@ -98,22 +879,54 @@ impl App {
     pub fn from_pool(pool: SqlitePool) -> Self {
         Self {
             data_dir: None,
-            magick_exec: None,
+            image_processor: None,
+            classifier: None,
             pool,
             inbox_dir: None,
             photos_dir: None,
             fails_dir: None,
+            staging_dir: None,
+            job_notify: Notify::new(),
+            duplicate_threshold: DEFAULT_DUPLICATE_THRESHOLD,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
         }
     }
 
+    /// Set the Hamming distance under which a new photo counts as a duplicate of an existing one.
+    /// 0 only rejects exact hash matches.
+    pub fn with_duplicate_threshold(mut self, threshold: u32) -> Self {
+        self.duplicate_threshold = threshold;
+        self
+    }
+
+    /// Set how many days deleted photos stay in the trash before they're purged for good.
+    pub fn with_trash_retention_days(mut self, days: u32) -> Self {
+        self.trash_retention_days = days;
+        self
+    }
+
+    /// Suggest tags for every photo imported from now on.
+    pub fn with_classifier(mut self, classifier: Arc<dyn Classifier>) -> Self {
+        info!("Using {} classifier", classifier.name());
+        self.classifier = Some(classifier);
+        self
+    }
+
     /// Get a reference to the database pool.
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
+
+    /// Notified whenever a new job is queued.
+    pub fn job_notify(&self) -> &Notify {
+        &self.job_notify
+    }
 }
 
 pub enum APIResult<T> {
     Ok(T),
+    /// The request itself is invalid, so sending it again won't help
+    BadRequest(String),
     NotFound(String),
     InternalError(String),
 }
@@ -122,6 +935,11 @@ impl<T: Serialize> IntoResponse for APIResult<T> {
     fn into_response(self) -> axum::response::Response {
         match self {
             APIResult::Ok(val) => (StatusCode::OK, axum::Json(val)).into_response(),
+            APIResult::BadRequest(msg) => (
+                StatusCode::BAD_REQUEST,
+                axum::Json(serde_json::json!({ "error": msg })),
+            )
+                .into_response(),
             APIResult::NotFound(msg) => (
                 StatusCode::NOT_FOUND,
                 axum::Json(serde_json::json!({ "error": msg })),
@@ -144,7 +962,7 @@ impl App {
     // ─────────────────────────────────────────────────────────────────────────
 
     #[api_handler(method = "GET", path = "/api/next")]
-    pub async fn get_next_photo(&self) -> APIResult<Next> {
+    pub async fn get_next_photo(&self, #[query] query: NextQuery) -> APIResult<Next> {
         // Get current settings from database
         let settings = match sqlx::query_as::<_, DbSettings>("SELECT * FROM settings WHERE id = 1")
             .fetch_one(&self.pool)
@@ -154,53 +972,177 @@ impl App {
             Err(e) => return APIResult::InternalError(format!("Failed to get settings: {}", e)),
         };
 
+        // A registered display keeps its own album, place and mode instead of the shared ones
+        let display = match &query.display {
+            Some(token) => {
+                match sqlx::query_as::<_, DbDisplay>("SELECT * FROM display WHERE token = ?")
+                    .bind(token)
+                    .fetch_optional(&self.pool)
+                    .await
+                {
+                    Ok(Some(display)) => Some(display),
+                    Ok(None) => return APIResult::NotFound("Unknown display".to_string()),
+                    Err(e) => {
+                        return APIResult::InternalError(format!("Failed to get display: {}", e));
+                    }
+                }
+            }
+            None => None,
+        };
+        let (current_album_id, current_photo_index, interval_seconds, rotation_mode) =
+            match &display {
+                Some(d) => (
+                    d.current_album_id,
+                    d.current_photo_index,
+                    d.interval_seconds,
+                    &d.rotation_mode,
+                ),
+                None => (
+                    settings.current_album_id,
+                    settings.current_photo_index,
+                    settings.interval_seconds,
+                    &settings.rotation_mode,
+                ),
+            };
+
+        if let Some(display) = &display {
+            let _ = sqlx::query("UPDATE display SET last_seen_at = CURRENT_TIMESTAMP WHERE id = ?")
+                .bind(display.id)
+                .execute(&self.pool)
+                .await;
+        }
+
+        // A schedule active right now overrides the album picked by hand
+        let schedules = match sqlx::query_as::<_, DbSchedule>(
+            "SELECT * FROM schedule WHERE enabled = 1 ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(s) => s.iter().map(db_schedule_to_schedule).collect::<Vec<_>>(),
+            Err(e) => return APIResult::InternalError(format!("Failed to get schedules: {}", e)),
+        };
+        let now = chrono::Local::now().naive_local();
+        let display_id = display.as_ref().map(|d| d.id);
+        let current_album_id =
+            match Schedule::active(&schedules, display_id.map(DisplayID::from), now) {
+                Some(schedule) => match schedule.action {
+                    ScheduleAction::Album { album } => Some(album.0),
+                    ScheduleAction::Blank => {
+                        return APIResult::NotFound(format!(
+                            "Nothing to show during {}",
+                            schedule.name
+                        ));
+                    }
+                },
+                None => current_album_id,
+            };
+
         // Get photos: from album if selected, otherwise from entire library
-        let photos = if let Some(album_id) = settings.current_album_id {
-            // Get photos in the current album
-            match sqlx::query_as::<_, DbPhoto>(
-                r#"
-                SELECT p.* FROM photo p
-                JOIN album_photo ap ON p.id = ap.photo_id
-                WHERE ap.album_id = ?
-                ORDER BY ap.position
-                "#,
-            )
-            .bind(album_id)
-            .fetch_all(&self.pool)
-            .await
-            {
+        let album = match current_album_id {
+            Some(album_id) => {
+                match sqlx::query_as::<_, DbAlbum>("SELECT * FROM album WHERE id = ?")
+                    .bind(album_id)
+                    .fetch_optional(&self.pool)
+                    .await
+                {
+                    Ok(album) => album,
+                    Err(e) => {
+                        return APIResult::InternalError(format!("Failed to get album: {}", e));
+                    }
+                }
+            }
+            None => None,
+        };
+        let mut photos = match &album {
+            Some(album) => match self.album_photos(album).await {
                 Ok(p) => p,
                 Err(e) => return APIResult::InternalError(format!("Failed to get photos: {}", e)),
-            }
-        } else {
+            },
+            None => vec![],
+        };
+
+        // A virtual album is often empty on a given day. Rather than going blank until it
+        // matches again, the frame shows the whole library
+        let whole_library =
+            album.is_none() || (photos.is_empty() && album.as_ref().is_some_and(is_virtual));
+        if whole_library {
             // No album selected, use entire photo library
-            match sqlx::query_as::<_, DbPhoto>("SELECT * FROM photo ORDER BY id")
-                .fetch_all(&self.pool)
-                .await
+            photos = match sqlx::query_as::<_, DbPhoto>(
+                "SELECT * FROM photo WHERE deleted_at IS NULL ORDER BY id",
+            )
+            .fetch_all(&self.pool)
+            .await
             {
                 Ok(p) => p,
                 Err(e) => return APIResult::InternalError(format!("Failed to get photos: {}", e)),
-            }
-        };
+            };
+        }
 
         if photos.is_empty() {
             return APIResult::NotFound("No photos available".to_string());
         }
 
-        // Get current photo (with wraparound)
-        let index = (settings.current_photo_index as usize) % photos.len();
-        let db_photo = &photos[index];
-        let photo = db_photo_to_photo(db_photo);
-
-        // Update index for next call
-        let next_index = ((index + 1) % photos.len()) as i32;
-        let _ = sqlx::query("UPDATE settings SET current_photo_index = ? WHERE id = 1")
-            .bind(next_index)
-            .execute(&self.pool)
-            .await;
+        let (last_shown, show_count) = match self
+            .rotation_history(&photos, display.as_ref(), &settings)
+            .await
+        {
+            Ok(history) => history,
+            Err(e) => {
+                return APIResult::InternalError(format!("Failed to get rotation history: {}", e));
+            }
+        };
+
+        let index = match RotationMode::from_db(rotation_mode) {
+            RotationMode::Sequential => {
+                // Get current photo (with wraparound)
+                let index = (current_photo_index as usize) % photos.len();
+
+                // Update index for next call
+                let next_index = ((index + 1) % photos.len()) as i32;
+                let update = match display_id {
+                    Some(id) => {
+                        sqlx::query("UPDATE display SET current_photo_index = ? WHERE id = ?")
+                            .bind(next_index)
+                            .bind(id)
+                    }
+                    None => sqlx::query("UPDATE settings SET current_photo_index = ? WHERE id = 1")
+                        .bind(next_index),
+                };
+                let _ = update.execute(&self.pool).await;
+                index
+            }
+            RotationMode::Shuffle => {
+                match self.next_in_shuffle(&photos, &last_shown, display_id).await {
+                    Ok(index) => index,
+                    Err(e) => return APIResult::InternalError(format!("Failed to shuffle: {}", e)),
+                }
+            }
+            RotationMode::WeightedRandom => {
+                rotation::pick_least_recently_shown(&last_shown, show_count, &mut rand::rng())
+            }
+        };
+        let db_photo = &photos[index];
+        let tags = match self.photo_tags(Some(db_photo.id)).await {
+            Ok(tags) => tags,
+            Err(e) => return APIResult::InternalError(format!("Failed to get tags: {}", e)),
+        };
+        let photo = db_photo_to_photo(db_photo, &tags);
+
+        // Remember when each photo was shown for weighted random and shuffle
+        if let Err(e) = self.record_shown(db_photo.id, display_id).await {
+            warn!(
+                "Failed to record that photo {} was shown: {}",
+                db_photo.id, e
+            );
+        }
 
-        let interval = Interval::from(settings.interval_seconds);
-        let mat_style = MatStyle::from_preset(&photo.mat_preset);
+        let interval = Interval::from(interval_seconds);
+        let mat_preset = display
+            .as_ref()
+            .and_then(|d| d.mat_preset.as_deref())
+            .unwrap_or(&photo.mat_preset);
+        let mat_style = MatStyle::from_preset(mat_preset);
         APIResult::Ok(Next {
             photo,
             interval,
@@ -212,943 +1154,3280 @@ impl App {
     // Photos
     // ─────────────────────────────────────────────────────────────────────────
 
+    /// Photos matching the query, newest first unless it asks for another sort. `?tags=a,b`
+    /// keeps photos with every one of the tags, or with any of them when `tag_match=any`.
+    /// Paged with `offset` and `limit`; `total` counts the matches across every page.
     #[api_handler(method = "GET", path = "/api/photos")]
-    pub async fn get_photos(&self) -> APIResult<Vec<Photo>> {
-        match sqlx::query_as::<_, DbPhoto>("SELECT * FROM photo ORDER BY created_at DESC")
-            .fetch_all(&self.pool)
+    pub async fn get_photos(&self, #[query] query: PhotoQuery) -> APIResult<PhotoPage> {
+        // Virtual and smart albums aren't in album_photo, so look up the album's photos first
+        let album_photos = match query.album {
+            Some(album_id) => {
+                let album = match sqlx::query_as::<_, DbAlbum>("SELECT * FROM album WHERE id = ?")
+                    .bind(album_id)
+                    .fetch_optional(&self.pool)
+                    .await
+                {
+                    Ok(Some(album)) => album,
+                    Ok(None) => {
+                        return APIResult::NotFound(format!("Album {} not found", album_id));
+                    }
+                    Err(e) => {
+                        return APIResult::InternalError(format!("Failed to get album: {}", e));
+                    }
+                };
+                match self.album_photos(&album).await {
+                    Ok(photos) => Some(photos.iter().map(|p| p.id).collect::<Vec<_>>()),
+                    Err(e) => {
+                        return APIResult::InternalError(format!(
+                            "Failed to get album photos: {}",
+                            e
+                        ));
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM photo");
+        push_photo_filters(&mut count, &query, album_photos.as_deref());
+        let total = match count
+            .build_query_scalar::<i64>()
+            .fetch_one(&self.pool)
             .await
         {
-            Ok(photos) => APIResult::Ok(photos.iter().map(db_photo_to_photo).collect()),
-            Err(e) => APIResult::InternalError(format!("Failed to get photos: {}", e)),
+            Ok(total) => total,
+            Err(e) => return APIResult::InternalError(format!("Failed to count photos: {}", e)),
+        };
+
+        let (sort_key, column) = match query.sort {
+            PhotoSort::Created => ("created_at", "created_at"),
+            PhotoSort::Taken => ("date_taken", "date_taken"),
+            PhotoSort::Title => ("title COLLATE NOCASE", "title"),
+            PhotoSort::LastShown => ("last_shown", "last_shown"),
+        };
+        let order = match query.order() {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+        let offset = query.offset.unwrap_or(0);
+
+        let mut sql = QueryBuilder::new("SELECT * FROM photo");
+        push_photo_filters(&mut sql, &query, album_photos.as_deref());
+        // Missing values go last either way, and the id keeps pages stable between requests
+        sql.push(format!(
+            " ORDER BY {column} IS NULL, {sort_key} {order}, id {order} LIMIT "
+        ))
+        .push_bind(query.limit.map_or(-1, i64::from))
+        .push(" OFFSET ")
+        .push_bind(offset as i64);
+        let photos = match sql.build_query_as::<DbPhoto>().fetch_all(&self.pool).await {
+            Ok(p) => p,
+            Err(e) => return APIResult::InternalError(format!("Failed to get photos: {}", e)),
+        };
+
+        let ids: Vec<i32> = photos.iter().map(|p| p.id).collect();
+        match self.tags_of_photos(&ids).await {
+            Ok(tags) => APIResult::Ok(PhotoPage {
+                photos: photos.iter().map(|p| db_photo_to_photo(p, &tags)).collect(),
+                total: total as u32,
+                offset,
+            }),
+            Err(e) => APIResult::InternalError(format!("Failed to get tags: {}", e)),
         }
     }
 
     #[api_handler(method = "GET", path = "/api/photos/{id}")]
     pub async fn get_photo(&self, #[path] id: i32) -> APIResult<Photo> {
-        match sqlx::query_as::<_, DbPhoto>("SELECT * FROM photo WHERE id = ?")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await
+        match sqlx::query_as::<_, DbPhoto>(
+            "SELECT * FROM photo WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
         {
-            Ok(Some(photo)) => APIResult::Ok(db_photo_to_photo(&photo)),
+            Ok(Some(photo)) => match self.photo_tags(Some(id)).await {
+                Ok(tags) => APIResult::Ok(db_photo_to_photo(&photo, &tags)),
+                Err(e) => APIResult::InternalError(format!("Failed to get tags: {}", e)),
+            },
             Ok(None) => APIResult::NotFound(format!("Photo {} not found", id)),
             Err(e) => APIResult::InternalError(format!("Failed to get photo: {}", e)),
         }
     }
 
+    /// Change a photo's details. Every field is checked before anything is written, and they
+    /// are all written by one statement, so a bad field leaves the photo as it was.
     #[api_handler(method = "PUT", path = "/api/photos/{id}")]
     pub async fn update_photo(
         &self,
         #[path] id: i32,
         #[body] req: UpdatePhotoRequest,
     ) -> APIResult<()> {
-        // Check photo exists
-        let exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM photo WHERE id = ?")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await;
-
-        match exists {
-            Ok(Some(_)) => {}
-            Ok(None) => return APIResult::NotFound(format!("Photo {} not found", id)),
-            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        let now = chrono::Local::now().naive_local();
+        if let Err(e) = check_photo_update(&req, now) {
+            return APIResult::BadRequest(e);
         }
 
-        // Update title if provided
-        if let Some(title_update) = &req.title {
-            match title_update {
-                Update::Set(title) => {
-                    if let Err(e) = sqlx::query(
-                        "UPDATE photo SET title = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
-                    )
-                    .bind(title)
-                    .bind(id)
-                    .execute(&self.pool)
-                    .await
-                    {
-                        return APIResult::InternalError(format!("Failed to update photo: {}", e));
-                    }
-                }
-                Update::Remove => {
-                    if let Err(e) = sqlx::query("UPDATE photo SET title = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
-                        .bind(id)
-                        .execute(&self.pool)
-                        .await
-                    {
-                        return APIResult::InternalError(format!("Failed to update photo: {}", e));
-                    }
-                }
+        match photo_update_sql(&req, id).build().execute(&self.pool).await {
+            Ok(result) if result.rows_affected() == 0 => {
+                APIResult::NotFound(format!("Photo {} not found", id))
             }
+            Ok(_) => APIResult::Ok(()),
+            Err(e) => APIResult::InternalError(format!("Failed to update photo: {}", e)),
         }
+    }
 
-        // Update artist if provided
-        if let Some(artist_update) = &req.artist {
-            match artist_update {
-                Update::Set(artist) => {
-                    if let Err(e) = sqlx::query(
-                        "UPDATE photo SET artist = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
-                    )
-                    .bind(artist)
-                    .bind(id)
-                    .execute(&self.pool)
-                    .await
-                    {
-                        return APIResult::InternalError(format!("Failed to update photo: {}", e));
-                    }
-                }
-                Update::Remove => {
-                    if let Err(e) = sqlx::query("UPDATE photo SET artist = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
-                        .bind(id)
-                        .execute(&self.pool)
-                        .await
-                    {
-                        return APIResult::InternalError(format!("Failed to update photo: {}", e));
-                    }
-                }
+    /// Move a photo to the trash. It's hidden everywhere but the trash, and keeps its albums
+    /// and tags in case it's restored.
+    #[api_handler(method = "DELETE", path = "/api/photos/{id}")]
+    pub async fn delete_photo(&self, #[path] id: i32) -> APIResult<()> {
+        match sqlx::query(
+            "UPDATE photo SET deleted_at = CURRENT_TIMESTAMP WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() == 0 => {
+                APIResult::NotFound(format!("Photo {} not found", id))
             }
+            Ok(_) => APIResult::Ok(()),
+            Err(e) => APIResult::InternalError(format!("Failed to delete photo: {}", e)),
         }
+    }
 
-        // Update copyright if provided
-        if let Some(copyright_update) = &req.copyright {
-            match copyright_update {
-                Update::Set(copyright) => {
-                    if let Err(e) = sqlx::query("UPDATE photo SET copyright = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
-                        .bind(copyright)
-                        .bind(id)
-                        .execute(&self.pool)
-                        .await
-                    {
-                        return APIResult::InternalError(format!("Failed to update photo: {}", e));
-                    }
-                }
-                Update::Remove => {
-                    if let Err(e) = sqlx::query("UPDATE photo SET copyright = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
-                        .bind(id)
-                        .execute(&self.pool)
-                        .await
-                    {
-                        return APIResult::InternalError(format!("Failed to update photo: {}", e));
+    /// Do one thing to many photos in a single transaction. Photos that aren't in the library
+    /// are skipped and say so in the results. A bad operation, e.g. an unknown album, fails
+    /// the whole request and nothing is changed.
+    #[api_handler(method = "POST", path = "/api/photos/bulk")]
+    pub async fn bulk_photos(
+        &self,
+        #[body] req: BulkPhotoRequest,
+    ) -> APIResult<Vec<BulkPhotoResult>> {
+        let operation = match req.operation {
+            BulkPhotoOperation::AddToAlbums { ref albums }
+            | BulkPhotoOperation::RemoveFromAlbums { ref albums } => {
+                for album in albums {
+                    if let Err(response) = self.find_manual_album(album.0).await {
+                        return response;
                     }
                 }
+                req.operation
             }
-        }
-
-        // Update date_taken if provided
-        if let Some(date_update) = &req.date_taken {
-            match date_update {
-                Update::Set(date) => {
-                    if let Err(e) = sqlx::query("UPDATE photo SET date_taken = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
-                        .bind(date)
-                        .bind(id)
-                        .execute(&self.pool)
-                        .await
-                    {
-                        return APIResult::InternalError(format!("Failed to update photo: {}", e));
-                    }
+            BulkPhotoOperation::SetMatPreset { ref mat_preset }
+                if !MatStyle::preset_names().contains(&mat_preset.as_str()) =>
+            {
+                return APIResult::BadRequest(format!("Unknown mat preset: {}", mat_preset));
+            }
+            BulkPhotoOperation::Update { ref details } => {
+                let now = chrono::Local::now().naive_local();
+                if let Err(e) = check_photo_update(details, now) {
+                    return APIResult::BadRequest(e);
                 }
-                Update::Remove => {
-                    if let Err(e) = sqlx::query("UPDATE photo SET date_taken = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
-                        .bind(id)
-                        .execute(&self.pool)
-                        .await
-                    {
-                        return APIResult::InternalError(format!("Failed to update photo: {}", e));
+                req.operation
+            }
+            BulkPhotoOperation::AttachTags { tags } => {
+                let mut names = Vec::new();
+                for name in &tags {
+                    match check_tag_name(name) {
+                        Ok(name) => names.push(name),
+                        Err(e) => return APIResult::BadRequest(e),
                     }
                 }
+                BulkPhotoOperation::AttachTags { tags: names }
             }
-        }
-
-        // Update mat_preset if provided
-        if let Some(preset) = &req.mat_preset {
-            // Validate preset exists
-            if !MatStyle::preset_names().contains(&preset.as_str()) {
-                return APIResult::InternalError(format!("Unknown mat preset: {}", preset));
-            }
-            if let Err(e) = sqlx::query(
-                "UPDATE photo SET mat_preset = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
-            )
-            .bind(preset)
-            .bind(id)
-            .execute(&self.pool)
-            .await
-            {
-                return APIResult::InternalError(format!("Failed to update photo: {}", e));
-            }
-        }
-
-        APIResult::Ok(())
-    }
-
-    #[api_handler(method = "DELETE", path = "/api/photos/{id}")]
-    pub async fn delete_photo(&self, #[path] id: i32) -> APIResult<()> {
-        // Check photo exists
-        let photo = match sqlx::query_as::<_, DbPhoto>("SELECT * FROM photo WHERE id = ?")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await
-        {
-            Ok(Some(p)) => p,
-            Ok(None) => return APIResult::NotFound(format!("Photo {} not found", id)),
-            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+            BulkPhotoOperation::DetachTags { tags } => BulkPhotoOperation::DetachTags {
+                tags: tags.iter().map(|name| name.trim().to_string()).collect(),
+            },
+            operation => operation,
         };
 
-        // Delete album-photo associations first
-        if let Err(e) = sqlx::query("DELETE FROM album_photo WHERE photo_id = ?")
-            .bind(id)
-            .execute(&self.pool)
-            .await
-        {
-            return APIResult::InternalError(format!("Failed to delete photo from albums: {}", e));
-        }
-
-        // Delete the database record
-        if let Err(e) = sqlx::query("DELETE FROM photo WHERE id = ?")
-            .bind(id)
-            .execute(&self.pool)
-            .await
-        {
-            return APIResult::InternalError(format!("Failed to delete photo: {}", e));
-        }
-
-        // Try to delete the image files (best effort, don't fail if files don't exist)
-        if let Some(photos_dir) = &self.photos_dir {
-            let paths = [
-                std::path::Path::new(&photo.fullsize_path),
-                std::path::Path::new(&photo.websize_path),
-                std::path::Path::new(&photo.thumbnail_path),
-            ];
-            for path in paths {
-                if path.starts_with(photos_dir) {
-                    let _ = std::fs::remove_file(path);
-                }
-            }
-        }
-
-        APIResult::Ok(())
-    }
-
-    // ─────────────────────────────────────────────────────────────────────────
-    // Albums
-    // ─────────────────────────────────────────────────────────────────────────
-
-    #[api_handler(method = "GET", path = "/api/albums")]
-    pub async fn get_albums(&self) -> APIResult<Vec<Album>> {
-        let db_albums = match sqlx::query_as::<_, DbAlbum>("SELECT * FROM album ORDER BY name")
-            .fetch_all(&self.pool)
-            .await
-        {
-            Ok(albums) => albums,
-            Err(e) => return APIResult::InternalError(format!("Failed to get albums: {}", e)),
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
         };
 
-        let mut albums = Vec::new();
-        for db_album in db_albums {
-            let photo_ids = match sqlx::query_scalar::<_, i32>(
-                "SELECT photo_id FROM album_photo WHERE album_id = ? ORDER BY position",
+        let mut results = Vec::with_capacity(req.photos.len());
+        for &photo_id in &req.photos {
+            let photo = match sqlx::query_scalar::<_, i32>(
+                "SELECT id FROM photo WHERE id = ? AND deleted_at IS NULL",
             )
-            .bind(db_album.id)
-            .fetch_all(&self.pool)
+            .bind(photo_id.0)
+            .fetch_optional(&mut *tx)
             .await
             {
-                Ok(ids) => ids.into_iter().map(PhotoID::from).collect(),
-                Err(e) => {
-                    return APIResult::InternalError(format!("Failed to get album photos: {}", e));
+                Ok(Some(id)) => id,
+                Ok(None) => {
+                    results.push(BulkPhotoResult {
+                        photo: photo_id,
+                        error: Some(format!("Photo {} not found", photo_id)),
+                    });
+                    continue;
                 }
+                Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
             };
 
-            albums.push(Album {
-                id: AlbumID::from(db_album.id),
-                name: db_album.name,
-                notes: db_album.notes,
-                photos: photo_ids,
+            if let Err(e) = apply_bulk_operation(&mut tx, &operation, photo).await {
+                return APIResult::InternalError(format!(
+                    "Failed to update photo {}: {}",
+                    photo_id, e
+                ));
+            }
+            results.push(BulkPhotoResult {
+                photo: photo_id,
+                error: None,
             });
         }
 
-        APIResult::Ok(albums)
+        // Close the gaps left in album orders
+        if let BulkPhotoOperation::RemoveFromAlbums { albums } = &operation {
+            for album in albums {
+                let renumbered = match album_order(&mut tx, album.0).await {
+                    Ok(order) => write_album_order(&mut tx, album.0, &order).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = renumbered {
+                    return APIResult::InternalError(format!("Failed to renumber album: {}", e));
+                }
+            }
+        }
+
+        match tx.commit().await {
+            Ok(()) => APIResult::Ok(results),
+            Err(e) => APIResult::InternalError(format!("Failed to update photos: {}", e)),
+        }
     }
 
-    #[api_handler(method = "GET", path = "/api/albums/{id}")]
-    pub async fn get_album(&self, #[path] id: i32) -> APIResult<Album> {
-        let db_album = match sqlx::query_as::<_, DbAlbum>("SELECT * FROM album WHERE id = ?")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await
-        {
-            Ok(Some(album)) => album,
-            Ok(None) => return APIResult::NotFound(format!("Album {} not found", id)),
-            Err(e) => return APIResult::InternalError(format!("Failed to get album: {}", e)),
-        };
+    #[api_handler(method = "GET", path = "/api/photos/duplicates")]
+    pub async fn get_duplicates(
+        &self,
+        #[query] query: DuplicatesQuery,
+    ) -> APIResult<Vec<DuplicateGroup>> {
+        let max_distance = query.max_distance.unwrap_or(self.duplicate_threshold);
 
-        let photo_ids = match sqlx::query_scalar::<_, i32>(
-            "SELECT photo_id FROM album_photo WHERE album_id = ? ORDER BY position",
+        let photos = match sqlx::query_as::<_, DbPhoto>(
+            "SELECT * FROM photo WHERE deleted_at IS NULL ORDER BY id",
         )
-        .bind(id)
         .fetch_all(&self.pool)
         .await
         {
-            Ok(ids) => ids.into_iter().map(PhotoID::from).collect(),
-            Err(e) => {
-                return APIResult::InternalError(format!("Failed to get album photos: {}", e));
-            }
+            Ok(p) => p,
+            Err(e) => return APIResult::InternalError(format!("Failed to get photos: {}", e)),
         };
 
-        APIResult::Ok(Album {
-            id: AlbumID::from(db_album.id),
-            name: db_album.name,
-            notes: db_album.notes,
-            photos: photo_ids,
-        })
-    }
+        let hashes: Vec<&str> = photos.iter().map(|p| p.hash.as_str()).collect();
+        let groups = on_disk_photo::group_similar(&hashes, max_distance);
 
-    #[api_handler(method = "POST", path = "/api/albums")]
-    pub async fn create_album(&self, #[body] req: CreateAlbumRequest) -> APIResult<Album> {
-        if req.name.trim().is_empty() {
-            return APIResult::InternalError("Album name cannot be empty".to_string());
-        }
+        let ids: Vec<i32> = groups
+            .iter()
+            .flat_map(|(members, _)| members.iter().map(|&i| photos[i].id))
+            .collect();
+        let tags = match self.tags_of_photos(&ids).await {
+            Ok(tags) => tags,
+            Err(e) => return APIResult::InternalError(format!("Failed to get tags: {}", e)),
+        };
 
-        let result = sqlx::query_scalar::<_, i32>(
-            "INSERT INTO album (name, notes) VALUES (?, ?) RETURNING id",
-        )
-        .bind(&req.name)
-        .bind(&req.notes)
-        .fetch_one(&self.pool)
-        .await;
+        let groups = groups
+            .into_iter()
+            .map(|(members, distance)| DuplicateGroup {
+                photos: members
+                    .iter()
+                    .map(|&i| db_photo_to_photo(&photos[i], &tags))
+                    .collect(),
+                distance,
+            })
+            .collect();
 
-        match result {
-            Ok(id) => APIResult::Ok(Album {
-                id: AlbumID::from(id),
-                name: req.name,
-                notes: req.notes,
-                photos: vec![],
-            }),
-            Err(e) => APIResult::InternalError(format!("Failed to create album: {}", e)),
-        }
+        APIResult::Ok(groups)
     }
 
-    #[api_handler(method = "PUT", path = "/api/albums/{id}")]
-    pub async fn update_album(
+    /// Keep one photo and fold its duplicates into it.
+    /// The kept photo joins every album a duplicate was in, at the duplicate's position, and
+    /// gets every tag a duplicate had. Then the duplicates are moved to the trash, where they
+    /// can still be restored until they're purged.
+    #[api_handler(method = "POST", path = "/api/photos/{id}/merge")]
+    pub async fn merge_photos(
         &self,
         #[path] id: i32,
-        #[body] req: UpdateAlbumRequest,
-    ) -> APIResult<()> {
-        // Check album exists
-        let exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM album WHERE id = ?")
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await;
+        #[body] req: MergePhotosRequest,
+    ) -> APIResult<Photo> {
+        if req.duplicates.iter().any(|dup| dup.0 == id) {
+            return APIResult::BadRequest(format!("Photo {} can't be merged into itself", id));
+        }
 
-        match exists {
-            Ok(Some(_)) => {}
-            Ok(None) => return APIResult::NotFound(format!("Album {} not found", id)),
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
             Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
-        }
+        };
 
-        // Update name if provided
-        if let Some(name) = &req.name {
-            if name.trim().is_empty() {
-                return APIResult::InternalError("Album name cannot be empty".to_string());
-            }
-            if let Err(e) = sqlx::query(
-                "UPDATE album SET name = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
-            )
-            .bind(name)
+        let keep = match sqlx::query_as::<_, DbPhoto>(
+            "SELECT * FROM photo WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        {
+            Ok(Some(p)) => p,
+            Ok(None) => return APIResult::NotFound(format!("Photo {} not found", id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+
+        let mut merged = 0;
+        for dup in &req.duplicates {
+            match sqlx::query_scalar::<_, i32>(
+                "SELECT id FROM photo WHERE id = ? AND deleted_at IS NULL",
+            )
+            .bind(dup.0)
+            .fetch_optional(&mut *tx)
+            .await
+            {
+                Ok(Some(_)) => {}
+                Ok(None) => return APIResult::NotFound(format!("Photo {} not found", dup)),
+                Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+            };
+
+            // Albums the kept photo is already in keep its existing position
+            if let Err(e) = sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO album_photo (album_id, photo_id, position)
+                SELECT album_id, ?, position FROM album_photo WHERE photo_id = ?
+                "#,
+            )
             .bind(id)
-            .execute(&self.pool)
+            .bind(dup.0)
+            .execute(&mut *tx)
             .await
             {
-                return APIResult::InternalError(format!("Failed to update album: {}", e));
+                return APIResult::InternalError(format!("Failed to merge albums: {}", e));
             }
-        }
 
-        // Update notes if provided
-        if let Some(notes_update) = &req.notes {
-            match notes_update {
-                Update::Set(notes) => {
-                    if let Err(e) = sqlx::query(
-                        "UPDATE album SET notes = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
-                    )
-                    .bind(notes)
-                    .bind(id)
-                    .execute(&self.pool)
-                    .await
-                    {
-                        return APIResult::InternalError(format!("Failed to update album: {}", e));
-                    }
-                }
-                Update::Remove => {
-                    if let Err(e) = sqlx::query("UPDATE album SET notes = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
-                        .bind(id)
-                        .execute(&self.pool)
-                        .await
-                    {
-                        return APIResult::InternalError(format!("Failed to update album: {}", e));
-                    }
-                }
+            if let Err(e) = sqlx::query("DELETE FROM album_photo WHERE photo_id = ?")
+                .bind(dup.0)
+                .execute(&mut *tx)
+                .await
+            {
+                return APIResult::InternalError(format!(
+                    "Failed to delete photo from albums: {}",
+                    e
+                ));
             }
-        }
-
-        APIResult::Ok(())
-    }
 
-    #[api_handler(method = "DELETE", path = "/api/albums/{id}")]
-    pub async fn delete_album(&self, #[path] id: i32) -> APIResult<()> {
-        // Check album exists
-        let exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM album WHERE id = ?")
+            if let Err(e) = sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO photo_tag (photo_id, tag_id)
+                SELECT ?, tag_id FROM photo_tag WHERE photo_id = ?
+                "#,
+            )
             .bind(id)
-            .fetch_optional(&self.pool)
-            .await;
+            .bind(dup.0)
+            .execute(&mut *tx)
+            .await
+            {
+                return APIResult::InternalError(format!("Failed to merge tags: {}", e));
+            }
 
-        match exists {
-            Ok(Some(_)) => {}
-            Ok(None) => return APIResult::NotFound(format!("Album {} not found", id)),
-            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+            if let Err(e) = sqlx::query("DELETE FROM photo_tag WHERE photo_id = ?")
+                .bind(dup.0)
+                .execute(&mut *tx)
+                .await
+            {
+                return APIResult::InternalError(format!("Failed to delete photo tags: {}", e));
+            }
+
+            if let Err(e) = sqlx::query("DELETE FROM tag_suggestion WHERE photo_id = ?")
+                .bind(dup.0)
+                .execute(&mut *tx)
+                .await
+            {
+                return APIResult::InternalError(format!(
+                    "Failed to delete tag suggestions: {}",
+                    e
+                ));
+            }
+
+            if let Err(e) =
+                sqlx::query("UPDATE photo SET deleted_at = CURRENT_TIMESTAMP WHERE id = ?")
+                    .bind(dup.0)
+                    .execute(&mut *tx)
+                    .await
+            {
+                return APIResult::InternalError(format!("Failed to delete photo: {}", e));
+            }
+
+            merged += 1;
         }
 
-        // Delete album-photo associations first
-        if let Err(e) = sqlx::query("DELETE FROM album_photo WHERE album_id = ?")
-            .bind(id)
-            .execute(&self.pool)
-            .await
-        {
-            return APIResult::InternalError(format!("Failed to delete album photos: {}", e));
+        if let Err(e) = tx.commit().await {
+            return APIResult::InternalError(format!("Failed to merge photos: {}", e));
         }
 
-        // If this album is the current album, clear it
-        let _ = sqlx::query("UPDATE settings SET current_album_id = NULL, current_photo_index = 0 WHERE current_album_id = ?")
-            .bind(id)
-            .execute(&self.pool)
-            .await;
+        info!("Merged {} duplicate(s) into photo {}", merged, keep.id);
 
-        // Delete the album
-        if let Err(e) = sqlx::query("DELETE FROM album WHERE id = ?")
-            .bind(id)
-            .execute(&self.pool)
-            .await
-        {
-            return APIResult::InternalError(format!("Failed to delete album: {}", e));
+        match self.photo_tags(Some(id)).await {
+            Ok(tags) => APIResult::Ok(db_photo_to_photo(&keep, &tags)),
+            Err(e) => APIResult::InternalError(format!("Failed to get tags: {}", e)),
         }
-
-        APIResult::Ok(())
     }
 
     // ─────────────────────────────────────────────────────────────────────────
-    // Album Photos (Managing photo membership in albums)
+    // Trash
     // ─────────────────────────────────────────────────────────────────────────
 
-    #[api_handler(method = "POST", path = "/api/albums/{album_id}/photos/{photo_id}")]
-    pub async fn add_photo_to_album(
-        &self,
-        #[path] album_id: i32,
-        #[path] photo_id: i32,
-    ) -> APIResult<()> {
-        // Check album exists
-        let album_exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM album WHERE id = ?")
-            .bind(album_id)
-            .fetch_optional(&self.pool)
-            .await;
+    /// Photos in the trash, most recently deleted first
+    #[api_handler(method = "GET", path = "/api/trash")]
+    pub async fn get_trash(&self) -> APIResult<Vec<TrashedPhoto>> {
+        let photos = match self.trashed_photos().await {
+            Ok(p) => p,
+            Err(e) => return APIResult::InternalError(format!("Failed to get trash: {}", e)),
+        };
+        let ids: Vec<i32> = photos.iter().map(|p| p.id).collect();
+        let tags = match self.tags_of_photos(&ids).await {
+            Ok(tags) => tags,
+            Err(e) => return APIResult::InternalError(format!("Failed to get tags: {}", e)),
+        };
 
-        match album_exists {
-            Ok(Some(_)) => {}
-            Ok(None) => return APIResult::NotFound(format!("Album {} not found", album_id)),
-            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        let retention = chrono::Duration::days(self.trash_retention_days.into());
+        APIResult::Ok(
+            photos
+                .iter()
+                .filter_map(|photo| {
+                    let deleted_at = photo.deleted_at?;
+                    Some(TrashedPhoto {
+                        photo: db_photo_to_photo(photo, &tags),
+                        deleted_at,
+                        purge_at: deleted_at + retention,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Put a photo back in the library, in the albums it was in
+    #[api_handler(method = "POST", path = "/api/trash/{id}/restore")]
+    pub async fn restore_photo(&self, #[path] id: i32) -> APIResult<Photo> {
+        match sqlx::query(
+            "UPDATE photo SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        {
+            Ok(result) if result.rows_affected() == 0 => {
+                APIResult::NotFound(format!("Photo {} is not in the trash", id))
+            }
+            Ok(_) => self.get_photo(id).await,
+            Err(e) => APIResult::InternalError(format!("Failed to restore photo: {}", e)),
         }
+    }
 
-        // Check photo exists
-        let photo_exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM photo WHERE id = ?")
-            .bind(photo_id)
-            .fetch_optional(&self.pool)
-            .await;
+    /// Delete a photo in the trash for good, along with its files
+    #[api_handler(method = "DELETE", path = "/api/trash/{id}")]
+    pub async fn purge_photo(&self, #[path] id: i32) -> APIResult<()> {
+        match self.purge_photos(Some(id), None).await {
+            Ok(0) => APIResult::NotFound(format!("Photo {} is not in the trash", id)),
+            Ok(_) => APIResult::Ok(()),
+            Err(e) => APIResult::InternalError(format!("Failed to purge photo: {}", e)),
+        }
+    }
 
-        match photo_exists {
-            Ok(Some(_)) => {}
-            Ok(None) => return APIResult::NotFound(format!("Photo {} not found", photo_id)),
-            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+    /// Delete everything in the trash for good. Returns the number of photos deleted.
+    #[api_handler(method = "DELETE", path = "/api/trash")]
+    pub async fn empty_trash(&self) -> APIResult<u32> {
+        match self.purge_photos(None, None).await {
+            Ok(purged) => APIResult::Ok(purged as u32),
+            Err(e) => APIResult::InternalError(format!("Failed to empty trash: {}", e)),
         }
+    }
 
-        // Check if photo is already in album
-        let already_in_album = sqlx::query_scalar::<_, i32>(
-            "SELECT 1 FROM album_photo WHERE album_id = ? AND photo_id = ?",
-        )
-        .bind(album_id)
-        .bind(photo_id)
-        .fetch_optional(&self.pool)
-        .await;
+    // ─────────────────────────────────────────────────────────────────────────
+    // Tags
+    // ─────────────────────────────────────────────────────────────────────────
 
-        if let Ok(Some(_)) = already_in_album {
-            // Photo already in album, nothing to do
-            return APIResult::Ok(());
+    #[api_handler(method = "GET", path = "/api/tags")]
+    pub async fn get_tags(&self) -> APIResult<Vec<Tag>> {
+        match sqlx::query_as::<_, DbTag>(&format!("{SELECT_TAGS} GROUP BY t.id ORDER BY t.name"))
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(tags) => APIResult::Ok(tags.iter().map(db_tag_to_tag).collect()),
+            Err(e) => APIResult::InternalError(format!("Failed to get tags: {}", e)),
         }
+    }
 
-        // Get the next position
-        let max_position = sqlx::query_scalar::<_, Option<i32>>(
-            "SELECT MAX(position) FROM album_photo WHERE album_id = ?",
-        )
-        .bind(album_id)
-        .fetch_one(&self.pool)
-        .await
-        .unwrap_or(None)
-        .unwrap_or(-1);
+    #[api_handler(method = "POST", path = "/api/tags")]
+    pub async fn create_tag(&self, #[body] req: TagRequest) -> APIResult<Tag> {
+        let name = match check_tag_name(&req.name) {
+            Ok(name) => name,
+            Err(e) => return APIResult::BadRequest(e),
+        };
 
-        // Add photo to album
-        if let Err(e) =
-            sqlx::query("INSERT INTO album_photo (album_id, photo_id, position) VALUES (?, ?, ?)")
-                .bind(album_id)
-                .bind(photo_id)
-                .bind(max_position + 1)
-                .execute(&self.pool)
+        let id =
+            match sqlx::query_scalar::<_, i32>("INSERT INTO tag (name) VALUES (?) RETURNING id")
+                .bind(&name)
+                .fetch_one(&self.pool)
                 .await
-        {
-            return APIResult::InternalError(format!("Failed to add photo to album: {}", e));
-        }
+            {
+                Ok(id) => id,
+                Err(e)
+                    if e.as_database_error()
+                        .is_some_and(|e| e.is_unique_violation()) =>
+                {
+                    return APIResult::BadRequest(format!("Tag {} already exists", name));
+                }
+                Err(e) => return APIResult::InternalError(format!("Failed to create tag: {}", e)),
+            };
 
-        APIResult::Ok(())
+        match self.find_tag(id).await {
+            Ok(Some(tag)) => APIResult::Ok(db_tag_to_tag(&tag)),
+            Ok(None) => APIResult::InternalError("Tag vanished after insert".to_string()),
+            Err(e) => APIResult::InternalError(format!("Failed to get tag: {}", e)),
+        }
     }
 
-    #[api_handler(method = "DELETE", path = "/api/albums/{album_id}/photos/{photo_id}")]
-    pub async fn remove_photo_from_album(
-        &self,
-        #[path] album_id: i32,
-        #[path] photo_id: i32,
-    ) -> APIResult<()> {
-        // Check album exists
-        let album_exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM album WHERE id = ?")
-            .bind(album_id)
-            .fetch_optional(&self.pool)
-            .await;
+    /// Rename a tag. Renaming onto another tag's name is refused, merge the tags instead.
+    #[api_handler(method = "PUT", path = "/api/tags/{id}")]
+    pub async fn rename_tag(&self, #[path] id: i32, #[body] req: TagRequest) -> APIResult<Tag> {
+        let old = match self.find_tag(id).await {
+            Ok(Some(tag)) => tag,
+            Ok(None) => return APIResult::NotFound(format!("Tag {} not found", id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+        let name = match check_tag_name(&req.name) {
+            Ok(name) => name,
+            Err(e) => return APIResult::BadRequest(e),
+        };
 
-        match album_exists {
-            Ok(Some(_)) => {}
-            Ok(None) => return APIResult::NotFound(format!("Album {} not found", album_id)),
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
             Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
-        }
+        };
 
-        // Remove photo from album (don't fail if it wasn't there)
-        if let Err(e) = sqlx::query("DELETE FROM album_photo WHERE album_id = ? AND photo_id = ?")
-            .bind(album_id)
-            .bind(photo_id)
-            .execute(&self.pool)
+        match sqlx::query("UPDATE tag SET name = ? WHERE id = ?")
+            .bind(&name)
+            .bind(id)
+            .execute(&mut *tx)
             .await
         {
-            return APIResult::InternalError(format!("Failed to remove photo from album: {}", e));
+            Ok(_) => {}
+            Err(e)
+                if e.as_database_error()
+                    .is_some_and(|e| e.is_unique_violation()) =>
+            {
+                return APIResult::BadRequest(format!(
+                    "Tag {} already exists, merge the tags instead",
+                    name
+                ));
+            }
+            Err(e) => return APIResult::InternalError(format!("Failed to rename tag: {}", e)),
         }
 
-        APIResult::Ok(())
-    }
+        if let Err(e) = self
+            .retag_smart_rules(&mut tx, std::slice::from_ref(&old.name), &name)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to update smart albums: {}", e));
+        }
 
-    // ─────────────────────────────────────────────────────────────────────────
-    // Mat Presets
-    // ─────────────────────────────────────────────────────────────────────────
+        if let Err(e) = tx.commit().await {
+            return APIResult::InternalError(format!("Failed to rename tag: {}", e));
+        }
 
-    #[api_handler(method = "GET", path = "/api/mat-presets")]
-    pub async fn get_mat_presets(&self) -> APIResult<Vec<MatStyle>> {
-        let presets = MatStyle::preset_names()
-            .iter()
-            .map(|name| MatStyle::from_preset(name))
-            .collect();
-        APIResult::Ok(presets)
+        match self.find_tag(id).await {
+            Ok(Some(tag)) => APIResult::Ok(db_tag_to_tag(&tag)),
+            Ok(None) => APIResult::NotFound(format!("Tag {} not found", id)),
+            Err(e) => APIResult::InternalError(format!("Failed to get tag: {}", e)),
+        }
     }
 
-    // ─────────────────────────────────────────────────────────────────────────
-    // Settings
-    // ─────────────────────────────────────────────────────────────────────────
+    /// Keep one tag and fold others into it. Every photo with a merged tag gets the kept one,
+    /// then the merged tags are deleted.
+    #[api_handler(method = "POST", path = "/api/tags/{id}/merge")]
+    pub async fn merge_tags(
+        &self,
+        #[path] id: i32,
+        #[body] req: MergeTagsRequest,
+    ) -> APIResult<Tag> {
+        if req.tags.iter().any(|tag| tag.0 == id) {
+            return APIResult::BadRequest(format!("Tag {} can't be merged into itself", id));
+        }
 
-    #[api_handler(method = "GET", path = "/api/settings")]
-    pub async fn get_settings(&self) -> APIResult<RotationSettings> {
-        let settings = match sqlx::query_as::<_, DbSettings>("SELECT * FROM settings WHERE id = 1")
-            .fetch_one(&self.pool)
-            .await
-        {
-            Ok(s) => s,
-            Err(e) => return APIResult::InternalError(format!("Failed to get settings: {}", e)),
+        let keep = match self.find_tag(id).await {
+            Ok(Some(tag)) => tag,
+            Ok(None) => return APIResult::NotFound(format!("Tag {} not found", id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
         };
 
-        let current_album = match settings.current_album_id {
-            Some(album_id) => Some(CurrentAlbum {
-                album: AlbumID::from(album_id),
-                index: settings.current_photo_index as usize,
-            }),
-            None => None,
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
         };
 
-        APIResult::Ok(RotationSettings {
-            current_album,
-            interval: Interval::from(settings.interval_seconds),
-        })
-    }
+        let mut merged_names = Vec::new();
+        for tag in &req.tags {
+            let name = match sqlx::query_scalar::<_, String>("SELECT name FROM tag WHERE id = ?")
+                .bind(tag.0)
+                .fetch_optional(&mut *tx)
+                .await
+            {
+                Ok(Some(name)) => name,
+                Ok(None) => return APIResult::NotFound(format!("Tag {} not found", tag)),
+                Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+            };
 
-    #[api_handler(method = "PUT", path = "/api/settings")]
-    pub async fn update_settings(&self, #[body] req: UpdateSettingsRequest) -> APIResult<()> {
-        // Handle current_album_id update
-        if let Some(album_update) = req.current_album_id {
-            match album_update {
-                Update::Set(album_id) => {
-                    // Verify album exists
-                    let exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM album WHERE id = ?")
-                        .bind(album_id.0)
-                        .fetch_optional(&self.pool)
-                        .await;
+            if let Err(e) = sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO photo_tag (photo_id, tag_id)
+                SELECT photo_id, ? FROM photo_tag WHERE tag_id = ?
+                "#,
+            )
+            .bind(id)
+            .bind(tag.0)
+            .execute(&mut *tx)
+            .await
+            {
+                return APIResult::InternalError(format!("Failed to merge tags: {}", e));
+            }
 
-                    match exists {
-                        Ok(Some(_)) => {
-                            // Album exists, update settings and reset index
-                            if let Err(e) = sqlx::query(
-                                "UPDATE settings SET current_album_id = ?, current_photo_index = 0 WHERE id = 1",
-                            )
-                            .bind(album_id.0)
-                            .execute(&self.pool)
-                            .await
-                            {
-                                return APIResult::InternalError(format!(
-                                    "Failed to update settings: {}",
-                                    e
-                                ));
-                            }
-                        }
-                        Ok(None) => {
-                            return APIResult::NotFound(format!("Album {} not found", album_id.0));
-                        }
-                        Err(e) => {
-                            return APIResult::InternalError(format!("Database error: {}", e));
-                        }
-                    }
+            if let Err(e) = sqlx::query("DELETE FROM photo_tag WHERE tag_id = ?")
+                .bind(tag.0)
+                .execute(&mut *tx)
+                .await
+            {
+                return APIResult::InternalError(format!(
+                    "Failed to delete tag from photos: {}",
+                    e
+                ));
+            }
+
+            if let Err(e) = sqlx::query("DELETE FROM tag WHERE id = ?")
+                .bind(tag.0)
+                .execute(&mut *tx)
+                .await
+            {
+                return APIResult::InternalError(format!("Failed to delete tag: {}", e));
+            }
+
+            merged_names.push(name);
+        }
+
+        if let Err(e) = self
+            .retag_smart_rules(&mut tx, &merged_names, &keep.name)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to update smart albums: {}", e));
+        }
+
+        if let Err(e) = tx.commit().await {
+            return APIResult::InternalError(format!("Failed to merge tags: {}", e));
+        }
+        info!("Merged {} tag(s) into {}", merged_names.len(), keep.name);
+
+        match self.find_tag(id).await {
+            Ok(Some(tag)) => APIResult::Ok(db_tag_to_tag(&tag)),
+            Ok(None) => APIResult::NotFound(format!("Tag {} not found", id)),
+            Err(e) => APIResult::InternalError(format!("Failed to get tag: {}", e)),
+        }
+    }
+
+    /// Delete a tag and take it off every photo. Tags that smart album rules need can't be
+    /// deleted, as dropping them from the rules would widen the albums instead.
+    #[api_handler(method = "DELETE", path = "/api/tags/{id}")]
+    pub async fn delete_tag(&self, #[path] id: i32) -> APIResult<()> {
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+
+        let name = match sqlx::query_scalar::<_, String>("SELECT name FROM tag WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+        {
+            Ok(Some(name)) => name,
+            Ok(None) => return APIResult::NotFound(format!("Tag {} not found", id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+
+        match self.smart_albums_using(&mut tx, &name).await {
+            Ok(albums) if albums.is_empty() => {}
+            Ok(albums) => {
+                return APIResult::BadRequest(format!(
+                    "Tag {} is used by smart albums: {}",
+                    name,
+                    albums.join(", ")
+                ));
+            }
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        }
+
+        // Delete photo-tag associations first
+        if let Err(e) = sqlx::query("DELETE FROM photo_tag WHERE tag_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to delete tag from photos: {}", e));
+        }
+
+        match sqlx::query("DELETE FROM tag WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+        {
+            Ok(result) if result.rows_affected() == 0 => {
+                return APIResult::NotFound(format!("Tag {} not found", id));
+            }
+            Ok(_) => {}
+            Err(e) => return APIResult::InternalError(format!("Failed to delete tag: {}", e)),
+        }
+
+        if let Err(e) = tx.commit().await {
+            return APIResult::InternalError(format!("Failed to delete tag: {}", e));
+        }
+
+        APIResult::Ok(())
+    }
+
+    /// Tag every photo in the request with every tag in it, creating tags that don't exist yet
+    #[api_handler(method = "POST", path = "/api/tags/attach")]
+    pub async fn attach_tags(&self, #[body] req: TagPhotosRequest) -> APIResult<()> {
+        let mut names = Vec::new();
+        for name in &req.tags {
+            match check_tag_name(name) {
+                Ok(name) => names.push(name),
+                Err(e) => return APIResult::BadRequest(e),
+            }
+        }
+
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+
+        if let Some(result) = check_photos_exist(&mut tx, &req.photos).await {
+            return result;
+        }
+
+        for name in &names {
+            for photo in &req.photos {
+                if let Err(e) = tag_photo(&mut tx, photo.0, name).await {
+                    return APIResult::InternalError(format!("Failed to tag photo: {}", e));
                 }
-                Update::Remove => {
-                    // Clear current album
-                    if let Err(e) = sqlx::query(
-                        "UPDATE settings SET current_album_id = NULL, current_photo_index = 0 WHERE id = 1",
-                    )
-                    .execute(&self.pool)
-                    .await
-                    {
+            }
+        }
+
+        if let Err(e) = tx.commit().await {
+            return APIResult::InternalError(format!("Failed to tag photos: {}", e));
+        }
+
+        APIResult::Ok(())
+    }
+
+    /// Remove every tag in the request from every photo in it. Tags are kept even when no
+    /// photo has them any more.
+    #[api_handler(method = "POST", path = "/api/tags/detach")]
+    pub async fn detach_tags(&self, #[body] req: TagPhotosRequest) -> APIResult<()> {
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+
+        if let Some(result) = check_photos_exist(&mut tx, &req.photos).await {
+            return result;
+        }
+
+        for name in &req.tags {
+            for photo in &req.photos {
+                if let Err(e) = untag_photo(&mut tx, photo.0, name.trim()).await {
+                    return APIResult::InternalError(format!("Failed to untag photo: {}", e));
+                }
+            }
+        }
+
+        if let Err(e) = tx.commit().await {
+            return APIResult::InternalError(format!("Failed to untag photos: {}", e));
+        }
+
+        APIResult::Ok(())
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Search
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Photos and albums matching every word of `q`, best matches first
+    #[api_handler(method = "GET", path = "/api/search")]
+    pub async fn search(&self, #[query] query: SearchQuery) -> APIResult<SearchResults> {
+        let Some(terms) = fts_query(&query.q) else {
+            return APIResult::Ok(SearchResults::default());
+        };
+
+        let photo_rows = sqlx::query(&format!(
+            r#"
+            SELECT p.*, snippet(photo_search, -1, char(2), char(3), '…', {SNIPPET_TOKENS}) AS snippet
+            FROM photo_search
+            JOIN photo p ON p.id = photo_search.rowid
+            WHERE photo_search MATCH ? AND p.deleted_at IS NULL
+            ORDER BY rank
+            LIMIT ?
+            "#
+        ))
+        .bind(&terms)
+        .bind(MAX_SEARCH_RESULTS)
+        .fetch_all(&self.pool)
+        .await;
+        let photo_rows = match photo_rows {
+            Ok(rows) => rows,
+            Err(e) => return APIResult::InternalError(format!("Failed to search photos: {}", e)),
+        };
+        let mut matches = Vec::new();
+        for row in &photo_rows {
+            match (DbPhoto::from_row(row), row.try_get::<String, _>("snippet")) {
+                (Ok(photo), Ok(snippet)) => matches.push((photo, snippet)),
+                (Err(e), _) | (_, Err(e)) => {
+                    return APIResult::InternalError(format!(
+                        "Failed to read search result: {}",
+                        e
+                    ));
+                }
+            }
+        }
+        let ids: Vec<i32> = matches.iter().map(|(photo, _)| photo.id).collect();
+        let tags = match self.tags_of_photos(&ids).await {
+            Ok(tags) => tags,
+            Err(e) => return APIResult::InternalError(format!("Failed to get tags: {}", e)),
+        };
+        let photos = matches
+            .iter()
+            .map(|(photo, snippet)| PhotoSearchResult {
+                photo: db_photo_to_photo(photo, &tags),
+                snippet: highlight_snippet(snippet),
+            })
+            .collect();
+
+        let album_rows = sqlx::query(&format!(
+            r#"
+            SELECT a.*, snippet(album_search, -1, char(2), char(3), '…', {SNIPPET_TOKENS}) AS snippet
+            FROM album_search
+            JOIN album a ON a.id = album_search.rowid
+            WHERE album_search MATCH ?
+            ORDER BY rank
+            LIMIT ?
+            "#
+        ))
+        .bind(&terms)
+        .bind(MAX_SEARCH_RESULTS)
+        .fetch_all(&self.pool)
+        .await;
+        let album_rows = match album_rows {
+            Ok(rows) => rows,
+            Err(e) => return APIResult::InternalError(format!("Failed to search albums: {}", e)),
+        };
+        let mut albums = Vec::new();
+        for row in &album_rows {
+            let (album, snippet) =
+                match (DbAlbum::from_row(row), row.try_get::<String, _>("snippet")) {
+                    (Ok(album), Ok(snippet)) => (album, snippet),
+                    (Err(e), _) | (_, Err(e)) => {
                         return APIResult::InternalError(format!(
-                            "Failed to update settings: {}",
+                            "Failed to read search result: {}",
                             e
                         ));
                     }
+                };
+            let album_photos = match self.album_photos(&album).await {
+                Ok(photos) => photos,
+                Err(e) => {
+                    return APIResult::InternalError(format!("Failed to get album photos: {}", e));
+                }
+            };
+            albums.push(AlbumSearchResult {
+                album: db_album_to_album(&album, &album_photos),
+                snippet: highlight_snippet(&snippet),
+            });
+        }
+
+        APIResult::Ok(SearchResults { photos, albums })
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Tag Suggestions
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Queue a job that runs the classifier on a photo, e.g. one imported before the
+    /// classifier was set up
+    #[api_handler(method = "POST", path = "/api/photos/{id}/classify")]
+    pub async fn classify_photo(&self, #[path] id: i32) -> APIResult<Job> {
+        if self.classifier.is_none() {
+            return APIResult::InternalError("No classifier is configured".to_string());
+        }
+
+        let photo = match sqlx::query_as::<_, DbPhoto>(
+            "SELECT * FROM photo WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        {
+            Ok(Some(p)) => p,
+            Ok(None) => return APIResult::NotFound(format!("Photo {} not found", id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+
+        match self.queue_classify_job(&photo).await {
+            Ok(job) => APIResult::Ok(db_job_to_job(&job)),
+            Err(e) => APIResult::InternalError(format!("Failed to queue job: {}", e)),
+        }
+    }
+
+    /// Suggestions waiting for review, most confident first within each photo
+    #[api_handler(method = "GET", path = "/api/suggestions")]
+    pub async fn get_suggestions(
+        &self,
+        #[query] query: SuggestionsQuery,
+    ) -> APIResult<Vec<TagSuggestion>> {
+        match sqlx::query_as::<_, DbTagSuggestion>(
+            r#"
+            SELECT * FROM tag_suggestion
+            WHERE status = 'pending' AND (? IS NULL OR photo_id = ?)
+            AND photo_id IN (SELECT id FROM photo WHERE deleted_at IS NULL)
+            ORDER BY photo_id, confidence DESC, id
+            "#,
+        )
+        .bind(query.photo)
+        .bind(query.photo)
+        .fetch_all(&self.pool)
+        .await
+        {
+            Ok(suggestions) => APIResult::Ok(
+                suggestions
+                    .iter()
+                    .map(db_suggestion_to_suggestion)
+                    .collect(),
+            ),
+            Err(e) => APIResult::InternalError(format!("Failed to get suggestions: {}", e)),
+        }
+    }
+
+    /// Tag the photo with the suggested label, creating the tag if needed
+    #[api_handler(method = "POST", path = "/api/suggestions/{id}/accept")]
+    pub async fn accept_suggestion(&self, #[path] id: i32) -> APIResult<()> {
+        let suggestion = match self.find_pending_suggestion(id).await {
+            Ok(suggestion) => suggestion,
+            Err(result) => return result,
+        };
+        let name = match check_tag_name(&suggestion.label) {
+            Ok(name) => name,
+            Err(e) => return APIResult::InternalError(e),
+        };
+
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+
+        if let Err(e) = sqlx::query("INSERT OR IGNORE INTO tag (name) VALUES (?)")
+            .bind(&name)
+            .execute(&mut *tx)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to create tag: {}", e));
+        }
+
+        if let Err(e) = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO photo_tag (photo_id, tag_id)
+            SELECT ?, id FROM tag WHERE name = ?
+            "#,
+        )
+        .bind(suggestion.photo_id)
+        .bind(&name)
+        .execute(&mut *tx)
+        .await
+        {
+            return APIResult::InternalError(format!("Failed to tag photo: {}", e));
+        }
+
+        if let Err(e) = sqlx::query("UPDATE tag_suggestion SET status = 'accepted' WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to accept suggestion: {}", e));
+        }
+
+        if let Err(e) = tx.commit().await {
+            return APIResult::InternalError(format!("Failed to accept suggestion: {}", e));
+        }
+
+        APIResult::Ok(())
+    }
+
+    /// Dismiss a suggestion. It won't be suggested for the photo again.
+    #[api_handler(method = "POST", path = "/api/suggestions/{id}/reject")]
+    pub async fn reject_suggestion(&self, #[path] id: i32) -> APIResult<()> {
+        if let Err(result) = self.find_pending_suggestion(id).await {
+            return result;
+        }
+
+        match sqlx::query("UPDATE tag_suggestion SET status = 'rejected' WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(_) => APIResult::Ok(()),
+            Err(e) => APIResult::InternalError(format!("Failed to reject suggestion: {}", e)),
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Albums
+    // ─────────────────────────────────────────────────────────────────────────
+
+    #[api_handler(method = "GET", path = "/api/albums")]
+    pub async fn get_albums(&self) -> APIResult<Vec<Album>> {
+        let db_albums = match sqlx::query_as::<_, DbAlbum>("SELECT * FROM album ORDER BY name")
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(albums) => albums,
+            Err(e) => return APIResult::InternalError(format!("Failed to get albums: {}", e)),
+        };
+
+        let mut albums = Vec::new();
+        for db_album in db_albums {
+            let photos = match self.album_photos(&db_album).await {
+                Ok(photos) => photos,
+                Err(e) => {
+                    return APIResult::InternalError(format!("Failed to get album photos: {}", e));
+                }
+            };
+            albums.push(db_album_to_album(&db_album, &photos));
+        }
+
+        APIResult::Ok(albums)
+    }
+
+    #[api_handler(method = "GET", path = "/api/albums/{id}")]
+    pub async fn get_album(&self, #[path] id: i32) -> APIResult<Album> {
+        let db_album = match sqlx::query_as::<_, DbAlbum>("SELECT * FROM album WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(Some(album)) => album,
+            Ok(None) => return APIResult::NotFound(format!("Album {} not found", id)),
+            Err(e) => return APIResult::InternalError(format!("Failed to get album: {}", e)),
+        };
+
+        match self.album_photos(&db_album).await {
+            Ok(photos) => APIResult::Ok(db_album_to_album(&db_album, &photos)),
+            Err(e) => APIResult::InternalError(format!("Failed to get album photos: {}", e)),
+        }
+    }
+
+    #[api_handler(method = "POST", path = "/api/albums")]
+    pub async fn create_album(&self, #[body] req: CreateAlbumRequest) -> APIResult<Album> {
+        if req.name.trim().is_empty() {
+            return APIResult::BadRequest("Album name cannot be empty".to_string());
+        }
+        if let Some(virtual_album) = &req.virtual_album
+            && let Err(e) = check_virtual_album(virtual_album)
+        {
+            return APIResult::BadRequest(e);
+        }
+        if req.virtual_album.is_some() && req.smart_rule.is_some() {
+            return APIResult::BadRequest("An album can't be both virtual and smart".to_string());
+        }
+        if let Some(rule) = &req.smart_rule
+            && let Err(e) = check_smart_rule(rule)
+        {
+            return APIResult::BadRequest(e);
+        }
+        let smart_rule = req
+            .smart_rule
+            .as_ref()
+            .map(|r| serde_json::to_string(r).expect("SmartRule serializes"));
+        let virtual_album = req
+            .virtual_album
+            .map(|v| serde_json::to_string(&v).expect("VirtualAlbum serializes"));
+
+        let result = sqlx::query_as::<_, DbAlbum>(
+            "INSERT INTO album (name, notes, virtual_album, smart_rule) VALUES (?, ?, ?, ?) RETURNING *",
+        )
+        .bind(&req.name)
+        .bind(&req.notes)
+        .bind(virtual_album)
+        .bind(smart_rule)
+        .fetch_one(&self.pool)
+        .await;
+
+        let db_album = match result {
+            Ok(album) => album,
+            Err(e) => return APIResult::InternalError(format!("Failed to create album: {}", e)),
+        };
+        // A new virtual album may already match photos
+        match self.album_photos(&db_album).await {
+            Ok(photos) => APIResult::Ok(db_album_to_album(&db_album, &photos)),
+            Err(e) => APIResult::InternalError(format!("Failed to get album photos: {}", e)),
+        }
+    }
+
+    #[api_handler(method = "PUT", path = "/api/albums/{id}")]
+    pub async fn update_album(
+        &self,
+        #[path] id: i32,
+        #[body] req: UpdateAlbumRequest,
+    ) -> APIResult<()> {
+        // Check album exists
+        let album = sqlx::query_as::<_, DbAlbum>("SELECT * FROM album WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await;
+
+        let album = match album {
+            Ok(Some(album)) => album,
+            Ok(None) => return APIResult::NotFound(format!("Album {} not found", id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+
+        // Validate the rule before changing anything
+        let smart_rule = match &req.smart_rule {
+            Some(Update::Set(rule)) => {
+                if is_virtual(&album) {
+                    return APIResult::BadRequest(format!(
+                        "Album {} is virtual and can't have a smart rule",
+                        id
+                    ));
+                }
+                if let Err(e) = check_smart_rule(rule) {
+                    return APIResult::BadRequest(e);
+                }
+                Some(Some(
+                    serde_json::to_string(rule).expect("SmartRule serializes"),
+                ))
+            }
+            Some(Update::Remove) => Some(None),
+            None => None,
+        };
+
+        // Update name if provided
+        if let Some(name) = &req.name {
+            if name.trim().is_empty() {
+                return APIResult::BadRequest("Album name cannot be empty".to_string());
+            }
+            if let Err(e) = sqlx::query(
+                "UPDATE album SET name = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            )
+            .bind(name)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            {
+                return APIResult::InternalError(format!("Failed to update album: {}", e));
+            }
+        }
+
+        // Update notes if provided
+        if let Some(notes_update) = &req.notes {
+            match notes_update {
+                Update::Set(notes) => {
+                    if let Err(e) = sqlx::query(
+                        "UPDATE album SET notes = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+                    )
+                    .bind(notes)
+                    .bind(id)
+                    .execute(&self.pool)
+                    .await
+                    {
+                        return APIResult::InternalError(format!("Failed to update album: {}", e));
+                    }
+                }
+                Update::Remove => {
+                    if let Err(e) = sqlx::query("UPDATE album SET notes = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+                        .bind(id)
+                        .execute(&self.pool)
+                        .await
+                    {
+                        return APIResult::InternalError(format!("Failed to update album: {}", e));
+                    }
+                }
+            }
+        }
+
+        // Update smart rule if provided. Removing it turns the album back into a manual album
+        if let Some(smart_rule) = smart_rule
+            && let Err(e) = sqlx::query(
+                "UPDATE album SET smart_rule = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            )
+            .bind(smart_rule)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to update album: {}", e));
+        }
+
+        APIResult::Ok(())
+    }
+
+    #[api_handler(method = "DELETE", path = "/api/albums/{id}")]
+    pub async fn delete_album(&self, #[path] id: i32) -> APIResult<()> {
+        // Check album exists
+        let exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM album WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await;
+
+        match exists {
+            Ok(Some(_)) => {}
+            Ok(None) => return APIResult::NotFound(format!("Album {} not found", id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        }
+
+        // Delete album-photo associations first
+        if let Err(e) = sqlx::query("DELETE FROM album_photo WHERE album_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to delete album photos: {}", e));
+        }
+
+        // If this album is the current album, clear it
+        let _ = sqlx::query("UPDATE settings SET current_album_id = NULL, current_photo_index = 0 WHERE current_album_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await;
+        let _ = sqlx::query("UPDATE display SET current_album_id = NULL, current_photo_index = 0 WHERE current_album_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await;
+
+        // Schedules playing this album have nothing left to play
+        if let Err(e) = sqlx::query("DELETE FROM schedule WHERE album_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to delete album schedules: {}", e));
+        }
+
+        // Delete the album
+        if let Err(e) = sqlx::query("DELETE FROM album WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to delete album: {}", e));
+        }
+
+        APIResult::Ok(())
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Album Photos (Managing photo membership in albums)
+    // ─────────────────────────────────────────────────────────────────────────
+
+    #[api_handler(method = "POST", path = "/api/albums/{album_id}/photos/{photo_id}")]
+    pub async fn add_photo_to_album(
+        &self,
+        #[path] album_id: i32,
+        #[path] photo_id: i32,
+    ) -> APIResult<()> {
+        // Check album exists and is managed by hand
+        let album = sqlx::query_as::<_, DbAlbum>("SELECT * FROM album WHERE id = ?")
+            .bind(album_id)
+            .fetch_optional(&self.pool)
+            .await;
+
+        match album {
+            Ok(Some(album)) if picks_own_photos(&album) => {
+                return APIResult::BadRequest(format!("Album {} picks its own photos", album_id));
+            }
+            Ok(Some(_)) => {}
+            Ok(None) => return APIResult::NotFound(format!("Album {} not found", album_id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        }
+
+        // Check photo exists
+        let photo_exists =
+            sqlx::query_scalar::<_, i32>("SELECT 1 FROM photo WHERE id = ? AND deleted_at IS NULL")
+                .bind(photo_id)
+                .fetch_optional(&self.pool)
+                .await;
+
+        match photo_exists {
+            Ok(Some(_)) => {}
+            Ok(None) => return APIResult::NotFound(format!("Photo {} not found", photo_id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        }
+
+        // Check if photo is already in album
+        let already_in_album = sqlx::query_scalar::<_, i32>(
+            "SELECT 1 FROM album_photo WHERE album_id = ? AND photo_id = ?",
+        )
+        .bind(album_id)
+        .bind(photo_id)
+        .fetch_optional(&self.pool)
+        .await;
+
+        if let Ok(Some(_)) = already_in_album {
+            // Photo already in album, nothing to do
+            return APIResult::Ok(());
+        }
+
+        // Get the next position
+        let max_position = sqlx::query_scalar::<_, Option<i32>>(
+            "SELECT MAX(position) FROM album_photo WHERE album_id = ?",
+        )
+        .bind(album_id)
+        .fetch_one(&self.pool)
+        .await
+        .unwrap_or(None)
+        .unwrap_or(-1);
+
+        // Add photo to album
+        if let Err(e) =
+            sqlx::query("INSERT INTO album_photo (album_id, photo_id, position) VALUES (?, ?, ?)")
+                .bind(album_id)
+                .bind(photo_id)
+                .bind(max_position + 1)
+                .execute(&self.pool)
+                .await
+        {
+            return APIResult::InternalError(format!("Failed to add photo to album: {}", e));
+        }
+
+        APIResult::Ok(())
+    }
+
+    #[api_handler(method = "DELETE", path = "/api/albums/{album_id}/photos/{photo_id}")]
+    pub async fn remove_photo_from_album(
+        &self,
+        #[path] album_id: i32,
+        #[path] photo_id: i32,
+    ) -> APIResult<()> {
+        // Check album exists and is managed by hand
+        let album = sqlx::query_as::<_, DbAlbum>("SELECT * FROM album WHERE id = ?")
+            .bind(album_id)
+            .fetch_optional(&self.pool)
+            .await;
+
+        match album {
+            Ok(Some(album)) if picks_own_photos(&album) => {
+                return APIResult::BadRequest(format!("Album {} picks its own photos", album_id));
+            }
+            Ok(Some(_)) => {}
+            Ok(None) => return APIResult::NotFound(format!("Album {} not found", album_id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        }
+
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+
+        // Remove photo from album (don't fail if it wasn't there)
+        if let Err(e) = sqlx::query("DELETE FROM album_photo WHERE album_id = ? AND photo_id = ?")
+            .bind(album_id)
+            .bind(photo_id)
+            .execute(&mut *tx)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to remove photo from album: {}", e));
+        }
+
+        // Close the gap it left
+        let renumbered = match album_order(&mut tx, album_id).await {
+            Ok(order) => write_album_order(&mut tx, album_id, &order).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = renumbered {
+            return APIResult::InternalError(format!("Failed to renumber album: {}", e));
+        }
+
+        match tx.commit().await {
+            Ok(()) => APIResult::Ok(()),
+            Err(e) => APIResult::InternalError(format!("Failed to remove photo from album: {}", e)),
+        }
+    }
+
+    /// Put the listed photos first, in the order given, followed by the album's other photos
+    /// in their current order. Sending every photo sets the whole order.
+    #[api_handler(method = "PUT", path = "/api/albums/{id}/order")]
+    pub async fn reorder_album(
+        &self,
+        #[path] id: i32,
+        #[body] req: ReorderAlbumRequest,
+    ) -> APIResult<()> {
+        if let Err(response) = self.find_manual_album(id).await {
+            return response;
+        }
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+        let current = match album_order(&mut tx, id).await {
+            Ok(order) => order,
+            Err(e) => {
+                return APIResult::InternalError(format!("Failed to get album photos: {}", e));
+            }
+        };
+
+        let mut order: Vec<i32> = Vec::with_capacity(current.len());
+        for photo in &req.photos {
+            if !current.contains(&photo.0) {
+                return APIResult::BadRequest(format!("Photo {} is not in album {}", photo, id));
+            }
+            if order.contains(&photo.0) {
+                return APIResult::BadRequest(format!("Photo {} is listed twice", photo));
+            }
+            order.push(photo.0);
+        }
+        order.extend(
+            current
+                .into_iter()
+                .filter(|photo| !req.photos.contains(&PhotoID(*photo))),
+        );
+
+        if let Err(e) = write_album_order(&mut tx, id, &order).await {
+            return APIResult::InternalError(format!("Failed to reorder album: {}", e));
+        }
+        match tx.commit().await {
+            Ok(()) => APIResult::Ok(()),
+            Err(e) => APIResult::InternalError(format!("Failed to reorder album: {}", e)),
+        }
+    }
+
+    #[api_handler(
+        method = "POST",
+        path = "/api/albums/{album_id}/photos/{photo_id}/move"
+    )]
+    pub async fn move_album_photo(
+        &self,
+        #[path] album_id: i32,
+        #[path] photo_id: i32,
+        #[body] req: MoveAlbumPhotoRequest,
+    ) -> APIResult<()> {
+        if let Err(response) = self.find_manual_album(album_id).await {
+            return response;
+        }
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+        let mut order = match album_order(&mut tx, album_id).await {
+            Ok(order) => order,
+            Err(e) => {
+                return APIResult::InternalError(format!("Failed to get album photos: {}", e));
+            }
+        };
+
+        let Some(index) = order.iter().position(|id| *id == photo_id) else {
+            return APIResult::NotFound(format!("Photo {} is not in album {}", photo_id, album_id));
+        };
+        let photo = order.remove(index);
+        match req.to {
+            AlbumEnd::Top => order.insert(0, photo),
+            AlbumEnd::Bottom => order.push(photo),
+        }
+
+        if let Err(e) = write_album_order(&mut tx, album_id, &order).await {
+            return APIResult::InternalError(format!("Failed to move photo: {}", e));
+        }
+        match tx.commit().await {
+            Ok(()) => APIResult::Ok(()),
+            Err(e) => APIResult::InternalError(format!("Failed to move photo: {}", e)),
+        }
+    }
+
+    /// Sort the album's photos once. Photos with the same value keep their current order.
+    #[api_handler(method = "POST", path = "/api/albums/{id}/sort")]
+    pub async fn sort_album(
+        &self,
+        #[path] id: i32,
+        #[body] req: SortAlbumRequest,
+    ) -> APIResult<()> {
+        if let Err(response) = self.find_manual_album(id).await {
+            return response;
+        }
+        let mut tx = match self.pool.begin().await {
+            Ok(tx) => tx,
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        };
+        let photos = sqlx::query_as::<_, DbPhoto>(
+            r#"
+            SELECT p.* FROM photo p
+            JOIN album_photo ap ON ap.photo_id = p.id
+            WHERE ap.album_id = ?
+            ORDER BY ap.position, ap.created_at
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await;
+        let mut photos = match photos {
+            Ok(photos) => photos,
+            Err(e) => {
+                return APIResult::InternalError(format!("Failed to get album photos: {}", e));
+            }
+        };
+
+        photos.sort_by(|a, b| match req.by {
+            AlbumPhotoSort::DateTaken => {
+                cmp_missing_last(a.date_taken, b.date_taken, req.descending)
+            }
+            AlbumPhotoSort::Title => cmp_missing_last(
+                a.title.as_ref().map(|t| t.to_lowercase()),
+                b.title.as_ref().map(|t| t.to_lowercase()),
+                req.descending,
+            ),
+        });
+
+        let order: Vec<i32> = photos.iter().map(|p| p.id).collect();
+        if let Err(e) = write_album_order(&mut tx, id, &order).await {
+            return APIResult::InternalError(format!("Failed to sort album: {}", e));
+        }
+        match tx.commit().await {
+            Ok(()) => APIResult::Ok(()),
+            Err(e) => APIResult::InternalError(format!("Failed to sort album: {}", e)),
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Jobs
+    // ─────────────────────────────────────────────────────────────────────────
+
+    #[api_handler(method = "GET", path = "/api/jobs")]
+    pub async fn get_jobs(&self) -> APIResult<Vec<Job>> {
+        match sqlx::query_as::<_, DbJob>("SELECT * FROM job ORDER BY id DESC LIMIT ?")
+            .bind(MAX_JOBS_LISTED)
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(jobs) => APIResult::Ok(jobs.iter().map(db_job_to_job).collect()),
+            Err(e) => APIResult::InternalError(format!("Failed to get jobs: {}", e)),
+        }
+    }
+
+    #[api_handler(method = "GET", path = "/api/jobs/{id}")]
+    pub async fn get_job(&self, #[path] id: i32) -> APIResult<Job> {
+        match sqlx::query_as::<_, DbJob>("SELECT * FROM job WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(Some(job)) => APIResult::Ok(db_job_to_job(&job)),
+            Ok(None) => APIResult::NotFound(format!("Job {} not found", id)),
+            Err(e) => APIResult::InternalError(format!("Failed to get job: {}", e)),
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Mat Presets
+    // ─────────────────────────────────────────────────────────────────────────
+
+    #[api_handler(method = "GET", path = "/api/mat-presets")]
+    pub async fn get_mat_presets(&self) -> APIResult<Vec<MatStyle>> {
+        let presets = MatStyle::preset_names()
+            .iter()
+            .map(|name| MatStyle::from_preset(name))
+            .collect();
+        APIResult::Ok(presets)
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Settings
+    // ─────────────────────────────────────────────────────────────────────────
+
+    #[api_handler(method = "GET", path = "/api/settings")]
+    pub async fn get_settings(&self) -> APIResult<RotationSettings> {
+        let settings = match sqlx::query_as::<_, DbSettings>("SELECT * FROM settings WHERE id = 1")
+            .fetch_one(&self.pool)
+            .await
+        {
+            Ok(s) => s,
+            Err(e) => return APIResult::InternalError(format!("Failed to get settings: {}", e)),
+        };
+
+        let current_album = settings.current_album_id.map(|album_id| CurrentAlbum {
+            album: AlbumID::from(album_id),
+            index: settings.current_photo_index as usize,
+        });
+
+        APIResult::Ok(RotationSettings {
+            current_album,
+            interval: Interval::from(settings.interval_seconds),
+            mode: RotationMode::from_db(&settings.rotation_mode),
+        })
+    }
+
+    #[api_handler(method = "PUT", path = "/api/settings")]
+    pub async fn update_settings(&self, #[body] req: UpdateSettingsRequest) -> APIResult<()> {
+        // The photos or their order change, so a shuffle has to start a fresh cycle
+        let restart_shuffle = req.current_album_id.is_some() || req.rotation_mode.is_some();
+
+        // Handle current_album_id update
+        if let Some(album_update) = req.current_album_id {
+            match album_update {
+                Update::Set(album_id) => {
+                    // Verify album exists
+                    let exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM album WHERE id = ?")
+                        .bind(album_id.0)
+                        .fetch_optional(&self.pool)
+                        .await;
+
+                    match exists {
+                        Ok(Some(_)) => {
+                            // Album exists, update settings and reset index
+                            if let Err(e) = sqlx::query(
+                                "UPDATE settings SET current_album_id = ?, current_photo_index = 0 WHERE id = 1",
+                            )
+                            .bind(album_id.0)
+                            .execute(&self.pool)
+                            .await
+                            {
+                                return APIResult::InternalError(format!(
+                                    "Failed to update settings: {}",
+                                    e
+                                ));
+                            }
+                        }
+                        Ok(None) => {
+                            return APIResult::NotFound(format!("Album {} not found", album_id.0));
+                        }
+                        Err(e) => {
+                            return APIResult::InternalError(format!("Database error: {}", e));
+                        }
+                    }
+                }
+                Update::Remove => {
+                    // Clear current album
+                    if let Err(e) = sqlx::query(
+                        "UPDATE settings SET current_album_id = NULL, current_photo_index = 0 WHERE id = 1",
+                    )
+                    .execute(&self.pool)
+                    .await
+                    {
+                        return APIResult::InternalError(format!(
+                            "Failed to update settings: {}",
+                            e
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Handle rotation_mode update
+        if let Some(mode) = req.rotation_mode
+            && let Err(e) = sqlx::query("UPDATE settings SET rotation_mode = ? WHERE id = 1")
+                .bind(mode.as_str())
+                .execute(&self.pool)
+                .await
+        {
+            return APIResult::InternalError(format!("Failed to update settings: {}", e));
+        }
+
+        if restart_shuffle
+            && let Err(e) = sqlx::query("DELETE FROM shuffle_queue WHERE display_id IS NULL")
+                .execute(&self.pool)
+                .await
+        {
+            return APIResult::InternalError(format!("Failed to update settings: {}", e));
+        }
+
+        // Handle interval_seconds update
+        if let Some(interval) = req.interval_seconds {
+            if interval < 1 {
+                return APIResult::BadRequest("Interval must be at least 1 second".to_string());
+            }
+            if let Err(e) = sqlx::query("UPDATE settings SET interval_seconds = ? WHERE id = 1")
+                .bind(interval)
+                .execute(&self.pool)
+                .await
+            {
+                return APIResult::InternalError(format!("Failed to update settings: {}", e));
+            }
+        }
+
+        APIResult::Ok(())
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Displays
+    // ─────────────────────────────────────────────────────────────────────────
+
+    #[api_handler(method = "GET", path = "/api/displays")]
+    pub async fn get_displays(&self) -> APIResult<Vec<Display>> {
+        match sqlx::query_as::<_, DbDisplay>("SELECT * FROM display ORDER BY name, id")
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(displays) => APIResult::Ok(displays.iter().map(db_display_to_display).collect()),
+            Err(e) => APIResult::InternalError(format!("Failed to get displays: {}", e)),
+        }
+    }
+
+    #[api_handler(method = "GET", path = "/api/displays/{id}")]
+    pub async fn get_display(&self, #[path] id: i32) -> APIResult<Display> {
+        match sqlx::query_as::<_, DbDisplay>("SELECT * FROM display WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(Some(display)) => APIResult::Ok(db_display_to_display(&display)),
+            Ok(None) => APIResult::NotFound(format!("Display {} not found", id)),
+            Err(e) => APIResult::InternalError(format!("Failed to get display: {}", e)),
+        }
+    }
+
+    #[api_handler(method = "POST", path = "/api/displays")]
+    pub async fn create_display(&self, #[body] req: CreateDisplayRequest) -> APIResult<Display> {
+        if req.name.trim().is_empty() {
+            return APIResult::BadRequest("Display name cannot be empty".to_string());
+        }
+
+        let token = Alphanumeric.sample_string(&mut rand::rng(), DISPLAY_TOKEN_LEN);
+        let result = sqlx::query_as::<_, DbDisplay>(
+            "INSERT INTO display (name, token) VALUES (?, ?) RETURNING *",
+        )
+        .bind(&req.name)
+        .bind(&token)
+        .fetch_one(&self.pool)
+        .await;
+
+        match result {
+            Ok(display) => APIResult::Ok(db_display_to_display(&display)),
+            Err(e) => APIResult::InternalError(format!("Failed to create display: {}", e)),
+        }
+    }
+
+    #[api_handler(method = "PUT", path = "/api/displays/{id}")]
+    pub async fn update_display(
+        &self,
+        #[path] id: i32,
+        #[body] req: UpdateDisplayRequest,
+    ) -> APIResult<Display> {
+        // Check display exists
+        let exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM display WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await;
+
+        match exists {
+            Ok(Some(_)) => {}
+            Ok(None) => return APIResult::NotFound(format!("Display {} not found", id)),
+            Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+        }
+
+        // The photos or their order change, so a shuffle has to start a fresh cycle
+        let restart_shuffle = req.current_album_id.is_some() || req.rotation_mode.is_some();
+
+        // Validate everything before changing anything
+        if let Some(name) = &req.name
+            && name.trim().is_empty()
+        {
+            return APIResult::BadRequest("Display name cannot be empty".to_string());
+        }
+        if let Some(interval) = req.interval_seconds
+            && interval < 1
+        {
+            return APIResult::BadRequest("Interval must be at least 1 second".to_string());
+        }
+        if let Some(Update::Set(preset)) = &req.mat_preset
+            && !MatStyle::preset_names().contains(&preset.as_str())
+        {
+            return APIResult::BadRequest(format!("Unknown mat preset: {}", preset));
+        }
+        let album_id = match req.current_album_id {
+            Some(Update::Set(album_id)) => {
+                let exists = sqlx::query_scalar::<_, i32>("SELECT 1 FROM album WHERE id = ?")
+                    .bind(album_id.0)
+                    .fetch_optional(&self.pool)
+                    .await;
+                match exists {
+                    Ok(Some(_)) => Some(Some(album_id.0)),
+                    Ok(None) => {
+                        return APIResult::NotFound(format!("Album {} not found", album_id.0));
+                    }
+                    Err(e) => return APIResult::InternalError(format!("Database error: {}", e)),
+                }
+            }
+            Some(Update::Remove) => Some(None),
+            None => None,
+        };
+
+        if let Some(name) = &req.name
+            && let Err(e) = sqlx::query("UPDATE display SET name = ? WHERE id = ?")
+                .bind(name)
+                .bind(id)
+                .execute(&self.pool)
+                .await
+        {
+            return APIResult::InternalError(format!("Failed to update display: {}", e));
+        }
+
+        // A new album starts from its first photo
+        if let Some(album_id) = album_id
+            && let Err(e) = sqlx::query(
+                "UPDATE display SET current_album_id = ?, current_photo_index = 0 WHERE id = ?",
+            )
+            .bind(album_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            return APIResult::InternalError(format!("Failed to update display: {}", e));
+        }
+
+        if let Some(interval) = req.interval_seconds
+            && let Err(e) = sqlx::query("UPDATE display SET interval_seconds = ? WHERE id = ?")
+                .bind(interval)
+                .bind(id)
+                .execute(&self.pool)
+                .await
+        {
+            return APIResult::InternalError(format!("Failed to update display: {}", e));
+        }
+
+        if let Some(mode) = req.rotation_mode
+            && let Err(e) = sqlx::query("UPDATE display SET rotation_mode = ? WHERE id = ?")
+                .bind(mode.as_str())
+                .bind(id)
+                .execute(&self.pool)
+                .await
+        {
+            return APIResult::InternalError(format!("Failed to update display: {}", e));
+        }
+
+        if let Some(mat_update) = &req.mat_preset {
+            let preset = match mat_update {
+                Update::Set(preset) => Some(preset),
+                Update::Remove => None,
+            };
+            if let Err(e) = sqlx::query("UPDATE display SET mat_preset = ? WHERE id = ?")
+                .bind(preset)
+                .bind(id)
+                .execute(&self.pool)
+                .await
+            {
+                return APIResult::InternalError(format!("Failed to update display: {}", e));
+            }
+        }
+
+        if restart_shuffle
+            && let Err(e) = sqlx::query("DELETE FROM shuffle_queue WHERE display_id = ?")
+                .bind(id)
+                .execute(&self.pool)
+                .await
+        {
+            return APIResult::InternalError(format!("Failed to update display: {}", e));
+        }
+
+        let _ = sqlx::query("UPDATE display SET updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await;
+
+        self.get_display(id).await
+    }
+
+    #[api_handler(method = "DELETE", path = "/api/displays/{id}")]
+    pub async fn delete_display(&self, #[path] id: i32) -> APIResult<()> {
+        // Drop its shuffle cycle and schedules first
+        for table in ["shuffle_queue", "schedule"] {
+            if let Err(e) = sqlx::query(&format!("DELETE FROM {table} WHERE display_id = ?"))
+                .bind(id)
+                .execute(&self.pool)
+                .await
+            {
+                return APIResult::InternalError(format!("Failed to delete display: {}", e));
+            }
+        }
+
+        match sqlx::query("DELETE FROM display WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) if result.rows_affected() == 0 => {
+                APIResult::NotFound(format!("Display {} not found", id))
+            }
+            Ok(_) => APIResult::Ok(()),
+            Err(e) => APIResult::InternalError(format!("Failed to delete display: {}", e)),
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Schedules
+    // ─────────────────────────────────────────────────────────────────────────
+
+    #[api_handler(method = "GET", path = "/api/schedules")]
+    pub async fn get_schedules(&self) -> APIResult<Vec<Schedule>> {
+        match sqlx::query_as::<_, DbSchedule>("SELECT * FROM schedule ORDER BY priority DESC, id")
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(schedules) => APIResult::Ok(schedules.iter().map(db_schedule_to_schedule).collect()),
+            Err(e) => APIResult::InternalError(format!("Failed to get schedules: {}", e)),
+        }
+    }
+
+    #[api_handler(method = "GET", path = "/api/schedules/{id}")]
+    pub async fn get_schedule(&self, #[path] id: i32) -> APIResult<Schedule> {
+        match sqlx::query_as::<_, DbSchedule>("SELECT * FROM schedule WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(Some(schedule)) => APIResult::Ok(db_schedule_to_schedule(&schedule)),
+            Ok(None) => APIResult::NotFound(format!("Schedule {} not found", id)),
+            Err(e) => APIResult::InternalError(format!("Failed to get schedule: {}", e)),
+        }
+    }
+
+    #[api_handler(method = "POST", path = "/api/schedules")]
+    pub async fn create_schedule(&self, #[body] req: ScheduleRequest) -> APIResult<Schedule> {
+        if let Some(error) = self.check_schedule_request(&req).await {
+            return error;
+        }
+
+        let result = sqlx::query_as::<_, DbSchedule>(
+            r#"
+            INSERT INTO schedule (name, album_id, display_id, priority, enabled, weekdays, months, start_time, end_time)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(&req.name)
+        .bind(schedule_album_id(&req.action))
+        .bind(req.display.map(|d| d.0))
+        .bind(req.priority)
+        .bind(req.enabled)
+        .bind(weekdays_mask(&req.days))
+        .bind(months_mask(&req.months))
+        .bind(req.start_time)
+        .bind(req.end_time)
+        .fetch_one(&self.pool)
+        .await;
+
+        match result {
+            Ok(schedule) => APIResult::Ok(db_schedule_to_schedule(&schedule)),
+            Err(e) => APIResult::InternalError(format!("Failed to create schedule: {}", e)),
+        }
+    }
+
+    #[api_handler(method = "PUT", path = "/api/schedules/{id}")]
+    pub async fn update_schedule(
+        &self,
+        #[path] id: i32,
+        #[body] req: ScheduleRequest,
+    ) -> APIResult<Schedule> {
+        if let Some(error) = self.check_schedule_request(&req).await {
+            return error;
+        }
+
+        let result = sqlx::query_as::<_, DbSchedule>(
+            r#"
+            UPDATE schedule
+            SET name = ?, album_id = ?, display_id = ?, priority = ?, enabled = ?, weekdays = ?,
+                months = ?, start_time = ?, end_time = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            RETURNING *
+            "#,
+        )
+        .bind(&req.name)
+        .bind(schedule_album_id(&req.action))
+        .bind(req.display.map(|d| d.0))
+        .bind(req.priority)
+        .bind(req.enabled)
+        .bind(weekdays_mask(&req.days))
+        .bind(months_mask(&req.months))
+        .bind(req.start_time)
+        .bind(req.end_time)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await;
+
+        match result {
+            Ok(Some(schedule)) => APIResult::Ok(db_schedule_to_schedule(&schedule)),
+            Ok(None) => APIResult::NotFound(format!("Schedule {} not found", id)),
+            Err(e) => APIResult::InternalError(format!("Failed to update schedule: {}", e)),
+        }
+    }
+
+    #[api_handler(method = "DELETE", path = "/api/schedules/{id}")]
+    pub async fn delete_schedule(&self, #[path] id: i32) -> APIResult<()> {
+        match sqlx::query("DELETE FROM schedule WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) if result.rows_affected() == 0 => {
+                APIResult::NotFound(format!("Schedule {} not found", id))
+            }
+            Ok(_) => APIResult::Ok(()),
+            Err(e) => APIResult::InternalError(format!("Failed to delete schedule: {}", e)),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Non-macro handlers (raw Axum handlers for binary/multipart data)
+// ─────────────────────────────────────────────────────────────────────────────
+
+impl App {
+    /// Upload a new photo via multipart form data.
+    /// This is a raw Axum handler because it needs multipart form handling.
+    ///
+    /// The file is streamed to the staging dir and queued as a job; the response is sent
+    /// before any processing happens. Poll `GET /api/jobs/{id}` to find out how it went.
+    /// Duplicates of photos already in the library are turned away with a 409. Two copies
+    /// uploaded before either is processed both get queued, and the second job fails.
+    pub async fn upload_photo(
+        &self,
+        mut multipart: axum::extract::Multipart,
+    ) -> axum::response::Response {
+        // Check if we have the required directories configured
+        let (Some(_), Some(staging_dir)) = (&self.photos_dir, &self.staging_dir) else {
+            return json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Server not configured for uploads",
+            );
+        };
+        let Some(processor) = &self.image_processor else {
+            return json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "No image processor available",
+            );
+        };
+
+        // Find the file field
+        let mut field = loop {
+            match multipart.next_field().await {
+                Ok(Some(field)) => {
+                    let name = field.name().unwrap_or("");
+                    if name == "file" || name == "photo" {
+                        break field;
+                    }
+                }
+                Ok(None) => {
+                    return json_error(
+                        StatusCode::BAD_REQUEST,
+                        "No file provided. Include a 'file' or 'photo' field.",
+                    );
+                }
+                Err(e) => {
+                    return json_error(
+                        StatusCode::BAD_REQUEST,
+                        format!("Failed to read upload: {}", e),
+                    );
+                }
+            }
+        };
+
+        // Only keep the final path component of the client supplied name
+        let filename = field
+            .file_name()
+            .and_then(|name| Path::new(name).file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "upload".to_string());
+
+        // Each upload gets its own staging dir so the original file name (used as the
+        // photo title) can be kept without colliding with other uploads
+        let upload_dir = match tempfile::Builder::new()
+            .prefix("upload-")
+            .tempdir_in(staging_dir)
+        {
+            Ok(d) => d,
+            Err(e) => {
+                return json_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to create staging directory: {}", e),
+                );
+            }
+        };
+        let staged_path = upload_dir.path().join(&filename);
+
+        // Stream the body to disk rather than buffering it in memory
+        let mut file = match tokio::fs::File::create(&staged_path).await {
+            Ok(f) => f,
+            Err(e) => {
+                return json_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to write staged file: {}", e),
+                );
+            }
+        };
+        loop {
+            match field.chunk().await {
+                Ok(Some(chunk)) => {
+                    if let Err(e) = file.write_all(&chunk).await {
+                        return json_error(
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Failed to write staged file: {}", e),
+                        );
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    return json_error(
+                        StatusCode::BAD_REQUEST,
+                        format!("Failed to read file: {}", e),
+                    );
+                }
+            }
+        }
+        if let Err(e) = file.flush().await {
+            return json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to write staged file: {}", e),
+            );
+        }
+
+        // The file name is only a hint. Check the content is an image we can handle
+        let format = match PhotoFormat::detect(&staged_path) {
+            Ok(format) => format,
+            Err(e) => return json_error(StatusCode::BAD_REQUEST, format!("{:#}", e)),
+        };
+        if !processor.supports(format) {
+            return json_error(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "{} photos are not supported by the {} image processor",
+                    format.name(),
+                    processor.name()
+                ),
+            );
+        }
+
+        // Turn duplicates away now rather than after a worker has done the expensive work.
+        // Files that can't be hashed are still queued so the job reports why they failed.
+        if let Ok(hash) = on_disk_photo::hash_photo(&staged_path, format, processor.as_ref()).await
+        {
+            match self.find_duplicate(&hash).await {
+                Ok(Some(duplicate)) => {
+                    info!("Rejected upload {}: {}", filename, duplicate);
+                    let body = DuplicateError {
+                        error: duplicate.to_string(),
+                        existing: PhotoID::from(duplicate.existing),
+                        distance: duplicate.distance,
+                    };
+                    return (StatusCode::CONFLICT, axum::Json(body)).into_response();
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    return json_error(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to check for duplicates: {}", e),
+                    );
+                }
+            }
+        }
+
+        // Queue the job. The staging dir now belongs to the job and is cleaned up by the worker.
+        let result = sqlx::query_as::<_, DbJob>(
+            "INSERT INTO job (kind, filename, staged_path) VALUES ('upload', ?, ?) RETURNING *",
+        )
+        .bind(&filename)
+        .bind(staged_path.display().to_string())
+        .fetch_one(&self.pool)
+        .await;
+
+        match result {
+            Ok(job) => {
+                let _ = upload_dir.keep();
+                self.job_notify.notify_waiters();
+                info!("Queued upload job {} for {}", job.id, filename);
+                (StatusCode::ACCEPTED, axum::Json(db_job_to_job(&job))).into_response()
+            }
+            Err(e) => json_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to queue upload: {}", e),
+            ),
+        }
+    }
+
+    /// Serve one of a photo's renditions.
+    /// This is a raw Axum handler (not using the api macro) because it returns binary data.
+    ///
+    /// The file is streamed rather than read into memory, and `Range`, `If-Modified-Since`
+    /// and `If-None-Match` are honored. URLs with the photo's current image key are cached
+    /// for good, anything else has to be revalidated.
+    pub async fn serve_image(
+        &self,
+        id: i32,
+        query: ImageQuery,
+        mut request: axum::extract::Request,
+    ) -> axum::response::Response {
+        use axum::body::Body;
+        use axum::http::{HeaderValue, Response, StatusCode, header};
+        use tower_http::services::ServeFile;
+        use tower_service::Service;
+
+        // Look up the photo
+        let photo = match sqlx::query_as::<_, DbPhoto>("SELECT * FROM photo WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(Some(p)) => p,
+            Ok(None) => {
+                return Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Body::from("Photo not found"))
+                    .unwrap();
+            }
+            Err(e) => {
+                return Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Body::from(format!("Database error: {}", e)))
+                    .unwrap();
+            }
+        };
+
+        let variant = query.variant.unwrap_or_default();
+        let path = match variant {
+            ImageVariant::Thumbnail => &photo.thumbnail_path,
+            ImageVariant::Web => &photo.websize_path,
+            ImageVariant::Full => &photo.fullsize_path,
+        };
+        let path = std::path::Path::new(path);
+
+        let etag = HeaderValue::from_str(&image_etag(
+            &photo.hash,
+            variant,
+            photo.image_key.as_deref(),
+        ))
+        .expect("hashes are base64 and keys hex so always a valid header value");
+        let cache_control = if photo.image_key.is_some() && query.v == photo.image_key {
+            HeaderValue::from_static(IMAGE_CACHE_IMMUTABLE)
+        } else {
+            HeaderValue::from_static(IMAGE_CACHE_REVALIDATE)
+        };
+
+        if let Some(if_none_match) = request.headers().get(header::IF_NONE_MATCH) {
+            if etag_matches(if_none_match, &etag) {
+                return Response::builder()
+                    .status(StatusCode::NOT_MODIFIED)
+                    .header(header::ETAG, etag)
+                    .header(header::CACHE_CONTROL, cache_control)
+                    .body(Body::empty())
+                    .unwrap();
+            }
+            // If-None-Match takes precedence, so a stale ETag must not be rescued by the date
+            request.headers_mut().remove(header::IF_MODIFIED_SINCE);
+        }
+
+        // Renditions are always JPEG. The original's type was recorded when it was imported
+        let content_type = match (variant, &photo.content_type) {
+            (ImageVariant::Full, Some(content_type)) => content_type.clone(),
+            (ImageVariant::Full, None) => {
+                let path = path.to_path_buf();
+                tokio::task::spawn_blocking(move || PhotoFormat::detect(&path))
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+                    .map(|format| format.content_type())
+                    .unwrap_or("application/octet-stream")
+                    .to_string()
+            }
+            _ => PhotoFormat::Jpeg.content_type().to_string(),
+        };
+
+        let response = match ServeFile::new(path).call(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to read image file {}: {}", path.display(), e);
+                return Response::builder()
+                    .status(StatusCode::INTERNAL_SERVER_ERROR)
+                    .body(Body::from("Unable to read image file"))
+                    .unwrap();
+            }
+        };
+        if response.status() == StatusCode::NOT_FOUND {
+            warn!("Image file {} is missing", path.display());
+            return Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(Body::from("Image file not found"))
+                .unwrap();
+        }
+
+        let mut response = response.map(Body::new);
+        let headers = response.headers_mut();
+        headers.insert(header::ETAG, etag);
+        headers.insert(header::CACHE_CONTROL, cache_control);
+        if headers.contains_key(header::CONTENT_TYPE)
+            && let Ok(content_type) = HeaderValue::from_str(&content_type)
+        {
+            headers.insert(header::CONTENT_TYPE, content_type);
+        }
+        response
+    }
+
+    /// Stream a backup of the whole library as a tar archive, see [`crate::backup`].
+    /// This is a raw Axum handler (not using the api macro) because it returns binary data.
+    ///
+    /// The database is snapshotted up front. The archive is then written on a blocking thread
+    /// as the client reads it, so it's never held in memory or on disk as a whole.
+    pub async fn backup(&self, query: BackupQuery) -> axum::response::Response {
+        use axum::body::Body;
+        use axum::http::header;
+        use tokio_util::io::{ReaderStream, SyncIoBridge};
+
+        let (Some(data_dir), Some(staging_dir)) = (&self.data_dir, &self.staging_dir) else {
+            return json_error(StatusCode::INTERNAL_SERVER_ERROR, "data_dir not configured");
+        };
+        let snapshot =
+            match Snapshot::take(&self.pool, data_dir, staging_dir, query.originals_only).await {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    error!("Unable to snapshot the library: {e:#}");
+                    return json_error(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Unable to snapshot the library: {e:#}"),
+                    );
+                }
+            };
+        let filename = format!(
+            "pictureframe-backup-{}.tar",
+            snapshot.manifest().created_at.format("%Y%m%d-%H%M%S")
+        );
+
+        let (writer, reader) = tokio::io::duplex(BACKUP_BUFFER_SIZE);
+        let writer = SyncIoBridge::new(writer);
+        tokio::task::spawn_blocking(move || {
+            // The client is left with an archive without a manifest, which restore refuses
+            if let Err(e) = snapshot.write_archive(writer) {
+                error!("Backup failed: {e:#}");
+            }
+        });
+
+        axum::response::Response::builder()
+            .header(header::CONTENT_TYPE, "application/x-tar")
+            .header(
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            )
+            .body(Body::from_stream(ReaderStream::new(reader)))
+            .unwrap()
+    }
+
+    /// Stream a zip of an album's originals, in album order, with a manifest of their details.
+    /// See [`crate::export`]. This is a raw Axum handler (not using the api macro) because it
+    /// returns binary data.
+    pub async fn export_album(&self, id: i32) -> axum::response::Response {
+        let album = match sqlx::query_as::<_, DbAlbum>("SELECT * FROM album WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(Some(album)) => album,
+            Ok(None) => return json_error(StatusCode::NOT_FOUND, format!("Album {id} not found")),
+            Err(e) => {
+                return json_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to get album: {e}"),
+                );
+            }
+        };
+        let photos = match self.album_photos(&album).await {
+            Ok(photos) => photos,
+            Err(e) => {
+                return json_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to get album photos: {e}"),
+                );
+            }
+        };
+
+        let mut filename = export::sanitize_file_name(&album.name);
+        if filename.is_empty() {
+            filename = format!("Album {id}");
+        }
+        stream_download("application/zip", &format!("{filename}.zip"), move |out| {
+            export::write_album_zip(&album.name, &photos, out)
+        })
+    }
+
+    /// Import a photo from disk and record it in the database.
+    /// Returns the new photo's ID.
+    pub async fn import_photo(&self, photo_path: &Path) -> Result<i32> {
+        info!("Processing {}", photo_path.display());
+
+        let Some(photos_dir) = &self.photos_dir else {
+            bail!("photos_dir not configured");
+        };
+        let Some(processor) = &self.image_processor else {
+            bail!("image_processor not configured");
+        };
+
+        let format = PhotoFormat::detect(photo_path)?;
+        if !processor.supports(format) {
+            bail!(
+                "{} photos are not supported by the {} image processor",
+                format.name(),
+                processor.name()
+            );
+        }
+        let hash = on_disk_photo::hash_photo(photo_path, format, processor.as_ref()).await?;
+        if let Some(duplicate) = self.find_duplicate(&hash).await? {
+            return Err(duplicate.into());
+        }
+
+        let photo =
+            OnDiskPhoto::import(photo_path, format, &hash, photos_dir, processor.as_ref()).await?;
+        let fullsize = photo.fullsize_path();
+        let websize = photo.websize_path();
+        let thumbnail = photo.thumbnail_path();
+
+        let metadata = photo.metadata();
+        let title = metadata.title();
+        let artist = metadata.artist();
+        let copyright = metadata.copyright();
+        let date_taken = metadata.date_taken();
+        let orientation = metadata.orientation();
+        let exif = metadata.exif();
+        let (width, height) = photo.dimensions();
+        let hash = photo.hash();
+
+        let id = sqlx::query_scalar::<_, i32>(
+            r#"
+            INSERT INTO photo (
+                hash, title, artist, copyright, date_taken, fullsize_path, websize_path, thumbnail_path,
+                width, height, orientation, camera_make, camera_model, lens, focal_length, aperture,
+                exposure_time, iso, latitude, longitude, pixel_width, pixel_height, date_taken_offset,
+                content_type, image_key
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(hash)
+        .bind(title)
+        .bind(artist)
+        .bind(copyright)
+        .bind(date_taken)
+        .bind(fullsize.display().to_string())
+        .bind(websize.display().to_string())
+        .bind(thumbnail.display().to_string())
+        .bind(width)
+        .bind(height)
+        .bind(orientation)
+        .bind(&exif.camera_make)
+        .bind(&exif.camera_model)
+        .bind(&exif.lens)
+        .bind(exif.focal_length)
+        .bind(exif.aperture)
+        .bind(exif.exposure_time)
+        .bind(exif.iso)
+        .bind(exif.latitude)
+        .bind(exif.longitude)
+        .bind(exif.pixel_width)
+        .bind(exif.pixel_height)
+        .bind(&exif.date_taken_offset)
+        .bind(format.content_type())
+        .bind(photo.image_key())
+        .fetch_one(&self.pool)
+        .await?;
+
+        debug!("Added photo {id} with hash {hash}");
+
+        if self.classifier.is_some() {
+            let photo = sqlx::query_as::<_, DbPhoto>("SELECT * FROM photo WHERE id = ?")
+                .bind(id)
+                .fetch_one(&self.pool)
+                .await?;
+            // The photo is in the library either way, so only warn
+            if let Err(e) = self.queue_classify_job(&photo).await {
+                warn!("Failed to queue classification of photo {id}: {e}");
+            }
+        }
+
+        Ok(id)
+    }
+
+    /// Queue a job to suggest tags for a photo. The job's staged path is the websize
+    /// rendition, which is what the classifier looks at.
+    pub async fn queue_classify_job(&self, photo: &DbPhoto) -> Result<DbJob> {
+        let filename = Path::new(&photo.fullsize_path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("photo {}", photo.id));
+        let job = sqlx::query_as::<_, DbJob>(
+            "INSERT INTO job (kind, filename, staged_path, photo_id) VALUES ('classify', ?, ?, ?) RETURNING *",
+        )
+        .bind(&filename)
+        .bind(&photo.websize_path)
+        .bind(photo.id)
+        .fetch_one(&self.pool)
+        .await?;
+        self.job_notify.notify_waiters();
+        debug!("Queued classify job {} for photo {}", job.id, photo.id);
+        Ok(job)
+    }
+
+    /// Run the classifier on a photo and store what it finds for review. Labels the photo
+    /// is already tagged with, and ones rejected before, aren't suggested again.
+    /// Returns the number of new suggestions.
+    pub async fn run_classifier(&self, photo_id: i32) -> Result<usize> {
+        let Some(classifier) = &self.classifier else {
+            bail!("classifier not configured");
+        };
+        let Some(photo) = sqlx::query_as::<_, DbPhoto>("SELECT * FROM photo WHERE id = ?")
+            .bind(photo_id)
+            .fetch_optional(&self.pool)
+            .await?
+        else {
+            bail!("Photo {photo_id} no longer exists");
+        };
+
+        let classifications = classifier.classify(Path::new(&photo.websize_path)).await?;
+        let tags = self.photo_tags(Some(photo_id)).await?;
+        let tags = tags.get(&photo_id).cloned().unwrap_or_default();
+
+        let mut tx = self.pool.begin().await?;
+        let mut added = 0;
+        for classification in classifications {
+            let label = classification.label.trim();
+            if classification.confidence < MIN_SUGGESTION_CONFIDENCE
+                || check_tag_name(label).is_err()
+                || tags.iter().any(|tag| tag.eq_ignore_ascii_case(label))
+            {
+                continue;
+            }
+            let inserted = sqlx::query(
+                r#"
+                INSERT INTO tag_suggestion (photo_id, label, kind, confidence) VALUES (?, ?, ?, ?)
+                ON CONFLICT (photo_id, label) DO NOTHING
+                "#,
+            )
+            .bind(photo_id)
+            .bind(label)
+            .bind(classification.kind.as_str())
+            .bind(classification.confidence as f64)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            if inserted > 0 {
+                added += 1;
+                continue;
+            }
+            // A pending suggestion takes the newest confidence. Reviewed ones stay as they are
+            sqlx::query(
+                r#"
+                UPDATE tag_suggestion SET kind = ?, confidence = ?
+                WHERE photo_id = ? AND label = ? AND status = 'pending'
+                "#,
+            )
+            .bind(classification.kind.as_str())
+            .bind(classification.confidence as f64)
+            .bind(photo_id)
+            .bind(label)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        info!("Classified photo {photo_id}: {added} new suggestion(s)");
+        Ok(added)
+    }
+
+    /// Rebuild a photo's websize and thumbnail from its fullsize original and record the
+    /// new dimensions and orientation.
+    pub async fn regenerate_renditions(&self, photo: &DbPhoto) -> Result<()> {
+        let Some(processor) = &self.image_processor else {
+            bail!("image_processor not configured");
+        };
+
+        let fullsize = Path::new(&photo.fullsize_path);
+        let format = PhotoFormat::detect(fullsize)?;
+        if !processor.supports(format) {
+            bail!(
+                "{} photos are not supported by the {} image processor",
+                format.name(),
+                processor.name()
+            );
+        }
+
+        let websize = Path::new(&photo.websize_path);
+        let thumbnail = Path::new(&photo.thumbnail_path);
+        let (width, height) =
+            on_disk_photo::regenerate_renditions(fullsize, websize, thumbnail, processor.as_ref())
+                .await?;
+        let orientation = image_processor::read_orientation(fullsize);
+        let image_key = on_disk_photo::image_key(fullsize, websize, thumbnail)?;
+
+        sqlx::query(
+            "UPDATE photo SET width = ?, height = ?, orientation = ?, content_type = ?, image_key = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+        )
+        .bind(width)
+        .bind(height)
+        .bind(orientation)
+        .bind(format.content_type())
+        .bind(image_key)
+        .bind(photo.id)
+        .execute(&self.pool)
+        .await?;
+
+        debug!("Regenerated renditions for photo {}", photo.id);
+        Ok(())
+    }
+
+    /// Rebuild the renditions of every photo in the library, e.g. after a change to how they
+    /// are generated. Photos that fail are logged and skipped.
+    /// Returns the number of photos regenerated and the number that failed.
+    pub async fn regenerate_all_renditions(&self) -> Result<(usize, usize)> {
+        let photos = sqlx::query_as::<_, DbPhoto>("SELECT * FROM photo ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+        self.regenerate_each(&photos).await
+    }
+
+    /// Rebuild the renditions of the photos missing one, e.g. after restoring a backup of only
+    /// the originals. Returns the number of photos regenerated and the number that failed.
+    pub async fn regenerate_missing_renditions(&self) -> Result<(usize, usize)> {
+        let photos = sqlx::query_as::<_, DbPhoto>("SELECT * FROM photo ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+        let missing: Vec<DbPhoto> = photos
+            .into_iter()
+            .filter(|photo| {
+                !Path::new(&photo.websize_path).is_file()
+                    || !Path::new(&photo.thumbnail_path).is_file()
+            })
+            .collect();
+        if missing.is_empty() {
+            return Ok((0, 0));
+        }
+        self.regenerate_each(&missing).await
+    }
+
+    /// Record the content type and image key of photos imported before they were stored.
+    /// Photos whose files can't be read are logged and skipped.
+    /// Returns the number of photos updated.
+    pub async fn backfill_image_details(&self) -> Result<usize> {
+        let photos = sqlx::query_as::<_, DbPhoto>(
+            "SELECT * FROM photo WHERE content_type IS NULL OR image_key IS NULL ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut updated = 0;
+        for photo in photos {
+            let fullsize = PathBuf::from(&photo.fullsize_path);
+            let websize = PathBuf::from(&photo.websize_path);
+            let thumbnail = PathBuf::from(&photo.thumbnail_path);
+            let details = tokio::task::spawn_blocking(move || -> Result<(&str, String)> {
+                let format = PhotoFormat::detect(&fullsize)?;
+                let image_key = on_disk_photo::image_key(&fullsize, &websize, &thumbnail)?;
+                Ok((format.content_type(), image_key))
+            })
+            .await?;
+            let (content_type, image_key) = match details {
+                Ok(details) => details,
+                Err(e) => {
+                    warn!("Unable to read the files of photo {}: {e:#}", photo.id);
+                    continue;
+                }
+            };
+
+            sqlx::query("UPDATE photo SET content_type = ?, image_key = ? WHERE id = ?")
+                .bind(content_type)
+                .bind(image_key)
+                .bind(photo.id)
+                .execute(&self.pool)
+                .await?;
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    /// Regenerate the renditions of each photo, logging and skipping those that fail
+    async fn regenerate_each(&self, photos: &[DbPhoto]) -> Result<(usize, usize)> {
+        let (mut done, mut failed) = (0, 0);
+        for photo in photos {
+            match self.regenerate_renditions(photo).await {
+                Ok(()) => done += 1,
+                Err(e) => {
+                    error!(
+                        "Unable to regenerate renditions for photo {}: {e:#}",
+                        photo.id
+                    );
+                    failed += 1;
                 }
             }
         }
+        info!("Regenerated renditions for {done} photos, {failed} failed");
+        Ok((done, failed))
+    }
 
-        // Handle interval_seconds update
-        if let Some(interval) = req.interval_seconds {
-            if interval < 1 {
-                return APIResult::InternalError("Interval must be at least 1 second".to_string());
-            }
-            if let Err(e) = sqlx::query("UPDATE settings SET interval_seconds = ? WHERE id = 1")
-                .bind(interval)
-                .execute(&self.pool)
-                .await
-            {
-                return APIResult::InternalError(format!("Failed to update settings: {}", e));
+    /// Delete a photo's image files (best effort, don't fail if files don't exist).
+    fn remove_photo_files(&self, photo: &DbPhoto) {
+        let Some(photos_dir) = &self.photos_dir else {
+            return;
+        };
+        let paths = [
+            Path::new(&photo.fullsize_path),
+            Path::new(&photo.websize_path),
+            Path::new(&photo.thumbnail_path),
+        ];
+        for path in paths {
+            if path.starts_with(photos_dir) {
+                let _ = std::fs::remove_file(path);
             }
         }
-
-        APIResult::Ok(())
+        // Renditions live in a directory named after the hash. Remove it if it's now empty
+        if let Some(dir) = paths[0].parent()
+            && dir.starts_with(photos_dir)
+            && dir != photos_dir
+        {
+            let _ = std::fs::remove_dir(dir);
+        }
     }
-}
 
-// ─────────────────────────────────────────────────────────────────────────────
-// Non-macro handlers (raw Axum handlers for binary/multipart data)
-// ─────────────────────────────────────────────────────────────────────────────
+    /// Photos in the trash, most recently deleted first
+    async fn trashed_photos(&self) -> Result<Vec<DbPhoto>, sqlx::Error> {
+        sqlx::query_as::<_, DbPhoto>(
+            "SELECT * FROM photo WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC, id DESC",
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
 
-impl App {
-    /// Upload a new photo via multipart form data.
-    /// This is a raw Axum handler because it needs multipart form handling.
-    pub async fn upload_photo(
+    /// Delete photos in the trash for good, along with their album memberships, tags and files.
+    /// `id` picks one photo and `older_than_days` those in the trash that long; with neither
+    /// the whole trash goes. The photos are looked up and deleted in one transaction, so one
+    /// restored in the meantime is kept. Returns the number deleted.
+    async fn purge_photos(
         &self,
-        mut multipart: axum::extract::Multipart,
-    ) -> axum::response::Response {
-        use axum::body::Body;
-        use axum::http::{Response, StatusCode};
-
-        // Check if we have the required directories configured
-        let Some(photos_dir) = &self.photos_dir else {
-            return Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .header("Content-Type", "application/json")
-                .body(Body::from(
-                    r#"{"error": "Server not configured for uploads"}"#,
-                ))
-                .unwrap();
-        };
-        let Some(magick_exec) = &self.magick_exec else {
-            return Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .header("Content-Type", "application/json")
-                .body(Body::from(r#"{"error": "ImageMagick not available"}"#))
-                .unwrap();
-        };
-
-        // Extract the file from multipart form
-        let mut file_data: Option<(String, Vec<u8>)> = None;
-
-        while let Ok(Some(field)) = multipart.next_field().await {
-            let name = field.name().unwrap_or("").to_string();
+        id: Option<i32>,
+        older_than_days: Option<u32>,
+    ) -> Result<usize, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let ids = sqlx::query_scalar::<_, i32>(
+            r#"
+            SELECT id FROM photo
+            WHERE deleted_at IS NOT NULL
+            AND (? IS NULL OR id = ?)
+            AND (? IS NULL OR deleted_at <= datetime('now', '-' || ? || ' days'))
+            "#,
+        )
+        .bind(id)
+        .bind(id)
+        .bind(older_than_days)
+        .bind(older_than_days)
+        .fetch_all(&mut *tx)
+        .await?;
 
-            if name == "file" || name == "photo" {
-                let filename = field.file_name().unwrap_or("upload.jpg").to_string();
-                match field.bytes().await {
-                    Ok(bytes) => {
-                        file_data = Some((filename, bytes.to_vec()));
-                        break;
-                    }
-                    Err(e) => {
-                        return Response::builder()
-                            .status(StatusCode::BAD_REQUEST)
-                            .header("Content-Type", "application/json")
-                            .body(Body::from(format!(
-                                r#"{{"error": "Failed to read file: {}"}}"#,
-                                e
-                            )))
-                            .unwrap();
-                    }
-                }
+        let mut purged = Vec::new();
+        for id in ids {
+            if let Some(photo) = purge_photo_rows(&mut tx, id).await? {
+                purged.push(photo);
             }
         }
+        tx.commit().await?;
 
-        let Some((filename, bytes)) = file_data else {
-            return Response::builder()
-                .status(StatusCode::BAD_REQUEST)
-                .header("Content-Type", "application/json")
-                .body(Body::from(
-                    r#"{"error": "No file provided. Include a 'file' or 'photo' field."}"#,
-                ))
-                .unwrap();
-        };
-
-        // Validate file extension
-        let ext = std::path::Path::new(&filename)
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("")
-            .to_lowercase();
+        // Only touch the files once the database changes are committed
+        for photo in &purged {
+            self.remove_photo_files(photo);
+        }
+        Ok(purged.len())
+    }
 
-        if !matches!(ext.as_str(), "jpg" | "jpeg") {
-            return Response::builder()
-                .status(StatusCode::BAD_REQUEST)
-                .header("Content-Type", "application/json")
-                .body(Body::from(r#"{"error": "Only JPEG files are supported"}"#))
-                .unwrap();
+    /// Purge the photos that have been in the trash longer than the retention period.
+    /// Returns the number purged.
+    pub async fn purge_expired_trash(&self) -> Result<usize> {
+        let purged = self
+            .purge_photos(None, Some(self.trash_retention_days))
+            .await?;
+        if purged > 0 {
+            info!("Purged {} photo(s) from the trash", purged);
         }
+        Ok(purged)
+    }
 
-        // Save to a temp file
-        let temp_dir = match tempfile::tempdir() {
-            Ok(d) => d,
-            Err(e) => {
-                return Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .header("Content-Type", "application/json")
-                    .body(Body::from(format!(
-                        r#"{{"error": "Failed to create temp directory: {}"}}"#,
-                        e
-                    )))
-                    .unwrap();
-            }
-        };
-        let temp_path = temp_dir.path().join(&filename);
+    /// Tag names of one photo, or of every photo when `photo_id` is `None`
+    async fn photo_tags(&self, photo_id: Option<i32>) -> Result<PhotoTags, sqlx::Error> {
+        let rows = sqlx::query_as::<_, (i32, String)>(
+            r#"
+            SELECT pt.photo_id, t.name FROM photo_tag pt
+            JOIN tag t ON t.id = pt.tag_id
+            WHERE ? IS NULL OR pt.photo_id = ?
+            ORDER BY t.name
+            "#,
+        )
+        .bind(photo_id)
+        .bind(photo_id)
+        .fetch_all(&self.pool)
+        .await?;
 
-        if let Err(e) = std::fs::write(&temp_path, &bytes) {
-            return Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .header("Content-Type", "application/json")
-                .body(Body::from(format!(
-                    r#"{{"error": "Failed to write temp file: {}"}}"#,
-                    e
-                )))
-                .unwrap();
+        let mut tags = PhotoTags::new();
+        for (photo_id, name) in rows {
+            tags.entry(photo_id).or_default().push(name);
         }
+        Ok(tags)
+    }
 
-        // Import the photo using OnDiskPhoto
-        let photo = match OnDiskPhoto::import(&temp_path, photos_dir, magick_exec).await {
-            Ok(p) => p,
-            Err(e) => {
-                error!("{e}");
-                return Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .header("Content-Type", "application/json")
-                    .body(Body::from(format!(
-                        r#"{{"error": "Failed to process photo: {}"}}"#,
-                        e
-                    )))
-                    .unwrap();
-            }
-        };
-
-        // Insert into database
-        let metadata = photo.metadata();
-        let result = sqlx::query_scalar::<_, i32>(
+    /// Tag names of the photos in `photo_ids`, for listing a page of photos without loading
+    /// the tags of the whole library
+    async fn tags_of_photos(&self, photo_ids: &[i32]) -> Result<PhotoTags, sqlx::Error> {
+        let rows = sqlx::query_as::<_, (i32, String)>(
             r#"
-            INSERT INTO photo (hash, title, artist, copyright, date_taken, fullsize_path, websize_path, thumbnail_path)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
-            RETURNING id
+            SELECT pt.photo_id, t.name FROM photo_tag pt
+            JOIN tag t ON t.id = pt.tag_id
+            WHERE pt.photo_id IN (SELECT value FROM json_each(?))
+            ORDER BY t.name
             "#,
         )
-        .bind(photo.hash())
-        .bind(metadata.title())
-        .bind(metadata.artist())
-        .bind(metadata.copyright())
-        .bind(metadata.date_taken())
-        .bind(photo.fullsize_path().display().to_string())
-        .bind(photo.websize_path().display().to_string())
-        .bind(photo.thumbnail_path().display().to_string())
-        .fetch_one(&self.pool)
-        .await;
-
-        match result {
-            Ok(id) => {
-                let response_photo = Photo {
-                    id: PhotoID::from(id),
-                    url: format!("/api/images/{}", id),
-                    title: Some(metadata.title().clone()),
-                    notes: None,
-                    artist: metadata.artist().cloned(),
-                    copyright: metadata.copyright().cloned(),
-                    date_taken: metadata.date_taken().cloned(),
-                    mat_preset: "classic".to_string(),
-                };
+        .bind(serde_json::json!(photo_ids).to_string())
+        .fetch_all(&self.pool)
+        .await?;
 
-                let json = serde_json::to_string(&response_photo).unwrap();
-                Response::builder()
-                    .status(StatusCode::CREATED)
-                    .header("Content-Type", "application/json")
-                    .body(Body::from(json))
-                    .unwrap()
-            }
-            Err(e) => Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .header("Content-Type", "application/json")
-                .body(Body::from(format!(
-                    r#"{{"error": "Failed to save photo: {}"}}"#,
-                    e
-                )))
-                .unwrap(),
+        let mut tags = PhotoTags::new();
+        for (photo_id, name) in rows {
+            tags.entry(photo_id).or_default().push(name);
         }
+        Ok(tags)
     }
 
-    /// Serve the websize image for a photo.
-    /// This is a raw Axum handler (not using the api macro) because it returns binary data.
-    pub async fn serve_image(&self, id: i32) -> axum::response::Response {
-        use axum::body::Body;
-        use axum::http::{Response, StatusCode, header};
+    async fn find_tag(&self, id: i32) -> Result<Option<DbTag>, sqlx::Error> {
+        sqlx::query_as::<_, DbTag>(&format!("{SELECT_TAGS} WHERE t.id = ? GROUP BY t.id"))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
 
-        // Look up the photo
-        let photo = match sqlx::query_as::<_, DbPhoto>("SELECT * FROM photo WHERE id = ?")
+    /// An album whose photos are added by hand, or the response to send if there isn't one
+    async fn find_manual_album<T>(&self, id: i32) -> Result<DbAlbum, APIResult<T>> {
+        match sqlx::query_as::<_, DbAlbum>("SELECT * FROM album WHERE id = ?")
             .bind(id)
             .fetch_optional(&self.pool)
             .await
         {
-            Ok(Some(p)) => p,
-            Ok(None) => {
-                return Response::builder()
-                    .status(StatusCode::NOT_FOUND)
-                    .body(Body::from("Photo not found"))
-                    .unwrap();
-            }
-            Err(e) => {
-                return Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .body(Body::from(format!("Database error: {}", e)))
-                    .unwrap();
-            }
-        };
+            Ok(Some(album)) if picks_own_photos(&album) => Err(APIResult::BadRequest(format!(
+                "Album {} picks its own photos",
+                id
+            ))),
+            Ok(Some(album)) => Ok(album),
+            Ok(None) => Err(APIResult::NotFound(format!("Album {} not found", id))),
+            Err(e) => Err(APIResult::InternalError(format!("Database error: {}", e))),
+        }
+    }
 
-        // Read the websize image file
-        let path = std::path::Path::new(&photo.websize_path);
-        match tokio::fs::read(path).await {
-            Ok(bytes) => {
-                // Determine content type from extension
-                let content_type = match path.extension().and_then(|e| e.to_str()) {
-                    Some("jpg") | Some("jpeg") => "image/jpeg",
-                    Some("png") => "image/png",
-                    Some("gif") => "image/gif",
-                    Some("webp") => "image/webp",
-                    _ => "application/octet-stream",
-                };
+    /// A suggestion that hasn't been reviewed yet, or the response to send if there isn't one
+    async fn find_pending_suggestion(&self, id: i32) -> Result<DbTagSuggestion, APIResult<()>> {
+        match sqlx::query_as::<_, DbTagSuggestion>("SELECT * FROM tag_suggestion WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(Some(s)) if s.status == "pending" => Ok(s),
+            Ok(Some(s)) => Err(APIResult::BadRequest(format!(
+                "Suggestion {} was already {}",
+                id, s.status
+            ))),
+            Ok(None) => Err(APIResult::NotFound(format!("Suggestion {} not found", id))),
+            Err(e) => Err(APIResult::InternalError(format!("Database error: {}", e))),
+        }
+    }
 
-                Response::builder()
-                    .status(StatusCode::OK)
-                    .header(header::CONTENT_TYPE, content_type)
-                    .header(header::CACHE_CONTROL, "public, max-age=86400") // Cache for 1 day
-                    .body(Body::from(bytes))
-                    .unwrap()
+    /// Point smart album rules that mention any of `old_names` at `new_name` instead, so
+    /// renaming or merging tags doesn't quietly empty the albums
+    async fn retag_smart_rules(
+        &self,
+        tx: &mut sqlx::SqliteConnection,
+        old_names: &[String],
+        new_name: &str,
+    ) -> Result<(), sqlx::Error> {
+        let albums =
+            sqlx::query_as::<_, DbAlbum>("SELECT * FROM album WHERE smart_rule IS NOT NULL")
+                .fetch_all(&mut *tx)
+                .await?;
+        for album in albums {
+            let Some(mut rule) = smart_rule(&album) else {
+                continue;
+            };
+            if !rule
+                .tags
+                .iter()
+                .any(|t| old_names.iter().any(|old| old.eq_ignore_ascii_case(t)))
+            {
+                continue;
             }
-            Err(e) => {
-                warn!("Failed to read image file {}: {}", path.display(), e);
-                Response::builder()
-                    .status(StatusCode::NOT_FOUND)
-                    .body(Body::from("Image file not found"))
-                    .unwrap()
+            rule.tags
+                .retain(|t| !old_names.iter().any(|old| old.eq_ignore_ascii_case(t)));
+            if !rule.tags.iter().any(|t| t.eq_ignore_ascii_case(new_name)) {
+                rule.tags.push(new_name.to_string());
             }
+            sqlx::query("UPDATE album SET smart_rule = ? WHERE id = ?")
+                .bind(serde_json::to_string(&rule).expect("SmartRule serializes"))
+                .bind(album.id)
+                .execute(&mut *tx)
+                .await?;
         }
+        Ok(())
     }
 
-    /// Internal method to import a photo from disk
-    #[allow(dead_code)]
-    async fn import_photo(&self, photo_path: &Path) -> Result<()> {
-        info!("Processing {}", photo_path.display());
+    /// Names of the smart albums whose rules need the tag `name`
+    async fn smart_albums_using(
+        &self,
+        tx: &mut sqlx::SqliteConnection,
+        name: &str,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let albums = sqlx::query_as::<_, DbAlbum>(
+            "SELECT * FROM album WHERE smart_rule IS NOT NULL ORDER BY name",
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        Ok(albums
+            .into_iter()
+            .filter(|album| {
+                smart_rule(album)
+                    .is_some_and(|rule| rule.tags.iter().any(|t| t.eq_ignore_ascii_case(name)))
+            })
+            .map(|album| album.name)
+            .collect())
+    }
 
-        let photos_dir = self.photos_dir.as_ref().expect("photos_dir not configured");
-        let magick_exec = self
-            .magick_exec
-            .as_ref()
-            .expect("magick_exec not configured");
-        let photo = OnDiskPhoto::import(photo_path, photos_dir, magick_exec).await?;
-        let fullsize = photo.fullsize_path();
-        let websize = photo.websize_path();
-        let thumbnail = photo.thumbnail_path();
+    /// Photos in an album, in the order they are shown. Virtual albums are worked out from
+    /// `date_taken` as of today, oldest first. Smart albums are in the order photos were
+    /// taken, undated photos last.
+    async fn album_photos(&self, album: &DbAlbum) -> Result<Vec<DbPhoto>, sqlx::Error> {
+        if let Some(rule) = smart_rule(album) {
+            let now = chrono::Utc::now().naive_utc();
+            // Only photos with every tag of the rule can match, so only their tags are needed
+            let mut names: Vec<&str> = Vec::new();
+            for tag in &rule.tags {
+                if !names.iter().any(|n| n.eq_ignore_ascii_case(tag)) {
+                    names.push(tag);
+                }
+            }
+            let mut sql =
+                QueryBuilder::<Sqlite>::new("SELECT * FROM photo WHERE deleted_at IS NULL");
+            if !names.is_empty() {
+                sql.push(
+                    " AND id IN (SELECT pt.photo_id FROM photo_tag pt JOIN tag t ON t.id = pt.tag_id WHERE t.name IN (",
+                );
+                let mut names_list = sql.separated(", ");
+                for name in &names {
+                    names_list.push_bind(*name);
+                }
+                sql.push(") GROUP BY pt.photo_id HAVING COUNT(*) >= ")
+                    .push_bind(names.len() as i64)
+                    .push(")");
+            }
+            sql.push(" ORDER BY date_taken IS NULL, date_taken, id");
+            let photos = sql
+                .build_query_as::<DbPhoto>()
+                .fetch_all(&self.pool)
+                .await?;
+            let ids: Vec<i32> = photos.iter().map(|p| p.id).collect();
+            let tags = self.tags_of_photos(&ids).await?;
+            return Ok(photos
+                .into_iter()
+                .filter(|p| rule.matches(&db_photo_to_photo(p, &tags), now))
+                .collect());
+        }
 
-        let metadata = photo.metadata();
-        let title = metadata.title();
-        let artist = metadata.artist();
-        let copyright = metadata.copyright();
-        let date_taken = metadata.date_taken();
-        let hash = photo.hash();
+        if let Some(virtual_album) = virtual_album(album) {
+            let today = chrono::Local::now().date_naive();
+            let photos = sqlx::query_as::<_, DbPhoto>(
+                r#"
+                SELECT * FROM photo WHERE date_taken IS NOT NULL AND deleted_at IS NULL
+                ORDER BY date_taken, id
+                "#,
+            )
+            .fetch_all(&self.pool)
+            .await?;
+            return Ok(photos
+                .into_iter()
+                .filter(|p| {
+                    p.date_taken
+                        .is_some_and(|t| virtual_album.contains(t, today))
+                })
+                .collect());
+        }
 
-        sqlx::query(
+        sqlx::query_as::<_, DbPhoto>(
             r#"
-            INSERT INTO photo (hash, title, artist, copyright, date_taken, fullsize_path, websize_path, thumbnail_path)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            SELECT p.* FROM photo p
+            JOIN album_photo ap ON p.id = ap.photo_id
+            WHERE ap.album_id = ? AND p.deleted_at IS NULL
+            ORDER BY ap.position
             "#,
         )
-        .bind(hash)
-        .bind(title)
-        .bind(artist)
-        .bind(copyright)
-        .bind(date_taken)
-        .bind(fullsize.display().to_string())
-        .bind(websize.display().to_string())
-        .bind(thumbnail.display().to_string())
-        .execute(&self.pool)
+        .bind(album.id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Check a schedule before saving it. Returns the error response if it is invalid.
+    async fn check_schedule_request<T>(&self, req: &ScheduleRequest) -> Option<APIResult<T>> {
+        if req.name.trim().is_empty() {
+            return Some(APIResult::BadRequest(
+                "Schedule name cannot be empty".to_string(),
+            ));
+        }
+        if let Some(month) = req.months.iter().find(|m| !(1..=12).contains(*m)) {
+            return Some(APIResult::BadRequest(format!("Invalid month: {}", month)));
+        }
+        if req.start_time.is_some() && req.start_time == req.end_time {
+            return Some(APIResult::BadRequest(
+                "Schedule start and end times cannot be the same".to_string(),
+            ));
+        }
+
+        let mut references = vec![];
+        if let ScheduleAction::Album { album } = req.action {
+            references.push(("album", "Album", album.0));
+        }
+        if let Some(display) = req.display {
+            references.push(("display", "Display", display.0));
+        }
+        for (table, name, id) in references {
+            let exists =
+                sqlx::query_scalar::<_, i32>(&format!("SELECT 1 FROM {table} WHERE id = ?"))
+                    .bind(id)
+                    .fetch_optional(&self.pool)
+                    .await;
+            match exists {
+                Ok(Some(_)) => {}
+                Ok(None) => return Some(APIResult::NotFound(format!("{name} {id} not found"))),
+                Err(e) => return Some(APIResult::InternalError(format!("Database error: {}", e))),
+            }
+        }
+        None
+    }
+
+    /// When each of `photos` was last shown, and the show count that's measured against. Each
+    /// display has its own history, `None` is the library-wide one.
+    async fn rotation_history(
+        &self,
+        photos: &[DbPhoto],
+        display: Option<&DbDisplay>,
+        settings: &DbSettings,
+    ) -> Result<(Vec<Option<i64>>, i64)> {
+        let Some(display) = display else {
+            let last_shown = photos.iter().map(|p| p.last_shown).collect();
+            return Ok((last_shown, settings.show_count));
+        };
+        let shown: HashMap<i32, i64> = sqlx::query_as::<_, (i32, i64)>(
+            "SELECT photo_id, last_shown FROM display_photo WHERE display_id = ?",
+        )
+        .bind(display.id)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .collect();
+        let last_shown = photos.iter().map(|p| shown.get(&p.id).copied()).collect();
+        Ok((last_shown, display.show_count))
+    }
+
+    /// Add a photo being shown to the library-wide history, and to the history of
+    /// `display_id` if it's being shown on a display.
+    async fn record_shown(&self, photo_id: i32, display_id: Option<i32>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let show_count: i64 = sqlx::query_scalar(
+            "UPDATE settings SET show_count = show_count + 1 WHERE id = 1 RETURNING show_count",
+        )
+        .fetch_one(&mut *tx)
         .await?;
+        sqlx::query("UPDATE photo SET last_shown = ? WHERE id = ?")
+            .bind(show_count)
+            .bind(photo_id)
+            .execute(&mut *tx)
+            .await?;
 
-        debug!("Added photo with hash {}", hash);
+        if let Some(display_id) = display_id {
+            let show_count: i64 = sqlx::query_scalar(
+                "UPDATE display SET show_count = show_count + 1 WHERE id = ? RETURNING show_count",
+            )
+            .bind(display_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            sqlx::query(
+                r#"
+                INSERT INTO display_photo (display_id, photo_id, last_shown) VALUES (?, ?, ?)
+                ON CONFLICT (display_id, photo_id) DO UPDATE SET last_shown = excluded.last_shown
+                "#,
+            )
+            .bind(display_id)
+            .bind(photo_id)
+            .bind(show_count)
+            .execute(&mut *tx)
+            .await?;
+        }
 
+        tx.commit().await?;
         Ok(())
     }
 
-    #[allow(dead_code)]
+    /// Take the next photo of the current shuffle cycle, shuffling a new cycle once every
+    /// photo has been shown. The cycle is kept in the database so it carries on after a
+    /// restart. Each display has its own cycle, `None` is the shared one. `last_shown` is the
+    /// rotation history of each photo. Returns an index into `photos`.
+    async fn next_in_shuffle(
+        &self,
+        photos: &[DbPhoto],
+        last_shown: &[Option<i64>],
+        display_id: Option<i32>,
+    ) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+
+        let queue = sqlx::query_as::<_, (i64, i32)>(
+            "SELECT position, photo_id FROM shuffle_queue WHERE display_id IS ? ORDER BY position",
+        )
+        .bind(display_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // Photos removed since the cycle was shuffled are skipped
+        let next = queue.iter().find_map(|(position, photo_id)| {
+            let index = photos.iter().position(|p| p.id == *photo_id)?;
+            Some((*position, index))
+        });
+
+        let index = match next {
+            Some((position, index)) => {
+                sqlx::query("DELETE FROM shuffle_queue WHERE display_id IS ? AND position <= ?")
+                    .bind(display_id)
+                    .bind(position)
+                    .execute(&mut *tx)
+                    .await?;
+                index
+            }
+            None => {
+                let ids: Vec<i32> = photos.iter().map(|p| p.id).collect();
+                let previous = photos
+                    .iter()
+                    .zip(last_shown)
+                    .filter_map(|(p, shown)| Some(((*shown)?, p.id)))
+                    .max()
+                    .map(|(_, id)| id);
+                let cycle = rotation::shuffle_cycle(&ids, previous, &mut rand::rng());
+
+                sqlx::query("DELETE FROM shuffle_queue WHERE display_id IS ?")
+                    .bind(display_id)
+                    .execute(&mut *tx)
+                    .await?;
+                for photo_id in &cycle[1..] {
+                    sqlx::query("INSERT INTO shuffle_queue (photo_id, display_id) VALUES (?, ?)")
+                        .bind(photo_id)
+                        .bind(display_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                photos.iter().position(|p| p.id == cycle[0]).unwrap()
+            }
+        };
+
+        tx.commit().await?;
+        Ok(index)
+    }
+
+    /// Look for a photo already in the library that matches `hash`.
+    ///
+    /// Exact matches are found through the `photo.hash` index. Otherwise the closest photo
+    /// within the duplicate threshold is returned.
+    pub async fn find_duplicate(&self, hash: &str) -> Result<Option<DuplicatePhoto>> {
+        // Photos in the trash still count, as restoring one would otherwise clash
+        let existing = sqlx::query_as::<_, (i32, bool)>(
+            "SELECT id, deleted_at IS NOT NULL FROM photo WHERE hash = ?",
+        )
+        .bind(hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        if let Some((existing, in_trash)) = existing {
+            return Ok(Some(DuplicatePhoto {
+                existing,
+                distance: 0,
+                in_trash,
+            }));
+        }
+
+        if self.duplicate_threshold == 0 {
+            return Ok(None);
+        }
+
+        let photos = sqlx::query_as::<_, (i32, String, bool)>(
+            "SELECT id, hash, deleted_at IS NOT NULL FROM photo",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let closest = photos
+            .iter()
+            .filter_map(|(id, other, in_trash)| {
+                on_disk_photo::hash_distance(hash, other).map(|distance| DuplicatePhoto {
+                    existing: *id,
+                    distance,
+                    in_trash: *in_trash,
+                })
+            })
+            .filter(|duplicate| duplicate.distance <= self.duplicate_threshold)
+            .min_by_key(|duplicate| duplicate.distance);
+
+        Ok(closest)
+    }
+
+    /// Import every file currently in the inbox.
+    ///
+    /// A file that fails to import is moved to the fails dir along with a sidecar error
+    /// report, and processing carries on with the next file. Only errors reading the inbox
+    /// itself or moving a bad file are returned.
     pub async fn process_inbox(&self) -> Result<()> {
-        let inbox_dir = self.inbox_dir.as_ref().expect("inbox_dir not configured");
-        let fails_dir = self.fails_dir.as_ref().expect("fails_dir not configured");
+        let files = self.inbox_files()?;
+        self.process_inbox_files(&files).await
+    }
+
+    /// Files in the inbox waiting to be imported
+    pub fn inbox_files(&self) -> Result<Vec<PathBuf>> {
+        let Some(inbox_dir) = &self.inbox_dir else {
+            bail!("inbox_dir not configured");
+        };
 
+        let mut files = Vec::new();
         for entry in fs::read_dir(inbox_dir)? {
-            let entry = entry?;
-            let path = entry.path();
+            let path = entry?.path();
+            if path.is_file() && !is_hidden(&path) {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
 
-            if is_photo(&path) {
-                if let Err(e) = self.import_photo(&path).await {
-                    move_bad_file(&path, fails_dir)?;
-                    bail!("Failed to process {}: {e}", path.display());
-                };
-            } else {
+    /// Import some of the files in the inbox, moving those that fail to the fails dir.
+    pub async fn process_inbox_files(&self, files: &[PathBuf]) -> Result<()> {
+        let Some(fails_dir) = &self.fails_dir else {
+            bail!("fails_dir not configured");
+        };
+
+        for path in files {
+            // Imported or moved away by an earlier scan
+            if !path.is_file() {
+                continue;
+            }
+
+            if let Err(e) = PhotoFormat::detect(path) {
                 warn!(
-                    "Unable to process file {} (not a jpg). Moving to bad dir",
+                    "Unable to process file {} ({e}). Moving to bad dir",
                     path.display()
                 );
-                move_bad_file(&path, fails_dir)?;
+                move_bad_file(path, fails_dir, &format!("{e:#}"))?;
+                continue;
+            }
+
+            if let Err(e) = self.import_photo(path).await {
+                error!("Failed to process {}: {e}", path.display());
+                move_bad_file(path, fails_dir, &format!("{e:#}"))?;
             }
         }
 
         Ok(())
     }
+
+    /// The directory watched for new photos, if configured.
+    pub fn inbox_dir(&self) -> Option<&Path> {
+        self.inbox_dir.as_deref()
+    }
 }
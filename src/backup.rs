@@ -0,0 +1,332 @@
+//! Backups of the whole library as a single tar archive, and restoring them.
+//!
+//! An archive holds everything under the data dir that can't be rebuilt:
+//!
+//! - `db.sqlite`, a consistent snapshot taken with `VACUUM INTO` while the server keeps
+//!   running. Photo paths in it are relative to the data dir so it can be restored anywhere
+//! - `photos/<hash>/`, each photo's original and, unless only originals were asked for, its
+//!   websize and thumbnail
+//! - `manifest.json`, written last so a truncated archive is caught on restore
+//!
+//! Archives are only restored by a build with the same database version. Renditions left out
+//! of the archive are rebuilt from the originals afterwards.
+
+use crate::app::DB_FILE_NAME;
+use anyhow::{Context, Result, bail};
+use chrono::{NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use tracing::{info, warn};
+
+/// Version of the archive layout. Bump it whenever the layout changes
+pub const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const PHOTOS_DIR_NAME: &str = "photos";
+
+/// Columns of the photo table holding paths to files under the data dir
+const PATH_COLUMNS: [&str; 3] = ["fullsize_path", "websize_path", "thumbnail_path"];
+
+/// What's in an archive and what made it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    /// Version of pictureframe that made the backup
+    pub app_version: String,
+    /// Latest database migration applied when the backup was taken
+    pub schema_version: i64,
+    pub created_at: NaiveDateTime,
+    /// Number of photos in the library, including those in the trash
+    pub photos: usize,
+    /// Only the originals were archived
+    pub originals_only: bool,
+}
+
+/// Latest database migration this build knows about
+pub fn schema_version() -> i64 {
+    sqlx::migrate!("./migrations")
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or_default()
+}
+
+/// Open the SQLite database at `path`
+async fn connect(path: &Path) -> Result<SqliteConnection> {
+    SqliteConnection::connect(&format!("sqlite:{}", path.display()))
+        .await
+        .with_context(|| format!("Unable to open {}", path.display()))
+}
+
+/// Latest migration recorded in a database
+async fn applied_schema_version(conn: &mut SqliteConnection) -> Result<i64> {
+    let version =
+        sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations WHERE success")
+            .fetch_one(conn)
+            .await?;
+    Ok(version)
+}
+
+/// `dir` with a trailing separator, as photo paths under it start
+fn path_prefix(dir: &Path) -> String {
+    dir.join("").to_string_lossy().into_owned()
+}
+
+/// A snapshot of the library, ready to be written out as an archive.
+#[derive(Debug)]
+pub struct Snapshot {
+    /// Holds the database snapshot. Removed once the snapshot is dropped
+    tmp_dir: TempDir,
+    data_dir: PathBuf,
+    /// Files to archive, relative to `data_dir`
+    files: Vec<PathBuf>,
+    manifest: Manifest,
+}
+
+impl Snapshot {
+    /// Snapshot the database behind `pool` into a temporary directory under `tmp_dir` and list
+    /// the photo files to go with it.
+    pub async fn take(
+        pool: &SqlitePool,
+        data_dir: &Path,
+        tmp_dir: &Path,
+        originals_only: bool,
+    ) -> Result<Self> {
+        let snapshot_dir = tempfile::tempdir_in(tmp_dir)?;
+        let db_path = snapshot_dir.path().join(DB_FILE_NAME);
+        sqlx::query("VACUUM INTO ?")
+            .bind(db_path.to_string_lossy())
+            .execute(pool)
+            .await
+            .context("Unable to snapshot the database")?;
+
+        let mut conn = connect(&db_path).await?;
+        let prefix = path_prefix(data_dir);
+        for column in PATH_COLUMNS {
+            sqlx::query(&format!(
+                "UPDATE photo SET {column} = substr({column}, length(?) + 1) WHERE substr({column}, 1, length(?)) = ?"
+            ))
+            .bind(&prefix)
+            .bind(&prefix)
+            .bind(&prefix)
+            .execute(&mut conn)
+            .await?;
+        }
+        let photos = sqlx::query_as::<_, (String, String, String)>(
+            "SELECT fullsize_path, websize_path, thumbnail_path FROM photo ORDER BY id",
+        )
+        .fetch_all(&mut conn)
+        .await?;
+        let schema_version = applied_schema_version(&mut conn).await?;
+        conn.close().await?;
+
+        let mut files = Vec::new();
+        for (fullsize, websize, thumbnail) in &photos {
+            let paths = if originals_only {
+                vec![fullsize]
+            } else {
+                vec![fullsize, websize, thumbnail]
+            };
+            // Files outside the data dir kept their absolute paths and can't come along
+            files.extend(
+                paths
+                    .into_iter()
+                    .map(PathBuf::from)
+                    .filter(|path| path.is_relative() && path.starts_with(PHOTOS_DIR_NAME)),
+            );
+        }
+
+        Ok(Self {
+            tmp_dir: snapshot_dir,
+            data_dir: data_dir.to_path_buf(),
+            files,
+            manifest: Manifest {
+                format_version: FORMAT_VERSION,
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                schema_version,
+                created_at: Utc::now().naive_utc(),
+                photos: photos.len(),
+                originals_only,
+            },
+        })
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// Write the archive to `out`. This blocks, so run it on a blocking thread.
+    pub fn write_archive(self, out: impl Write) -> Result<()> {
+        let mut archive = tar::Builder::new(out);
+        archive.append_path_with_name(self.tmp_dir.path().join(DB_FILE_NAME), DB_FILE_NAME)?;
+
+        for file in &self.files {
+            let path = self.data_dir.join(file);
+            match File::open(&path) {
+                Ok(mut f) => archive.append_file(file, &mut f)?,
+                // Purged since the snapshot was taken
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    warn!("{} disappeared during the backup", path.display())
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Unable to read {}", path.display()));
+                }
+            }
+        }
+
+        let manifest = serde_json::to_vec_pretty(&self.manifest)?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(self.manifest.created_at.and_utc().timestamp() as u64);
+        header.set_cksum();
+        archive.append_data(&mut header, MANIFEST_FILE_NAME, manifest.as_slice())?;
+
+        archive.into_inner()?.flush()?;
+        Ok(())
+    }
+}
+
+/// Unpack the archive at `archive` into `dest`, refusing anything that isn't part of a backup.
+fn unpack(archive: &Path, dest: &Path) -> Result<()> {
+    let file =
+        File::open(archive).with_context(|| format!("Unable to open {}", archive.display()))?;
+    let mut archive = tar::Archive::new(file);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if path != Path::new(MANIFEST_FILE_NAME)
+            && path != Path::new(DB_FILE_NAME)
+            && !path.starts_with(PHOTOS_DIR_NAME)
+        {
+            bail!("Unexpected file {} in the backup", path.display());
+        }
+        if !entry.unpack_in(dest)? {
+            bail!("Refusing to unpack {} outside the data dir", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Restore the backup at `archive` into `data_dir`, replacing the library there only if
+/// `replace` is set. Nothing in `data_dir` is touched until the whole archive has been checked.
+/// Renditions left out of the archive still need rebuilding afterwards, see
+/// [`App::regenerate_missing_renditions`](crate::App::regenerate_missing_renditions).
+pub async fn restore(archive: &Path, data_dir: &Path, replace: bool) -> Result<Manifest> {
+    let db_path = data_dir.join(DB_FILE_NAME);
+    let photos_dir = data_dir.join(PHOTOS_DIR_NAME);
+    if db_path.exists() && !replace {
+        bail!(
+            "{} already has a library. Restore into an empty data dir, or replace it with --replace",
+            data_dir.display()
+        );
+    }
+
+    fs::create_dir_all(data_dir)?;
+    let staging = tempfile::tempdir_in(data_dir)?;
+    let (archive_path, staging_path) = (archive.to_path_buf(), staging.path().to_path_buf());
+    tokio::task::spawn_blocking(move || unpack(&archive_path, &staging_path))
+        .await?
+        .with_context(|| format!("{} is not a valid backup", archive.display()))?;
+
+    let Ok(manifest) = fs::read(staging.path().join(MANIFEST_FILE_NAME)) else {
+        bail!(
+            "{} is not a complete backup: it has no manifest",
+            archive.display()
+        );
+    };
+    let manifest: Manifest = serde_json::from_slice(&manifest)
+        .with_context(|| format!("{} has an invalid manifest", archive.display()))?;
+    if manifest.format_version != FORMAT_VERSION {
+        bail!(
+            "Backup format {} is not supported, expected {FORMAT_VERSION}. Restore it with pictureframe {}",
+            manifest.format_version,
+            manifest.app_version
+        );
+    }
+    if manifest.schema_version != schema_version() {
+        bail!(
+            "Backup was made by pictureframe {} with database version {}, this is pictureframe {} with database version {}. Restore it with pictureframe {}",
+            manifest.app_version,
+            manifest.schema_version,
+            env!("CARGO_PKG_VERSION"),
+            schema_version(),
+            manifest.app_version
+        );
+    }
+
+    let staged_db = staging.path().join(DB_FILE_NAME);
+    if !staged_db.is_file() {
+        bail!("{} has no database", archive.display());
+    }
+    let mut conn = connect(&staged_db).await?;
+    let integrity: String = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_one(&mut conn)
+        .await?;
+    if integrity != "ok" {
+        bail!(
+            "The database in {} is corrupt: {integrity}",
+            archive.display()
+        );
+    }
+    if applied_schema_version(&mut conn).await? != manifest.schema_version {
+        bail!(
+            "The database in {} doesn't match its manifest",
+            archive.display()
+        );
+    }
+
+    // Every original must have made it into the archive. Renditions can be rebuilt
+    let originals: Vec<String> = sqlx::query_scalar("SELECT fullsize_path FROM photo")
+        .fetch_all(&mut conn)
+        .await?;
+    let missing = originals
+        .iter()
+        .map(Path::new)
+        .filter(|path| path.is_relative() && !staging.path().join(path).is_file())
+        .count();
+    if missing > 0 {
+        bail!(
+            "{} is missing the originals of {missing} photo(s)",
+            archive.display()
+        );
+    }
+
+    // Point the photo paths at their new home
+    let prefix = path_prefix(data_dir);
+    for column in PATH_COLUMNS {
+        sqlx::query(&format!(
+            "UPDATE photo SET {column} = ? || {column} WHERE {column} LIKE '{PHOTOS_DIR_NAME}/%'"
+        ))
+        .bind(&prefix)
+        .execute(&mut conn)
+        .await?;
+    }
+    conn.close().await?;
+
+    // Swap the restored library in
+    for suffix in ["", "-wal", "-shm"] {
+        let path = data_dir.join(format!("{DB_FILE_NAME}{suffix}"));
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+    }
+    if photos_dir.exists() {
+        fs::remove_dir_all(&photos_dir)?;
+    }
+    fs::rename(&staged_db, &db_path)?;
+    let staged_photos = staging.path().join(PHOTOS_DIR_NAME);
+    if staged_photos.is_dir() {
+        fs::rename(&staged_photos, &photos_dir)?;
+    }
+
+    info!(
+        "Restored {} photo(s) from a backup taken {}",
+        manifest.photos, manifest.created_at
+    );
+    Ok(manifest)
+}
@@ -0,0 +1,101 @@
+//! Classifiers that look at a photo and suggest tags for it.
+//!
+//! Classification is an optional step after import. Everything runs on this machine: the
+//! classifier only ever sees the websize rendition on disk, and its suggestions are stored for
+//! review in the admin page rather than being applied straight away.
+
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use pictureframe_common::SuggestionKind;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tracing::error;
+
+/// One thing a classifier recognised in a photo
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Classification {
+    pub label: String,
+    #[serde(default)]
+    pub kind: SuggestionKind,
+    /// From 0 to 1
+    pub confidence: f32,
+}
+
+impl Classification {
+    pub fn new(label: &str, kind: SuggestionKind, confidence: f32) -> Self {
+        Self {
+            label: label.to_string(),
+            kind,
+            confidence,
+        }
+    }
+}
+
+#[async_trait]
+pub trait Classifier: std::fmt::Debug + Send + Sync {
+    /// Short name for logs and error messages
+    fn name(&self) -> &'static str;
+
+    /// Suggest labels for the JPEG at `websize`.
+    async fn classify(&self, websize: &Path) -> Result<Vec<Classification>>;
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Command
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Runs a local program, e.g. a script wrapping an offline vision model. The program is
+/// called with the path of the websize JPEG and prints a JSON array of
+/// `{"label": "beach", "kind": "scene", "confidence": 0.93}` to stdout. `kind` is one of
+/// `tag`, `face` or `scene` and defaults to `tag`.
+#[derive(Debug)]
+pub struct CommandClassifier {
+    program: PathBuf,
+}
+
+impl CommandClassifier {
+    /// Find `program` on the PATH, or use it as is if it's a path to an executable.
+    pub fn new(program: &Path) -> Result<Self> {
+        let Ok(program) = which::which(program) else {
+            bail!("{}: command not found", program.display())
+        };
+        Ok(Self { program })
+    }
+}
+
+#[async_trait]
+impl Classifier for CommandClassifier {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    async fn classify(&self, websize: &Path) -> Result<Vec<Classification>> {
+        let output = Command::new(&self.program).arg(websize).output().await?;
+        let status = output.status;
+
+        if !status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            error!("Classifier failed. Stderr:\n{stderr}");
+            bail!("Classifier {} exited with {status}", self.program.display());
+        }
+
+        let classifications: Vec<Classification> = serde_json::from_slice(&output.stdout)
+            .with_context(|| {
+                format!(
+                    "Classifier {} printed invalid output",
+                    self.program.display()
+                )
+            })?;
+        for classification in &classifications {
+            if !(0.0..=1.0).contains(&classification.confidence) {
+                bail!(
+                    "Classifier gave {} a confidence of {}, expected 0 to 1",
+                    classification.label,
+                    classification.confidence
+                );
+            }
+        }
+        Ok(classifications)
+    }
+}
@@ -0,0 +1,135 @@
+//! Albums exported as a zip of their originals, for sharing with people who don't have a frame.
+//!
+//! The zip holds each photo's fullsize original in album order, named after its position and
+//! its title or the date it was taken, e.g. `003 - Beach at dusk.jpg`. A `manifest.json` lists
+//! the details of each photo alongside its file name.
+
+use crate::models::DbPhoto;
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use tracing::warn;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Longest name, without the extension, given to a file in the zip
+const MAX_FILE_STEM_LEN: usize = 80;
+
+#[derive(Debug, Serialize)]
+struct Manifest<'a> {
+    album: &'a str,
+    photos: Vec<ManifestPhoto<'a>>,
+}
+
+/// Details of one photo, as listed in the manifest
+#[derive(Debug, Serialize)]
+struct ManifestPhoto<'a> {
+    file: String,
+    title: Option<&'a str>,
+    artist: Option<&'a str>,
+    copyright: Option<&'a str>,
+    notes: Option<&'a str>,
+    date_taken: Option<NaiveDateTime>,
+}
+
+/// Replace the characters that aren't allowed in file names on some systems, and shorten
+/// overly long names.
+pub fn sanitize_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || r#"/\:*?"<>|"#.contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    // Leading dots hide files, trailing ones are dropped by Windows
+    let name = name.trim().trim_matches('.').trim();
+    name.chars().take(MAX_FILE_STEM_LEN).collect()
+}
+
+/// Name of the photo at `position` (from 1) in the zip. Positions are padded to `width` digits
+/// so the files sort in album order.
+fn file_name(position: usize, width: usize, photo: &DbPhoto) -> String {
+    let label = photo
+        .title
+        .as_deref()
+        .map(sanitize_file_name)
+        .filter(|title| !title.is_empty())
+        .or_else(|| {
+            photo
+                .date_taken
+                .map(|date| date.format("%Y-%m-%d %H.%M.%S").to_string())
+        })
+        .unwrap_or_else(|| format!("Photo {}", photo.id));
+    let extension = Path::new(&photo.fullsize_path)
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase()))
+        .unwrap_or_default();
+    format!("{position:0width$} - {label}{extension}")
+}
+
+/// Photos are already compressed, so they're stored as is. Files are dated when the photo was
+/// taken, so file browsers can sort by it.
+fn file_options(modified: NaiveDateTime) -> SimpleFileOptions {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    match modified.try_into() {
+        Ok(modified) => options.last_modified_time(modified),
+        // Zip dates only run from 1980 to 2107
+        Err(_) => options,
+    }
+}
+
+/// Write a zip of an album's `photos`, in the order given, to `out`. Photos whose original is
+/// missing are logged and left out. This blocks, so run it on a blocking thread.
+pub fn write_album_zip(album: &str, photos: &[DbPhoto], out: impl Write) -> Result<()> {
+    let mut zip = ZipWriter::new_stream(out);
+    let width = photos.len().to_string().len().max(3);
+    let mut manifest = Manifest {
+        album,
+        photos: Vec::with_capacity(photos.len()),
+    };
+
+    for (i, photo) in photos.iter().enumerate() {
+        let mut original = match File::open(&photo.fullsize_path) {
+            Ok(original) => original,
+            Err(e) => {
+                warn!(
+                    "Leaving photo {} out of the export of {album}: {}: {e}",
+                    photo.id, photo.fullsize_path
+                );
+                continue;
+            }
+        };
+        let name = file_name(i + 1, width, photo);
+        zip.start_file(
+            name.as_str(),
+            file_options(photo.date_taken.unwrap_or(photo.created_at)),
+        )?;
+        io::copy(&mut original, &mut zip)?;
+
+        manifest.photos.push(ManifestPhoto {
+            file: name,
+            title: photo.title.as_deref(),
+            artist: photo.artist.as_deref(),
+            copyright: photo.copyright.as_deref(),
+            notes: photo.notes.as_deref(),
+            date_taken: photo.date_taken,
+        });
+    }
+
+    zip.start_file(
+        MANIFEST_FILE_NAME,
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+    )?;
+    serde_json::to_writer_pretty(&mut zip, &manifest)?;
+    zip.finish()?.flush()?;
+    Ok(())
+}
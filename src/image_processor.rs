@@ -0,0 +1,303 @@
+//! Backends that generate the websize and thumbnail renditions of a photo.
+//!
+//! Both backends produce the same output: an upright, progressive, metadata-free sRGB JPEG.
+//! The websize rendition keeps the original dimensions, the thumbnail fits within
+//! [`THUMBNAIL_SIZE`]. Since the metadata is stripped, the EXIF orientation has to be baked
+//! into the pixels or portrait photos would show up sideways.
+
+use crate::photo_format::PhotoFormat;
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageReader, RgbImage};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use tracing::{debug, error, warn};
+
+/// Thumbnails are scaled down to fit in a square this many pixels wide
+pub const THUMBNAIL_SIZE: u32 = 320;
+
+const WEBSIZE_QUALITY: u8 = 82;
+const THUMBNAIL_QUALITY: u8 = 60;
+
+/// Read the EXIF orientation (1-8) of a photo. Photos without one are upright (1).
+pub fn read_orientation(path: &Path) -> u16 {
+    let read = || -> Result<u16> {
+        let f = File::open(path)?;
+        let exif = exif::Reader::new().read_from_container(&mut BufReader::new(&f))?;
+        let orientation = exif
+            .get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0))
+            .unwrap_or(1);
+        Ok(orientation as u16)
+    };
+    match read() {
+        Ok(orientation @ 1..=8) => orientation,
+        Ok(other) => {
+            warn!("Ignoring invalid orientation {other} in {}", path.display());
+            1
+        }
+        Err(e) => {
+            debug!("No orientation for {}: {e}", path.display());
+            1
+        }
+    }
+}
+
+#[async_trait]
+pub trait ImageProcessor: std::fmt::Debug + Send + Sync {
+    /// Short name for logs and error messages
+    fn name(&self) -> &'static str;
+
+    /// Whether this backend can read photos in `format`
+    fn supports(&self, format: PhotoFormat) -> bool;
+
+    /// Write a browser-friendly JPEG copy of `input` at full resolution.
+    async fn make_websize(&self, input: &Path, outpath: &Path) -> Result<()>;
+
+    /// Write a small JPEG preview of `input`.
+    async fn make_thumbnail(&self, input: &Path, outpath: &Path) -> Result<()>;
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ImageMagick
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Shells out to the `magick` command. Reads every format ImageMagick has a delegate for,
+/// including HEIC.
+#[derive(Debug)]
+pub struct MagickProcessor {
+    magick_exec: PathBuf,
+}
+
+impl MagickProcessor {
+    /// Find `magick` on the PATH.
+    pub fn new() -> Result<Self> {
+        let Ok(magick_exec) = which::which("magick") else {
+            bail!("magick: command not found")
+        };
+        Ok(Self { magick_exec })
+    }
+
+    async fn magick(&self, args: &[&str]) -> Result<()> {
+        let output = Command::new(&self.magick_exec).args(args).output().await?;
+        let status = output.status;
+
+        if !status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            error!("Command failed. Stderr:\n{stderr}");
+            bail!(
+                "Command {} with args {args:?} exited with {status}",
+                self.magick_exec.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// ImageMagick input spec for the first frame only. Multi-page TIFFs and HEIC image
+/// sequences would otherwise produce one output file per frame.
+fn first_frame(input: &Path) -> String {
+    format!("{}[0]", input.as_os_str().to_str().unwrap())
+}
+
+#[async_trait]
+impl ImageProcessor for MagickProcessor {
+    fn name(&self) -> &'static str {
+        "magick"
+    }
+
+    fn supports(&self, _format: PhotoFormat) -> bool {
+        true
+    }
+
+    async fn make_websize(&self, input: &Path, outpath: &Path) -> Result<()> {
+        // TODO: tune args like quality
+        let input = first_frame(input);
+        let outpath = outpath.as_os_str().to_str().unwrap();
+        let quality = WEBSIZE_QUALITY.to_string();
+        self.magick(&[
+            &input,
+            // Rotate to match the EXIF orientation before -strip throws it away
+            "-auto-orient",
+            // JPEG has no transparency. Put see-through PNG/WebP areas on white, not black
+            "-background",
+            "white",
+            "-alpha",
+            "remove",
+            "-colorspace",
+            "sRGB",
+            "-strip",
+            "-interlace",
+            "Plane",
+            "-sampling-factor",
+            "4:2:0",
+            "-quality",
+            &quality,
+            outpath,
+        ])
+        .await
+    }
+
+    async fn make_thumbnail(&self, input: &Path, outpath: &Path) -> Result<()> {
+        // TODO: tune args like quality
+        let input = first_frame(input);
+        let outpath = outpath.as_os_str().to_str().unwrap();
+        let size = format!("{THUMBNAIL_SIZE}x{THUMBNAIL_SIZE}>");
+        let quality = THUMBNAIL_QUALITY.to_string();
+        self.magick(&[
+            &input,
+            "-auto-orient",
+            "-background",
+            "white",
+            "-alpha",
+            "remove",
+            "-resize",
+            &size,
+            "-filter",
+            "Triangle",
+            "-strip",
+            "-colorspace",
+            "sRGB",
+            "-interlace",
+            "Plane",
+            "-sampling-factor",
+            "4:2:0",
+            "-quality",
+            &quality,
+            outpath,
+        ])
+        .await
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Native
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Pure Rust backend built on the `image` crate. Needs nothing installed on the host, but
+/// can't read HEIC.
+#[derive(Debug, Default)]
+pub struct NativeProcessor;
+
+impl NativeProcessor {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl ImageProcessor for NativeProcessor {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn supports(&self, format: PhotoFormat) -> bool {
+        format.is_decodable()
+    }
+
+    async fn make_websize(&self, input: &Path, outpath: &Path) -> Result<()> {
+        let (input, outpath) = (input.to_path_buf(), outpath.to_path_buf());
+        tokio::task::spawn_blocking(move || {
+            let img = load_srgb(&input)?;
+            write_progressive_jpeg(&img, &outpath, WEBSIZE_QUALITY)
+        })
+        .await?
+    }
+
+    async fn make_thumbnail(&self, input: &Path, outpath: &Path) -> Result<()> {
+        let (input, outpath) = (input.to_path_buf(), outpath.to_path_buf());
+        tokio::task::spawn_blocking(move || {
+            let mut img = load_srgb(&input)?;
+            // Only ever shrink, like ImageMagick's `>` geometry flag
+            if img.width() > THUMBNAIL_SIZE || img.height() > THUMBNAIL_SIZE {
+                img = DynamicImage::ImageRgb8(img)
+                    .resize(
+                        THUMBNAIL_SIZE,
+                        THUMBNAIL_SIZE,
+                        image::imageops::FilterType::Triangle,
+                    )
+                    .to_rgb8();
+            }
+            write_progressive_jpeg(&img, &outpath, THUMBNAIL_QUALITY)
+        })
+        .await?
+    }
+}
+
+/// Decode a photo to upright 8-bit sRGB, flattening any transparency onto white.
+fn load_srgb(input: &Path) -> Result<RgbImage> {
+    let mut decoder = ImageReader::open(input)?
+        .with_guessed_format()?
+        .into_decoder()
+        .with_context(|| format!("Unable to decode {}", input.display()))?;
+    let icc = decoder.icc_profile().ok().flatten();
+    let mut img = DynamicImage::from_decoder(decoder)?;
+
+    // Read the tag ourselves rather than asking the decoder, which not every format implements
+    if let Some(orientation) = Orientation::from_exif(read_orientation(input) as u8) {
+        img.apply_orientation(orientation);
+    }
+
+    let rgba = img.to_rgba8();
+    let mut rgb = RgbImage::new(rgba.width(), rgba.height());
+    for (src, dst) in rgba.pixels().zip(rgb.pixels_mut()) {
+        let [r, g, b, a] = src.0;
+        let a = a as u32;
+        let blend = |c: u8| ((c as u32 * a + 255 * (255 - a) + 127) / 255) as u8;
+        dst.0 = [blend(r), blend(g), blend(b)];
+    }
+
+    // Photos without a profile are assumed to already be sRGB
+    if let Some(icc) = icc
+        && let Err(e) = convert_to_srgb(&mut rgb, &icc)
+    {
+        warn!(
+            "Unable to apply color profile of {}: {e}. Assuming sRGB",
+            input.display()
+        );
+    }
+
+    Ok(rgb)
+}
+
+/// Convert pixels from the embedded ICC profile's color space (e.g. Display P3 from phones,
+/// Adobe RGB from cameras) to sRGB.
+fn convert_to_srgb(img: &mut RgbImage, icc: &[u8]) -> Result<()> {
+    let source = moxcms::ColorProfile::new_from_slice(icc)
+        .map_err(|e| anyhow::anyhow!("invalid ICC profile: {e:?}"))?;
+    let srgb = moxcms::ColorProfile::new_srgb();
+    let transform = source
+        .create_transform_8bit(
+            moxcms::Layout::Rgb,
+            &srgb,
+            moxcms::Layout::Rgb,
+            moxcms::TransformOptions::default(),
+        )
+        .map_err(|e| anyhow::anyhow!("unsupported ICC profile: {e:?}"))?;
+
+    let src = img.as_raw().clone();
+    transform
+        .transform(&src, img.as_mut())
+        .map_err(|e| anyhow::anyhow!("color conversion failed: {e:?}"))?;
+    Ok(())
+}
+
+/// Encode as a progressive JPEG with 4:2:0 chroma subsampling. Nothing but pixels is
+/// written, so all metadata from the original is stripped.
+fn write_progressive_jpeg(img: &RgbImage, outpath: &Path, quality: u8) -> Result<()> {
+    let (Ok(width), Ok(height)) = (u16::try_from(img.width()), u16::try_from(img.height())) else {
+        bail!(
+            "Image is too large to encode as JPEG ({}x{})",
+            img.width(),
+            img.height()
+        );
+    };
+    let mut encoder = jpeg_encoder::Encoder::new_file(outpath, quality)?;
+    encoder.set_progressive(true);
+    encoder.set_sampling_factor(jpeg_encoder::SamplingFactor::R_4_2_0);
+    encoder.encode(img.as_raw(), width, height, jpeg_encoder::ColorType::Rgb)?;
+    Ok(())
+}
@@ -0,0 +1,229 @@
+//! Background task that imports photos dropped into the inbox directory.
+//!
+//! Filesystem notifications are used when the platform supports them. A periodic rescan
+//! runs regardless so that photos still get picked up on filesystems where notifications
+//! are unreliable (network shares, some container mounts). A file can be mid-copy however it
+//! was found, so every scan only imports files whose size and modification time have stopped
+//! changing since the scan before.
+
+use crate::App;
+use anyhow::{Result, bail};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tokio::time::{Instant, sleep_until};
+use tracing::{debug, error, info, warn};
+
+/// How long the inbox must be quiet before we start importing.
+/// Copies over a file share arrive as a stream of write events; importing on the first one
+/// would pick up a half-written file.
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+
+/// Size and modification time of each file in the inbox, and since when it's been like that
+type InboxState = HashMap<PathBuf, ((u64, Option<SystemTime>), Instant)>;
+
+/// Tells which inbox files have stopped changing for long enough to be safe to import.
+#[derive(Debug)]
+pub struct SettleCheck {
+    settle_time: Duration,
+    last_seen: InboxState,
+}
+
+impl SettleCheck {
+    pub fn new(settle_time: Duration) -> Self {
+        Self {
+            settle_time,
+            last_seen: InboxState::new(),
+        }
+    }
+
+    /// Record the size and modification time of `files` at `now`. Returns the files that
+    /// haven't changed for the settle time, and how many others are new or still changing.
+    pub fn check(&mut self, files: Vec<PathBuf>, now: Instant) -> (Vec<PathBuf>, usize) {
+        let mut seen = InboxState::new();
+        for path in files {
+            let Ok(metadata) = path.metadata() else {
+                continue;
+            };
+            let state = (metadata.len(), metadata.modified().ok());
+            let since = match self.last_seen.get(&path) {
+                Some((last, since)) if *last == state => *since,
+                _ => now,
+            };
+            seen.insert(path, (state, since));
+        }
+        let mut settled: Vec<PathBuf> = seen
+            .iter()
+            .filter(|(_, (_, since))| now.duration_since(*since) >= self.settle_time)
+            .map(|(path, _)| path.clone())
+            .collect();
+        settled.sort();
+        let changing = seen.len() - settled.len();
+        self.last_seen = seen;
+        (settled, changing)
+    }
+}
+
+/// A deadline pushed back by every event, so work waits until a burst of events is over.
+#[derive(Debug)]
+pub struct Debounce {
+    quiet: Duration,
+    deadline: Option<Instant>,
+}
+
+impl Debounce {
+    pub fn new(quiet: Duration) -> Self {
+        Self {
+            quiet,
+            deadline: None,
+        }
+    }
+
+    /// Note an event at `now`
+    pub fn event(&mut self, now: Instant) {
+        self.deadline = Some(now + self.quiet);
+    }
+
+    /// When the events will have been quiet for long enough, if any are waiting
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Whether the events have been quiet for long enough at `now`. Once due, the events
+    /// are cleared.
+    pub fn take_due(&mut self, now: Instant) -> bool {
+        if self.deadline.is_some_and(|deadline| deadline <= now) {
+            self.deadline = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+pub struct InboxWatcher {
+    app: Arc<App>,
+    poll_interval: Duration,
+    settle_time: Duration,
+}
+
+impl InboxWatcher {
+    pub fn new(app: Arc<App>, poll_interval: Duration) -> Self {
+        Self {
+            app,
+            poll_interval,
+            settle_time: SETTLE_TIME,
+        }
+    }
+
+    /// Wait `settle_time` for the inbox to go quiet and for files to stop changing.
+    pub fn with_settle_time(mut self, settle_time: Duration) -> Self {
+        self.settle_time = settle_time;
+        self
+    }
+
+    /// Watch the inbox forever, importing photos as they arrive.
+    pub async fn run(self) -> Result<()> {
+        let Some(inbox_dir) = self.app.inbox_dir().map(|p| p.to_path_buf()) else {
+            bail!("inbox_dir not configured");
+        };
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<()>();
+        let watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+            Ok(event) => {
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Any
+                ) {
+                    let _ = tx.send(());
+                }
+            }
+            Err(e) => warn!("Inbox watch error: {e}"),
+        });
+        // Keep the watcher alive for as long as we're running. Dropping it stops notifications.
+        let _watcher = match watcher {
+            Ok(mut watcher) => match watcher.watch(&inbox_dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    info!("Watching {} for new photos", inbox_dir.display());
+                    Some(watcher)
+                }
+                Err(e) => {
+                    warn!(
+                        "Unable to watch {}: {e}. Falling back to polling",
+                        inbox_dir.display()
+                    );
+                    None
+                }
+            },
+            Err(e) => {
+                warn!("Filesystem notifications unavailable: {e}. Falling back to polling");
+                None
+            }
+        };
+
+        // Every scan goes through the settle check, as a file can be mid-copy whether we
+        // heard about it, polled for it or found it at startup
+        let mut settle = SettleCheck::new(self.settle_time);
+        let mut events = Debounce::new(self.settle_time);
+
+        // Pick up anything that arrived while we weren't running. When to rescan files that
+        // were still changing at the last rescan
+        let mut recheck = self.scan_settled(&mut settle).await;
+
+        let mut poll = tokio::time::interval(self.poll_interval);
+        poll.reset();
+
+        loop {
+            tokio::select! {
+                Some(()) = rx.recv() => {
+                    // Push the deadline back on every event so we wait for writes to finish
+                    events.event(Instant::now());
+                }
+                _ = sleep_until(events.deadline().unwrap_or_else(Instant::now)),
+                    if events.deadline().is_some() =>
+                {
+                    if events.take_due(Instant::now()) {
+                        recheck = self.scan_settled(&mut settle).await;
+                    }
+                }
+                _ = sleep_until(recheck.unwrap_or_else(Instant::now)), if recheck.is_some() => {
+                    recheck = self.scan_settled(&mut settle).await;
+                }
+                _ = poll.tick() => {
+                    if events.deadline().is_none() {
+                        recheck = self.scan_settled(&mut settle).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Import the files that haven't changed since the last rescan. Returns when to look again
+    /// if some are still being written.
+    async fn scan_settled(&self, settle: &mut SettleCheck) -> Option<Instant> {
+        debug!("Rescanning inbox");
+        let files = match self.app.inbox_files() {
+            Ok(files) => files,
+            Err(e) => {
+                error!("Failed to read inbox: {e:#}");
+                return None;
+            }
+        };
+
+        let (settled, changing) = settle.check(files, Instant::now());
+        if !settled.is_empty()
+            && let Err(e) = self.app.process_inbox_files(&settled).await
+        {
+            error!("Failed to process inbox: {e:#}");
+        }
+        if changing == 0 {
+            None
+        } else {
+            debug!("Waiting for {changing} file(s) to finish copying");
+            Some(Instant::now() + self.settle_time)
+        }
+    }
+}
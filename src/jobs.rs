@@ -0,0 +1,152 @@
+//! Worker pool that processes queued background jobs.
+//!
+//! Jobs live in the `job` table so that anything queued survives a restart. Workers claim
+//! the oldest queued job, run it, and record the outcome on the row.
+
+use crate::App;
+use crate::app::DuplicatePhoto;
+use crate::models::DbJob;
+use anyhow::{Result, bail};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// How often idle workers check the queue even without being notified
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Spawn `workers` tasks that process jobs until the process exits.
+///
+/// Jobs left `processing` by a previous run (e.g. the server was killed mid-import) are
+/// put back in the queue first.
+pub async fn spawn_workers(app: Arc<App>, workers: usize) -> Result<()> {
+    let requeued = sqlx::query(
+        "UPDATE job SET status = 'queued', updated_at = CURRENT_TIMESTAMP WHERE status = 'processing'",
+    )
+    .execute(app.pool())
+    .await?
+    .rows_affected();
+    if requeued > 0 {
+        info!("Requeued {requeued} interrupted job(s)");
+    }
+
+    for worker in 0..workers.max(1) {
+        let app = app.clone();
+        tokio::spawn(async move {
+            run_worker(app, worker).await;
+        });
+    }
+
+    Ok(())
+}
+
+async fn run_worker(app: Arc<App>, worker: usize) {
+    debug!("Job worker {worker} started");
+    loop {
+        // Register for notifications before checking the queue so a job queued in between
+        // isn't missed
+        let notified = app.job_notify().notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        match claim_next_job(&app).await {
+            Ok(Some(job)) => run_job(&app, worker, job).await,
+            Ok(None) => {
+                let _ = tokio::time::timeout(IDLE_POLL_INTERVAL, notified).await;
+            }
+            Err(e) => {
+                error!("Worker {worker} failed to claim a job: {e}");
+                tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+/// Atomically move the oldest queued job to `processing` and return it.
+async fn claim_next_job(app: &App) -> Result<Option<DbJob>> {
+    let job = sqlx::query_as::<_, DbJob>(
+        r#"
+        UPDATE job SET status = 'processing', updated_at = CURRENT_TIMESTAMP
+        WHERE id = (SELECT id FROM job WHERE status = 'queued' ORDER BY id LIMIT 1)
+        RETURNING *
+        "#,
+    )
+    .fetch_optional(app.pool())
+    .await?;
+    Ok(job)
+}
+
+async fn run_job(app: &App, worker: usize, job: DbJob) {
+    info!(
+        "Worker {worker} processing job {} ({})",
+        job.id, job.filename
+    );
+
+    let result = match job.kind.as_str() {
+        "upload" => run_upload(app, &job).await,
+        "classify" => run_classify(app, &job).await,
+        other => Err(anyhow::anyhow!("Unknown job kind: {other}")),
+    };
+
+    let update = match result {
+        Ok(photo_id) => {
+            info!("Job {} done (photo {photo_id})", job.id);
+            sqlx::query(
+                "UPDATE job SET status = 'done', photo_id = ?, error = NULL, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            )
+            .bind(photo_id)
+            .bind(job.id)
+            .execute(app.pool())
+            .await
+        }
+        Err(e) => {
+            warn!("Job {} failed: {e:#}", job.id);
+            // Point duplicate uploads at the photo already in the library
+            let photo_id = e
+                .downcast_ref::<DuplicatePhoto>()
+                .map(|duplicate| duplicate.existing)
+                .or(job.photo_id);
+            sqlx::query(
+                "UPDATE job SET status = 'failed', photo_id = ?, error = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            )
+            .bind(photo_id)
+            .bind(format!("{e:#}"))
+            .bind(job.id)
+            .execute(app.pool())
+            .await
+        }
+    };
+    if let Err(e) = update {
+        error!("Failed to record result of job {}: {e}", job.id);
+    }
+}
+
+/// Import a staged upload. The staging dir is removed whether or not the import succeeds.
+async fn run_upload(app: &App, job: &DbJob) -> Result<i32> {
+    let staged_path = Path::new(&job.staged_path);
+    if !staged_path.is_file() {
+        bail!("Staged file {} is missing", staged_path.display());
+    }
+
+    let result = app.import_photo(staged_path).await;
+
+    if let Some(upload_dir) = staged_path.parent()
+        && let Err(e) = std::fs::remove_dir_all(upload_dir)
+    {
+        warn!(
+            "Failed to clean up staging dir {}: {e}",
+            upload_dir.display()
+        );
+    }
+
+    result
+}
+
+/// Suggest tags for the job's photo.
+async fn run_classify(app: &App, job: &DbJob) -> Result<i32> {
+    let Some(photo_id) = job.photo_id else {
+        bail!("Photo was deleted before it could be classified");
+    };
+    app.run_classifier(photo_id).await?;
+    Ok(photo_id)
+}
@@ -4,8 +4,17 @@
 //! and the main binary.
 
 pub mod app;
+pub mod backup;
+pub mod classifier;
+pub mod export;
+pub mod image_processor;
+pub mod inbox;
+pub mod jobs;
 pub mod models;
 pub mod on_disk_photo;
+pub mod photo_format;
+pub mod rotation;
 pub mod test_helpers;
+pub mod trash;
 
 pub use app::{App, APIResult};
@@ -1,21 +1,29 @@
 use anyhow::{Result, bail};
 use axum::{
     Json, Router,
-    extract::{DefaultBodyLimit, Multipart, Path, State},
+    extract::{DefaultBodyLimit, Multipart, Path, Query, Request, State},
     http::StatusCode,
     routing::{get, post},
 };
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use directories::ProjectDirs;
 use pictureframe::App;
-use std::{env, fs, path::PathBuf, sync::Arc};
+use pictureframe::app::{DEFAULT_DUPLICATE_THRESHOLD, DEFAULT_TRASH_RETENTION_DAYS};
+use pictureframe::backup;
+use pictureframe::classifier::CommandClassifier;
+use pictureframe::image_processor::{ImageProcessor, MagickProcessor, NativeProcessor};
+use pictureframe::inbox::InboxWatcher;
+use pictureframe::jobs;
+use pictureframe::trash;
+use pictureframe_common::{BackupQuery, ImageQuery};
+use std::{env, fs, path::PathBuf, sync::Arc, time::Duration};
 use tokio::net::TcpListener;
 use tower_http::services::{ServeDir, ServeFile};
-use tracing::info;
+use tracing::{error, info};
 
-const THIS_CRATE_NAME: &'static str = env!("CARGO_PKG_NAME");
-const VERSION: &'static str = env!("CARGO_PKG_VERSION");
-const AUTHOR: &'static str = env!("CARGO_PKG_AUTHORS");
+const THIS_CRATE_NAME: &str = env!("CARGO_PKG_NAME");
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const AUTHOR: &str = env!("CARGO_PKG_AUTHORS");
 
 #[derive(serde::Serialize)]
 struct HealthResponse {
@@ -48,8 +56,26 @@ async fn health() -> (StatusCode, Json<HealthResponse>) {
 async fn serve_image(
     State(state): State<Arc<App>>,
     Path(id): Path<i32>,
+    Query(query): Query<ImageQuery>,
+    request: Request,
 ) -> axum::response::Response {
-    state.serve_image(id).await
+    state.serve_image(id, query, request).await
+}
+
+/// Handler for downloading a backup of the whole library.
+async fn backup(
+    State(state): State<Arc<App>>,
+    Query(query): Query<BackupQuery>,
+) -> axum::response::Response {
+    state.backup(query).await
+}
+
+/// Handler for downloading an album as a zip.
+async fn export_album(
+    State(state): State<Arc<App>>,
+    Path(id): Path<i32>,
+) -> axum::response::Response {
+    state.export_album(id).await
 }
 
 /// Handler for uploading photos via multipart form.
@@ -60,8 +86,36 @@ async fn upload_photo(
     state.upload_photo(multipart).await
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ImageBackend {
+    /// Shell out to ImageMagick's `magick`. Supports HEIC
+    Magick,
+    /// Built-in Rust image processing. Nothing to install, but no HEIC support
+    Native,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Rebuild the websize and thumbnail images of every photo from its original, then exit.
+    /// Fixes sideways photos imported before EXIF orientation was applied
+    RegenerateRenditions,
+    /// Restore a backup downloaded from /api/backup into the data directory, rebuild any
+    /// renditions it left out, then exit. Backups are only restored by a pictureframe with the
+    /// same database version
+    Restore {
+        /// Backup archive
+        archive: PathBuf,
+        /// Replace the library already in the data directory
+        #[clap(long)]
+        replace: bool,
+    },
+}
+
 #[derive(Debug, Parser)]
 struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// Data directory. Defaults to ${XDG_DATA_HOME}/pictureframe
     #[clap(long)]
     data_dir: Option<PathBuf>,
@@ -77,6 +131,35 @@ struct Cli {
     /// Port to listen on
     #[clap(long, default_value = "3000")]
     port: u16,
+
+    /// Seconds between inbox rescans. Filesystem notifications trigger imports immediately;
+    /// this is the fallback for filesystems that don't deliver them
+    #[clap(long, default_value = "60")]
+    inbox_poll_interval: u64,
+
+    /// Number of uploads to process in parallel
+    #[clap(long, default_value = "2")]
+    upload_workers: usize,
+
+    /// Maximum Hamming distance between perceptual hashes for a new photo to be rejected as a
+    /// near-duplicate of one already in the library. 0 only rejects exact matches
+    #[clap(long, default_value_t = DEFAULT_DUPLICATE_THRESHOLD)]
+    duplicate_threshold: u32,
+
+    /// Days a deleted photo stays in the trash, where it can be restored, before it and its
+    /// files are deleted for good. The trash is checked hourly
+    #[clap(long, default_value_t = DEFAULT_TRASH_RETENTION_DAYS)]
+    trash_retention_days: u32,
+
+    /// How to generate websize and thumbnail images
+    #[clap(long, value_enum, default_value_t = ImageBackend::Magick)]
+    image_backend: ImageBackend,
+
+    /// Local program that suggests tags for imported photos, e.g. a script around an offline
+    /// vision model. It's run with the path of a JPEG and prints a JSON array of
+    /// {"label", "kind", "confidence"} objects. Suggestions wait for review in the admin page
+    #[clap(long)]
+    classifier_command: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -105,12 +188,64 @@ async fn main() -> Result<()> {
         fs::create_dir_all(&dist_dir)?;
     }
 
-    let app = App::new(data_dir.to_path_buf()).await?;
-    // TODO: renable
-    // app.process_inbox().await?;
+    let image_processor: Arc<dyn ImageProcessor> = match args.image_backend {
+        ImageBackend::Magick => Arc::new(MagickProcessor::new()?),
+        ImageBackend::Native => Arc::new(NativeProcessor::new()),
+    };
+
+    // Restore before the app opens the database it replaces
+    if let Some(Command::Restore { archive, replace }) = &args.command {
+        backup::restore(archive, &data_dir, *replace).await?;
+    }
+
+    let mut app = App::new(data_dir.to_path_buf(), image_processor)
+        .await?
+        .with_duplicate_threshold(args.duplicate_threshold)
+        .with_trash_retention_days(args.trash_retention_days);
+    if let Some(command) = &args.classifier_command {
+        app = app.with_classifier(Arc::new(CommandClassifier::new(command)?));
+    }
+
+    match args.command {
+        Some(Command::RegenerateRenditions) => {
+            let (_, failed) = app.regenerate_all_renditions().await?;
+            if failed > 0 {
+                bail!("Unable to regenerate renditions for {failed} photos");
+            }
+            return Ok(());
+        }
+        Some(Command::Restore { .. }) => {
+            let (_, failed) = app.regenerate_missing_renditions().await?;
+            if failed > 0 {
+                bail!("Restored, but unable to regenerate renditions for {failed} photos");
+            }
+            return Ok(());
+        }
+        None => {}
+    }
 
     let state = Arc::new(app);
 
+    jobs::spawn_workers(state.clone(), args.upload_workers).await?;
+    trash::spawn_purger(state.clone());
+
+    let backfill_state = state.clone();
+    tokio::spawn(async move {
+        match backfill_state.backfill_image_details().await {
+            Ok(0) => {}
+            Ok(updated) => info!("Recorded image details for {updated} photos"),
+            Err(e) => error!("Unable to record image details: {e:#}"),
+        }
+    });
+
+    let inbox_watcher =
+        InboxWatcher::new(state.clone(), Duration::from_secs(args.inbox_poll_interval));
+    tokio::spawn(async move {
+        if let Err(e) = inbox_watcher.run().await {
+            error!("Inbox watcher stopped: {e:#}");
+        }
+    });
+
     // let api_photos_router = Router::new()
     // .route("/next", get(get_next_photo))
     // .route("/", get(get_photos));
@@ -132,6 +267,8 @@ async fn main() -> Result<()> {
     // Routes for binary/multipart data (not part of the macro-generated router)
     let extra_routes = Router::new()
         .route("/api/images/{id}", get(serve_image))
+        .route("/api/backup", get(backup))
+        .route("/api/albums/{id}/export", get(export_album))
         .route("/api/photos", post(upload_photo))
         .layer(DefaultBodyLimit::max(50 * 1024 * 1024)) // 50MB limit for uploads
         .with_state(state);
@@ -1,4 +1,4 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, NaiveTime};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -18,6 +18,32 @@ pub struct DbPhoto {
     pub mat_preset: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// Width of the upright renditions. NULL until the renditions have been regenerated for
+    /// photos imported before dimensions were recorded
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// EXIF orientation (1-8) of the original
+    pub orientation: i32,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens: Option<String>,
+    pub focal_length: Option<f64>,
+    pub aperture: Option<f64>,
+    pub exposure_time: Option<f64>,
+    pub iso: Option<i32>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub pixel_width: Option<i32>,
+    pub pixel_height: Option<i32>,
+    pub date_taken_offset: Option<String>,
+    /// `settings.show_count` when this photo was last displayed
+    pub last_shown: Option<i64>,
+    /// When the photo was moved to the trash. NULL for photos in the library
+    pub deleted_at: Option<NaiveDateTime>,
+    /// MIME type of the fullsize original
+    pub content_type: Option<String>,
+    /// Digest of the fullsize, websize and thumbnail files, see [`crate::on_disk_photo::image_key`]
+    pub image_key: Option<String>,
 }
 
 /// Database model for an album
@@ -28,6 +54,10 @@ pub struct DbAlbum {
     pub notes: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// JSON `VirtualAlbum`. NULL for albums whose photos are added by hand
+    pub virtual_album: Option<String>,
+    /// JSON `SmartRule`. NULL for albums that aren't smart albums
+    pub smart_rule: Option<String>,
 }
 
 /// Database model for album-photo junction
@@ -47,6 +77,80 @@ pub struct DbSettings {
     pub current_photo_index: i32,
     pub interval_seconds: i32,
     pub updated_at: NaiveDateTime,
+    pub rotation_mode: String,
+    /// Number of photos served by /api/next so far
+    pub show_count: i64,
+}
+
+/// Database model for a registered display
+#[derive(Debug, Clone, FromRow)]
+pub struct DbDisplay {
+    pub id: i32,
+    pub name: String,
+    pub token: String,
+    pub current_album_id: Option<i32>,
+    pub current_photo_index: i32,
+    pub interval_seconds: i32,
+    pub rotation_mode: String,
+    pub mat_preset: Option<String>,
+    pub last_seen_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// Number of photos this display has shown so far
+    pub show_count: i64,
+}
+
+/// Database model for a schedule
+#[derive(Debug, Clone, FromRow)]
+pub struct DbSchedule {
+    pub id: i32,
+    pub name: String,
+    pub album_id: Option<i32>,
+    pub display_id: Option<i32>,
+    pub priority: i32,
+    pub enabled: bool,
+    /// Bit 0 is Monday
+    pub weekdays: i32,
+    /// Bit 0 is January
+    pub months: i32,
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Database model for a tag, with the number of photos that have it
+#[derive(Debug, Clone, FromRow)]
+pub struct DbTag {
+    pub id: i32,
+    pub name: String,
+    pub photo_count: i64,
+}
+
+/// Database model for a tag proposed by the classifier
+#[derive(Debug, Clone, FromRow)]
+pub struct DbTagSuggestion {
+    pub id: i32,
+    pub photo_id: i32,
+    pub label: String,
+    pub kind: String,
+    pub confidence: f64,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// Database model for a background job
+#[derive(Debug, Clone, FromRow)]
+pub struct DbJob {
+    pub id: i32,
+    pub kind: String,
+    pub status: String,
+    pub filename: String,
+    pub staged_path: String,
+    pub error: Option<String>,
+    pub photo_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
 }
 
 /// For inserting a new photo
@@ -1,12 +1,16 @@
+use crate::image_processor::{self, ImageProcessor};
+use crate::photo_format::PhotoFormat;
 use anyhow::{Result, bail};
 use chrono::NaiveDateTime;
-use image_hasher::HasherConfig;
+use exif::{Exif, In, Tag, Value};
+use image_hasher::{HasherConfig, ImageHash};
+use pictureframe_common::PhotoExif;
+use sha2::{Digest, Sha256};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{self, BufReader};
 use std::path::Path;
 use std::{fs, path::PathBuf};
-use tokio::process::Command;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 #[derive(Debug)]
 pub struct PhotoMetadata {
@@ -14,6 +18,8 @@ pub struct PhotoMetadata {
     artist: Option<String>,
     copyright: Option<String>,
     date_taken: Option<NaiveDateTime>,
+    exif: PhotoExif,
+    orientation: u16,
 }
 
 impl PhotoMetadata {
@@ -32,6 +38,15 @@ impl PhotoMetadata {
     pub fn date_taken(&self) -> Option<&NaiveDateTime> {
         self.date_taken.as_ref()
     }
+
+    pub fn exif(&self) -> &PhotoExif {
+        &self.exif
+    }
+
+    /// EXIF orientation (1-8) of the original. Already applied to the renditions
+    pub fn orientation(&self) -> u16 {
+        self.orientation
+    }
 }
 
 #[derive(Debug)]
@@ -41,17 +56,35 @@ pub struct OnDiskPhoto {
     fullsize: PathBuf,
     websize: PathBuf,
     thumbnail: PathBuf,
+    dimensions: (u32, u32),
+    image_key: String,
 }
 
 impl OnDiskPhoto {
-    pub async fn import(orig: &Path, photos_dir: &Path, magick_exec: &Path) -> Result<Self> {
+    /// Generate renditions for `orig` and move everything into `photos_dir`.
+    ///
+    /// `hash` must come from [`hash_photo`]. Callers are expected to have already checked it
+    /// against the database so that duplicates are turned away before the expensive work.
+    ///
+    /// The original is kept as the fullsize file in its own format. The websize and thumbnail
+    /// renditions are always JPEG so that every browser can display them.
+    pub async fn import(
+        orig: &Path,
+        format: PhotoFormat,
+        hash: &str,
+        photos_dir: &Path,
+        processor: &dyn ImageProcessor,
+    ) -> Result<Self> {
         let orig_name = orig.file_stem().unwrap().to_string_lossy().to_string();
-        let orig_ext = orig.extension().unwrap().to_string_lossy();
+        let hash = hash.to_string();
 
-        debug!("\n\ncheck 1\n\n");
-
-        // TODO: the hash and parse_exif functions both open the photo file and can probably be optimized to do it once
-        let hash = hash_photo(&orig)?;
+        // Cheap last line of defence in case the database and photos dir disagree
+        if photos_dir.join(&hash).exists() {
+            bail!(
+                "Output dir for input {} already exists (photo has already been imported)",
+                orig.display()
+            );
+        }
 
         debug!("\n\ncheck 2\n\n");
 
@@ -73,52 +106,47 @@ impl OnDiskPhoto {
         debug!("\n\ncheck 3\n\n");
 
         let title = orig_name;
-        let (artist, copyright, date_taken) = match parse_exif(orig) {
+        let fields = match parse_exif(orig) {
             Ok(fields) => fields,
             Err(e) => {
                 error!("unable to process metadata: {e}");
-                (None, None, None)
+                ExifFields::default()
             }
         };
         let metadata = PhotoMetadata {
             title,
-            artist,
-            copyright,
-            date_taken,
+            artist: fields.artist,
+            copyright: fields.copyright,
+            date_taken: fields.date_taken,
+            exif: fields.exif,
+            orientation: image_processor::read_orientation(orig),
         };
         debug!("metadata: {metadata:?}");
 
         debug!("\n\ncheck 4\n\n");
 
-        let websize_name = format!("{hash}-websize.{orig_ext}");
+        let websize_name = format!("{hash}-websize.jpg");
         let tmp_websize = working_dir.join(&websize_name);
-        make_websize(magick_exec, &orig, &tmp_websize).await?;
-        debug!("Generated websize image {}", tmp_websize.display());
-
-        debug!("\n\ncheck 5\n\n");
-
-        let thumbnail_name = format!("{hash}-thumbnail.{orig_ext}");
+        let thumbnail_name = format!("{hash}-thumbnail.jpg");
         let tmp_thumbnail = working_dir.join(&thumbnail_name);
-        make_thumbnail(magick_exec, &orig, &tmp_thumbnail).await?;
-        debug!("Generated thumbnail image {}", tmp_thumbnail.display());
+        let dimensions = make_renditions(orig, &tmp_websize, &tmp_thumbnail, processor).await?;
 
         debug!("\n\ncheck 6\n\n");
 
-        let fullsize_name = format!("{hash}-fullsize.{orig_ext}");
+        let fullsize_name = format!("{hash}-fullsize.{}", format.extension());
         let fullsize_tmp = working_dir.join(&fullsize_name);
         // Note: fs::rename fails when /tmp and data dir are on different filesystems so we use copy+remove instead
         // This was triggered when running pictureframe under systemd
         // https://stackoverflow.com/a/24210631
         // fs::rename(orig, &fullsize_tmp)?;
-        fs::copy(&orig, &fullsize_tmp)?;
-        fs::remove_file(&orig)?;
+        fs::copy(orig, &fullsize_tmp)?;
+        fs::remove_file(orig)?;
 
         debug!("\n\ncheck 7\n\n");
 
         // TODO: can we add a clippy lint to block the usage of fs::rename()?
 
         // Move the entire working directory to the final output location
-        // TODO: this fails if we've previously processed the photo. We need to use the hash to check the database before we get this far. Should be done before generating thumbs too
         let outdir = photos_dir.join(&hash);
         let mut opts = fs_extra::dir::CopyOptions::new();
         opts.copy_inside = true;
@@ -131,6 +159,7 @@ impl OnDiskPhoto {
         let fullsize = outdir.join(&fullsize_name);
         let websize = outdir.join(&websize_name);
         let thumbnail = outdir.join(&thumbnail_name);
+        let image_key = image_key(&fullsize, &websize, &thumbnail)?;
 
         debug!("\n\ncheck 9\n\n");
 
@@ -140,6 +169,8 @@ impl OnDiskPhoto {
             fullsize,
             websize,
             thumbnail,
+            dimensions,
+            image_key,
         })
     }
 
@@ -162,105 +193,292 @@ impl OnDiskPhoto {
     pub fn thumbnail_path(&self) -> &Path {
         &self.thumbnail
     }
+
+    /// Width and height of the upright websize rendition
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.dimensions
+    }
+
+    pub fn image_key(&self) -> &str {
+        &self.image_key
+    }
 }
 
-fn hash_photo(path: &Path) -> Result<String> {
-    let img = image::open(path)?;
+/// Digest of a photo's files, stored in `photo.image_key` and put in its image URLs. Any
+/// change to the files, like regenerating the renditions, gives a new key and so new URLs.
+pub fn image_key(fullsize: &Path, websize: &Path, thumbnail: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    for path in [fullsize, websize, thumbnail] {
+        io::copy(&mut File::open(path)?, &mut hasher)?;
+    }
+    let digest = hasher.finalize();
+    // 64 bits is plenty to tell one photo's versions apart
+    Ok(digest[..8].iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// Write the websize and thumbnail renditions of `orig` and return the websize dimensions.
+async fn make_renditions(
+    orig: &Path,
+    websize: &Path,
+    thumbnail: &Path,
+    processor: &dyn ImageProcessor,
+) -> Result<(u32, u32)> {
+    processor.make_websize(orig, websize).await?;
+    debug!("Generated websize image {}", websize.display());
+
+    processor.make_thumbnail(orig, thumbnail).await?;
+    debug!("Generated thumbnail image {}", thumbnail.display());
+
+    Ok(image::image_dimensions(websize)?)
+}
+
+/// Rebuild the websize and thumbnail renditions of an already imported photo from its
+/// fullsize original, replacing the existing files. Returns the new websize dimensions.
+///
+/// The new renditions are written next to the old ones first so that a failure leaves the
+/// photo as it was.
+pub async fn regenerate_renditions(
+    fullsize: &Path,
+    websize: &Path,
+    thumbnail: &Path,
+    processor: &dyn ImageProcessor,
+) -> Result<(u32, u32)> {
+    let tmp_path = |path: &Path| {
+        let mut name = std::ffi::OsString::from(".regen-");
+        name.push(path.file_name().unwrap_or_default());
+        path.with_file_name(name)
+    };
+    let tmp_websize = tmp_path(websize);
+    let tmp_thumbnail = tmp_path(thumbnail);
+
+    let result = make_renditions(fullsize, &tmp_websize, &tmp_thumbnail, processor).await;
+    if result.is_ok() {
+        // Same directory, so unlike the import there's no cross-filesystem move to worry about
+        fs::rename(&tmp_websize, websize)?;
+        fs::rename(&tmp_thumbnail, thumbnail)?;
+    } else {
+        let _ = fs::remove_file(&tmp_websize);
+        let _ = fs::remove_file(&tmp_thumbnail);
+    }
+    result
+}
+
+/// Perceptual hash of the photo, base64 encoded as stored in `photo.hash`.
+///
+/// Formats the `image` crate can't decode (HEIC) are hashed from a thumbnail made by the
+/// image processor. The perceptual hash is insensitive to the downscale so the result still
+/// matches other copies of the same shot.
+// TODO: the hash and parse_exif functions both open the photo file and can probably be optimized to do it once
+pub async fn hash_photo(
+    path: &Path,
+    format: PhotoFormat,
+    processor: &dyn ImageProcessor,
+) -> Result<String> {
+    if format.is_decodable() {
+        let path = path.to_path_buf();
+        return tokio::task::spawn_blocking(move || hash_decodable(&path)).await?;
+    }
+
+    let tmp = tempfile::tempdir()?;
+    let decoded = tmp.path().join("hash-source.jpg");
+    processor.make_thumbnail(path, &decoded).await?;
+    tokio::task::spawn_blocking(move || hash_decodable(&decoded)).await?
+}
+
+fn hash_decodable(path: &Path) -> Result<String> {
+    // Go by content, not extension, to match how the format was detected
+    let img = image::ImageReader::open(path)?
+        .with_guessed_format()?
+        .decode()?;
     let hasher = HasherConfig::new().to_hasher();
     let hash = hasher.hash_image(&img);
     let hash_str = hash.to_base64();
     Ok(hash_str)
 }
 
-async fn magick(magick_exec: &Path, args: &[&str]) -> Result<()> {
-    let output = Command::new(magick_exec).args(args).output().await?;
-    let status = output.status;
-
-    if !status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        error!("Command failed. Stderr:\n{stderr}");
-        bail!(
-            "Command {} with args {args:?} exited with {status}",
-            magick_exec.display()
-        );
+/// Hamming distance between two hashes produced by [`hash_photo`].
+/// Returns `None` if either isn't a valid hash or they were made with different hasher settings.
+pub fn hash_distance(a: &str, b: &str) -> Option<u32> {
+    let a = ImageHash::<Box<[u8]>>::from_base64(a).ok()?;
+    let b = ImageHash::<Box<[u8]>>::from_base64(b).ok()?;
+    if a.as_bytes().len() != b.as_bytes().len() {
+        return None;
     }
-    Ok(())
+    Some(a.dist(&b))
 }
 
-async fn make_websize(magick_exec: &Path, input: &Path, outpath: &Path) -> Result<()> {
-    // TODO: tune args like quality
-    let input = input.as_os_str().to_str().unwrap();
-    let outpath = outpath.as_os_str().to_str().unwrap();
-    magick(
-        magick_exec,
-        &[
-            input,
-            "-strip",
-            "-interlace",
-            "Plane",
-            "-sampling-factor",
-            "4:2:0",
-            "-quality",
-            "82",
-            outpath,
-        ],
-    )
-    .await
+/// Group hashes that are within `max_distance` of each other.
+///
+/// Returns the indices of each group with more than one member, along with the largest
+/// distance between any two of its members. Grouping is transitive so that a burst of similar
+/// shots comes back as one group even if the ends of the burst drifted further apart.
+pub fn group_similar(hashes: &[&str], max_distance: u32) -> Vec<(Vec<usize>, u32)> {
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut parent: Vec<usize> = (0..hashes.len()).collect();
+    for i in 0..hashes.len() {
+        for j in (i + 1)..hashes.len() {
+            if hash_distance(hashes[i], hashes[j]).is_some_and(|d| d <= max_distance) {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); hashes.len()];
+    for i in 0..hashes.len() {
+        let r = root(&mut parent, i);
+        members[r].push(i);
+    }
+
+    members
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| {
+            let mut distance = 0;
+            for (n, &i) in group.iter().enumerate() {
+                for &j in &group[n + 1..] {
+                    if let Some(d) = hash_distance(hashes[i], hashes[j]) {
+                        distance = distance.max(d);
+                    }
+                }
+            }
+            (group, distance)
+        })
+        .collect()
 }
 
-async fn make_thumbnail(magick_exec: &Path, input: &Path, outpath: &Path) -> Result<()> {
-    // TODO: tune args like quality
-    let input = input.as_os_str().to_str().unwrap();
-    let outpath = outpath.as_os_str().to_str().unwrap();
-    magick(
-        magick_exec,
-        &[
-            input,
-            "-resize",
-            "320x320>",
-            "-filter",
-            "Triangle",
-            "-strip",
-            "-colorspace",
-            "sRGB",
-            "-interlace",
-            "Plane",
-            "-sampling-factor",
-            "4:2:0",
-            "-quality",
-            "60",
-            outpath,
-        ],
-    )
-    .await
+/// Everything read from a photo's EXIF data
+#[derive(Debug, Default)]
+struct ExifFields {
+    artist: Option<String>,
+    copyright: Option<String>,
+    date_taken: Option<NaiveDateTime>,
+    exif: PhotoExif,
 }
 
-fn parse_exif(path: &Path) -> Result<(Option<String>, Option<String>, Option<NaiveDateTime>)> {
+/// Read the EXIF data of a photo. Each field is read on its own, so a tag that is missing or
+/// malformed only loses that one field.
+fn parse_exif(path: &Path) -> Result<ExifFields> {
     debug!("Parsing exif data");
-    let f = File::open(&path)?;
+    let f = File::open(path)?;
     let mut reader = BufReader::new(&f);
     let exifreader = exif::Reader::new();
     let Ok(exif) = exifreader.read_from_container(&mut reader) else {
-        return Ok((None, None, None));
+        return Ok(ExifFields::default());
     };
 
-    let artist = exif
-        .get_field(exif::Tag::Artist, exif::In::PRIMARY)
-        .map(|field| field.value.display_as(exif::Tag::Artist).to_string());
-    let copyright = exif
-        .get_field(exif::Tag::Copyright, exif::In::PRIMARY)
-        .map(|field| field.value.display_as(exif::Tag::Copyright).to_string());
-    let date_taken = exif
-        .get_field(exif::Tag::DateTime, exif::In::PRIMARY)
-        .map(|field| field.value.display_as(exif::Tag::DateTime).to_string());
-
-    let date_taken = match date_taken {
-        Some(raw) => {
-            let parsed = NaiveDateTime::parse_from_str(&raw, "%Y:%m:%d %H:%M:%S")?;
-            Some(parsed)
-        }
-        None => None,
+    // Prefer when the shutter fired over when the file was last written
+    let (date_taken, date_taken_offset) = match parse_exif_datetime(&exif, Tag::DateTimeOriginal) {
+        Some(date) => (Some(date), exif_ascii(&exif, Tag::OffsetTimeOriginal)),
+        None => (
+            parse_exif_datetime(&exif, Tag::DateTime),
+            exif_ascii(&exif, Tag::OffsetTime),
+        ),
+    };
+    let date_taken_offset = date_taken_offset.filter(|offset| is_utc_offset(offset));
+
+    let pixel_width =
+        exif_uint(&exif, Tag::PixelXDimension).or_else(|| exif_uint(&exif, Tag::ImageWidth));
+    let pixel_height =
+        exif_uint(&exif, Tag::PixelYDimension).or_else(|| exif_uint(&exif, Tag::ImageLength));
+
+    Ok(ExifFields {
+        artist: exif_ascii(&exif, Tag::Artist),
+        copyright: exif_ascii(&exif, Tag::Copyright),
+        date_taken,
+        exif: PhotoExif {
+            camera_make: exif_ascii(&exif, Tag::Make),
+            camera_model: exif_ascii(&exif, Tag::Model),
+            lens: exif_ascii(&exif, Tag::LensModel),
+            focal_length: exif_rational(&exif, Tag::FocalLength),
+            aperture: exif_rational(&exif, Tag::FNumber),
+            exposure_time: exif_rational(&exif, Tag::ExposureTime),
+            iso: exif_uint(&exif, Tag::PhotographicSensitivity),
+            latitude: parse_gps_coordinate(&exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, b'S'),
+            longitude: parse_gps_coordinate(&exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, b'W'),
+            pixel_width,
+            pixel_height,
+            date_taken_offset,
+        },
+    })
+}
+
+/// A text tag with surrounding whitespace removed. Blank values count as missing.
+fn exif_ascii(exif: &Exif, tag: Tag) -> Option<String> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let Value::Ascii(ref values) = field.value else {
+        debug!("Ignoring {tag}: not text");
+        return None;
+    };
+    let text = String::from_utf8_lossy(values.first()?);
+    let text = text.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn exif_uint(exif: &Exif, tag: Tag) -> Option<u32> {
+    exif.get_field(tag, In::PRIMARY)?.value.get_uint(0)
+}
+
+/// A rational tag as a float. Zero denominators (some cameras write 0/0 for "unknown") count
+/// as missing.
+fn exif_rational(exif: &Exif, tag: Tag) -> Option<f64> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let Value::Rational(ref values) = field.value else {
+        debug!("Ignoring {tag}: not a rational");
+        return None;
     };
+    let value = values.first()?;
+    (value.denom != 0).then(|| value.to_f64())
+}
+
+fn parse_exif_datetime(exif: &Exif, tag: Tag) -> Option<NaiveDateTime> {
+    let raw = exif_ascii(exif, tag)?;
+    match NaiveDateTime::parse_from_str(&raw, "%Y:%m:%d %H:%M:%S") {
+        Ok(date) => Some(date),
+        Err(e) => {
+            warn!("Ignoring {tag} {raw:?}: {e}");
+            None
+        }
+    }
+}
+
+/// `+HH:MM` or `-HH:MM`, the form the EXIF OffsetTime tags use
+fn is_utc_offset(offset: &str) -> bool {
+    let bytes = offset.as_bytes();
+    bytes.len() == 6
+        && matches!(bytes[0], b'+' | b'-')
+        && bytes[3] == b':'
+        && [1, 2, 4, 5].iter().all(|&i| bytes[i].is_ascii_digit())
+}
 
-    Ok((artist, copyright, date_taken))
+/// Convert a GPS degrees/minutes/seconds tag to signed decimal degrees. `negative` is the
+/// reference letter (S or W) that puts the coordinate below zero.
+fn parse_gps_coordinate(exif: &Exif, tag: Tag, ref_tag: Tag, negative: u8) -> Option<f64> {
+    let field = exif.get_field(tag, In::PRIMARY)?;
+    let Value::Rational(ref dms) = field.value else {
+        debug!("Ignoring {tag}: not a rational");
+        return None;
+    };
+    if dms.len() != 3 || dms.iter().any(|part| part.denom == 0) {
+        debug!("Ignoring {tag}: expected degrees, minutes and seconds");
+        return None;
+    }
+    let degrees = dms[0].to_f64() + dms[1].to_f64() / 60.0 + dms[2].to_f64() / 3600.0;
+
+    // Without a reference the hemisphere is unknown and the coordinate is meaningless
+    let reference = exif_ascii(exif, ref_tag)?;
+    let degrees = if reference.as_bytes().first() == Some(&negative) {
+        -degrees
+    } else {
+        degrees
+    };
+    let limit = if tag == Tag::GPSLatitude { 90.0 } else { 180.0 };
+    (degrees.abs() <= limit).then_some(degrees)
 }
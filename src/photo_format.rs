@@ -0,0 +1,119 @@
+//! Image format detection.
+//!
+//! Formats are identified from the file's leading bytes rather than its extension. Phones and
+//! scanners are inconsistent about extensions (`.HEIC`, `.heif`, `.tif`, `.JPG`, none at all)
+//! and a mislabelled file should still import.
+
+use anyhow::{Result, bail};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Number of leading bytes needed to recognise every supported format
+const SNIFF_LEN: usize = 16;
+
+/// HEIF brands (ISO/IEC 23008-12) used by phones for still photos
+const HEIF_BRANDS: &[&[u8; 4]] = &[b"heic", b"heix", b"hevc", b"hevx", b"mif1", b"msf1"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhotoFormat {
+    Jpeg,
+    Png,
+    Webp,
+    Tiff,
+    Heic,
+}
+
+impl PhotoFormat {
+    pub const SUPPORTED: &[PhotoFormat] = &[
+        PhotoFormat::Jpeg,
+        PhotoFormat::Png,
+        PhotoFormat::Webp,
+        PhotoFormat::Tiff,
+        PhotoFormat::Heic,
+    ];
+
+    /// Identify the format from the first bytes of a file.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            return Some(Self::Jpeg);
+        }
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Some(Self::Png);
+        }
+        if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            return Some(Self::Webp);
+        }
+        if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+            return Some(Self::Tiff);
+        }
+        if bytes.len() >= 12
+            && &bytes[4..8] == b"ftyp"
+            && HEIF_BRANDS.iter().any(|brand| &bytes[8..12] == *brand)
+        {
+            return Some(Self::Heic);
+        }
+        None
+    }
+
+    /// Identify the format of a file on disk.
+    pub fn detect(path: &Path) -> Result<Self> {
+        let mut head = Vec::with_capacity(SNIFF_LEN);
+        File::open(path)?
+            .take(SNIFF_LEN as u64)
+            .read_to_end(&mut head)?;
+        match Self::from_bytes(&head) {
+            Some(format) => Ok(format),
+            None => bail!(
+                "Unsupported image format. Supported formats: {}",
+                Self::supported_names()
+            ),
+        }
+    }
+
+    /// Comma separated list of supported formats for error messages.
+    pub fn supported_names() -> String {
+        Self::SUPPORTED
+            .iter()
+            .map(|format| format.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "JPEG",
+            Self::Png => "PNG",
+            Self::Webp => "WebP",
+            Self::Tiff => "TIFF",
+            Self::Heic => "HEIC",
+        }
+    }
+
+    /// Canonical file extension, used when naming the stored original.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+            Self::Tiff => "tiff",
+            Self::Heic => "heic",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Webp => "image/webp",
+            Self::Tiff => "image/tiff",
+            Self::Heic => "image/heic",
+        }
+    }
+
+    /// Whether the `image` crate can decode this format. Anything else has to go through
+    /// the image processor first.
+    pub fn is_decodable(&self) -> bool {
+        !matches!(self, Self::Heic)
+    }
+}
@@ -0,0 +1,45 @@
+//! Picking the next photo for the shuffle and weighted random rotation modes.
+//!
+//! Sequential rotation is just an index into the photo list and lives in
+//! [`App::get_next_photo`](crate::App::get_next_photo).
+
+use rand::Rng;
+use rand::distr::weighted::WeightedIndex;
+use rand::seq::SliceRandom;
+
+/// Shuffle `ids` into the order for the next shuffle cycle.
+///
+/// `previous` is the photo on screen now. It is kept off the front of the new cycle so that
+/// the end of one cycle and the start of the next don't show it twice in a row.
+pub fn shuffle_cycle(ids: &[i32], previous: Option<i32>, rng: &mut impl Rng) -> Vec<i32> {
+    let mut cycle = ids.to_vec();
+    cycle.shuffle(rng);
+    if cycle.len() > 1 && cycle.first() == previous.as_ref() {
+        let swap_with = rng.random_range(1..cycle.len());
+        cycle.swap(0, swap_with);
+    }
+    cycle
+}
+
+/// Pick a photo at random, favoring the ones that haven't been shown for a while.
+///
+/// `last_shown` holds each photo's `photo.last_shown` and `show_count` is the current
+/// `settings.show_count`. A photo's weight is the number of photos shown since it was last on
+/// screen, so the photo showing right now is never picked again unless it's the only one.
+/// Photos that have never been shown weigh slightly more than the least recently shown one.
+/// Returns an index into `last_shown`.
+pub fn pick_least_recently_shown(
+    last_shown: &[Option<i64>],
+    show_count: i64,
+    rng: &mut impl Rng,
+) -> usize {
+    let weights = last_shown.iter().map(|shown| match shown {
+        Some(shown) => (show_count - shown).max(0) as u64,
+        None => show_count.max(0) as u64 + 1,
+    });
+    match WeightedIndex::new(weights) {
+        Ok(dist) => rng.sample(dist),
+        // Nothing has any weight (a single photo, or every candidate was just shown)
+        Err(_) => rng.random_range(0..last_shown.len().max(1)),
+    }
+}
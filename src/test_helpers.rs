@@ -1,15 +1,27 @@
 //! Test utilities for creating test databases and seeding test data.
 
 use crate::App;
-use axum::extract::{DefaultBodyLimit, Multipart, Path, State};
+use crate::classifier::{Classification, Classifier};
+use axum::extract::{DefaultBodyLimit, Multipart, Path, Query, Request, State};
 use axum::routing::{get, post};
 use axum::Router;
-use sqlx::sqlite::SqlitePoolOptions;
+use pictureframe_common::{BackupQuery, ImageQuery};
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use std::path::Path as FsPath;
 use std::sync::Arc;
 
 /// Create an App instance with an in-memory SQLite database for testing.
 /// Migrations are applied automatically.
 pub async fn create_test_app() -> Arc<App> {
+    Arc::new(App::from_pool(create_test_pool().await))
+}
+
+/// Create a test App that suggests tags with `classifier`.
+pub async fn create_test_app_with_classifier(classifier: Arc<dyn Classifier>) -> Arc<App> {
+    Arc::new(App::from_pool(create_test_pool().await).with_classifier(classifier))
+}
+
+async fn create_test_pool() -> SqlitePool {
     let pool = SqlitePoolOptions::new()
         .max_connections(1)
         .connect("sqlite::memory:")
@@ -22,13 +34,13 @@ pub async fn create_test_app() -> Arc<App> {
         .await
         .expect("Failed to run migrations");
 
-    Arc::new(App::from_pool(pool))
+    pool
 }
 
 /// Insert a test photo into the database.
 /// Returns the photo ID.
 pub async fn seed_photo(app: &App, hash: &str, title: &str) -> i32 {
-    let result = sqlx::query_scalar::<_, i32>(
+    sqlx::query_scalar::<_, i32>(
         r#"
         INSERT INTO photo (hash, title, fullsize_path, websize_path, thumbnail_path)
         VALUES (?, ?, '/test/full.jpg', '/test/web.jpg', '/test/thumb.jpg')
@@ -39,15 +51,13 @@ pub async fn seed_photo(app: &App, hash: &str, title: &str) -> i32 {
     .bind(title)
     .fetch_one(app.pool())
     .await
-    .expect("Failed to insert test photo");
-
-    result
+    .expect("Failed to insert test photo")
 }
 
 /// Insert a test photo with a specific mat preset.
 /// Returns the photo ID.
 pub async fn seed_photo_with_mat(app: &App, hash: &str, title: &str, mat_preset: &str) -> i32 {
-    let result = sqlx::query_scalar::<_, i32>(
+    sqlx::query_scalar::<_, i32>(
         r#"
         INSERT INTO photo (hash, title, fullsize_path, websize_path, thumbnail_path, mat_preset)
         VALUES (?, ?, '/test/full.jpg', '/test/web.jpg', '/test/thumb.jpg', ?)
@@ -59,15 +69,13 @@ pub async fn seed_photo_with_mat(app: &App, hash: &str, title: &str, mat_preset:
     .bind(mat_preset)
     .fetch_one(app.pool())
     .await
-    .expect("Failed to insert test photo");
-
-    result
+    .expect("Failed to insert test photo")
 }
 
 /// Insert a test album into the database.
 /// Returns the album ID.
 pub async fn seed_album(app: &App, name: &str) -> i32 {
-    let result = sqlx::query_scalar::<_, i32>(
+    sqlx::query_scalar::<_, i32>(
         r#"
         INSERT INTO album (name)
         VALUES (?)
@@ -77,9 +85,7 @@ pub async fn seed_album(app: &App, name: &str) -> i32 {
     .bind(name)
     .fetch_one(app.pool())
     .await
-    .expect("Failed to insert test album");
-
-    result
+    .expect("Failed to insert test album")
 }
 
 /// Link a photo to an album with a given position.
@@ -111,6 +117,106 @@ pub async fn set_current_album(app: &App, album_id: i32) {
     .expect("Failed to set current album");
 }
 
+/// Set when a photo was taken.
+pub async fn set_date_taken(app: &App, photo_id: i32, date_taken: chrono::NaiveDateTime) {
+    sqlx::query("UPDATE photo SET date_taken = ? WHERE id = ?")
+        .bind(date_taken)
+        .bind(photo_id)
+        .execute(app.pool())
+        .await
+        .expect("Failed to set date taken");
+}
+
+/// Set when a photo was last shown, as stored by `/api/next`.
+pub async fn set_last_shown(app: &App, photo_id: i32, last_shown: i64) {
+    sqlx::query("UPDATE photo SET last_shown = ? WHERE id = ?")
+        .bind(last_shown)
+        .bind(photo_id)
+        .execute(app.pool())
+        .await
+        .expect("Failed to set last shown");
+}
+
+/// Move a photo to the trash as if it was deleted `days_ago` days ago.
+pub async fn set_deleted_days_ago(app: &App, photo_id: i32, days_ago: u32) {
+    sqlx::query("UPDATE photo SET deleted_at = datetime('now', '-' || ? || ' days') WHERE id = ?")
+        .bind(days_ago)
+        .bind(photo_id)
+        .execute(app.pool())
+        .await
+        .expect("Failed to set deleted at");
+}
+
+/// Set the rotation mode in settings ("sequential", "shuffle" or "weighted_random").
+pub async fn set_rotation_mode(app: &App, mode: &str) {
+    sqlx::query(
+        r#"
+        UPDATE settings SET rotation_mode = ? WHERE id = 1
+        "#,
+    )
+    .bind(mode)
+    .execute(app.pool())
+    .await
+    .expect("Failed to set rotation mode");
+}
+
+/// Insert a test display and return its ID.
+pub async fn seed_display(app: &App, name: &str, token: &str) -> i32 {
+    sqlx::query_scalar::<_, i32>(
+        r#"
+        INSERT INTO display (name, token) VALUES (?, ?)
+        RETURNING id
+        "#,
+    )
+    .bind(name)
+    .bind(token)
+    .fetch_one(app.pool())
+    .await
+    .expect("Failed to seed display")
+}
+
+/// Insert a test schedule that is active all the time and return its ID. `album_id` of
+/// `None` shows nothing.
+pub async fn seed_schedule(app: &App, name: &str, album_id: Option<i32>, priority: i32) -> i32 {
+    sqlx::query_scalar::<_, i32>(
+        r#"
+        INSERT INTO schedule (name, album_id, priority) VALUES (?, ?, ?)
+        RETURNING id
+        "#,
+    )
+    .bind(name)
+    .bind(album_id)
+    .bind(priority)
+    .fetch_one(app.pool())
+    .await
+    .expect("Failed to seed schedule")
+}
+
+/// Build a perceptual hash string (as stored in `photo.hash`) from raw bits.
+/// Photos seeded with hashes a few bits apart look like near-duplicates.
+pub fn test_hash(bits: u64) -> String {
+    image_hasher::ImageHash::<Box<[u8]>>::from_bytes(&bits.to_be_bytes())
+        .expect("8 bytes is a valid hash")
+        .to_base64()
+}
+
+/// Insert a test job with the given status.
+/// Returns the job ID.
+pub async fn seed_job(app: &App, filename: &str, status: &str) -> i32 {
+    sqlx::query_scalar::<_, i32>(
+        r#"
+        INSERT INTO job (filename, staged_path, status)
+        VALUES (?, '/test/staging/upload.jpg', ?)
+        RETURNING id
+        "#,
+    )
+    .bind(filename)
+    .bind(status)
+    .fetch_one(app.pool())
+    .await
+    .expect("Failed to insert test job")
+}
+
 /// Create a test router that includes extra routes (upload, image serving).
 /// This is useful for testing endpoints that aren't generated by the macro.
 /// Includes a 50MB body limit to allow large photo uploads in tests.
@@ -118,8 +224,24 @@ pub fn create_test_router_with_extras(app: Arc<App>) -> Router {
     async fn serve_image(
         State(state): State<Arc<App>>,
         Path(id): Path<i32>,
+        Query(query): Query<ImageQuery>,
+        request: Request,
+    ) -> axum::response::Response {
+        state.serve_image(id, query, request).await
+    }
+
+    async fn backup(
+        State(state): State<Arc<App>>,
+        Query(query): Query<BackupQuery>,
     ) -> axum::response::Response {
-        state.serve_image(id).await
+        state.backup(query).await
+    }
+
+    async fn export_album(
+        State(state): State<Arc<App>>,
+        Path(id): Path<i32>,
+    ) -> axum::response::Response {
+        state.export_album(id).await
     }
 
     async fn upload_photo(
@@ -132,6 +254,8 @@ pub fn create_test_router_with_extras(app: Arc<App>) -> Router {
     let api_router = app.clone().router();
     let extra_routes = Router::new()
         .route("/api/images/{id}", get(serve_image))
+        .route("/api/backup", get(backup))
+        .route("/api/albums/{id}/export", get(export_album))
         .route("/api/photos", post(upload_photo))
         .with_state(app);
 
@@ -139,3 +263,40 @@ pub fn create_test_router_with_extras(app: Arc<App>) -> Router {
         .merge(extra_routes)
         .layer(DefaultBodyLimit::max(50 * 1024 * 1024)) // 50MB limit for uploads
 }
+
+/// Insert a pending tag suggestion for a photo.
+/// Returns the suggestion ID.
+pub async fn seed_suggestion(app: &App, photo_id: i32, label: &str, confidence: f64) -> i32 {
+    sqlx::query_scalar::<_, i32>(
+        "INSERT INTO tag_suggestion (photo_id, label, confidence) VALUES (?, ?, ?) RETURNING id",
+    )
+    .bind(photo_id)
+    .bind(label)
+    .bind(confidence)
+    .fetch_one(app.pool())
+    .await
+    .expect("Failed to insert test suggestion")
+}
+
+/// Classifier that suggests the same labels for every photo, so tests don't need a model.
+#[derive(Debug, Default)]
+pub struct StubClassifier {
+    pub classifications: Vec<Classification>,
+}
+
+impl StubClassifier {
+    pub fn new(classifications: Vec<Classification>) -> Self {
+        Self { classifications }
+    }
+}
+
+#[async_trait::async_trait]
+impl Classifier for StubClassifier {
+    fn name(&self) -> &'static str {
+        "stub"
+    }
+
+    async fn classify(&self, _websize: &FsPath) -> anyhow::Result<Vec<Classification>> {
+        Ok(self.classifications.clone())
+    }
+}
@@ -0,0 +1,24 @@
+//! Background task that empties the trash.
+//!
+//! Deleted photos wait in the trash so a misclick can be undone. Once they have been there
+//! longer than the retention period they are purged for good, files and all.
+
+use crate::App;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::error;
+
+/// How often the trash is checked for photos past the retention period
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Spawn a task that purges expired photos from the trash now, then every hour.
+pub fn spawn_purger(app: Arc<App>) {
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = app.purge_expired_trash().await {
+                error!("Failed to purge the trash: {e:#}");
+            }
+            tokio::time::sleep(PURGE_INTERVAL).await;
+        }
+    });
+}
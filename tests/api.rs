@@ -7,11 +7,22 @@ use axum::{
     body::Body,
     http::{Request, StatusCode},
 };
-use pictureframe_common::{AlbumID, CreateAlbumRequest, Update, UpdateAlbumRequest, UpdatePhotoRequest, UpdateSettingsRequest};
 use http_body_util::BodyExt;
+use pictureframe::app::DEFAULT_TRASH_RETENTION_DAYS;
+use pictureframe::classifier::Classification;
 use pictureframe::test_helpers::{
-    create_test_app, create_test_router_with_extras, seed_album, seed_album_photo, seed_photo,
-    seed_photo_with_mat, set_current_album,
+    StubClassifier, create_test_app, create_test_app_with_classifier,
+    create_test_router_with_extras, seed_album, seed_album_photo, seed_display, seed_job,
+    seed_photo, seed_photo_with_mat, seed_schedule, seed_suggestion, set_current_album,
+    set_date_taken, set_deleted_days_ago, set_last_shown, set_rotation_mode, test_hash,
+};
+use pictureframe_common::{
+    AlbumEnd, AlbumID, AlbumPhotoSort, BulkPhotoOperation, BulkPhotoRequest, CreateAlbumRequest,
+    CreateDisplayRequest, DisplayID, MergePhotosRequest, MergeTagsRequest, MoveAlbumPhotoRequest,
+    PhotoID, ReorderAlbumRequest, RotationMode, ScheduleAction, ScheduleRequest, SmartRule,
+    SortAlbumRequest, SuggestionKind, TagID, TagPhotosRequest, TagRequest, Update,
+    UpdateAlbumRequest, UpdateDisplayRequest, UpdatePhotoRequest, UpdateSettingsRequest,
+    VirtualAlbum,
 };
 use serde_json::Value;
 use tower::ServiceExt;
@@ -100,7 +111,8 @@ async fn test_get_photos_empty() {
     let (status, json) = get_json(router, "/api/photos").await;
 
     assert_eq!(status, StatusCode::OK);
-    assert_eq!(json, serde_json::json!([]));
+    assert_eq!(json["photos"], serde_json::json!([]));
+    assert_eq!(json["total"], 0);
 }
 
 #[tokio::test]
@@ -115,8 +127,9 @@ async fn test_get_photos_with_data() {
     let (status, json) = get_json(router, "/api/photos").await;
 
     assert_eq!(status, StatusCode::OK);
-    let photos = json.as_array().unwrap();
+    let photos = json["photos"].as_array().unwrap();
     assert_eq!(photos.len(), 2);
+    assert_eq!(json["total"], 2);
 
     // Check that both photos are present (order may vary with same timestamp)
     let titles: Vec<&str> = photos
@@ -139,6 +152,15 @@ async fn test_get_photo_found() {
     assert_eq!(status, StatusCode::OK);
     assert_eq!(json["title"], "Test Photo");
     assert_eq!(json["id"], photo_id);
+    assert_eq!(json["url"], format!("/api/images/{}", photo_id));
+    assert_eq!(
+        json["thumbnail_url"],
+        format!("/api/images/{}?variant=thumbnail", photo_id)
+    );
+    assert_eq!(
+        json["full_url"],
+        format!("/api/images/{}?variant=full", photo_id)
+    );
 }
 
 #[tokio::test]
@@ -152,6 +174,356 @@ async fn test_get_photo_not_found() {
     assert!(json["error"].as_str().unwrap().contains("not found"));
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Photo Query Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// IDs of the photos returned for a `/api/photos` query string, in the order returned
+async fn photo_page(app: &std::sync::Arc<pictureframe::App>, query: &str) -> (Vec<i64>, i64) {
+    let (status, json) = get_json(app.clone().router(), &format!("/api/photos?{}", query)).await;
+    assert_eq!(status, StatusCode::OK, "{:?}", json);
+    let ids = json["photos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].as_i64().unwrap())
+        .collect();
+    (ids, json["total"].as_i64().unwrap())
+}
+
+/// Noon on the given day
+fn taken(ymd: &str) -> chrono::NaiveDateTime {
+    date(ymd).and_hms_opt(12, 0, 0).unwrap()
+}
+
+#[tokio::test]
+async fn test_get_photos_paged() {
+    let app = create_test_app().await;
+    let ids: Vec<i64> = seed_photos(&app, 5)
+        .await
+        .into_iter()
+        .map(i64::from)
+        .collect();
+
+    // Newest first, with the id breaking ties between photos added in the same second
+    let (all, total) = photo_page(&app, "").await;
+    assert_eq!(all, ids.iter().rev().copied().collect::<Vec<_>>());
+    assert_eq!(total, 5);
+
+    assert_eq!(photo_page(&app, "limit=2").await, (all[..2].to_vec(), 5));
+    assert_eq!(
+        photo_page(&app, "offset=2&limit=2").await,
+        (all[2..4].to_vec(), 5)
+    );
+    assert_eq!(
+        photo_page(&app, "offset=4&limit=2").await,
+        (all[4..].to_vec(), 5)
+    );
+    assert_eq!(photo_page(&app, "offset=10").await, (Vec::new(), 5));
+    assert_eq!(
+        photo_page(&app, "order=asc&limit=1").await,
+        (vec![ids[0]], 5)
+    );
+
+    let (status, json) = get_json(app.router(), "/api/photos?offset=2&limit=1").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["offset"], 2);
+}
+
+#[tokio::test]
+async fn test_get_photos_sorted() {
+    let app = create_test_app().await;
+    let banana = seed_photo(&app, "hash1", "banana").await as i64;
+    let apple = seed_photo(&app, "hash2", "Apple").await as i64;
+    let cherry = seed_photo(&app, "hash3", "cherry").await as i64;
+    set_date_taken(&app, banana as i32, taken("2021-05-01")).await;
+    set_date_taken(&app, cherry as i32, taken("2019-05-01")).await;
+    set_last_shown(&app, apple as i32, 7).await;
+    set_last_shown(&app, cherry as i32, 3).await;
+
+    assert_eq!(
+        photo_page(&app, "sort=title").await.0,
+        vec![apple, banana, cherry]
+    );
+    assert_eq!(
+        photo_page(&app, "sort=title&order=desc").await.0,
+        vec![cherry, banana, apple]
+    );
+    // Photos without a value come last in either order
+    assert_eq!(
+        photo_page(&app, "sort=taken").await.0,
+        vec![banana, cherry, apple]
+    );
+    assert_eq!(
+        photo_page(&app, "sort=taken&order=asc").await.0,
+        vec![cherry, banana, apple]
+    );
+    assert_eq!(
+        photo_page(&app, "sort=last_shown").await.0,
+        vec![apple, cherry, banana]
+    );
+
+    let (status, _) = get_json(app.router(), "/api/photos?sort=colour").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_get_photos_filtered_by_album() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    let album = seed_album(&app, "Album").await;
+    seed_album_photo(&app, album, ids[0], 0).await;
+    seed_album_photo(&app, album, ids[2], 1).await;
+    set_date_taken(&app, ids[1], taken("2020-01-01")).await;
+    let rule = SmartRule {
+        taken_after: Some(date("2019-01-01")),
+        ..Default::default()
+    };
+    let (_, json) = post_json(
+        app.clone().router(),
+        "/api/albums",
+        &smart_album_request("Taken", rule),
+    )
+    .await;
+    let smart = json["id"].as_i64().unwrap();
+
+    let (page, total) = photo_page(&app, &format!("album={}&sort=created&order=asc", album)).await;
+    assert_eq!(page, vec![ids[0] as i64, ids[2] as i64]);
+    assert_eq!(total, 2);
+    assert_eq!(
+        photo_page(&app, &format!("album={}", smart)).await.0,
+        vec![ids[1] as i64]
+    );
+    assert_eq!(
+        photo_page(&app, "without_album=true").await.0,
+        vec![ids[1] as i64]
+    );
+
+    let (status, _) = get_json(app.router(), "/api/photos?album=999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_get_photos_filtered_by_date_and_text() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    let sale = seed_photo(&app, "hash9", "100% off").await as i64;
+    set_date_taken(&app, ids[0], taken("2020-01-01")).await;
+    set_date_taken(&app, ids[1], taken("2020-06-30")).await;
+    set_date_taken(&app, ids[2], taken("2021-01-01")).await;
+
+    let (page, total) = photo_page(&app, "taken_after=2020-01-01&taken_before=2020-06-30").await;
+    assert_eq!(page, vec![ids[1] as i64, ids[0] as i64]);
+    assert_eq!(total, 2);
+    assert_eq!(
+        photo_page(&app, "taken_after=2020-07-01").await.0,
+        vec![ids[2] as i64]
+    );
+
+    // Case-insensitive, and % and _ are matched literally
+    assert_eq!(
+        photo_page(&app, "search=PHOTO%201").await.0,
+        vec![ids[1] as i64]
+    );
+    assert_eq!(photo_page(&app, "search=%25").await.0, vec![sale]);
+    assert!(photo_page(&app, "search=_").await.0.is_empty());
+
+    // Filters combine, and the total counts every match rather than the page
+    let (page, total) = photo_page(&app, "search=photo&taken_after=2020-01-01&limit=1").await;
+    assert_eq!(page, vec![ids[2] as i64]);
+    assert_eq!(total, 3);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Search Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+async fn search(app: &std::sync::Arc<pictureframe::App>, q: &str) -> serde_json::Value {
+    let query = serde_urlencoded::to_string([("q", q)]).unwrap();
+    let (status, json) = get_json(app.clone().router(), &format!("/api/search?{}", query)).await;
+    assert_eq!(status, StatusCode::OK, "{:?}", json);
+    json
+}
+
+fn photo_hits(results: &serde_json::Value) -> Vec<i64> {
+    results["photos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["photo"]["id"].as_i64().unwrap())
+        .collect()
+}
+
+fn album_hits(results: &serde_json::Value) -> Vec<i64> {
+    results["albums"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["album"]["id"].as_i64().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_search_photos() {
+    let app = create_test_app().await;
+    let beach = seed_photo(&app, "hash1", "Grandma at the beach").await as i64;
+    let garden = seed_photo(&app, "hash2", "Grandma in the garden").await as i64;
+    let sunset = seed_photo(&app, "hash3", "Sunset").await;
+    tag_photos(&app, &[beach as i32, sunset], &["family"]).await;
+
+    // Every word has to match, each as the start of a word
+    let json = search(&app, "grand beach").await;
+    assert_eq!(photo_hits(&json), vec![beach]);
+    assert_eq!(
+        json["photos"][0]["snippet"],
+        "<mark>Grandma</mark> at the <mark>beach</mark>"
+    );
+    assert_eq!(json["photos"][0]["photo"]["title"], "Grandma at the beach");
+    assert_eq!(
+        json["photos"][0]["photo"]["tags"],
+        serde_json::json!(["family"])
+    );
+
+    let mut ids = photo_hits(&search(&app, "GRANDMA").await);
+    ids.sort();
+    assert_eq!(ids, vec![beach, garden]);
+
+    // Words are stemmed
+    assert_eq!(photo_hits(&search(&app, "beaches").await), vec![beach]);
+    assert!(
+        search(&app, "mountain").await["photos"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_search_ranks_better_matches_first() {
+    let app = create_test_app().await;
+    let once = seed_photo(
+        &app,
+        "hash1",
+        "A long walk along the coast, then lunch, then a swim at the beach",
+    )
+    .await as i64;
+    let short = seed_photo(&app, "hash2", "Beach").await as i64;
+
+    assert_eq!(photo_hits(&search(&app, "beach").await), vec![short, once]);
+}
+
+#[tokio::test]
+async fn test_search_follows_edits_and_deletes() {
+    let app = create_test_app().await;
+    let id = seed_photo(&app, "hash1", "Old title").await;
+    let req = UpdatePhotoRequest {
+        title: Some(Update::Set("New title".to_string())),
+        artist: Some(Update::Set("Ansel Adams".to_string())),
+        copyright: None,
+        notes: None,
+        date_taken: None,
+        mat_preset: None,
+    };
+    let (status, _) = put_json(app.clone().router(), &format!("/api/photos/{}", id), &req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert!(
+        search(&app, "old").await["photos"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(photo_hits(&search(&app, "new").await), vec![id as i64]);
+    let json = search(&app, "ansel").await;
+    assert_eq!(json["photos"][0]["snippet"], "<mark>Ansel</mark> Adams");
+
+    let (status, _) = delete(app.clone().router(), &format!("/api/photos/{}", id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        search(&app, "new").await["photos"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_search_albums() {
+    let app = create_test_app().await;
+    let album = seed_album(&app, "Summer at the lake").await;
+    let photo = seed_photo(&app, "hash1", "Lake house").await;
+    seed_album_photo(&app, album, photo, 0).await;
+
+    let json = search(&app, "lake").await;
+    assert_eq!(album_hits(&json), vec![album as i64]);
+    assert_eq!(
+        json["albums"][0]["album"]["photos"],
+        serde_json::json!([photo])
+    );
+    assert_eq!(
+        json["albums"][0]["snippet"],
+        "Summer at the <mark>lake</mark>"
+    );
+    assert_eq!(photo_hits(&json), vec![photo as i64]);
+
+    let req = UpdateAlbumRequest {
+        name: Some("Winter".to_string()),
+        notes: Some(Update::Set("Skiing with the cousins".to_string())),
+        smart_rule: None,
+    };
+    let (status, _) = put_json(
+        app.clone().router(),
+        &format!("/api/albums/{}", album),
+        &req,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        search(&app, "summer").await["albums"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        album_hits(&search(&app, "cousin").await),
+        vec![album as i64]
+    );
+
+    let (status, _) = delete(app.clone().router(), &format!("/api/albums/{}", album)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        search(&app, "winter").await["albums"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_search_escapes_text_and_query() {
+    let app = create_test_app().await;
+    let id = seed_photo(&app, "hash1", "<b>Tom & Jerry's</b> \"cartoon\"").await as i64;
+
+    let json = search(&app, "jerry's").await;
+    assert_eq!(photo_hits(&json), vec![id]);
+    assert_eq!(
+        json["photos"][0]["snippet"],
+        "&lt;b&gt;Tom &amp; <mark>Jerry&#39;s</mark>&lt;/b&gt; &quot;cartoon&quot;"
+    );
+
+    // Query syntax is treated as plain text
+    assert_eq!(photo_hits(&search(&app, "\"cartoon").await), vec![id]);
+    assert!(
+        search(&app, "NOT OR AND (").await["photos"]
+            .as_array()
+            .unwrap()
+            .is_empty()
+    );
+
+    let json = search(&app, "   ").await;
+    assert_eq!(json, serde_json::json!({"photos": [], "albums": []}));
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Albums Tests
 // ─────────────────────────────────────────────────────────────────────────────
@@ -231,6 +603,7 @@ async fn test_get_settings_default() {
     assert_eq!(status, StatusCode::OK);
     assert!(json["current_album"].is_null());
     assert_eq!(json["interval"], 180); // Default 3 minutes
+    assert_eq!(json["mode"], "sequential");
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -349,6 +722,7 @@ async fn test_update_settings_set_album() {
     let req = UpdateSettingsRequest {
         current_album_id: Some(Update::Set(AlbumID(album_id))),
         interval_seconds: None,
+        rotation_mode: None,
     };
 
     let router = app.clone().router();
@@ -369,6 +743,7 @@ async fn test_update_settings_set_nonexistent_album() {
     let req = UpdateSettingsRequest {
         current_album_id: Some(Update::Set(AlbumID(999))),
         interval_seconds: None,
+        rotation_mode: None,
     };
 
     let router = app.router();
@@ -388,6 +763,7 @@ async fn test_update_settings_remove_album() {
     let req = UpdateSettingsRequest {
         current_album_id: Some(Update::Remove),
         interval_seconds: None,
+        rotation_mode: None,
     };
 
     let router = app.clone().router();
@@ -407,6 +783,7 @@ async fn test_update_settings_set_interval() {
     let req = UpdateSettingsRequest {
         current_album_id: None,
         interval_seconds: Some(60),
+        rotation_mode: None,
     };
 
     let router = app.clone().router();
@@ -429,6 +806,7 @@ async fn test_update_settings_both() {
     let req = UpdateSettingsRequest {
         current_album_id: Some(Update::Set(AlbumID(album_id))),
         interval_seconds: Some(30),
+        rotation_mode: None,
     };
 
     let router = app.clone().router();
@@ -443,295 +821,2628 @@ async fn test_update_settings_both() {
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
-// Album CRUD Tests
+// Rotation Mode Tests
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Seed `count` photos into the library and return their IDs
+async fn seed_photos(app: &pictureframe::App, count: usize) -> Vec<i32> {
+    let mut ids = Vec::new();
+    for i in 0..count {
+        ids.push(seed_photo(app, &format!("hash{}", i), &format!("Photo {}", i)).await);
+    }
+    ids
+}
+
+/// Call /api/next `count` times and return the photo IDs served
+async fn next_photo_ids(app: &std::sync::Arc<pictureframe::App>, count: usize) -> Vec<i64> {
+    let mut ids = Vec::new();
+    for _ in 0..count {
+        let (status, json) = get_json(app.clone().router(), "/api/next").await;
+        assert_eq!(status, StatusCode::OK);
+        ids.push(json["photo"]["id"].as_i64().unwrap());
+    }
+    ids
+}
+
 #[tokio::test]
-async fn test_create_album() {
+async fn test_update_settings_rotation_mode() {
     let app = create_test_app().await;
 
-    let req = CreateAlbumRequest {
-        name: "My New Album".to_string(),
-        notes: Some("Album notes".to_string()),
+    let req = UpdateSettingsRequest {
+        current_album_id: None,
+        interval_seconds: None,
+        rotation_mode: Some(RotationMode::WeightedRandom),
     };
 
     let router = app.clone().router();
-    let (status, json) = post_json(router, "/api/albums", &req).await;
-
+    let (status, _) = put_json(router, "/api/settings", &req).await;
     assert_eq!(status, StatusCode::OK);
-    assert_eq!(json["name"], "My New Album");
-    assert_eq!(json["notes"], "Album notes");
-    assert!(json["id"].as_i64().is_some());
-    assert_eq!(json["photos"], serde_json::json!([]));
 
-    // Verify album was persisted
     let router = app.router();
-    let (status, json) = get_json(router, "/api/albums").await;
-    assert_eq!(status, StatusCode::OK);
-    let albums = json.as_array().unwrap();
-    assert_eq!(albums.len(), 1);
-    assert_eq!(albums[0]["name"], "My New Album");
+    let (_, json) = get_json(router, "/api/settings").await;
+    assert_eq!(json["mode"], "weighted_random");
 }
 
 #[tokio::test]
-async fn test_create_album_without_notes() {
+async fn test_update_settings_rejects_unknown_rotation_mode() {
     let app = create_test_app().await;
 
-    let req = CreateAlbumRequest {
-        name: "Simple Album".to_string(),
-        notes: None,
-    };
+    let router = app.clone().router();
+    let req = serde_json::json!({ "rotation_mode": "backwards" });
+    let (status, _) = put_json(router, "/api/settings", &req).await;
+    assert!(status.is_client_error());
 
     let router = app.router();
-    let (status, json) = post_json(router, "/api/albums", &req).await;
-
-    assert_eq!(status, StatusCode::OK);
-    assert_eq!(json["name"], "Simple Album");
-    assert!(json["notes"].is_null());
+    let (_, json) = get_json(router, "/api/settings").await;
+    assert_eq!(json["mode"], "sequential");
 }
 
 #[tokio::test]
-async fn test_update_album_name() {
+async fn test_shuffle_shows_every_photo_once_per_cycle() {
     let app = create_test_app().await;
+    let mut ids: Vec<i64> = seed_photos(&app, 5)
+        .await
+        .into_iter()
+        .map(i64::from)
+        .collect();
+    ids.sort();
+    set_rotation_mode(&app, "shuffle").await;
 
-    let album_id = seed_album(&app, "Original Name").await;
+    let shown = next_photo_ids(&app, 15).await;
+    for cycle in shown.chunks(5) {
+        let mut cycle = cycle.to_vec();
+        cycle.sort();
+        assert_eq!(
+            cycle, ids,
+            "each cycle should show every photo exactly once"
+        );
+    }
+    for pair in shown.windows(2) {
+        assert_ne!(
+            pair[0], pair[1],
+            "no photo should be shown twice in a row: {:?}",
+            shown
+        );
+    }
+}
 
-    let req = UpdateAlbumRequest {
-        name: Some("Updated Name".to_string()),
-        notes: None,
-    };
+#[tokio::test]
+async fn test_shuffle_survives_restart() {
+    let app = create_test_app().await;
+    seed_photos(&app, 5).await;
+    set_rotation_mode(&app, "shuffle").await;
+
+    let mut shown = next_photo_ids(&app, 2).await;
+
+    // A new App on the same database picks up the cycle where the old one left off
+    let restarted = std::sync::Arc::new(pictureframe::App::from_pool(app.pool().clone()));
+    shown.extend(next_photo_ids(&restarted, 3).await);
+
+    let mut unique = shown.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(
+        unique.len(),
+        5,
+        "cycle should continue after a restart: {:?}",
+        shown
+    );
+}
 
-    let router = app.clone().router();
-    let (status, _) = put_json(router, &format!("/api/albums/{}", album_id), &req).await;
+#[tokio::test]
+async fn test_shuffle_restarts_when_album_changes() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 4).await;
+    let album_id = seed_album(&app, "Two").await;
+    seed_album_photo(&app, album_id, ids[0], 0).await;
+    seed_album_photo(&app, album_id, ids[1], 1).await;
+    set_rotation_mode(&app, "shuffle").await;
+
+    // Start a cycle over the whole library, then switch to the album part way through
+    next_photo_ids(&app, 1).await;
+    let req = UpdateSettingsRequest {
+        current_album_id: Some(Update::Set(AlbumID(album_id))),
+        interval_seconds: None,
+        rotation_mode: None,
+    };
+    let (status, _) = put_json(app.clone().router(), "/api/settings", &req).await;
     assert_eq!(status, StatusCode::OK);
 
-    // Verify the update
-    let router = app.router();
-    let (_, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
-    assert_eq!(json["name"], "Updated Name");
+    let mut shown = next_photo_ids(&app, 2).await;
+    shown.sort();
+    assert_eq!(shown, vec![ids[0] as i64, ids[1] as i64]);
 }
 
 #[tokio::test]
-async fn test_update_album_notes() {
+async fn test_weighted_random_never_repeats_current_photo() {
     let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    set_rotation_mode(&app, "weighted_random").await;
+
+    let shown = next_photo_ids(&app, 30).await;
+    for pair in shown.windows(2) {
+        assert_ne!(
+            pair[0], pair[1],
+            "no photo should be shown twice in a row: {:?}",
+            shown
+        );
+    }
+    for id in ids {
+        assert!(shown.contains(&(id as i64)), "photo {} was never shown", id);
+    }
+}
 
-    let album_id = seed_album(&app, "Test Album").await;
+#[tokio::test]
+async fn test_weighted_random_favors_least_recently_shown() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    set_rotation_mode(&app, "weighted_random").await;
+
+    // Photo 0 was shown one photo ago, photo 1 is on screen and photo 2 hasn't been shown for
+    // 99 photos, so it should win about 99 times in 100
+    let mut picked_stale = 0;
+    for _ in 0..50 {
+        sqlx::query("UPDATE settings SET show_count = 100 WHERE id = 1")
+            .execute(app.pool())
+            .await
+            .unwrap();
+        for (id, last_shown) in ids.iter().zip([99, 100, 1]) {
+            sqlx::query("UPDATE photo SET last_shown = ? WHERE id = ?")
+                .bind(last_shown)
+                .bind(id)
+                .execute(app.pool())
+                .await
+                .unwrap();
+        }
+        let shown = next_photo_ids(&app, 1).await;
+        assert_ne!(
+            shown[0], ids[1] as i64,
+            "the photo on screen should not be picked"
+        );
+        if shown[0] == ids[2] as i64 {
+            picked_stale += 1;
+        }
+    }
+    assert!(
+        picked_stale >= 40,
+        "stale photo picked {} of 50 times",
+        picked_stale
+    );
+}
 
-    // Set notes
-    let req = UpdateAlbumRequest {
-        name: None,
-        notes: Some(Update::Set("New notes".to_string())),
+// ─────────────────────────────────────────────────────────────────────────────
+// Display Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Call /api/next for the display with `token` and return the photo ID served
+async fn next_photo_id_for(app: &std::sync::Arc<pictureframe::App>, token: &str) -> i64 {
+    let uri = format!("/api/next?display={}", token);
+    let (status, json) = get_json(app.clone().router(), &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    json["photo"]["id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn test_create_and_list_displays() {
+    let app = create_test_app().await;
+
+    let req = CreateDisplayRequest {
+        name: "Kitchen".to_string(),
     };
+    let (status, json) = post_json(app.clone().router(), "/api/displays", &req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "Kitchen");
+    assert_eq!(json["mode"], "sequential");
+    assert_eq!(json["interval"], 180);
+    assert!(json["current_album"].is_null());
+    assert!(json["last_seen_at"].is_null());
+    let token = json["token"].as_str().unwrap();
+    assert_eq!(token.len(), 16);
 
-    let router = app.clone().router();
-    let (status, _) = put_json(router, &format!("/api/albums/{}", album_id), &req).await;
+    let (status, json) = get_json(app.router(), "/api/displays").await;
     assert_eq!(status, StatusCode::OK);
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["name"], "Kitchen");
+}
 
-    // Verify notes were set
-    let router = app.clone().router();
-    let (_, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
-    assert_eq!(json["notes"], "New notes");
+#[tokio::test]
+async fn test_create_display_tokens_are_unique() {
+    let app = create_test_app().await;
 
-    // Remove notes
-    let req = UpdateAlbumRequest {
-        name: None,
-        notes: Some(Update::Remove),
+    let req = CreateDisplayRequest {
+        name: "Frame".to_string(),
     };
+    let (_, first) = post_json(app.clone().router(), "/api/displays", &req).await;
+    let (_, second) = post_json(app.router(), "/api/displays", &req).await;
+    assert_ne!(first["token"], second["token"]);
+}
 
-    let router = app.clone().router();
-    let (status, _) = put_json(router, &format!("/api/albums/{}", album_id), &req).await;
+#[tokio::test]
+async fn test_create_display_empty_name_fails() {
+    let app = create_test_app().await;
+
+    let req = CreateDisplayRequest {
+        name: "  ".to_string(),
+    };
+    let (status, _) = post_json(app.router(), "/api/displays", &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_update_display() {
+    let app = create_test_app().await;
+    let album_id = seed_album(&app, "Vacation").await;
+    let display_id = seed_display(&app, "Kitchen", "kitchen").await;
+
+    let req = UpdateDisplayRequest {
+        name: Some("Living Room".to_string()),
+        current_album_id: Some(Update::Set(AlbumID(album_id))),
+        interval_seconds: Some(60),
+        rotation_mode: Some(RotationMode::Shuffle),
+        mat_preset: Some(Update::Set("gallery".to_string())),
+    };
+    let uri = format!("/api/displays/{}", display_id);
+    let (status, json) = put_json(app.clone().router(), &uri, &req).await;
     assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "Living Room");
+    assert_eq!(json["current_album"]["album"], album_id);
+    assert_eq!(json["interval"], 60);
+    assert_eq!(json["mode"], "shuffle");
+    assert_eq!(json["mat_preset"], "gallery");
 
-    // Verify notes were removed
-    let router = app.router();
-    let (_, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
-    assert!(json["notes"].is_null());
+    // The shared settings are untouched
+    let (_, json) = get_json(app.router(), "/api/settings").await;
+    assert!(json["current_album"].is_null());
+    assert_eq!(json["mode"], "sequential");
+}
+
+#[tokio::test]
+async fn test_update_display_rejects_invalid_values() {
+    let app = create_test_app().await;
+    let display_id = seed_display(&app, "Kitchen", "kitchen").await;
+    let uri = format!("/api/displays/{}", display_id);
+
+    let req = UpdateDisplayRequest {
+        interval_seconds: Some(0),
+        ..Default::default()
+    };
+    let (status, _) = put_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let req = UpdateDisplayRequest {
+        mat_preset: Some(Update::Set("bogus".to_string())),
+        ..Default::default()
+    };
+    let (status, _) = put_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let req = UpdateDisplayRequest {
+        current_album_id: Some(Update::Set(AlbumID(999))),
+        ..Default::default()
+    };
+    let (status, _) = put_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let req = UpdateDisplayRequest {
+        name: Some("Elsewhere".to_string()),
+        ..Default::default()
+    };
+    let (status, _) = put_json(app.router(), "/api/displays/999", &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_delete_display() {
+    let app = create_test_app().await;
+    seed_photo(&app, "hash1", "Photo").await;
+    let display_id = seed_display(&app, "Kitchen", "kitchen").await;
+
+    let uri = format!("/api/displays/{}", display_id);
+    let (status, _) = delete(app.clone().router(), &uri).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = get_json(app.clone().router(), &uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // The viewer link stops working
+    let (status, _) = get_json(app.clone().router(), "/api/next?display=kitchen").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = delete(app.router(), &uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_next_photo_unknown_display() {
+    let app = create_test_app().await;
+    seed_photo(&app, "hash1", "Photo").await;
+
+    let (status, _) = get_json(app.router(), "/api/next?display=nope").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_displays_rotate_independently() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    seed_display(&app, "Kitchen", "kitchen").await;
+    seed_display(&app, "Hallway", "hallway").await;
+
+    assert_eq!(next_photo_id_for(&app, "kitchen").await, ids[0] as i64);
+    assert_eq!(next_photo_id_for(&app, "kitchen").await, ids[1] as i64);
+    // Another display starts from its own beginning
+    assert_eq!(next_photo_id_for(&app, "hallway").await, ids[0] as i64);
+    assert_eq!(next_photo_id_for(&app, "kitchen").await, ids[2] as i64);
+    // And so does the shared rotation
+    assert_eq!(next_photo_ids(&app, 1).await, vec![ids[0] as i64]);
+
+    let (_, json) = get_json(app.router(), "/api/displays").await;
+    let kitchen = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["name"] == "Kitchen")
+        .unwrap();
+    assert!(kitchen["last_seen_at"].is_string());
+}
+
+#[tokio::test]
+async fn test_display_uses_its_own_album_and_interval() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    let album_id = seed_album(&app, "Just one").await;
+    seed_album_photo(&app, album_id, ids[2], 0).await;
+    let display_id = seed_display(&app, "Kitchen", "kitchen").await;
+
+    let req = UpdateDisplayRequest {
+        current_album_id: Some(Update::Set(AlbumID(album_id))),
+        interval_seconds: Some(45),
+        ..Default::default()
+    };
+    let uri = format!("/api/displays/{}", display_id);
+    put_json(app.clone().router(), &uri, &req).await;
+
+    let (status, json) = get_json(app.router(), "/api/next?display=kitchen").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["photo"]["id"], ids[2]);
+    assert_eq!(json["interval"], 45);
+}
+
+#[tokio::test]
+async fn test_display_mat_override() {
+    let app = create_test_app().await;
+    seed_photo_with_mat(&app, "hash1", "Photo", "modern").await;
+    let display_id = seed_display(&app, "Kitchen", "kitchen").await;
+
+    // Without an override the photo's own mat is used
+    let (_, json) = get_json(app.clone().router(), "/api/next?display=kitchen").await;
+    assert_eq!(json["mat_style"]["name"], "modern");
+
+    let req = UpdateDisplayRequest {
+        mat_preset: Some(Update::Set("gallery".to_string())),
+        ..Default::default()
+    };
+    let uri = format!("/api/displays/{}", display_id);
+    put_json(app.clone().router(), &uri, &req).await;
+
+    let (_, json) = get_json(app.clone().router(), "/api/next?display=kitchen").await;
+    assert_eq!(json["mat_style"]["name"], "gallery");
+
+    // The shared rotation keeps the photo's mat
+    let (_, json) = get_json(app.router(), "/api/next").await;
+    assert_eq!(json["mat_style"]["name"], "modern");
+}
+
+#[tokio::test]
+async fn test_displays_shuffle_independently() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 4).await;
+    set_rotation_mode(&app, "shuffle").await;
+    let display_id = seed_display(&app, "Kitchen", "kitchen").await;
+
+    let req = UpdateDisplayRequest {
+        rotation_mode: Some(RotationMode::Shuffle),
+        ..Default::default()
+    };
+    let uri = format!("/api/displays/{}", display_id);
+    put_json(app.clone().router(), &uri, &req).await;
+
+    // Interleaved, each still sees every photo once per cycle
+    let mut kitchen = Vec::new();
+    let mut shared = Vec::new();
+    for _ in 0..4 {
+        kitchen.push(next_photo_id_for(&app, "kitchen").await);
+        shared.extend(next_photo_ids(&app, 1).await);
+    }
+    kitchen.sort();
+    shared.sort();
+    let expected: Vec<i64> = ids.iter().map(|&id| id as i64).collect();
+    assert_eq!(kitchen, expected);
+    assert_eq!(shared, expected);
+}
+
+#[tokio::test]
+async fn test_display_weighted_random_uses_its_own_history() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    let display_id = seed_display(&app, "Kitchen", "kitchen").await;
+
+    let req = UpdateDisplayRequest {
+        rotation_mode: Some(RotationMode::WeightedRandom),
+        ..Default::default()
+    };
+    let uri = format!("/api/displays/{}", display_id);
+    put_json(app.clone().router(), &uri, &req).await;
+
+    // Another display has just shown photo 2, but the kitchen hasn't shown it for 99 photos
+    let mut picked_stale = 0;
+    for _ in 0..50 {
+        sqlx::query("UPDATE settings SET show_count = 100 WHERE id = 1")
+            .execute(app.pool())
+            .await
+            .unwrap();
+        sqlx::query("UPDATE display SET show_count = 100 WHERE id = ?")
+            .bind(display_id)
+            .execute(app.pool())
+            .await
+            .unwrap();
+        for (id, (shared, kitchen)) in ids.iter().zip([(1, 99), (2, 100), (100, 1)]) {
+            sqlx::query("UPDATE photo SET last_shown = ? WHERE id = ?")
+                .bind(shared)
+                .bind(id)
+                .execute(app.pool())
+                .await
+                .unwrap();
+            sqlx::query(
+                "INSERT OR REPLACE INTO display_photo (display_id, photo_id, last_shown) VALUES (?, ?, ?)",
+            )
+            .bind(display_id)
+            .bind(id)
+            .bind(kitchen)
+            .execute(app.pool())
+            .await
+            .unwrap();
+        }
+        let shown = next_photo_id_for(&app, "kitchen").await;
+        assert_ne!(
+            shown, ids[1] as i64,
+            "the photo on the kitchen display should not be picked"
+        );
+        if shown == ids[2] as i64 {
+            picked_stale += 1;
+        }
+    }
+    assert!(
+        picked_stale >= 40,
+        "stale photo picked {} of 50 times",
+        picked_stale
+    );
+
+    // Showing a photo moves on both the kitchen's history and the library-wide one
+    let (display_count, shared_count): (i64, i64) = sqlx::query_as(
+        "SELECT display.show_count, settings.show_count FROM display, settings WHERE display.id = ?",
+    )
+    .bind(display_id)
+    .fetch_one(app.pool())
+    .await
+    .unwrap();
+    assert_eq!((display_count, shared_count), (101, 101));
+}
+
+#[tokio::test]
+async fn test_delete_album_clears_display_album() {
+    let app = create_test_app().await;
+    let album_id = seed_album(&app, "Vacation").await;
+    let display_id = seed_display(&app, "Kitchen", "kitchen").await;
+
+    let req = UpdateDisplayRequest {
+        current_album_id: Some(Update::Set(AlbumID(album_id))),
+        ..Default::default()
+    };
+    let uri = format!("/api/displays/{}", display_id);
+    put_json(app.clone().router(), &uri, &req).await;
+
+    let (status, _) = delete(app.clone().router(), &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, json) = get_json(app.router(), &uri).await;
+    assert!(json["current_album"].is_null());
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Schedule Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A schedule that plays `album_id` all the time
+fn always_schedule(name: &str, album_id: i32) -> ScheduleRequest {
+    ScheduleRequest {
+        name: name.to_string(),
+        action: ScheduleAction::Album {
+            album: AlbumID(album_id),
+        },
+        display: None,
+        priority: 0,
+        enabled: true,
+        days: vec![],
+        months: vec![],
+        start_time: None,
+        end_time: None,
+    }
+}
+
+#[tokio::test]
+async fn test_create_and_list_schedules() {
+    let app = create_test_app().await;
+    let album_id = seed_album(&app, "Holidays").await;
+
+    let req = ScheduleRequest {
+        days: vec![chrono::Weekday::Sat, chrono::Weekday::Sun],
+        months: vec![12],
+        start_time: chrono::NaiveTime::from_hms_opt(22, 0, 0),
+        priority: 3,
+        ..always_schedule("December weekends", album_id)
+    };
+    let (status, json) = post_json(app.clone().router(), "/api/schedules", &req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "December weekends");
+    assert_eq!(json["action"]["kind"], "album");
+    assert_eq!(json["action"]["album"], album_id);
+    assert_eq!(json["days"], serde_json::json!(["Sat", "Sun"]));
+    assert_eq!(json["months"], serde_json::json!([12]));
+    assert_eq!(json["start_time"], "22:00:00");
+    assert!(json["end_time"].is_null());
+
+    seed_schedule(&app, "Low", None, 0).await;
+    let (status, json) = get_json(app.router(), "/api/schedules").await;
+    assert_eq!(status, StatusCode::OK);
+    // Highest priority first
+    assert_eq!(json[0]["name"], "December weekends");
+    assert_eq!(json[1]["name"], "Low");
+    assert_eq!(json[1]["action"]["kind"], "blank");
+}
+
+#[tokio::test]
+async fn test_create_schedule_validation() {
+    let app = create_test_app().await;
+    let album_id = seed_album(&app, "Holidays").await;
+
+    let req = always_schedule(" ", album_id);
+    let (status, _) = post_json(app.clone().router(), "/api/schedules", &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let req = ScheduleRequest {
+        months: vec![13],
+        ..always_schedule("Bad month", album_id)
+    };
+    let (status, _) = post_json(app.clone().router(), "/api/schedules", &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let req = always_schedule("Missing album", 999);
+    let (status, _) = post_json(app.clone().router(), "/api/schedules", &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let req = ScheduleRequest {
+        display: Some(DisplayID(999)),
+        ..always_schedule("Missing display", album_id)
+    };
+    let (status, _) = post_json(app.router(), "/api/schedules", &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_update_and_delete_schedule() {
+    let app = create_test_app().await;
+    let album_id = seed_album(&app, "Holidays").await;
+    let schedule_id = seed_schedule(&app, "Nights", None, 0).await;
+    let uri = format!("/api/schedules/{}", schedule_id);
+
+    let req = ScheduleRequest {
+        enabled: false,
+        ..always_schedule("Renamed", album_id)
+    };
+    let (status, json) = put_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "Renamed");
+    assert_eq!(json["enabled"], false);
+    assert_eq!(json["action"]["album"], album_id);
+
+    let (status, _) = put_json(app.clone().router(), "/api/schedules/999", &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = delete(app.clone().router(), &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = get_json(app.router(), &uri).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_next_photo_follows_active_schedule() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 2).await;
+    let manual = seed_album(&app, "Manual").await;
+    seed_album_photo(&app, manual, ids[0], 0).await;
+    let scheduled = seed_album(&app, "Scheduled").await;
+    seed_album_photo(&app, scheduled, ids[1], 0).await;
+    set_current_album(&app, manual).await;
+
+    // Falls back to the manual album when nothing is scheduled
+    assert_eq!(next_photo_ids(&app, 1).await, vec![ids[0] as i64]);
+
+    seed_schedule(&app, "Always", Some(scheduled), 0).await;
+    assert_eq!(next_photo_ids(&app, 1).await, vec![ids[1] as i64]);
+}
+
+#[tokio::test]
+async fn test_next_photo_highest_priority_schedule_wins() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 2).await;
+    let low = seed_album(&app, "Low").await;
+    seed_album_photo(&app, low, ids[0], 0).await;
+    let high = seed_album(&app, "High").await;
+    seed_album_photo(&app, high, ids[1], 0).await;
+
+    seed_schedule(&app, "High", Some(high), 10).await;
+    seed_schedule(&app, "Low", Some(low), 1).await;
+    assert_eq!(next_photo_ids(&app, 1).await, vec![ids[1] as i64]);
+}
+
+#[tokio::test]
+async fn test_next_photo_ignores_disabled_and_inactive_schedules() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 2).await;
+    let album_id = seed_album(&app, "Scheduled").await;
+    seed_album_photo(&app, album_id, ids[1], 0).await;
+
+    let disabled = ScheduleRequest {
+        enabled: false,
+        ..always_schedule("Disabled", album_id)
+    };
+    post_json(app.clone().router(), "/api/schedules", &disabled).await;
+
+    // A one minute window that ended a minute ago
+    let now = chrono::Local::now().naive_local().time();
+    let past = ScheduleRequest {
+        start_time: Some(now - chrono::Duration::minutes(2)),
+        end_time: Some(now - chrono::Duration::minutes(1)),
+        ..always_schedule("Past", album_id)
+    };
+    post_json(app.clone().router(), "/api/schedules", &past).await;
+
+    assert_eq!(next_photo_ids(&app, 1).await, vec![ids[0] as i64]);
+}
+
+#[tokio::test]
+async fn test_blank_schedule_shows_nothing() {
+    let app = create_test_app().await;
+    seed_photo(&app, "hash1", "Photo").await;
+    seed_schedule(&app, "Overnight", None, 0).await;
+
+    let (status, json) = get_json(app.router(), "/api/next").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["error"].as_str().unwrap().contains("Overnight"));
+}
+
+#[tokio::test]
+async fn test_display_schedule_only_applies_to_that_display() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 2).await;
+    let album_id = seed_album(&app, "Kids").await;
+    seed_album_photo(&app, album_id, ids[1], 0).await;
+    let display_id = seed_display(&app, "Kitchen", "kitchen").await;
+    seed_display(&app, "Hallway", "hallway").await;
+
+    let req = ScheduleRequest {
+        display: Some(DisplayID(display_id)),
+        ..always_schedule("Kitchen kids", album_id)
+    };
+    let (status, _) = post_json(app.clone().router(), "/api/schedules", &req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert_eq!(next_photo_id_for(&app, "kitchen").await, ids[1] as i64);
+    assert_eq!(next_photo_id_for(&app, "hallway").await, ids[0] as i64);
+    assert_eq!(next_photo_ids(&app, 1).await, vec![ids[0] as i64]);
+}
+
+#[tokio::test]
+async fn test_delete_album_removes_its_schedules() {
+    let app = create_test_app().await;
+    let album_id = seed_album(&app, "Holidays").await;
+    seed_schedule(&app, "December", Some(album_id), 0).await;
+
+    let (status, _) = delete(app.clone().router(), &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, json) = get_json(app.router(), "/api/schedules").await;
+    assert_eq!(json, serde_json::json!([]));
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Album CRUD Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_create_album() {
+    let app = create_test_app().await;
+
+    let req = CreateAlbumRequest {
+        name: "My New Album".to_string(),
+        notes: Some("Album notes".to_string()),
+        virtual_album: None,
+        smart_rule: None,
+    };
+
+    let router = app.clone().router();
+    let (status, json) = post_json(router, "/api/albums", &req).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "My New Album");
+    assert_eq!(json["notes"], "Album notes");
+    assert!(json["id"].as_i64().is_some());
+    assert_eq!(json["photos"], serde_json::json!([]));
+
+    // Verify album was persisted
+    let router = app.router();
+    let (status, json) = get_json(router, "/api/albums").await;
+    assert_eq!(status, StatusCode::OK);
+    let albums = json.as_array().unwrap();
+    assert_eq!(albums.len(), 1);
+    assert_eq!(albums[0]["name"], "My New Album");
+}
+
+#[tokio::test]
+async fn test_create_album_without_notes() {
+    let app = create_test_app().await;
+
+    let req = CreateAlbumRequest {
+        name: "Simple Album".to_string(),
+        notes: None,
+        virtual_album: None,
+        smart_rule: None,
+    };
+
+    let router = app.router();
+    let (status, json) = post_json(router, "/api/albums", &req).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "Simple Album");
+    assert!(json["notes"].is_null());
+}
+
+#[tokio::test]
+async fn test_update_album_name() {
+    let app = create_test_app().await;
+
+    let album_id = seed_album(&app, "Original Name").await;
+
+    let req = UpdateAlbumRequest {
+        name: Some("Updated Name".to_string()),
+        notes: None,
+        smart_rule: None,
+    };
+
+    let router = app.clone().router();
+    let (status, _) = put_json(router, &format!("/api/albums/{}", album_id), &req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Verify the update
+    let router = app.router();
+    let (_, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(json["name"], "Updated Name");
+}
+
+#[tokio::test]
+async fn test_update_album_notes() {
+    let app = create_test_app().await;
+
+    let album_id = seed_album(&app, "Test Album").await;
+
+    // Set notes
+    let req = UpdateAlbumRequest {
+        name: None,
+        notes: Some(Update::Set("New notes".to_string())),
+        smart_rule: None,
+    };
+
+    let router = app.clone().router();
+    let (status, _) = put_json(router, &format!("/api/albums/{}", album_id), &req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Verify notes were set
+    let router = app.clone().router();
+    let (_, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(json["notes"], "New notes");
+
+    // Remove notes
+    let req = UpdateAlbumRequest {
+        name: None,
+        notes: Some(Update::Remove),
+        smart_rule: None,
+    };
+
+    let router = app.clone().router();
+    let (status, _) = put_json(router, &format!("/api/albums/{}", album_id), &req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Verify notes were removed
+    let router = app.router();
+    let (_, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
+    assert!(json["notes"].is_null());
+}
+
+#[tokio::test]
+async fn test_update_album_not_found() {
+    let app = create_test_app().await;
+
+    let req = UpdateAlbumRequest {
+        name: Some("New Name".to_string()),
+        notes: None,
+        smart_rule: None,
+    };
+
+    let router = app.router();
+    let (status, json) = put_json(router, "/api/albums/999", &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["error"].as_str().unwrap().contains("not found"));
+}
+
+#[tokio::test]
+async fn test_delete_album() {
+    let app = create_test_app().await;
+
+    let album_id = seed_album(&app, "To Delete").await;
+
+    let router = app.clone().router();
+    let (status, _) = delete(router, &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Verify album was deleted
+    let router = app.router();
+    let (status, _) = get_json(router, &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_delete_album_clears_current_album() {
+    let app = create_test_app().await;
+
+    let album_id = seed_album(&app, "Current Album").await;
+    set_current_album(&app, album_id).await;
+
+    // Verify it's set as current
+    let router = app.clone().router();
+    let (_, json) = get_json(router, "/api/settings").await;
+    assert_eq!(json["current_album"]["album"], album_id);
+
+    // Delete the album
+    let router = app.clone().router();
+    let (status, _) = delete(router, &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Verify current album was cleared
+    let router = app.router();
+    let (_, json) = get_json(router, "/api/settings").await;
+    assert!(json["current_album"].is_null());
+}
+
+#[tokio::test]
+async fn test_delete_album_not_found() {
+    let app = create_test_app().await;
+
+    let router = app.router();
+    let (status, json) = delete(router, "/api/albums/999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["error"].as_str().unwrap().contains("not found"));
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Virtual Album Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Noon on today's date `years` years ago
+fn years_ago(years: i32) -> chrono::NaiveDateTime {
+    use chrono::Datelike;
+    let today = chrono::Local::now().date_naive();
+    let date = today.with_year(today.year() - years).unwrap_or_else(|| {
+        today
+            .pred_opt()
+            .unwrap()
+            .with_year(today.year() - years)
+            .unwrap()
+    });
+    date.and_hms_opt(12, 0, 0).unwrap()
+}
+
+/// Create a virtual album through the API and return its ID
+async fn create_virtual_album(
+    app: &std::sync::Arc<pictureframe::App>,
+    name: &str,
+    virtual_album: VirtualAlbum,
+) -> i32 {
+    let req = CreateAlbumRequest {
+        name: name.to_string(),
+        notes: None,
+        virtual_album: Some(virtual_album),
+        smart_rule: None,
+    };
+    let (status, json) = post_json(app.clone().router(), "/api/albums", &req).await;
+    assert_eq!(status, StatusCode::OK);
+    json["id"].as_i64().unwrap() as i32
+}
+
+#[tokio::test]
+async fn test_on_this_day_album() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 4).await;
+    set_date_taken(&app, ids[0], years_ago(3)).await;
+    set_date_taken(&app, ids[1], years_ago(1)).await;
+    // Taken today is not a memory yet
+    set_date_taken(&app, ids[2], years_ago(0)).await;
+    set_date_taken(&app, ids[3], years_ago(2) - chrono::Duration::days(10)).await;
+
+    let album_id = create_virtual_album(&app, "Memories", VirtualAlbum::OnThisDay).await;
+
+    let (status, json) = get_json(app.router(), &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["virtual_album"]["kind"], "on_this_day");
+    // Oldest first
+    assert_eq!(json["photos"], serde_json::json!([ids[0], ids[1]]));
+}
+
+#[tokio::test]
+async fn test_this_week_album_years_ago() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    set_date_taken(&app, ids[0], years_ago(2) + chrono::Duration::days(2)).await;
+    set_date_taken(&app, ids[1], years_ago(5)).await;
+    set_date_taken(&app, ids[2], years_ago(2) - chrono::Duration::days(20)).await;
+
+    let album_id = create_virtual_album(
+        &app,
+        "Two years ago",
+        VirtualAlbum::ThisWeek { years_ago: Some(2) },
+    )
+    .await;
+
+    let (_, json) = get_json(app.router(), "/api/albums").await;
+    let album = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["id"] == album_id)
+        .unwrap();
+    assert_eq!(album["photos"], serde_json::json!([ids[0]]));
+}
+
+#[tokio::test]
+async fn test_taken_in_month_album() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    let at = |ymd: &str| {
+        chrono::NaiveDate::parse_from_str(ymd, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+    };
+    set_date_taken(&app, ids[0], at("2021-12-24")).await;
+    set_date_taken(&app, ids[1], at("2019-12-01")).await;
+    set_date_taken(&app, ids[2], at("2021-11-30")).await;
+
+    let album_id =
+        create_virtual_album(&app, "Decembers", VirtualAlbum::TakenInMonth { month: 12 }).await;
+
+    let (_, json) = get_json(app.router(), &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(json["photos"], serde_json::json!([ids[1], ids[0]]));
+}
+
+#[tokio::test]
+async fn test_create_virtual_album_rejects_invalid_month() {
+    let app = create_test_app().await;
+
+    let req = CreateAlbumRequest {
+        name: "Bad".to_string(),
+        notes: None,
+        virtual_album: Some(VirtualAlbum::TakenInMonth { month: 13 }),
+        smart_rule: None,
+    };
+    let (status, _) = post_json(app.router(), "/api/albums", &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_create_virtual_album_rejects_rules_that_never_match() {
+    let app = create_test_app().await;
+
+    for virtual_album in [
+        VirtualAlbum::TakenInMonth { month: 0 },
+        VirtualAlbum::ThisWeek { years_ago: Some(0) },
+    ] {
+        let req = CreateAlbumRequest {
+            name: "Bad".to_string(),
+            notes: None,
+            virtual_album: Some(virtual_album),
+            smart_rule: None,
+        };
+        let (status, _) = post_json(app.clone().router(), "/api/albums", &req).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let (_, json) = get_json(app.router(), "/api/albums").await;
+    assert_eq!(json, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_virtual_album_as_current_album() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    set_date_taken(&app, ids[1], years_ago(4)).await;
+    let album_id = create_virtual_album(&app, "Memories", VirtualAlbum::OnThisDay).await;
+
+    let req = UpdateSettingsRequest {
+        current_album_id: Some(Update::Set(AlbumID(album_id))),
+        interval_seconds: None,
+        rotation_mode: None,
+    };
+    let (status, _) = put_json(app.clone().router(), "/api/settings", &req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert_eq!(
+        next_photo_ids(&app, 2).await,
+        vec![ids[1] as i64, ids[1] as i64]
+    );
+}
+
+#[tokio::test]
+async fn test_empty_virtual_album_falls_back_to_library() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 2).await;
+    let album_id = create_virtual_album(&app, "Memories", VirtualAlbum::OnThisDay).await;
+    set_current_album(&app, album_id).await;
+
+    assert_eq!(
+        next_photo_ids(&app, 2).await,
+        vec![ids[0] as i64, ids[1] as i64]
+    );
+}
+
+#[tokio::test]
+async fn test_cannot_add_photos_to_virtual_album() {
+    let app = create_test_app().await;
+    let photo_id = seed_photo(&app, "hash1", "Photo").await;
+    let album_id = create_virtual_album(&app, "Memories", VirtualAlbum::OnThisDay).await;
+
+    let uri = format!("/api/albums/{}/photos/{}", album_id, photo_id);
+    let (status, _) = post_empty(app.router(), &uri).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Smart Album Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn date(ymd: &str) -> chrono::NaiveDate {
+    chrono::NaiveDate::parse_from_str(ymd, "%Y-%m-%d").unwrap()
+}
+
+fn smart_album_request(name: &str, rule: SmartRule) -> CreateAlbumRequest {
+    CreateAlbumRequest {
+        name: name.to_string(),
+        notes: None,
+        virtual_album: None,
+        smart_rule: Some(rule),
+    }
+}
+
+/// Create a smart album through the API and return its ID
+async fn create_smart_album(
+    app: &std::sync::Arc<pictureframe::App>,
+    name: &str,
+    rule: SmartRule,
+) -> i32 {
+    let (status, json) = post_json(
+        app.clone().router(),
+        "/api/albums",
+        &smart_album_request(name, rule),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    json["id"].as_i64().unwrap() as i32
+}
+
+#[tokio::test]
+async fn test_smart_album_matches_rule() {
+    let app = create_test_app().await;
+    let a = seed_photo_with_mat(&app, "hash1", "A", "modern").await;
+    let b = seed_photo_with_mat(&app, "hash2", "B", "modern").await;
+    let c = seed_photo_with_mat(&app, "hash3", "C", "classic").await;
+    // Undated, so never taken after anything
+    seed_photo_with_mat(&app, "hash4", "D", "modern").await;
+    set_date_taken(&app, a, date("2022-06-01").and_hms_opt(9, 0, 0).unwrap()).await;
+    set_date_taken(&app, b, date("2021-03-15").and_hms_opt(9, 0, 0).unwrap()).await;
+    set_date_taken(&app, c, date("2022-01-01").and_hms_opt(9, 0, 0).unwrap()).await;
+
+    let rule = SmartRule {
+        mat_preset: Some("modern".to_string()),
+        taken_after: Some(date("2021-01-01")),
+        ..Default::default()
+    };
+    let album_id = create_smart_album(&app, "Modern", rule).await;
+
+    let (status, json) = get_json(app.router(), &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["smart_rule"]["mat_preset"], "modern");
+    // In the order they were taken
+    assert_eq!(json["photos"], serde_json::json!([b, a]));
+}
+
+#[tokio::test]
+async fn test_smart_album_picks_up_new_photos() {
+    let app = create_test_app().await;
+    let album_id = create_smart_album(
+        &app,
+        "Everything recent",
+        SmartRule {
+            added_within_days: Some(7),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let photo_id = seed_photo(&app, "hash1", "New").await;
+
+    let (_, json) = get_json(app.router(), "/api/albums").await;
+    let album = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|a| a["id"] == album_id)
+        .unwrap();
+    assert_eq!(album["photos"], serde_json::json!([photo_id]));
+}
+
+#[tokio::test]
+async fn test_smart_album_as_current_album() {
+    let app = create_test_app().await;
+    seed_photo_with_mat(&app, "hash1", "A", "classic").await;
+    let modern = seed_photo_with_mat(&app, "hash2", "B", "modern").await;
+    let album_id = create_smart_album(
+        &app,
+        "Modern",
+        SmartRule {
+            mat_preset: Some("modern".to_string()),
+            ..Default::default()
+        },
+    )
+    .await;
+    set_current_album(&app, album_id).await;
+
+    assert_eq!(
+        next_photo_ids(&app, 2).await,
+        vec![modern as i64, modern as i64]
+    );
+}
+
+#[tokio::test]
+async fn test_update_album_smart_rule() {
+    let app = create_test_app().await;
+    let a = seed_photo_with_mat(&app, "hash1", "A", "modern").await;
+    let b = seed_photo_with_mat(&app, "hash2", "B", "classic").await;
+    let album_id = seed_album(&app, "Album").await;
+    seed_album_photo(&app, album_id, b, 0).await;
+    let uri = format!("/api/albums/{}", album_id);
+
+    // Turn the manual album into a smart album
+    let req = UpdateAlbumRequest {
+        name: None,
+        notes: None,
+        smart_rule: Some(Update::Set(SmartRule {
+            mat_preset: Some("modern".to_string()),
+            ..Default::default()
+        })),
+    };
+    let (status, _) = put_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, json) = get_json(app.clone().router(), &uri).await;
+    assert_eq!(json["photos"], serde_json::json!([a]));
+
+    // And back again, keeping the photos it had before
+    let req = UpdateAlbumRequest {
+        name: None,
+        notes: None,
+        smart_rule: Some(Update::Remove),
+    };
+    let (status, _) = put_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, json) = get_json(app.router(), &uri).await;
+    assert!(json["smart_rule"].is_null());
+    assert_eq!(json["photos"], serde_json::json!([b]));
+}
+
+#[tokio::test]
+async fn test_create_smart_album_rejects_invalid_rule() {
+    let app = create_test_app().await;
+
+    let invalid = [
+        SmartRule {
+            mat_preset: Some("nonexistent".to_string()),
+            ..Default::default()
+        },
+        SmartRule {
+            taken_after: Some(date("2023-01-01")),
+            taken_before: Some(date("2022-01-01")),
+            ..Default::default()
+        },
+        SmartRule {
+            added_within_days: Some(0),
+            ..Default::default()
+        },
+    ];
+    for rule in invalid {
+        let (status, _) = post_json(
+            app.clone().router(),
+            "/api/albums",
+            &smart_album_request("Bad", rule),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    // An album can't be virtual and smart at once
+    let req = CreateAlbumRequest {
+        virtual_album: Some(VirtualAlbum::OnThisDay),
+        ..smart_album_request("Bad", SmartRule::default())
+    };
+    let (status, _) = post_json(app.clone().router(), "/api/albums", &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, json) = get_json(app.router(), "/api/albums").await;
+    assert_eq!(json, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_cannot_set_smart_rule_on_virtual_album() {
+    let app = create_test_app().await;
+    let album_id = create_virtual_album(&app, "Memories", VirtualAlbum::OnThisDay).await;
+
+    let req = UpdateAlbumRequest {
+        name: None,
+        notes: None,
+        smart_rule: Some(Update::Set(SmartRule::default())),
+    };
+    let (status, _) = put_json(app.router(), &format!("/api/albums/{}", album_id), &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_cannot_add_photos_to_smart_album() {
+    let app = create_test_app().await;
+    let photo_id = seed_photo(&app, "hash1", "Photo").await;
+    let album_id = create_smart_album(&app, "Everything", SmartRule::default()).await;
+
+    let uri = format!("/api/albums/{}/photos/{}", album_id, photo_id);
+    let (status, _) = post_empty(app.router(), &uri).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tag Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Tag `photos` with `tags` through the API
+async fn tag_photos(app: &std::sync::Arc<pictureframe::App>, photos: &[i32], tags: &[&str]) {
+    let req = TagPhotosRequest {
+        photos: photos.iter().map(|&id| PhotoID(id)).collect(),
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+    };
+    let (status, _) = post_json(app.clone().router(), "/api/tags/attach", &req).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+/// IDs of the photos returned for a `/api/photos` query string
+async fn filtered_photo_ids(app: &std::sync::Arc<pictureframe::App>, query: &str) -> Vec<i64> {
+    let (status, json) = get_json(app.clone().router(), &format!("/api/photos?{}", query)).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut ids: Vec<i64> = json["photos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].as_i64().unwrap())
+        .collect();
+    ids.sort();
+    ids
+}
+
+async fn create_tag(app: &std::sync::Arc<pictureframe::App>, name: &str) -> i32 {
+    let req = TagRequest {
+        name: name.to_string(),
+    };
+    let (status, json) = post_json(app.clone().router(), "/api/tags", &req).await;
+    assert_eq!(status, StatusCode::OK);
+    json["id"].as_i64().unwrap() as i32
+}
+
+#[tokio::test]
+async fn test_attach_tags_creates_tags() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+
+    tag_photos(&app, &ids[..2], &["beach", " Family "]).await;
+
+    let (status, json) = get_json(app.clone().router(), "/api/tags").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json.as_array().unwrap().len(), 2);
+    assert_eq!(json[0]["name"], "beach");
+    assert_eq!(json[0]["photo_count"], 2);
+    assert_eq!(json[1]["name"], "Family");
+
+    // Attaching again, in a different case, changes nothing
+    tag_photos(&app, &ids[..1], &["BEACH"]).await;
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", ids[0])).await;
+    assert_eq!(json["tags"], serde_json::json!(["beach", "Family"]));
+    let (_, json) = get_json(app.router(), &format!("/api/photos/{}", ids[2])).await;
+    assert_eq!(json["tags"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_photo_page_includes_tags() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    tag_photos(&app, &[ids[0], ids[2]], &["beach"]).await;
+    tag_photos(&app, &[ids[2]], &["dog"]).await;
+
+    // Newest first, so the page holds the last two photos
+    let (status, json) = get_json(app.router(), "/api/photos?limit=2").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["photos"][0]["id"], ids[2]);
+    assert_eq!(
+        json["photos"][0]["tags"],
+        serde_json::json!(["beach", "dog"])
+    );
+    assert_eq!(json["photos"][1]["tags"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_attach_tags_unknown_photo() {
+    let app = create_test_app().await;
+    let id = seed_photo(&app, "hash1", "Photo").await;
+
+    let req = TagPhotosRequest {
+        photos: vec![PhotoID(id), PhotoID(999)],
+        tags: vec!["beach".to_string()],
+    };
+    let (status, _) = post_json(app.clone().router(), "/api/tags/attach", &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Nothing was tagged
+    let (_, json) = get_json(app.router(), "/api/tags").await;
+    assert_eq!(json, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_detach_tags() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 2).await;
+    tag_photos(&app, &ids, &["beach", "family"]).await;
+
+    let req = TagPhotosRequest {
+        photos: vec![PhotoID(ids[0])],
+        tags: vec!["Beach".to_string(), "never used".to_string()],
+    };
+    let (status, _) = post_json(app.clone().router(), "/api/tags/detach", &req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", ids[0])).await;
+    assert_eq!(json["tags"], serde_json::json!(["family"]));
+    let (_, json) = get_json(app.router(), &format!("/api/photos/{}", ids[1])).await;
+    assert_eq!(json["tags"], serde_json::json!(["beach", "family"]));
+}
+
+#[tokio::test]
+async fn test_get_photos_filtered_by_tags() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 4).await;
+    tag_photos(&app, &[ids[0], ids[1]], &["beach"]).await;
+    tag_photos(&app, &[ids[1], ids[2]], &["family"]).await;
+    let [a, b, c, _] = [ids[0] as i64, ids[1] as i64, ids[2] as i64, ids[3] as i64];
+
+    assert_eq!(filtered_photo_ids(&app, "tags=beach").await, vec![a, b]);
+    assert_eq!(filtered_photo_ids(&app, "tags=beach,family").await, vec![b]);
+    assert_eq!(
+        filtered_photo_ids(&app, "tags=beach,family&tag_match=all").await,
+        vec![b]
+    );
+    assert_eq!(
+        filtered_photo_ids(&app, "tags=beach,family&tag_match=any").await,
+        vec![a, b, c]
+    );
+    // Repeats and case don't matter
+    assert_eq!(
+        filtered_photo_ids(&app, "tags=BEACH,beach").await,
+        vec![a, b]
+    );
+    // A tag nobody has
+    assert!(filtered_photo_ids(&app, "tags=beach,snow").await.is_empty());
+    assert_eq!(
+        filtered_photo_ids(&app, "tags=beach,snow&tag_match=any").await,
+        vec![a, b]
+    );
+    // No tags means no filter
+    assert_eq!(filtered_photo_ids(&app, "tags=").await.len(), 4);
+}
+
+#[tokio::test]
+async fn test_create_tag_validation() {
+    let app = create_test_app().await;
+    create_tag(&app, "Beach").await;
+
+    for name in ["beach", "  ", "sun, sand"] {
+        let req = TagRequest {
+            name: name.to_string(),
+        };
+        let (status, _) = post_json(app.clone().router(), "/api/tags", &req).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{name:?}");
+    }
+
+    let (_, json) = get_json(app.router(), "/api/tags").await;
+    assert_eq!(json[0]["photo_count"], 0);
+    assert_eq!(json.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_rename_tag() {
+    let app = create_test_app().await;
+    let id = seed_photo(&app, "hash1", "Photo").await;
+    tag_photos(&app, &[id], &["beach", "family"]).await;
+    let (_, tags) = get_json(app.clone().router(), "/api/tags").await;
+    let beach = tags[0]["id"].as_i64().unwrap();
+
+    // Smart albums follow the new name
+    let album_id = create_smart_album(
+        &app,
+        "Beach",
+        SmartRule {
+            tags: vec!["Beach".to_string()],
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let req = TagRequest {
+        name: "Seaside".to_string(),
+    };
+    let (status, json) =
+        put_json(app.clone().router(), &format!("/api/tags/{}", beach), &req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "Seaside");
+    assert_eq!(json["photo_count"], 1);
+
+    let (_, json) = get_json(app.clone().router(), &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(json["smart_rule"]["tags"], serde_json::json!(["Seaside"]));
+    assert_eq!(json["photos"], serde_json::json!([id]));
+
+    // Taking another tag's name is refused
+    let req = TagRequest {
+        name: "FAMILY".to_string(),
+    };
+    let (status, json) =
+        put_json(app.clone().router(), &format!("/api/tags/{}", beach), &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["error"].as_str().unwrap().contains("merge"));
+
+    let (status, _) = put_json(app.router(), "/api/tags/999", &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_merge_tags() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    tag_photos(&app, &[ids[0], ids[1]], &["beach"]).await;
+    tag_photos(&app, &[ids[1], ids[2]], &["seaside"]).await;
+    tag_photos(&app, &[ids[2]], &["shore"]).await;
+    let (_, tags) = get_json(app.clone().router(), "/api/tags").await;
+    let tag_id = |i: usize| TagID(tags[i]["id"].as_i64().unwrap() as i32);
+
+    let req = MergeTagsRequest {
+        tags: vec![tag_id(1), tag_id(2)],
+    };
+    let (status, json) = post_json(
+        app.clone().router(),
+        &format!("/api/tags/{}/merge", tag_id(0)),
+        &req,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "beach");
+    assert_eq!(json["photo_count"], 3);
+
+    let (_, json) = get_json(app.clone().router(), "/api/tags").await;
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", ids[2])).await;
+    assert_eq!(json["tags"], serde_json::json!(["beach"]));
+
+    // Can't merge a tag into itself
+    let req = MergeTagsRequest {
+        tags: vec![tag_id(0)],
+    };
+    let (status, _) = post_json(
+        app.router(),
+        &format!("/api/tags/{}/merge", tag_id(0)),
+        &req,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_delete_tag() {
+    let app = create_test_app().await;
+    let id = seed_photo(&app, "hash1", "Photo").await;
+    tag_photos(&app, &[id], &["beach"]).await;
+    let tag_id = create_tag(&app, "unused").await;
+
+    let (status, _) = delete(app.clone().router(), &format!("/api/tags/{}", tag_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, json) = get_json(app.clone().router(), "/api/tags").await;
+    let beach = json[0]["id"].as_i64().unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+
+    let (status, _) = delete(app.clone().router(), &format!("/api/tags/{}", beach)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", id)).await;
+    assert_eq!(json["tags"], serde_json::json!([]));
+
+    let (status, _) = delete(app.router(), &format!("/api/tags/{}", beach)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_delete_tag_used_by_smart_album_fails() {
+    let app = create_test_app().await;
+    let id = seed_photo(&app, "hash1", "Photo").await;
+    tag_photos(&app, &[id], &["beach"]).await;
+    let (_, tags) = get_json(app.clone().router(), "/api/tags").await;
+    let beach = tags[0]["id"].as_i64().unwrap();
+    let album_id = create_smart_album(
+        &app,
+        "Seaside",
+        SmartRule {
+            tags: vec!["Beach".to_string()],
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let (status, json) = delete(app.clone().router(), &format!("/api/tags/{}", beach)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        json["error"].as_str().unwrap().contains("Seaside"),
+        "{:?}",
+        json
+    );
+
+    // Nothing was changed
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", id)).await;
+    assert_eq!(json["tags"], serde_json::json!(["beach"]));
+    let (_, json) = get_json(app.clone().router(), &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(json["photos"], serde_json::json!([id]));
+
+    // Once the album no longer needs it, the tag can go
+    let (status, _) = delete(app.clone().router(), &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = delete(app.router(), &format!("/api/tags/{}", beach)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_deleted_photo_leaves_tag_counts() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 2).await;
+    tag_photos(&app, &ids, &["beach"]).await;
+
+    let (status, _) = delete(app.clone().router(), &format!("/api/photos/{}", ids[0])).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, json) = get_json(app.router(), "/api/tags").await;
+    assert_eq!(json[0]["photo_count"], 1);
+}
+
+#[tokio::test]
+async fn test_tagged_smart_album_as_rotation_source() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    tag_photos(&app, &[ids[0], ids[2]], &["Holiday"]).await;
+    let album_id = create_smart_album(
+        &app,
+        "Holidays",
+        SmartRule {
+            tags: vec!["holiday".to_string()],
+            ..Default::default()
+        },
+    )
+    .await;
+    set_current_album(&app, album_id).await;
+
+    let served = next_photo_ids(&app, 3).await;
+    assert_eq!(served, vec![ids[0] as i64, ids[2] as i64, ids[0] as i64]);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tag Suggestion Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// App whose classifier always suggests a beach scene, a dog, family and a blurry guess
+async fn create_classifying_app() -> std::sync::Arc<pictureframe::App> {
+    let classifier = StubClassifier::new(vec![
+        Classification::new("dog", SuggestionKind::Tag, 0.6),
+        Classification::new("beach", SuggestionKind::Scene, 0.9),
+        Classification::new("Family", SuggestionKind::Face, 0.8),
+        Classification::new("blurry", SuggestionKind::Tag, 0.1),
+    ]);
+    create_test_app_with_classifier(std::sync::Arc::new(classifier)).await
+}
+
+/// Labels of the pending suggestions for a photo, most confident first
+async fn suggested_labels(app: &std::sync::Arc<pictureframe::App>, photo_id: i32) -> Vec<String> {
+    let (status, json) = get_json(
+        app.clone().router(),
+        &format!("/api/suggestions?photo={}", photo_id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    json.as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["label"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_classify_photo_without_classifier() {
+    let app = create_test_app().await;
+    let id = seed_photo(&app, "hash1", "Photo").await;
+
+    let (status, json) = post_empty(app.router(), &format!("/api/photos/{}/classify", id)).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(json["error"].as_str().unwrap().contains("classifier"));
+}
+
+#[tokio::test]
+async fn test_classify_photo_queues_job() {
+    let app = create_classifying_app().await;
+    let id = seed_photo(&app, "hash1", "Photo").await;
+
+    let (status, json) = post_empty(
+        app.clone().router(),
+        &format!("/api/photos/{}/classify", id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["kind"], "classify");
+    assert_eq!(json["status"], "queued");
+    assert_eq!(json["photo"], id);
+
+    let (status, _) = post_empty(app.router(), "/api/photos/999/classify").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_run_classifier_stores_suggestions() {
+    let app = create_classifying_app().await;
+    let ids = seed_photos(&app, 2).await;
+    tag_photos(&app, &[ids[0]], &["family"]).await;
+
+    assert_eq!(app.run_classifier(ids[0]).await.unwrap(), 2);
+
+    // Low confidence guesses and tags the photo already has are left out
+    assert_eq!(suggested_labels(&app, ids[0]).await, vec!["beach", "dog"]);
+    assert!(suggested_labels(&app, ids[1]).await.is_empty());
+
+    let (_, json) = get_json(app.clone().router(), "/api/suggestions").await;
+    assert_eq!(json[0]["kind"], "scene");
+    assert!((json[0]["confidence"].as_f64().unwrap() - 0.9).abs() < 1e-6);
+
+    // Classifying again doesn't duplicate anything
+    assert_eq!(app.run_classifier(ids[0]).await.unwrap(), 0);
+    assert_eq!(suggested_labels(&app, ids[0]).await, vec!["beach", "dog"]);
+}
+
+#[tokio::test]
+async fn test_accept_suggestion_tags_photo() {
+    let app = create_test_app().await;
+    let id = seed_photo(&app, "hash1", "Photo").await;
+    let suggestion = seed_suggestion(&app, id, "Beach", 0.9).await;
+
+    let uri = format!("/api/suggestions/{}/accept", suggestion);
+    let (status, _) = post_empty(app.clone().router(), &uri).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", id)).await;
+    assert_eq!(json["tags"], serde_json::json!(["Beach"]));
+    assert!(suggested_labels(&app, id).await.is_empty());
+
+    // Already reviewed
+    let (status, _) = post_empty(app.clone().router(), &uri).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = post_empty(app.router(), "/api/suggestions/999/accept").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_rejected_suggestion_is_not_suggested_again() {
+    let app = create_classifying_app().await;
+    let id = seed_photo(&app, "hash1", "Photo").await;
+    app.run_classifier(id).await.unwrap();
+
+    let (_, json) = get_json(
+        app.clone().router(),
+        &format!("/api/suggestions?photo={}", id),
+    )
+    .await;
+    let beach = json[0]["id"].as_i64().unwrap();
+    let (status, _) = post_empty(
+        app.clone().router(),
+        &format!("/api/suggestions/{}/reject", beach),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(suggested_labels(&app, id).await, vec!["Family", "dog"]);
+
+    app.run_classifier(id).await.unwrap();
+    assert_eq!(suggested_labels(&app, id).await, vec!["Family", "dog"]);
+
+    // Rejecting doesn't tag anything
+    let (_, json) = get_json(app.router(), "/api/tags").await;
+    assert_eq!(json, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_deleted_photo_loses_suggestions() {
+    let app = create_test_app().await;
+    let id = seed_photo(&app, "hash1", "Photo").await;
+    seed_suggestion(&app, id, "beach", 0.9).await;
+
+    let (status, _) = delete(app.clone().router(), &format!("/api/photos/{}", id)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, json) = get_json(app.router(), "/api/suggestions").await;
+    assert_eq!(json, serde_json::json!([]));
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Photo CRUD Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_update_photo_title() {
+    let app = create_test_app().await;
+
+    let photo_id = seed_photo(&app, "hash1", "Original Title").await;
+
+    // Update title
+    let req = UpdatePhotoRequest {
+        title: Some(Update::Set("New Title".to_string())),
+        artist: None,
+        copyright: None,
+        notes: None,
+        date_taken: None,
+        mat_preset: None,
+    };
+
+    let router = app.clone().router();
+    let (status, _) = put_json(router, &format!("/api/photos/{}", photo_id), &req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Verify title was updated
+    let router = app.router();
+    let (_, json) = get_json(router, &format!("/api/photos/{}", photo_id)).await;
+    assert_eq!(json["title"], "New Title");
+}
+
+#[tokio::test]
+async fn test_update_photo_remove_title() {
+    let app = create_test_app().await;
+
+    let photo_id = seed_photo(&app, "hash1", "Original Title").await;
+
+    // Remove title
+    let req = UpdatePhotoRequest {
+        title: Some(Update::Remove),
+        artist: None,
+        copyright: None,
+        notes: None,
+        date_taken: None,
+        mat_preset: None,
+    };
+
+    let router = app.clone().router();
+    let (status, _) = put_json(router, &format!("/api/photos/{}", photo_id), &req).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Verify title was removed
+    let router = app.router();
+    let (_, json) = get_json(router, &format!("/api/photos/{}", photo_id)).await;
+    assert!(json["title"].is_null());
+}
+
+#[tokio::test]
+async fn test_update_photo_not_found() {
+    let app = create_test_app().await;
+
+    let req = UpdatePhotoRequest {
+        title: Some(Update::Set("New Title".to_string())),
+        artist: None,
+        copyright: None,
+        notes: None,
+        date_taken: None,
+        mat_preset: None,
+    };
+
+    let router = app.router();
+    let (status, json) = put_json(router, "/api/photos/999", &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["error"].as_str().unwrap().contains("not found"));
+}
+
+#[tokio::test]
+async fn test_update_photo_all_fields() {
+    let app = create_test_app().await;
+    let photo_id = seed_photo(&app, "hash1", "Original Title").await;
+
+    let taken = date("1999-12-31").and_hms_opt(23, 59, 0).unwrap();
+    let req = UpdatePhotoRequest {
+        title: Some(Update::Set("  Grandma at the beach ".to_string())),
+        artist: Some(Update::Set("Grandpa".to_string())),
+        copyright: Some(Update::Set("The family".to_string())),
+        notes: Some(Update::Set("Her first time at the sea".to_string())),
+        date_taken: Some(Update::Set(taken)),
+        mat_preset: Some("gallery".to_string()),
+    };
+    let (status, _) = put_json(
+        app.clone().router(),
+        &format!("/api/photos/{}", photo_id),
+        &req,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", photo_id)).await;
+    assert_eq!(json["title"], "Grandma at the beach");
+    assert_eq!(json["artist"], "Grandpa");
+    assert_eq!(json["copyright"], "The family");
+    assert_eq!(json["notes"], "Her first time at the sea");
+    assert_eq!(json["date_taken"], "1999-12-31T23:59:00");
+    assert_eq!(json["mat_preset"], "gallery");
+
+    // Blank text removes the field, and fields left out are kept
+    let req = UpdatePhotoRequest {
+        title: None,
+        artist: Some(Update::Remove),
+        copyright: None,
+        notes: Some(Update::Set("   ".to_string())),
+        date_taken: Some(Update::Remove),
+        mat_preset: None,
+    };
+    let (status, _) = put_json(
+        app.clone().router(),
+        &format!("/api/photos/{}", photo_id),
+        &req,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, json) = get_json(app.router(), &format!("/api/photos/{}", photo_id)).await;
+    assert_eq!(json["title"], "Grandma at the beach");
+    assert!(json["artist"].is_null());
+    assert!(json["notes"].is_null());
+    assert!(json["date_taken"].is_null());
+    assert_eq!(json["mat_preset"], "gallery");
+}
+
+#[tokio::test]
+async fn test_update_photo_rejects_invalid_values_without_changes() {
+    let app = create_test_app().await;
+    let photo_id = seed_photo(&app, "hash1", "Original Title").await;
+    let uri = format!("/api/photos/{}", photo_id);
+
+    let update = |notes: &str, date_taken: Option<chrono::NaiveDateTime>| UpdatePhotoRequest {
+        title: Some(Update::Set("New Title".to_string())),
+        artist: None,
+        copyright: None,
+        notes: Some(Update::Set(notes.to_string())),
+        date_taken: date_taken.map(Update::Set),
+        mat_preset: None,
+    };
+    let future = chrono::Local::now().naive_local() + chrono::Duration::days(3);
+    let invalid = [
+        (
+            update(&"x".repeat(5001), None),
+            "Notes must be at most 5000 characters",
+        ),
+        (
+            update("ok", Some(date("1800-01-01").and_hms_opt(0, 0, 0).unwrap())),
+            "Date taken",
+        ),
+        (update("ok", Some(future)), "Date taken"),
+    ];
+    for (req, error) in invalid {
+        let (status, json) = put_json(app.clone().router(), &uri, &req).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(
+            json["error"].as_str().unwrap().contains(error),
+            "{:?}",
+            json
+        );
+    }
+
+    let req = UpdatePhotoRequest {
+        title: Some(Update::Set("x".repeat(201))),
+        artist: None,
+        copyright: None,
+        notes: None,
+        date_taken: None,
+        mat_preset: None,
+    };
+    let (status, json) = put_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["error"].as_str().unwrap().contains("Title"));
+
+    // The valid title sent alongside the bad fields wasn't saved
+    let (_, json) = get_json(app.router(), &uri).await;
+    assert_eq!(json["title"], "Original Title");
+    assert!(json["notes"].is_null());
+}
+
+#[tokio::test]
+async fn test_update_photo_notes_without_field_in_body() {
+    let app = create_test_app().await;
+    let photo_id = seed_photo(&app, "hash1", "Original Title").await;
+
+    // Older clients don't send notes at all
+    let body = serde_json::json!({
+        "title": {"Set": "New Title"},
+        "artist": null,
+        "copyright": null,
+        "date_taken": null,
+        "mat_preset": null
+    });
+    let (status, _) = put_json(
+        app.clone().router(),
+        &format!("/api/photos/{}", photo_id),
+        &body,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_delete_photo() {
+    let app = create_test_app().await;
+
+    let photo_id = seed_photo(&app, "hash1", "To Delete").await;
+
+    let router = app.clone().router();
+    let (status, _) = delete(router, &format!("/api/photos/{}", photo_id)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Verify photo was deleted
+    let router = app.router();
+    let (status, _) = get_json(router, &format!("/api/photos/{}", photo_id)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_delete_photo_removes_from_albums() {
+    let app = create_test_app().await;
+
+    let album_id = seed_album(&app, "Test Album").await;
+    let photo_id = seed_photo(&app, "hash1", "Photo to Delete").await;
+    seed_album_photo(&app, album_id, photo_id, 0).await;
+
+    // Verify photo is in album
+    let router = app.clone().router();
+    let (_, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(json["photos"].as_array().unwrap().len(), 1);
+
+    // Delete the photo
+    let router = app.clone().router();
+    let (status, _) = delete(router, &format!("/api/photos/{}", photo_id)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Verify album no longer has the photo
+    let router = app.router();
+    let (_, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(json["photos"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_delete_photo_not_found() {
+    let app = create_test_app().await;
+
+    let router = app.router();
+    let (status, json) = delete(router, "/api/photos/999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["error"].as_str().unwrap().contains("not found"));
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Bulk Photo Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Run a bulk operation and return the status and response
+async fn bulk(
+    app: &std::sync::Arc<pictureframe::App>,
+    photos: &[i64],
+    operation: BulkPhotoOperation,
+) -> (StatusCode, Value) {
+    let req = BulkPhotoRequest {
+        photos: photos.iter().map(|&id| PhotoID(id as i32)).collect(),
+        operation,
+    };
+    post_json(app.clone().router(), "/api/photos/bulk", &req).await
+}
+
+/// Photos a bulk operation was done to, and those it skipped
+fn bulk_outcome(results: &Value) -> (Vec<i64>, Vec<i64>) {
+    let (done, skipped): (Vec<&Value>, Vec<&Value>) = results
+        .as_array()
+        .unwrap()
+        .iter()
+        .partition(|r| r["error"].is_null());
+    let ids = |results: Vec<&Value>| {
+        results
+            .iter()
+            .map(|r| r["photo"].as_i64().unwrap())
+            .collect()
+    };
+    (ids(done), ids(skipped))
+}
+
+#[tokio::test]
+async fn test_bulk_add_to_and_remove_from_albums() {
+    let app = create_test_app().await;
+    let (album_id, ids) = seed_album_with_photos(&app, 2).await;
+    let other_album = seed_album(&app, "Other").await;
+    let c = seed_photo(&app, "hash_c", "C").await as i64;
+    let d = seed_photo(&app, "hash_d", "D").await as i64;
+    let albums = vec![AlbumID(album_id), AlbumID(other_album)];
+
+    let (status, json) = bulk(
+        &app,
+        &[ids[1], c, 999, d],
+        BulkPhotoOperation::AddToAlbums {
+            albums: albums.clone(),
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bulk_outcome(&json), (vec![ids[1], c, d], vec![999]));
+    assert!(json[2]["error"].as_str().unwrap().contains("not found"));
+    // Photos already in the album keep their place, new ones go on the end
+    assert_eq!(
+        album_photo_ids(&app, album_id).await,
+        vec![ids[0], ids[1], c, d]
+    );
+    assert_eq!(album_photo_ids(&app, other_album).await, vec![ids[1], c, d]);
+
+    let (status, json) = bulk(
+        &app,
+        &[ids[0], c],
+        BulkPhotoOperation::RemoveFromAlbums { albums },
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bulk_outcome(&json), (vec![ids[0], c], Vec::new()));
+    assert_eq!(album_photo_ids(&app, album_id).await, vec![ids[1], d]);
+    assert_eq!(album_photo_ids(&app, other_album).await, vec![ids[1], d]);
+
+    // The gaps are closed, so moving to the top still works
+    let (status, _) = post_json(
+        app.clone().router(),
+        &format!("/api/albums/{}/photos/{}/move", album_id, d),
+        &MoveAlbumPhotoRequest { to: AlbumEnd::Top },
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(album_photo_ids(&app, album_id).await, vec![d, ids[1]]);
+}
+
+#[tokio::test]
+async fn test_bulk_rejects_bad_operations_without_changes() {
+    let app = create_test_app().await;
+    let (album_id, ids) = seed_album_with_photos(&app, 2).await;
+    let smart = create_smart_album(&app, "Smart", SmartRule::default()).await;
+
+    let (status, _) = bulk(
+        &app,
+        &ids,
+        BulkPhotoOperation::AddToAlbums {
+            albums: vec![AlbumID(999)],
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, json) = bulk(
+        &app,
+        &ids,
+        BulkPhotoOperation::RemoveFromAlbums {
+            albums: vec![AlbumID(album_id), AlbumID(smart)],
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("picks its own photos")
+    );
+    assert_eq!(album_photo_ids(&app, album_id).await, ids);
+
+    let (status, json) = bulk(
+        &app,
+        &ids,
+        BulkPhotoOperation::SetMatPreset {
+            mat_preset: "velvet".to_string(),
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("Unknown mat preset")
+    );
+
+    let details = UpdatePhotoRequest {
+        title: Some(Update::Set("x".repeat(201))),
+        ..Default::default()
+    };
+    let (status, _) = bulk(&app, &ids, BulkPhotoOperation::Update { details }).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = bulk(
+        &app,
+        &ids,
+        BulkPhotoOperation::AttachTags {
+            tags: vec!["a,b".to_string()],
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", ids[0])).await;
+    assert_eq!(json["title"], "Photo 0");
+    assert_eq!(json["tags"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_bulk_delete() {
+    let app = create_test_app().await;
+    let (album_id, ids) = seed_album_with_photos(&app, 3).await;
+
+    let (status, json) = bulk(&app, &[ids[0], 999, ids[2]], BulkPhotoOperation::Delete).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bulk_outcome(&json), (vec![ids[0], ids[2]], vec![999]));
+
+    for id in [ids[0], ids[2]] {
+        let (status, _) = get_json(app.clone().router(), &format!("/api/photos/{}", id)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+    assert_eq!(album_photo_ids(&app, album_id).await, vec![ids[1]]);
+}
+
+#[tokio::test]
+async fn test_bulk_set_mat_and_details() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 2).await;
+    let ids: Vec<i64> = ids.into_iter().map(i64::from).collect();
+
+    let (status, json) = bulk(
+        &app,
+        &ids,
+        BulkPhotoOperation::SetMatPreset {
+            mat_preset: "gallery".to_string(),
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bulk_outcome(&json), (ids.clone(), Vec::new()));
+
+    let details = UpdatePhotoRequest {
+        artist: Some(Update::Set(" Ansel Adams ".to_string())),
+        date_taken: Some(Update::Set(taken("1941-11-01"))),
+        ..Default::default()
+    };
+    let (status, _) = bulk(&app, &ids, BulkPhotoOperation::Update { details }).await;
+    assert_eq!(status, StatusCode::OK);
+
+    for (i, id) in ids.iter().enumerate() {
+        let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", id)).await;
+        assert_eq!(json["mat_preset"], "gallery");
+        assert_eq!(json["artist"], "Ansel Adams");
+        assert_eq!(json["date_taken"], "1941-11-01T12:00:00");
+        // Fields left out are untouched
+        assert_eq!(json["title"], format!("Photo {}", i));
+    }
+}
+
+#[tokio::test]
+async fn test_bulk_tags() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    let ids: Vec<i64> = ids.into_iter().map(i64::from).collect();
+
+    let tags = vec![" beach ".to_string(), "family".to_string()];
+    let (status, _) = bulk(&app, &ids[..2], BulkPhotoOperation::AttachTags { tags }).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = bulk(
+        &app,
+        &ids[1..],
+        BulkPhotoOperation::DetachTags {
+            tags: vec!["family".to_string()],
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let photo_tags = |json: Value| json["tags"].clone();
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", ids[0])).await;
+    assert_eq!(photo_tags(json), serde_json::json!(["beach", "family"]));
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", ids[1])).await;
+    assert_eq!(photo_tags(json), serde_json::json!(["beach"]));
+    let (_, json) = get_json(app.clone().router(), &format!("/api/photos/{}", ids[2])).await;
+    assert_eq!(photo_tags(json), serde_json::json!([]));
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Trash Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn trash_ids(json: &Value) -> Vec<i64> {
+    json.as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["photo"]["id"].as_i64().unwrap())
+        .collect()
 }
 
 #[tokio::test]
-async fn test_update_album_not_found() {
+async fn test_deleted_photo_is_hidden_but_kept() {
     let app = create_test_app().await;
+    let (album_id, ids) = seed_album_with_photos(&app, 2).await;
+    tag_photos(&app, &[ids[0] as i32], &["beach"]).await;
+    set_current_album(&app, album_id).await;
 
-    let req = UpdateAlbumRequest {
-        name: Some("New Name".to_string()),
-        notes: None,
-    };
+    let (status, _) = delete(app.clone().router(), &format!("/api/photos/{}", ids[0])).await;
+    assert_eq!(status, StatusCode::OK);
 
-    let router = app.router();
-    let (status, json) = put_json(router, "/api/albums/999", &req).await;
+    // Gone from the library...
+    assert_eq!(photo_page(&app, "").await, (vec![ids[1]], 1));
+    assert_eq!(album_photo_ids(&app, album_id).await, vec![ids[1]]);
+    let (_, json) = get_json(app.clone().router(), "/api/tags").await;
+    assert_eq!(json[0]["photo_count"], 0);
+    for _ in 0..3 {
+        let (_, json) = get_json(app.clone().router(), "/api/next").await;
+        assert_eq!(json["photo"]["id"], ids[1]);
+    }
+    let (status, _) = delete(app.clone().router(), &format!("/api/photos/{}", ids[0])).await;
     assert_eq!(status, StatusCode::NOT_FOUND);
-    assert!(json["error"].as_str().unwrap().contains("not found"));
+
+    // ...but waiting in the trash
+    let (status, json) = get_json(app.clone().router(), "/api/trash").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(trash_ids(&json), vec![ids[0]]);
+    assert_eq!(json[0]["photo"]["tags"], serde_json::json!(["beach"]));
+    let deleted_at = chrono::NaiveDateTime::parse_from_str(
+        json[0]["deleted_at"].as_str().unwrap(),
+        "%Y-%m-%dT%H:%M:%S",
+    )
+    .unwrap();
+    let purge_at = chrono::NaiveDateTime::parse_from_str(
+        json[0]["purge_at"].as_str().unwrap(),
+        "%Y-%m-%dT%H:%M:%S",
+    )
+    .unwrap();
+    assert_eq!(
+        purge_at - deleted_at,
+        chrono::Duration::days(DEFAULT_TRASH_RETENTION_DAYS as i64)
+    );
 }
 
 #[tokio::test]
-async fn test_delete_album() {
+async fn test_restore_photo() {
     let app = create_test_app().await;
+    let (album_id, ids) = seed_album_with_photos(&app, 3).await;
 
-    let album_id = seed_album(&app, "To Delete").await;
+    let (status, _) = delete(app.clone().router(), &format!("/api/photos/{}", ids[1])).await;
+    assert_eq!(status, StatusCode::OK);
 
-    let router = app.clone().router();
-    let (status, _) = delete(router, &format!("/api/albums/{}", album_id)).await;
+    let (status, json) = post_json(
+        app.clone().router(),
+        &format!("/api/trash/{}/restore", ids[1]),
+        &(),
+    )
+    .await;
     assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["id"], ids[1]);
 
-    // Verify album was deleted
-    let router = app.router();
-    let (status, _) = get_json(router, &format!("/api/albums/{}", album_id)).await;
+    // Back where it was in the album
+    assert_eq!(album_photo_ids(&app, album_id).await, ids);
+    let (_, json) = get_json(app.clone().router(), "/api/trash").await;
+    assert_eq!(json, serde_json::json!([]));
+
+    // Only photos in the trash can be restored
+    let (status, json) = post_json(
+        app.clone().router(),
+        &format!("/api/trash/{}/restore", ids[1]),
+        &(),
+    )
+    .await;
     assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["error"].as_str().unwrap().contains("not in the trash"));
 }
 
 #[tokio::test]
-async fn test_delete_album_clears_current_album() {
+async fn test_purge_photo_and_empty_trash() {
     let app = create_test_app().await;
+    let (album_id, ids) = seed_album_with_photos(&app, 4).await;
+    for id in &ids[..3] {
+        let (status, _) = delete(app.clone().router(), &format!("/api/photos/{}", id)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
 
-    let album_id = seed_album(&app, "Current Album").await;
-    set_current_album(&app, album_id).await;
+    // Photos still in the library can't be purged
+    let (status, _) = delete(app.clone().router(), &format!("/api/trash/{}", ids[3])).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
 
-    // Verify it's set as current
-    let router = app.clone().router();
-    let (_, json) = get_json(router, "/api/settings").await;
-    assert_eq!(json["current_album"]["album"], album_id);
+    let (status, _) = delete(app.clone().router(), &format!("/api/trash/{}", ids[0])).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, json) = get_json(app.clone().router(), "/api/trash").await;
+    let mut trashed = trash_ids(&json);
+    trashed.sort();
+    assert_eq!(trashed, vec![ids[1], ids[2]]);
+    let (status, _) = post_json(
+        app.clone().router(),
+        &format!("/api/trash/{}/restore", ids[0]),
+        &(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
 
-    // Delete the album
-    let router = app.clone().router();
-    let (status, _) = delete(router, &format!("/api/albums/{}", album_id)).await;
+    let (status, json) = delete(app.clone().router(), "/api/trash").await;
     assert_eq!(status, StatusCode::OK);
+    assert_eq!(json, 2);
+    let (_, json) = get_json(app.clone().router(), "/api/trash").await;
+    assert_eq!(json, serde_json::json!([]));
+    assert_eq!(album_photo_ids(&app, album_id).await, vec![ids[3]]);
+}
 
-    // Verify current album was cleared
-    let router = app.router();
-    let (_, json) = get_json(router, "/api/settings").await;
-    assert!(json["current_album"].is_null());
+#[tokio::test]
+async fn test_purge_expired_trash() {
+    let app = create_test_app().await;
+    let ids = seed_photos(&app, 3).await;
+    set_deleted_days_ago(&app, ids[0], DEFAULT_TRASH_RETENTION_DAYS + 1).await;
+    set_deleted_days_ago(&app, ids[1], DEFAULT_TRASH_RETENTION_DAYS - 1).await;
+
+    assert_eq!(app.purge_expired_trash().await.unwrap(), 1);
+    let (_, json) = get_json(app.clone().router(), "/api/trash").await;
+    assert_eq!(trash_ids(&json), vec![ids[1] as i64]);
+    assert_eq!(photo_page(&app, "").await, (vec![ids[2] as i64], 1));
 }
 
 #[tokio::test]
-async fn test_delete_album_not_found() {
+async fn test_trashed_photos_are_left_out_of_bulk_operations() {
     let app = create_test_app().await;
+    let ids: Vec<i64> = seed_photos(&app, 2)
+        .await
+        .into_iter()
+        .map(i64::from)
+        .collect();
 
-    let router = app.router();
-    let (status, json) = delete(router, "/api/albums/999").await;
-    assert_eq!(status, StatusCode::NOT_FOUND);
-    assert!(json["error"].as_str().unwrap().contains("not found"));
+    let (status, json) = bulk(&app, &ids[..1], BulkPhotoOperation::Delete).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(bulk_outcome(&json), (vec![ids[0]], Vec::new()));
+
+    let (_, json) = bulk(
+        &app,
+        &ids,
+        BulkPhotoOperation::SetMatPreset {
+            mat_preset: "gallery".to_string(),
+        },
+    )
+    .await;
+    assert_eq!(bulk_outcome(&json), (vec![ids[1]], vec![ids[0]]));
+    let (_, json) = get_json(app.clone().router(), "/api/trash").await;
+    assert_ne!(json[0]["photo"]["mat_preset"], "gallery");
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
-// Photo CRUD Tests
+// Duplicate Tests
 // ─────────────────────────────────────────────────────────────────────────────
 
 #[tokio::test]
-async fn test_update_photo_title() {
+async fn test_get_duplicates_none() {
     let app = create_test_app().await;
 
-    let photo_id = seed_photo(&app, "hash1", "Original Title").await;
+    seed_photo(&app, &test_hash(0), "Photo 1").await;
+    seed_photo(&app, &test_hash(u64::MAX), "Photo 2").await;
 
-    // Update title
-    let req = UpdatePhotoRequest {
-        title: Some(Update::Set("New Title".to_string())),
-        artist: None,
-        copyright: None,
-        date_taken: None,
-        mat_preset: None,
-    };
+    let router = app.router();
+    let (status, json) = get_json(router, "/api/photos/duplicates").await;
 
-    let router = app.clone().router();
-    let (status, _) = put_json(router, &format!("/api/photos/{}", photo_id), &req).await;
     assert_eq!(status, StatusCode::OK);
+    assert_eq!(json, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_get_duplicates_groups_near_matches() {
+    let app = create_test_app().await;
+
+    let burst1 = seed_photo(&app, &test_hash(0b0000), "Burst 1").await;
+    let other = seed_photo(&app, &test_hash(u64::MAX), "Something else").await;
+    let burst2 = seed_photo(&app, &test_hash(0b0011), "Burst 2").await;
+    // Within range of burst 2 but not burst 1, so it's grouped transitively
+    let burst3 = seed_photo(&app, &test_hash(0b11111), "Burst 3").await;
 
-    // Verify title was updated
     let router = app.router();
-    let (_, json) = get_json(router, &format!("/api/photos/{}", photo_id)).await;
-    assert_eq!(json["title"], "New Title");
+    let (status, json) = get_json(router, "/api/photos/duplicates").await;
+
+    assert_eq!(status, StatusCode::OK);
+    let groups = json.as_array().unwrap();
+    assert_eq!(groups.len(), 1);
+    let ids: Vec<i64> = groups[0]["photos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![burst1 as i64, burst2 as i64, burst3 as i64]);
+    assert!(!ids.contains(&(other as i64)));
+    assert_eq!(groups[0]["distance"], 5);
 }
 
 #[tokio::test]
-async fn test_update_photo_remove_title() {
+async fn test_get_duplicates_max_distance() {
     let app = create_test_app().await;
 
-    let photo_id = seed_photo(&app, "hash1", "Original Title").await;
+    seed_photo(&app, &test_hash(0), "Photo 1").await;
+    seed_photo(&app, &test_hash(0xFF), "Photo 2").await;
 
-    // Remove title
-    let req = UpdatePhotoRequest {
-        title: Some(Update::Remove),
-        artist: None,
-        copyright: None,
-        date_taken: None,
-        mat_preset: None,
-    };
+    // 8 bits apart is outside the default threshold
+    let router = app.clone().router();
+    let (_, json) = get_json(router, "/api/photos/duplicates").await;
+    assert_eq!(json.as_array().unwrap().len(), 0);
 
     let router = app.clone().router();
-    let (status, _) = put_json(router, &format!("/api/photos/{}", photo_id), &req).await;
+    let (status, json) = get_json(router, "/api/photos/duplicates?max_distance=8").await;
     assert_eq!(status, StatusCode::OK);
+    assert_eq!(json.as_array().unwrap().len(), 1);
 
-    // Verify title was removed
     let router = app.router();
-    let (_, json) = get_json(router, &format!("/api/photos/{}", photo_id)).await;
-    assert!(json["title"].is_null());
+    let (_, json) = get_json(router, "/api/photos/duplicates?max_distance=0").await;
+    assert_eq!(json.as_array().unwrap().len(), 0);
 }
 
 #[tokio::test]
-async fn test_update_photo_not_found() {
+async fn test_merge_photos_moves_album_memberships() {
     let app = create_test_app().await;
 
-    let req = UpdatePhotoRequest {
-        title: Some(Update::Set("New Title".to_string())),
-        artist: None,
-        copyright: None,
-        date_taken: None,
-        mat_preset: None,
+    let keep = seed_photo(&app, &test_hash(0), "Keeper").await;
+    let dup1 = seed_photo(&app, &test_hash(1), "Dup 1").await;
+    let dup2 = seed_photo(&app, &test_hash(3), "Dup 2").await;
+    let other = seed_photo(&app, &test_hash(u64::MAX), "Other").await;
+
+    let shared = seed_album(&app, "Shared").await;
+    seed_album_photo(&app, shared, other, 0).await;
+    seed_album_photo(&app, shared, keep, 1).await;
+    seed_album_photo(&app, shared, dup1, 2).await;
+    let dup_only = seed_album(&app, "Dup Only").await;
+    seed_album_photo(&app, dup_only, other, 0).await;
+    seed_album_photo(&app, dup_only, dup2, 1).await;
+
+    let req = MergePhotosRequest {
+        duplicates: vec![PhotoID(dup1), PhotoID(dup2)],
     };
+    let router = app.clone().router();
+    let (status, json) = post_json(router, &format!("/api/photos/{}/merge", keep), &req).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["id"], keep);
+
+    // Duplicates are gone, into the trash
+    let router = app.clone().router();
+    let (_, json) = get_json(router, "/api/photos").await;
+    assert_eq!(json["total"], 2);
+    let (_, json) = get_json(app.clone().router(), "/api/trash").await;
+    let mut trashed = trash_ids(&json);
+    trashed.sort();
+    assert_eq!(trashed, vec![i64::from(dup1), i64::from(dup2)]);
+
+    // The keeper stays where it was in albums it was already in...
+    let router = app.clone().router();
+    let (_, json) = get_json(router, &format!("/api/albums/{}", shared)).await;
+    assert_eq!(json["photos"], serde_json::json!([other, keep]));
 
+    // ...and takes the duplicate's place in the others
     let router = app.router();
-    let (status, json) = put_json(router, "/api/photos/999", &req).await;
-    assert_eq!(status, StatusCode::NOT_FOUND);
-    assert!(json["error"].as_str().unwrap().contains("not found"));
+    let (_, json) = get_json(router, &format!("/api/albums/{}", dup_only)).await;
+    assert_eq!(json["photos"], serde_json::json!([other, keep]));
 }
 
 #[tokio::test]
-async fn test_delete_photo() {
+async fn test_merge_photos_moves_tags() {
     let app = create_test_app().await;
 
-    let photo_id = seed_photo(&app, "hash1", "To Delete").await;
+    let keep = seed_photo(&app, &test_hash(0), "Keeper").await;
+    let dup = seed_photo(&app, &test_hash(1), "Dup").await;
+    tag_photos(&app, &[keep], &["beach"]).await;
+    tag_photos(&app, &[dup], &["beach", "family"]).await;
 
-    let router = app.clone().router();
-    let (status, _) = delete(router, &format!("/api/photos/{}", photo_id)).await;
+    let req = MergePhotosRequest {
+        duplicates: vec![PhotoID(dup)],
+    };
+    let (status, json) = post_json(
+        app.clone().router(),
+        &format!("/api/photos/{}/merge", keep),
+        &req,
+    )
+    .await;
     assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["tags"], serde_json::json!(["beach", "family"]));
 
-    // Verify photo was deleted
-    let router = app.router();
-    let (status, _) = get_json(router, &format!("/api/photos/{}", photo_id)).await;
-    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, json) = get_json(app.router(), "/api/tags").await;
+    assert_eq!(json[0]["photo_count"], 1);
+    assert_eq!(json[1]["photo_count"], 1);
 }
 
 #[tokio::test]
-async fn test_delete_photo_removes_from_albums() {
+async fn test_merge_photos_not_found() {
     let app = create_test_app().await;
 
-    let album_id = seed_album(&app, "Test Album").await;
-    let photo_id = seed_photo(&app, "hash1", "Photo to Delete").await;
-    seed_album_photo(&app, album_id, photo_id, 0).await;
+    let keep = seed_photo(&app, &test_hash(0), "Keeper").await;
+    let dup = seed_photo(&app, &test_hash(1), "Dup").await;
 
-    // Verify photo is in album
+    let req = MergePhotosRequest {
+        duplicates: vec![PhotoID(dup), PhotoID(999)],
+    };
     let router = app.clone().router();
-    let (_, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
-    assert_eq!(json["photos"].as_array().unwrap().len(), 1);
+    let (status, _) = post_json(router, &format!("/api/photos/{}/merge", keep), &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
 
-    // Delete the photo
+    // Nothing was deleted
     let router = app.clone().router();
-    let (status, _) = delete(router, &format!("/api/photos/{}", photo_id)).await;
-    assert_eq!(status, StatusCode::OK);
+    let (_, json) = get_json(router, "/api/photos").await;
+    assert_eq!(json["total"], 2);
 
-    // Verify album no longer has the photo
+    let req = MergePhotosRequest {
+        duplicates: vec![PhotoID(dup)],
+    };
     let router = app.router();
-    let (_, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
-    assert_eq!(json["photos"].as_array().unwrap().len(), 0);
+    let (status, _) = post_json(router, "/api/photos/999/merge", &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
 }
 
 #[tokio::test]
-async fn test_delete_photo_not_found() {
+async fn test_merge_photo_into_itself_fails() {
     let app = create_test_app().await;
 
+    let keep = seed_photo(&app, &test_hash(0), "Keeper").await;
+
+    let req = MergePhotosRequest {
+        duplicates: vec![PhotoID(keep)],
+    };
+    let router = app.clone().router();
+    let (status, json) = post_json(router, &format!("/api/photos/{}/merge", keep), &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        json["error"].as_str().unwrap().contains("into itself"),
+        "{:?}",
+        json
+    );
+
     let router = app.router();
-    let (status, json) = delete(router, "/api/photos/999").await;
-    assert_eq!(status, StatusCode::NOT_FOUND);
-    assert!(json["error"].as_str().unwrap().contains("not found"));
+    let (status, _) = get_json(router, &format!("/api/photos/{}", keep)).await;
+    assert_eq!(status, StatusCode::OK);
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -875,6 +3586,197 @@ async fn test_remove_photo_not_in_album() {
     assert_eq!(status, StatusCode::OK);
 }
 
+/// Album of `count` photos in the order they were seeded
+async fn seed_album_with_photos(app: &pictureframe::App, count: usize) -> (i32, Vec<i64>) {
+    let album_id = seed_album(app, "Ordered").await;
+    let ids = seed_photos(app, count).await;
+    for (position, id) in ids.iter().enumerate() {
+        seed_album_photo(app, album_id, *id, position as i32).await;
+    }
+    (album_id, ids.into_iter().map(i64::from).collect())
+}
+
+async fn album_photo_ids(app: &std::sync::Arc<pictureframe::App>, album_id: i32) -> Vec<i64> {
+    let (_, json) = get_json(app.clone().router(), &format!("/api/albums/{}", album_id)).await;
+    json["photos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p.as_i64().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_reorder_album() {
+    let app = create_test_app().await;
+    let (album_id, ids) = seed_album_with_photos(&app, 4).await;
+    let uri = format!("/api/albums/{}/order", album_id);
+
+    let photos = |order: &[usize]| ReorderAlbumRequest {
+        photos: order.iter().map(|i| PhotoID(ids[*i] as i32)).collect(),
+    };
+    let (status, _) = put_json(app.clone().router(), &uri, &photos(&[3, 1, 0, 2])).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        album_photo_ids(&app, album_id).await,
+        vec![ids[3], ids[1], ids[0], ids[2]]
+    );
+
+    // Photos left out follow in their current order
+    let (status, _) = put_json(app.clone().router(), &uri, &photos(&[2])).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        album_photo_ids(&app, album_id).await,
+        vec![ids[2], ids[3], ids[1], ids[0]]
+    );
+}
+
+#[tokio::test]
+async fn test_reorder_album_rejects_bad_lists() {
+    let app = create_test_app().await;
+    let (album_id, ids) = seed_album_with_photos(&app, 2).await;
+    let outsider = seed_photo(&app, "outsider", "Not in the album").await;
+    let uri = format!("/api/albums/{}/order", album_id);
+
+    let req = ReorderAlbumRequest {
+        photos: vec![PhotoID(ids[1] as i32), PhotoID(outsider)],
+    };
+    let (status, json) = put_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["error"].as_str().unwrap().contains("not in album"));
+
+    let req = ReorderAlbumRequest {
+        photos: vec![PhotoID(ids[1] as i32), PhotoID(ids[1] as i32)],
+    };
+    let (status, json) = put_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(json["error"].as_str().unwrap().contains("twice"));
+
+    // Nothing was changed by either
+    assert_eq!(album_photo_ids(&app, album_id).await, ids);
+
+    let (status, _) = put_json(app.clone().router(), "/api/albums/999/order", &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let smart = create_smart_album(&app, "Everything", SmartRule::default()).await;
+    let req = ReorderAlbumRequest { photos: vec![] };
+    let (status, json) =
+        put_json(app.router(), &format!("/api/albums/{}/order", smart), &req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("picks its own photos")
+    );
+}
+
+#[tokio::test]
+async fn test_move_album_photo_to_either_end() {
+    let app = create_test_app().await;
+    let (album_id, ids) = seed_album_with_photos(&app, 3).await;
+    let uri = |photo: i64| format!("/api/albums/{}/photos/{}/move", album_id, photo);
+
+    let req = MoveAlbumPhotoRequest { to: AlbumEnd::Top };
+    let (status, _) = post_json(app.clone().router(), &uri(ids[2]), &req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        album_photo_ids(&app, album_id).await,
+        vec![ids[2], ids[0], ids[1]]
+    );
+
+    let req = MoveAlbumPhotoRequest {
+        to: AlbumEnd::Bottom,
+    };
+    let (status, _) = post_json(app.clone().router(), &uri(ids[2]), &req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        album_photo_ids(&app, album_id).await,
+        vec![ids[0], ids[1], ids[2]]
+    );
+
+    let outsider = seed_photo(&app, "outsider", "Not in the album").await;
+    let (status, _) = post_json(app.router(), &uri(outsider as i64), &req).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_sort_album() {
+    let app = create_test_app().await;
+    let album_id = seed_album(&app, "Sorted").await;
+    let untitled = seed_photo(&app, "hash1", "").await;
+    let pear = seed_photo(&app, "hash2", "pear").await;
+    let apple = seed_photo(&app, "hash3", "Apple").await;
+    let fig = seed_photo(&app, "hash4", "fig").await;
+    sqlx::query("UPDATE photo SET title = NULL WHERE id = ?")
+        .bind(untitled)
+        .execute(app.pool())
+        .await
+        .unwrap();
+    for (position, id) in [untitled, pear, apple, fig].into_iter().enumerate() {
+        seed_album_photo(&app, album_id, id, position as i32).await;
+    }
+    set_date_taken(&app, pear, taken("2020-01-01")).await;
+    set_date_taken(&app, fig, taken("2010-01-01")).await;
+    let uri = format!("/api/albums/{}/sort", album_id);
+    let ids = |ids: [i32; 4]| ids.map(i64::from).to_vec();
+
+    let req = SortAlbumRequest {
+        by: AlbumPhotoSort::Title,
+        descending: false,
+    };
+    let (status, _) = post_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        album_photo_ids(&app, album_id).await,
+        ids([apple, fig, pear, untitled])
+    );
+
+    // Undated photos stay last, in the order they were in
+    let req = SortAlbumRequest {
+        by: AlbumPhotoSort::DateTaken,
+        descending: false,
+    };
+    post_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(
+        album_photo_ids(&app, album_id).await,
+        ids([fig, pear, apple, untitled])
+    );
+
+    let req = SortAlbumRequest {
+        by: AlbumPhotoSort::DateTaken,
+        descending: true,
+    };
+    post_json(app.clone().router(), &uri, &req).await;
+    assert_eq!(
+        album_photo_ids(&app, album_id).await,
+        ids([pear, fig, apple, untitled])
+    );
+}
+
+#[tokio::test]
+async fn test_remove_photo_from_album_closes_gap() {
+    let app = create_test_app().await;
+    let (album_id, ids) = seed_album_with_photos(&app, 3).await;
+
+    let (status, _) = delete(
+        app.clone().router(),
+        &format!("/api/albums/{}/photos/{}", album_id, ids[1]),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let positions = sqlx::query_scalar::<_, i32>(
+        "SELECT position FROM album_photo WHERE album_id = ? ORDER BY position",
+    )
+    .bind(album_id)
+    .fetch_all(app.pool())
+    .await
+    .unwrap();
+    assert_eq!(positions, vec![0, 1]);
+    assert_eq!(album_photo_ids(&app, album_id).await, vec![ids[0], ids[2]]);
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Mat Presets Tests
 // ─────────────────────────────────────────────────────────────────────────────
@@ -957,6 +3859,7 @@ async fn test_update_photo_mat_preset() {
         title: None,
         artist: None,
         copyright: None,
+        notes: None,
         date_taken: None,
         mat_preset: Some("modern".to_string()),
     };
@@ -981,6 +3884,7 @@ async fn test_update_photo_mat_preset_all_presets() {
             title: None,
             artist: None,
             copyright: None,
+            notes: None,
             date_taken: None,
             mat_preset: Some(preset.to_string()),
         };
@@ -1004,13 +3908,14 @@ async fn test_update_photo_invalid_mat_preset_fails() {
         title: None,
         artist: None,
         copyright: None,
+        notes: None,
         date_taken: None,
         mat_preset: Some("invalid_preset".to_string()),
     };
     let router = app.router();
     let (status, json) = put_json(router, &format!("/api/photos/{}", photo_id), &update).await;
 
-    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(status, StatusCode::BAD_REQUEST);
     assert!(json["error"]
         .as_str()
         .unwrap()
@@ -1048,7 +3953,7 @@ async fn test_next_photo_mat_style_matches_preset() {
 
 #[tokio::test]
 async fn test_next_photo_mat_style_for_each_preset() {
-    let presets_with_shadow = vec!["modern", "rich"];
+    let presets_with_shadow = ["modern", "rich"];
 
     for preset in ["classic", "modern", "gallery", "minimal", "rich", "none"] {
         let app = create_test_app().await;
@@ -1091,7 +3996,9 @@ async fn test_photos_list_includes_mat_preset() {
     let (status, json) = get_json(router, "/api/photos").await;
 
     assert_eq!(status, StatusCode::OK);
-    let photos = json.as_array().expect("response should be an array");
+    let photos = json["photos"]
+        .as_array()
+        .expect("response should have photos");
     assert_eq!(photos.len(), 2);
 
     // Each photo should have mat_preset
@@ -1169,3 +4076,62 @@ async fn test_upload_photo_no_file() {
     assert!(status == StatusCode::BAD_REQUEST || status == StatusCode::INTERNAL_SERVER_ERROR);
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Job Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_get_jobs_empty() {
+    let app = create_test_app().await;
+    let router = app.router();
+
+    let (status, json) = get_json(router, "/api/jobs").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_get_jobs_newest_first() {
+    let app = create_test_app().await;
+    seed_job(&app, "first.jpg", "done").await;
+    seed_job(&app, "second.jpg", "queued").await;
+
+    let router = app.router();
+    let (status, json) = get_json(router, "/api/jobs").await;
+
+    assert_eq!(status, StatusCode::OK);
+    let jobs = json.as_array().unwrap();
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[0]["filename"], "second.jpg");
+    assert_eq!(jobs[0]["status"], "queued");
+    assert_eq!(jobs[1]["filename"], "first.jpg");
+    assert_eq!(jobs[1]["status"], "done");
+}
+
+#[tokio::test]
+async fn test_get_job_found() {
+    let app = create_test_app().await;
+    let job_id = seed_job(&app, "beach.jpg", "processing").await;
+
+    let router = app.router();
+    let (status, json) = get_json(router, &format!("/api/jobs/{}", job_id)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["id"], job_id);
+    assert_eq!(json["filename"], "beach.jpg");
+    assert_eq!(json["status"], "processing");
+    assert!(json["photo"].is_null());
+    assert!(json["error"].is_null());
+}
+
+#[tokio::test]
+async fn test_get_job_not_found() {
+    let app = create_test_app().await;
+    let router = app.router();
+
+    let (status, json) = get_json(router, "/api/jobs/999").await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(json["error"].as_str().unwrap().contains("not found"));
+}
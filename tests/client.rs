@@ -59,8 +59,9 @@ async fn test_client_get_photos_empty() {
     let addr = spawn_server().await;
     let client = AppClient::new(format!("http://{}", addr));
 
-    let photos = client.get_photos().await.unwrap();
-    assert!(photos.is_empty());
+    let page = client.get_photos(&Default::default()).await.unwrap();
+    assert!(page.photos.is_empty());
+    assert_eq!(page.total, 0);
 }
 
 #[tokio::test]
@@ -68,8 +69,29 @@ async fn test_client_get_photos_with_data() {
     let addr = spawn_server_with_data().await;
     let client = AppClient::new(format!("http://{}", addr));
 
-    let photos = client.get_photos().await.unwrap();
-    assert_eq!(photos.len(), 2);
+    let page = client.get_photos(&Default::default()).await.unwrap();
+    assert_eq!(page.photos.len(), 2);
+    assert_eq!(page.total, 2);
+}
+
+#[tokio::test]
+async fn test_client_get_photos_page() {
+    let addr = spawn_server_with_data().await;
+    let client = AppClient::new(format!("http://{}", addr));
+
+    let query = pictureframe_common::PhotoQuery {
+        sort: pictureframe_common::PhotoSort::Title,
+        ..Default::default()
+    };
+    let page = client.get_photos(&query.clone().page(0, 1)).await.unwrap();
+    assert_eq!(page.photos.len(), 1);
+    assert_eq!(page.total, 2);
+    assert_eq!(page.next_offset(), Some(1));
+
+    let next = client.get_photos(&query.page(1, 1)).await.unwrap();
+    assert_eq!(next.offset, 1);
+    assert_eq!(next.next_offset(), None);
+    assert_ne!(next.photos[0].id, page.photos[0].id);
 }
 
 #[tokio::test]
@@ -78,7 +100,7 @@ async fn test_client_get_photo() {
     let client = AppClient::new(format!("http://{}", addr));
 
     // Get photos first to find a valid ID
-    let photos = client.get_photos().await.unwrap();
+    let photos = client.get_photos(&Default::default()).await.unwrap().photos;
     let photo_id = photos[0].id.0;
 
     let photo = client.get_photo(photo_id).await.unwrap();
@@ -117,3 +139,168 @@ async fn test_client_get_settings() {
     assert!(settings.current_album.is_none());
     assert_eq!(settings.interval.0, 180);
 }
+
+#[tokio::test]
+async fn test_client_get_duplicates_with_query() {
+    let addr = spawn_server().await;
+    let client = AppClient::new(format!("http://{}", addr));
+
+    let query = pictureframe_common::DuplicatesQuery {
+        max_distance: Some(2),
+    };
+    let groups = client.get_duplicates(&query).await.unwrap();
+    assert!(groups.is_empty());
+
+    let groups = client.get_duplicates(&Default::default()).await.unwrap();
+    assert!(groups.is_empty());
+}
+
+#[tokio::test]
+async fn test_client_display_round_trip() {
+    let addr = spawn_server_with_data().await;
+    let client = AppClient::new(format!("http://{}", addr));
+
+    let req = pictureframe_common::CreateDisplayRequest {
+        name: "Kitchen".to_string(),
+    };
+    let display = client.create_display(&req).await.unwrap();
+    assert_eq!(display.name, "Kitchen");
+
+    let query = pictureframe_common::NextQuery {
+        display: Some(display.token.clone()),
+    };
+    let next = client.get_next_photo(&query).await.unwrap();
+    assert_eq!(next.photo.title.as_deref(), Some("Photo 1"));
+
+    let displays = client.get_displays().await.unwrap();
+    assert_eq!(displays.len(), 1);
+    assert!(displays[0].last_seen_at.is_some());
+}
+
+#[tokio::test]
+async fn test_client_tag_photos_and_filter() {
+    let addr = spawn_server_with_data().await;
+    let client = AppClient::new(format!("http://{}", addr));
+
+    let photos = client.get_photos(&Default::default()).await.unwrap().photos;
+    let req = pictureframe_common::TagPhotosRequest {
+        photos: vec![photos[0].id],
+        tags: vec!["sun & sand".to_string()],
+    };
+    client.attach_tags(&req).await.unwrap();
+
+    let query = pictureframe_common::PhotoQuery::tagged(&["Sun & Sand"]);
+    let tagged = client.get_photos(&query).await.unwrap().photos;
+    assert_eq!(tagged.len(), 1);
+    assert_eq!(tagged[0].tags, vec!["sun & sand"]);
+
+    let tags = client.get_tags().await.unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].photo_count, 1);
+}
+
+#[tokio::test]
+async fn test_client_search() {
+    let addr = spawn_server_with_data().await;
+    let client = AppClient::new(format!("http://{}", addr));
+
+    let results = client
+        .search(&pictureframe_common::SearchQuery {
+            q: "test".to_string(),
+        })
+        .await
+        .unwrap();
+    assert!(results.photos.is_empty());
+    assert_eq!(results.albums.len(), 1);
+    assert_eq!(results.albums[0].album.photos.len(), 2);
+
+    let results = client
+        .search(&pictureframe_common::SearchQuery {
+            q: "photo 2".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(results.photos.len(), 1);
+    assert_eq!(
+        results.photos[0].snippet,
+        "<mark>Photo</mark> <mark>2</mark>"
+    );
+}
+
+#[tokio::test]
+async fn test_client_reorder_album() {
+    let addr = spawn_server_with_data().await;
+    let client = AppClient::new(format!("http://{}", addr));
+
+    let album = client.get_albums().await.unwrap().remove(0);
+    let reversed: Vec<_> = album.photos.iter().rev().copied().collect();
+    let req = pictureframe_common::ReorderAlbumRequest {
+        photos: reversed.clone(),
+    };
+    client.reorder_album(album.id.0, &req).await.unwrap();
+    assert_eq!(client.get_album(album.id.0).await.unwrap().photos, reversed);
+
+    let req = pictureframe_common::MoveAlbumPhotoRequest {
+        to: pictureframe_common::AlbumEnd::Top,
+    };
+    client
+        .move_album_photo(album.id.0, album.photos[0].0, &req)
+        .await
+        .unwrap();
+    assert_eq!(
+        client.get_album(album.id.0).await.unwrap().photos,
+        album.photos
+    );
+}
+
+#[tokio::test]
+async fn test_client_bulk_photos() {
+    let addr = spawn_server_with_data().await;
+    let client = AppClient::new(format!("http://{}", addr));
+
+    let album = client.get_albums().await.unwrap().remove(0);
+    let mut photos = album.photos.clone();
+    photos.push(pictureframe_common::PhotoID(999));
+    let req = pictureframe_common::BulkPhotoRequest {
+        photos,
+        operation: pictureframe_common::BulkPhotoOperation::RemoveFromAlbums {
+            albums: vec![album.id],
+        },
+    };
+    let results = client.bulk_photos(&req).await.unwrap();
+    assert_eq!(results.len(), 3);
+    assert!(results[..2].iter().all(|r| r.is_ok()));
+    assert!(!results[2].is_ok());
+    assert!(
+        client
+            .get_album(album.id.0)
+            .await
+            .unwrap()
+            .photos
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_client_trash() {
+    let addr = spawn_server_with_data().await;
+    let client = AppClient::new(format!("http://{}", addr));
+
+    let album = client.get_albums().await.unwrap().remove(0);
+    for photo in &album.photos {
+        client.delete_photo(photo.0).await.unwrap();
+    }
+    let trash = client.get_trash().await.unwrap();
+    assert_eq!(trash.len(), 2);
+
+    let restored = client.restore_photo(album.photos[0].0).await.unwrap();
+    assert_eq!(restored.id, album.photos[0]);
+    assert_eq!(
+        client.get_album(album.id.0).await.unwrap().photos,
+        vec![album.photos[0]]
+    );
+
+    client.purge_photo(album.photos[1].0).await.unwrap();
+    assert!(client.get_trash().await.unwrap().is_empty());
+    assert_eq!(client.empty_trash().await.unwrap(), 0);
+}
@@ -7,20 +7,34 @@ use axum::{
     body::Body,
     http::{Request, StatusCode},
 };
-use pictureframe_common::{AlbumID, CreateAlbumRequest, Update, UpdateSettingsRequest};
 use http_body_util::BodyExt;
 use pictureframe::App;
-use pictureframe::test_helpers::create_test_router_with_extras;
+use pictureframe::app::{DEFAULT_DUPLICATE_THRESHOLD, DEFAULT_TRASH_RETENTION_DAYS};
+use pictureframe::backup;
+use pictureframe::classifier::Classification;
+use pictureframe::image_processor::{
+    ImageProcessor, MagickProcessor, NativeProcessor, THUMBNAIL_SIZE,
+};
+use pictureframe::inbox::{Debounce, InboxWatcher, SettleCheck};
+use pictureframe::jobs;
+use pictureframe::test_helpers::{
+    StubClassifier, create_test_router_with_extras, set_deleted_days_ago,
+};
+use pictureframe_common::{
+    AlbumID, CreateAlbumRequest, PhotoID, ReorderAlbumRequest, SuggestionKind, Update,
+    UpdatePhotoRequest, UpdateSettingsRequest,
+};
 use serde_json::Value;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tempfile::TempDir;
 use tower::ServiceExt;
 
 /// Path to test images directory
 const TEST_IMAGES_DIR: &str = "./data/inbox-todo";
 
-/// Check if ImageMagick is available
+/// Check if ImageMagick is available (only needed for the magick backend tests)
 fn has_imagemagick() -> bool {
     which::which("magick").is_ok()
 }
@@ -38,20 +52,53 @@ fn get_test_images() -> Vec<PathBuf> {
         .map(|e| e.path())
         .filter(|p| {
             p.extension()
-                .map(|ext| ext.to_ascii_lowercase() == "jpg")
+                .map(|ext| ext.eq_ignore_ascii_case("jpg"))
                 .unwrap_or(false)
         })
         .take(3) // Only use 3 images for tests
         .collect()
 }
 
-/// Create an App with a real temporary data directory
+/// Write a generated photo so tests don't depend on the sample images being present.
+/// `seed` varies the picture so different seeds don't look like duplicates.
+fn write_generated_photo(dest: &std::path::Path, width: u32, height: u32, seed: u32) {
+    let img = image::RgbImage::from_fn(width, height, |x, y| {
+        let band = ((x * (seed + 1) / 37) ^ (y * (seed + 3) / 23)).is_multiple_of(2);
+        let shade = ((x + y) * 255 / (width + height)) as u8;
+        if band {
+            image::Rgb([shade, 255 - shade, (seed * 40) as u8])
+        } else {
+            image::Rgb([255 - shade, (seed * 70) as u8, shade])
+        }
+    });
+    img.save(dest).unwrap();
+}
+
+/// Create an App with a real temporary data directory and running job workers
 async fn create_e2e_app() -> (Arc<App>, TempDir) {
+    create_e2e_app_with_duplicate_threshold(DEFAULT_DUPLICATE_THRESHOLD).await
+}
+
+/// Create an e2e App that treats photos within `threshold` of each other as duplicates
+async fn create_e2e_app_with_duplicate_threshold(threshold: u32) -> (Arc<App>, TempDir) {
+    create_e2e_app_with(Arc::new(NativeProcessor::new()), threshold).await
+}
+
+/// Create an e2e App using the given image processor
+async fn create_e2e_app_with(
+    image_processor: Arc<dyn ImageProcessor>,
+    threshold: u32,
+) -> (Arc<App>, TempDir) {
     let temp_dir = TempDir::new().expect("Failed to create temp dir");
-    let app = App::new(temp_dir.path().to_path_buf())
+    let app = App::new(temp_dir.path().to_path_buf(), image_processor)
+        .await
+        .expect("Failed to create app")
+        .with_duplicate_threshold(threshold);
+    let app = Arc::new(app);
+    jobs::spawn_workers(app.clone(), 2)
         .await
-        .expect("Failed to create app");
-    (Arc::new(app), temp_dir)
+        .expect("Failed to start job workers");
+    (app, temp_dir)
 }
 
 /// Helper to make a GET request and return the response body as JSON.
@@ -134,6 +181,23 @@ async fn post_empty(router: axum::Router, uri: &str) -> (StatusCode, Value) {
     (status, json)
 }
 
+/// Helper to make a DELETE request.
+async fn delete(router: axum::Router, uri: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("DELETE")
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    let status = response.status();
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    (status, json)
+}
+
 /// Helper to upload a photo file via multipart form.
 async fn upload_photo_file(router: axum::Router, file_path: &std::path::Path) -> (StatusCode, Value) {
     let file_bytes = std::fs::read(file_path).expect("Failed to read test image");
@@ -171,6 +235,44 @@ async fn upload_bytes(router: axum::Router, filename: &str, file_bytes: &[u8]) -
     (status, json)
 }
 
+/// Helper to poll an upload job until it finishes. Returns the final job JSON.
+async fn wait_for_job(app: &Arc<App>, job: &Value) -> Value {
+    let job_id = job["id"].as_i64().expect("Upload response should be a job");
+    for _ in 0..300 {
+        let router = create_test_router_with_extras(app.clone());
+        let (status, json) = get_json(router, &format!("/api/jobs/{}", job_id)).await;
+        assert_eq!(status, StatusCode::OK);
+        if json["status"] == "done" || json["status"] == "failed" {
+            return json;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("Job {job_id} did not finish");
+}
+
+/// Helper to upload a photo and wait for it to be processed. Returns the new photo ID.
+async fn upload_and_wait(app: &Arc<App>, file_path: &std::path::Path) -> i32 {
+    let router = create_test_router_with_extras(app.clone());
+    let (status, json) = upload_photo_file(router, file_path).await;
+    assert_eq!(
+        status,
+        StatusCode::ACCEPTED,
+        "Upload failed for {:?}: {:?}",
+        file_path,
+        json
+    );
+
+    let job = wait_for_job(app, &json).await;
+    assert_eq!(
+        job["status"], "done",
+        "Processing failed for {:?}: {:?}",
+        file_path, job
+    );
+    job["photo"]
+        .as_i64()
+        .expect("Finished job should have a photo") as i32
+}
+
 /// Helper to get raw bytes from a GET request.
 async fn get_bytes(router: axum::Router, uri: &str) -> (StatusCode, Vec<u8>) {
     let request = Request::builder()
@@ -193,11 +295,6 @@ async fn get_bytes(router: axum::Router, uri: &str) -> (StatusCode, Vec<u8>) {
 
 #[tokio::test]
 async fn test_e2e_upload_single_photo() {
-    if !has_imagemagick() {
-        eprintln!("Skipping test: ImageMagick not available");
-        return;
-    }
-
     let images = get_test_images();
     if images.is_empty() {
         eprintln!("Skipping test: No test images found in {}", TEST_IMAGES_DIR);
@@ -207,29 +304,36 @@ async fn test_e2e_upload_single_photo() {
     let (app, _temp_dir) = create_e2e_app().await;
     let router = create_test_router_with_extras(app.clone());
 
-    // Upload a photo
+    // Upload a photo. It is accepted straight away and processed in the background
     let (status, json) = upload_photo_file(router, &images[0]).await;
 
-    assert_eq!(status, StatusCode::CREATED, "Upload failed: {:?}", json);
-    assert!(json["id"].as_i64().is_some(), "Response should have id");
-    assert!(json["url"].as_str().is_some(), "Response should have url");
+    assert_eq!(status, StatusCode::ACCEPTED, "Upload failed: {:?}", json);
+    assert!(
+        json["id"].as_i64().is_some(),
+        "Response should have a job id"
+    );
+    assert!(
+        json["status"] == "queued" || json["status"] == "processing" || json["status"] == "done"
+    );
+
+    let job = wait_for_job(&app, &json).await;
+    assert_eq!(job["status"], "done", "Processing failed: {:?}", job);
+    assert!(
+        job["photo"].as_i64().is_some(),
+        "Finished job should have a photo"
+    );
 
     // Verify photo appears in list
     let router = create_test_router_with_extras(app);
     let (status, json) = get_json(router, "/api/photos").await;
 
     assert_eq!(status, StatusCode::OK);
-    let photos = json.as_array().unwrap();
+    let photos = json["photos"].as_array().unwrap();
     assert_eq!(photos.len(), 1);
 }
 
 #[tokio::test]
 async fn test_e2e_upload_multiple_photos() {
-    if !has_imagemagick() {
-        eprintln!("Skipping test: ImageMagick not available");
-        return;
-    }
-
     let images = get_test_images();
     if images.len() < 2 {
         eprintln!("Skipping test: Need at least 2 test images");
@@ -238,11 +342,23 @@ async fn test_e2e_upload_multiple_photos() {
 
     let (app, _temp_dir) = create_e2e_app().await;
 
-    // Upload multiple photos
+    // Upload multiple photos, queueing them all before waiting on any
+    let mut jobs = vec![];
     for image in &images {
         let router = create_test_router_with_extras(app.clone());
         let (status, json) = upload_photo_file(router, image).await;
-        assert_eq!(status, StatusCode::CREATED, "Upload failed for {:?}: {:?}", image, json);
+        assert_eq!(
+            status,
+            StatusCode::ACCEPTED,
+            "Upload failed for {:?}: {:?}",
+            image,
+            json
+        );
+        jobs.push(json);
+    }
+    for job in &jobs {
+        let job = wait_for_job(&app, job).await;
+        assert_eq!(job["status"], "done", "Processing failed: {:?}", job);
     }
 
     // Verify all photos appear in list
@@ -250,17 +366,12 @@ async fn test_e2e_upload_multiple_photos() {
     let (status, json) = get_json(router, "/api/photos").await;
 
     assert_eq!(status, StatusCode::OK);
-    let photos = json.as_array().unwrap();
+    let photos = json["photos"].as_array().unwrap();
     assert_eq!(photos.len(), images.len());
 }
 
 #[tokio::test]
 async fn test_e2e_full_workflow() {
-    if !has_imagemagick() {
-        eprintln!("Skipping test: ImageMagick not available");
-        return;
-    }
-
     let images = get_test_images();
     if images.len() < 2 {
         eprintln!("Skipping test: Need at least 2 test images");
@@ -272,10 +383,7 @@ async fn test_e2e_full_workflow() {
     // 1. Upload photos
     let mut photo_ids = vec![];
     for image in &images {
-        let router = create_test_router_with_extras(app.clone());
-        let (status, json) = upload_photo_file(router, image).await;
-        assert_eq!(status, StatusCode::CREATED);
-        photo_ids.push(json["id"].as_i64().unwrap() as i32);
+        photo_ids.push(upload_and_wait(&app, image).await);
     }
 
     // 2. Create an album
@@ -286,6 +394,8 @@ async fn test_e2e_full_workflow() {
         &CreateAlbumRequest {
             name: "Test Album".to_string(),
             notes: Some("End-to-end test album".to_string()),
+            virtual_album: None,
+            smart_rule: None,
         },
     )
     .await;
@@ -317,6 +427,7 @@ async fn test_e2e_full_workflow() {
         &UpdateSettingsRequest {
             current_album_id: Some(Update::Set(AlbumID(album_id))),
             interval_seconds: Some(60),
+            rotation_mode: None,
         },
     )
     .await;
@@ -342,11 +453,6 @@ async fn test_e2e_full_workflow() {
 
 #[tokio::test]
 async fn test_e2e_image_serving() {
-    if !has_imagemagick() {
-        eprintln!("Skipping test: ImageMagick not available");
-        return;
-    }
-
     let images = get_test_images();
     if images.is_empty() {
         eprintln!("Skipping test: No test images found");
@@ -356,10 +462,7 @@ async fn test_e2e_image_serving() {
     let (app, _temp_dir) = create_e2e_app().await;
 
     // Upload a photo
-    let router = create_test_router_with_extras(app.clone());
-    let (status, json) = upload_photo_file(router, &images[0]).await;
-    assert_eq!(status, StatusCode::CREATED);
-    let photo_id = json["id"].as_i64().unwrap();
+    let photo_id = upload_and_wait(&app, &images[0]).await;
 
     // Serve the image
     let router = create_test_router_with_extras(app);
@@ -372,12 +475,258 @@ async fn test_e2e_image_serving() {
 }
 
 #[tokio::test]
-async fn test_e2e_photo_metadata_from_exif() {
-    if !has_imagemagick() {
-        eprintln!("Skipping test: ImageMagick not available");
-        return;
+async fn test_e2e_image_variants() {
+    let (app, temp_dir) = create_e2e_app().await;
+
+    let source = temp_dir.path().join("variants.png");
+    write_generated_photo(&source, 1000, 500, 7);
+    let original = std::fs::read(&source).unwrap();
+    let photo_id = upload_and_wait(&app, &source).await;
+    let router = create_test_router_with_extras(app);
+
+    let (status, photo) = get_json(router.clone(), &format!("/api/photos/{photo_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // No variant is the websize rendition
+    let (status, web) = get_bytes(router.clone(), photo["url"].as_str().unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(image::guess_format(&web).unwrap(), image::ImageFormat::Jpeg);
+    let web_img = image::load_from_memory(&web).unwrap();
+    assert_eq!((web_img.width(), web_img.height()), (1000, 500));
+    let (_, explicit_web) = get_bytes(
+        router.clone(),
+        &format!("/api/images/{photo_id}?variant=web"),
+    )
+    .await;
+    assert_eq!(explicit_web, web);
+
+    let (status, thumbnail) =
+        get_bytes(router.clone(), photo["thumbnail_url"].as_str().unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    let thumbnail_img = image::load_from_memory(&thumbnail).unwrap();
+    assert_eq!(
+        (thumbnail_img.width(), thumbnail_img.height()),
+        (THUMBNAIL_SIZE, THUMBNAIL_SIZE / 2)
+    );
+
+    // The original comes back untouched, still a PNG
+    let (status, full) = get_bytes(router.clone(), photo["full_url"].as_str().unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(full, original);
+
+    let (status, _) = get_bytes(router, &format!("/api/images/{photo_id}?variant=huge")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+/// GET with extra request headers, returning the status, response headers and body
+async fn get_with_headers(
+    router: axum::Router,
+    uri: &str,
+    headers: &[(&str, &str)],
+) -> (StatusCode, axum::http::HeaderMap, Vec<u8>) {
+    let mut request = Request::builder().method("GET").uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = router
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, headers, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_e2e_image_cache_validators() {
+    let (app, temp_dir) = create_e2e_app().await;
+
+    let source = temp_dir.path().join("cached.jpg");
+    write_generated_photo(&source, 600, 400, 8);
+    let photo_id = upload_and_wait(&app, &source).await;
+    let router = create_test_router_with_extras(app);
+    let uri = format!("/api/images/{photo_id}");
+
+    let (status, headers, body) = get_with_headers(router.clone(), &uri, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "image/jpeg");
+    assert_eq!(headers["content-length"], body.len().to_string().as_str());
+    assert_eq!(headers["cache-control"], "public, no-cache");
+    let etag = headers["etag"].to_str().unwrap().to_string();
+    assert!(
+        etag.starts_with('"') && !etag.starts_with("W/"),
+        "should be a strong ETag: {etag}"
+    );
+    let last_modified = headers["last-modified"].to_str().unwrap().to_string();
+
+    // The URLs handed out name the exact files, so they can be cached for good
+    let (_, photo) = get_json(router.clone(), &format!("/api/photos/{photo_id}")).await;
+    for key in ["url", "thumbnail_url", "full_url"] {
+        let (status, headers, _) =
+            get_with_headers(router.clone(), photo[key].as_str().unwrap(), &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            headers["cache-control"], "public, max-age=31536000, immutable",
+            "{key}"
+        );
     }
+    let (_, headers, _) = get_with_headers(router.clone(), &format!("{uri}?v=stale"), &[]).await;
+    assert_eq!(headers["cache-control"], "public, no-cache");
+
+    // Each rendition has its own ETag
+    let (_, thumbnail_headers, _) =
+        get_with_headers(router.clone(), &format!("{uri}?variant=thumbnail"), &[]).await;
+    assert_ne!(thumbnail_headers["etag"], etag.as_str());
+
+    let (status, headers, body) =
+        get_with_headers(router.clone(), &uri, &[("if-none-match", &etag)]).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+    assert_eq!(headers["etag"], etag.as_str());
+    assert!(body.is_empty());
+
+    let weak = format!("\"other\", W/{etag}");
+    let (status, _, _) = get_with_headers(router.clone(), &uri, &[("if-none-match", &weak)]).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+
+    let (status, _, _) = get_with_headers(
+        router.clone(),
+        &uri,
+        &[("if-modified-since", &last_modified)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+
+    // A stale ETag wins over a current date
+    let (status, _, body) = get_with_headers(
+        router,
+        &uri,
+        &[
+            ("if-none-match", "\"stale\""),
+            ("if-modified-since", &last_modified),
+        ],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body.is_empty());
+}
+
+#[tokio::test]
+async fn test_e2e_image_urls_change_when_renditions_are_regenerated() {
+    let (app, temp_dir) = create_e2e_app().await;
+
+    let source = temp_dir.path().join("regenerated.jpg");
+    write_generated_photo(&source, 600, 400, 8);
+    let photo_id = upload_and_wait(&app, &source).await;
+    let router = create_test_router_with_extras(app.clone());
+
+    let (_, photo) = get_json(router.clone(), &format!("/api/photos/{photo_id}")).await;
+    let url = photo["url"].as_str().unwrap().to_string();
+    let (_, headers, _) = get_with_headers(router.clone(), &url, &[]).await;
+    let etag = headers["etag"].to_str().unwrap().to_string();
+
+    // Regenerating from a different original rewrites the renditions in place
+    let fullsize = sqlx::query_scalar::<_, String>("SELECT fullsize_path FROM photo WHERE id = ?")
+        .bind(photo_id)
+        .fetch_one(app.pool())
+        .await
+        .unwrap();
+    write_generated_photo(std::path::Path::new(&fullsize), 300, 200, 9);
+    assert_eq!(app.regenerate_all_renditions().await.unwrap(), (1, 0));
+
+    let (_, photo) = get_json(router.clone(), &format!("/api/photos/{photo_id}")).await;
+    let new_url = photo["url"].as_str().unwrap();
+    assert_ne!(new_url, url);
+
+    let (status, headers, body) =
+        get_with_headers(router.clone(), new_url, &[("if-none-match", &etag)]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(headers["etag"], etag.as_str());
+    assert_eq!(
+        headers["cache-control"],
+        "public, max-age=31536000, immutable"
+    );
+    assert_eq!(image::load_from_memory(&body).unwrap().width(), 300);
+
+    // The old URL still works, but no longer names what it serves so mustn't be kept
+    let (status, headers, _) = get_with_headers(router, &url, &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["cache-control"], "public, no-cache");
+}
+
+#[tokio::test]
+async fn test_e2e_backfill_image_details_of_older_photos() {
+    let (app, temp_dir) = create_e2e_app().await;
+
+    let source = temp_dir.path().join("older.png");
+    write_generated_photo(&source, 300, 200, 5);
+    let photo_id = upload_and_wait(&app, &source).await;
+    let details = "SELECT content_type, image_key FROM photo WHERE id = ?";
+    let imported = sqlx::query_as::<_, (Option<String>, Option<String>)>(details)
+        .bind(photo_id)
+        .fetch_one(app.pool())
+        .await
+        .unwrap();
+    assert_eq!(imported.0.as_deref(), Some("image/png"));
+    assert!(imported.1.is_some());
+
+    // As imported before the details were stored
+    sqlx::query("UPDATE photo SET content_type = NULL, image_key = NULL WHERE id = ?")
+        .bind(photo_id)
+        .execute(app.pool())
+        .await
+        .unwrap();
+    let router = create_test_router_with_extras(app.clone());
+    let (_, photo) = get_json(router.clone(), &format!("/api/photos/{photo_id}")).await;
+    let (status, headers, _) =
+        get_with_headers(router, photo["full_url"].as_str().unwrap(), &[]).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "image/png");
+    assert_eq!(headers["cache-control"], "public, no-cache");
+
+    assert_eq!(app.backfill_image_details().await.unwrap(), 1);
+    let backfilled = sqlx::query_as::<_, (Option<String>, Option<String>)>(details)
+        .bind(photo_id)
+        .fetch_one(app.pool())
+        .await
+        .unwrap();
+    assert_eq!(backfilled, imported);
+    assert_eq!(app.backfill_image_details().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_e2e_image_range_requests() {
+    let (app, temp_dir) = create_e2e_app().await;
+
+    let source = temp_dir.path().join("ranged.png");
+    write_generated_photo(&source, 300, 200, 9);
+    let original = std::fs::read(&source).unwrap();
+    let photo_id = upload_and_wait(&app, &source).await;
+    let router = create_test_router_with_extras(app);
+    let uri = format!("/api/images/{photo_id}?variant=full");
+
+    let (status, headers, body) =
+        get_with_headers(router.clone(), &uri, &[("range", "bytes=0-7")]).await;
+    assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(body, &original[0..8]);
+    assert_eq!(
+        headers["content-range"],
+        format!("bytes 0-7/{}", original.len()).as_str()
+    );
+    assert_eq!(headers["content-type"], "image/png");
+    assert!(headers.contains_key("etag"));
 
+    let (status, _, body) = get_with_headers(router.clone(), &uri, &[("range", "bytes=-16")]).await;
+    assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+    assert_eq!(body, &original[original.len() - 16..]);
+
+    let past_end = format!("bytes={}-", original.len() + 10);
+    let (status, _, _) = get_with_headers(router, &uri, &[("range", &past_end)]).await;
+    assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+}
+
+#[tokio::test]
+async fn test_e2e_photo_metadata_from_exif() {
     let images = get_test_images();
     if images.is_empty() {
         eprintln!("Skipping test: No test images found");
@@ -387,47 +736,255 @@ async fn test_e2e_photo_metadata_from_exif() {
     let (app, _temp_dir) = create_e2e_app().await;
 
     // Upload a photo
+    let photo_id = upload_and_wait(&app, &images[0]).await;
     let router = create_test_router_with_extras(app.clone());
-    let (status, json) = upload_photo_file(router, &images[0]).await;
-    assert_eq!(status, StatusCode::CREATED);
+    let (status, json) = get_json(router, &format!("/api/photos/{}", photo_id)).await;
+    assert_eq!(status, StatusCode::OK);
 
     // The title should be set from the filename (without extension)
     let expected_title = images[0].file_stem().unwrap().to_string_lossy();
     assert_eq!(json["title"].as_str().unwrap(), expected_title.as_ref());
 }
 
+/// Write a generated JPEG carrying the given EXIF fields
+fn write_photo_with_exif(dest: &std::path::Path, seed: u32, fields: &[exif::Field]) {
+    write_generated_photo(dest, 320, 240, seed);
+
+    let mut writer = exif::experimental::Writer::new();
+    for field in fields {
+        writer.push_field(field);
+    }
+    let mut tiff = std::io::Cursor::new(Vec::new());
+    writer.write(&mut tiff, false).unwrap();
+    let tiff = tiff.into_inner();
+
+    let mut segment = vec![0xFF, 0xE1];
+    segment.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+    segment.extend_from_slice(b"Exif\0\0");
+    segment.extend_from_slice(&tiff);
+    let mut bytes = std::fs::read(dest).unwrap();
+    bytes.splice(2..2, segment);
+    std::fs::write(dest, &bytes).unwrap();
+}
+
+fn exif_field(tag: exif::Tag, value: exif::Value) -> exif::Field {
+    exif::Field {
+        tag,
+        ifd_num: exif::In::PRIMARY,
+        value,
+    }
+}
+
+fn exif_text(text: &str) -> exif::Value {
+    exif::Value::Ascii(vec![text.as_bytes().to_vec()])
+}
+
+fn exif_rationals(parts: &[(u32, u32)]) -> exif::Value {
+    exif::Value::Rational(
+        parts
+            .iter()
+            .map(|&(num, denom)| exif::Rational { num, denom })
+            .collect(),
+    )
+}
+
+#[tokio::test]
+async fn test_e2e_photo_exif_details() {
+    use exif::{Tag, Value};
+
+    let (app, temp_dir) = create_e2e_app().await;
+
+    let source = temp_dir.path().join("camera.jpg");
+    write_photo_with_exif(
+        &source,
+        5,
+        &[
+            exif_field(Tag::Artist, exif_text("Jane Doe")),
+            exif_field(Tag::Copyright, exif_text("2023 Jane Doe")),
+            exif_field(Tag::Make, exif_text("Canon")),
+            exif_field(Tag::Model, exif_text("Canon EOS R5")),
+            exif_field(Tag::DateTime, exif_text("2024:01:02 09:00:00")),
+            exif_field(Tag::LensModel, exif_text("RF50mm F1.8 STM")),
+            exif_field(Tag::FocalLength, exif_rationals(&[(50, 1)])),
+            exif_field(Tag::FNumber, exif_rationals(&[(18, 10)])),
+            exif_field(Tag::ExposureTime, exif_rationals(&[(1, 250)])),
+            exif_field(Tag::PhotographicSensitivity, Value::Short(vec![100])),
+            exif_field(Tag::PixelXDimension, Value::Long(vec![320])),
+            exif_field(Tag::PixelYDimension, Value::Long(vec![240])),
+            exif_field(Tag::DateTimeOriginal, exif_text("2023:06:15 14:30:00")),
+            exif_field(Tag::OffsetTimeOriginal, exif_text("+02:00")),
+            exif_field(Tag::GPSLatitudeRef, exif_text("S")),
+            exif_field(
+                Tag::GPSLatitude,
+                exif_rationals(&[(33, 1), (51, 1), (36, 1)]),
+            ),
+            exif_field(Tag::GPSLongitudeRef, exif_text("E")),
+            exif_field(
+                Tag::GPSLongitude,
+                exif_rationals(&[(151, 1), (12, 1), (36, 1)]),
+            ),
+        ],
+    );
+    let photo_id = upload_and_wait(&app, &source).await;
+
+    let router = create_test_router_with_extras(app);
+    let (status, photo) = get_json(router, &format!("/api/photos/{photo_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert_eq!(photo["artist"], "Jane Doe");
+    assert_eq!(photo["copyright"], "2023 Jane Doe");
+    // The original capture time wins over the file's modification time
+    assert_eq!(photo["date_taken"], "2023-06-15T14:30:00");
+
+    let exif = &photo["exif"];
+    assert_eq!(exif["camera_make"], "Canon");
+    assert_eq!(exif["camera_model"], "Canon EOS R5");
+    assert_eq!(exif["lens"], "RF50mm F1.8 STM");
+    assert_eq!(exif["focal_length"], 50.0);
+    assert_eq!(exif["aperture"], 1.8);
+    assert_eq!(exif["exposure_time"], 0.004);
+    assert_eq!(exif["iso"], 100);
+    assert_eq!(exif["pixel_width"], 320);
+    assert_eq!(exif["pixel_height"], 240);
+    assert_eq!(exif["date_taken_offset"], "+02:00");
+    let latitude = exif["latitude"].as_f64().unwrap();
+    let longitude = exif["longitude"].as_f64().unwrap();
+    assert!((latitude + 33.86).abs() < 1e-9, "{latitude}");
+    assert!((longitude - 151.21).abs() < 1e-9, "{longitude}");
+}
+
+#[tokio::test]
+async fn test_e2e_photo_exif_bad_tags_only_lose_themselves() {
+    use exif::{Tag, Value};
+
+    let (app, temp_dir) = create_e2e_app().await;
+
+    let source = temp_dir.path().join("odd-camera.jpg");
+    write_photo_with_exif(
+        &source,
+        6,
+        &[
+            exif_field(Tag::Make, exif_text("NIKON CORPORATION")),
+            exif_field(Tag::DateTime, exif_text("2024:01:02 09:00:00")),
+            exif_field(Tag::FNumber, exif_rationals(&[(0, 0)])),
+            exif_field(Tag::PhotographicSensitivity, Value::Short(vec![400])),
+            exif_field(Tag::DateTimeOriginal, exif_text("0000:00:00 00:00:00")),
+            exif_field(Tag::OffsetTimeOriginal, exif_text("local")),
+            // Coordinates without a hemisphere can't be placed
+            exif_field(
+                Tag::GPSLatitude,
+                exif_rationals(&[(33, 1), (51, 1), (36, 1)]),
+            ),
+        ],
+    );
+    let photo_id = upload_and_wait(&app, &source).await;
+
+    let router = create_test_router_with_extras(app);
+    let (status, photo) = get_json(router, &format!("/api/photos/{photo_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // An unreadable capture time falls back to the modification time
+    assert_eq!(photo["date_taken"], "2024-01-02T09:00:00");
+
+    let exif = &photo["exif"];
+    assert_eq!(exif["camera_make"], "NIKON CORPORATION");
+    assert_eq!(exif["iso"], 400);
+    assert!(exif["aperture"].is_null());
+    assert!(exif["date_taken_offset"].is_null());
+    assert!(exif["latitude"].is_null());
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // File Type Validation Tests
 // ─────────────────────────────────────────────────────────────────────────────
 
-#[tokio::test]
-async fn test_e2e_upload_rejects_png() {
-    if !has_imagemagick() {
-        eprintln!("Skipping test: ImageMagick not available");
+/// Write a copy of a test image re-encoded in another format.
+/// PNGs get a transparent corner to exercise alpha handling.
+fn write_converted_copy(
+    image: &std::path::Path,
+    dest: &std::path::Path,
+    format: image::ImageFormat,
+) {
+    let img = image::open(image)
+        .unwrap()
+        .resize(400, 400, image::imageops::FilterType::Triangle);
+    let img = if format == image::ImageFormat::Png {
+        let mut rgba = img.to_rgba8();
+        for y in 0..20 {
+            for x in 0..20 {
+                rgba.get_pixel_mut(x, y)[3] = 0;
+            }
+        }
+        image::DynamicImage::ImageRgba8(rgba)
+    } else {
+        image::DynamicImage::ImageRgb8(img.to_rgb8())
+    };
+    img.save_with_format(dest, format).unwrap();
+}
+
+/// Upload a converted copy of a test image and check the stored renditions.
+async fn check_upload_converted(format: image::ImageFormat, filename: &str, fullsize_ext: &str) {
+    let images = get_test_images();
+    if images.is_empty() {
+        eprintln!("Skipping test: No test images found in {}", TEST_IMAGES_DIR);
         return;
     }
 
-    let (app, _temp_dir) = create_e2e_app().await;
+    let (app, temp_dir) = create_e2e_app().await;
+    let converted = temp_dir.path().join(filename);
+    write_converted_copy(&images[0], &converted, format);
+
+    let photo_id = upload_and_wait(&app, &converted).await;
+
+    // The original is kept as-is, the renditions are JPEG
+    let (fullsize, websize, thumbnail) = sqlx::query_as::<_, (String, String, String)>(
+        "SELECT fullsize_path, websize_path, thumbnail_path FROM photo WHERE id = ?",
+    )
+    .bind(photo_id)
+    .fetch_one(app.pool())
+    .await
+    .unwrap();
+    assert!(fullsize.ends_with(fullsize_ext), "{fullsize}");
+    assert_eq!(
+        std::fs::read(&fullsize).unwrap(),
+        std::fs::read(&converted).unwrap()
+    );
+    assert!(websize.ends_with(".jpg"), "{websize}");
+    assert!(thumbnail.ends_with(".jpg"), "{thumbnail}");
+
     let router = create_test_router_with_extras(app);
+    let request = Request::builder()
+        .uri(format!("/api/images/{}", photo_id))
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/jpeg");
+}
 
-    // Try to upload a fake PNG file
-    let fake_png = b"\x89PNG\r\n\x1a\nfake png data";
-    let (status, json) = upload_bytes(router, "test.png", fake_png).await;
+#[tokio::test]
+async fn test_e2e_upload_png() {
+    check_upload_converted(image::ImageFormat::Png, "scan.png", ".png").await;
+}
 
-    assert_eq!(status, StatusCode::BAD_REQUEST);
-    assert!(json["error"]
-        .as_str()
-        .unwrap()
-        .contains("Only JPEG files are supported"));
+#[tokio::test]
+async fn test_e2e_upload_webp() {
+    check_upload_converted(image::ImageFormat::WebP, "export.webp", ".webp").await;
 }
 
 #[tokio::test]
-async fn test_e2e_upload_rejects_gif() {
-    if !has_imagemagick() {
-        eprintln!("Skipping test: ImageMagick not available");
-        return;
-    }
+async fn test_e2e_upload_tiff() {
+    check_upload_converted(image::ImageFormat::Tiff, "scan.tif", ".tiff").await;
+}
+
+#[tokio::test]
+async fn test_e2e_upload_detects_format_from_content() {
+    // A PNG with a .jpg name is still stored as a PNG
+    check_upload_converted(image::ImageFormat::Png, "mislabelled.jpg", ".png").await;
+}
 
+#[tokio::test]
+async fn test_e2e_upload_rejects_gif() {
     let (app, _temp_dir) = create_e2e_app().await;
     let router = create_test_router_with_extras(app);
 
@@ -436,29 +993,1145 @@ async fn test_e2e_upload_rejects_gif() {
     let (status, json) = upload_bytes(router, "test.gif", fake_gif).await;
 
     assert_eq!(status, StatusCode::BAD_REQUEST);
-    assert!(json["error"]
-        .as_str()
-        .unwrap()
-        .contains("Only JPEG files are supported"));
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("Unsupported image format")
+    );
 }
 
 #[tokio::test]
-async fn test_e2e_upload_rejects_webp() {
-    if !has_imagemagick() {
-        eprintln!("Skipping test: ImageMagick not available");
-        return;
-    }
+async fn test_e2e_upload_rejects_non_image_with_image_extension() {
+    let (app, _temp_dir) = create_e2e_app().await;
+    let router = create_test_router_with_extras(app);
 
+    let (status, json) = upload_bytes(router, "test.jpg", b"just some text").await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("Unsupported image format")
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_upload_bad_photo_fails_job() {
+    let (app, temp_dir) = create_e2e_app().await;
+    let router = create_test_router_with_extras(app.clone());
+
+    // Passes the extension check but can't be decoded
+    let (status, json) = upload_bytes(router, "broken.jpg", b"\xFF\xD8\xFF\xE0truncated").await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+
+    let job = wait_for_job(&app, &json).await;
+    assert_eq!(job["status"], "failed");
+    assert!(job["error"].as_str().is_some_and(|e| !e.is_empty()));
+    assert!(job["photo"].is_null());
+
+    // The staged upload is cleaned up either way
+    let staging_dir = temp_dir.path().join("staging");
+    assert_eq!(std::fs::read_dir(staging_dir).unwrap().count(), 0);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Image Processor Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Look up the stored websize and thumbnail paths for a photo
+async fn rendition_paths(app: &Arc<App>, photo_id: i32) -> (PathBuf, PathBuf) {
+    let (websize, thumbnail) = sqlx::query_as::<_, (String, String)>(
+        "SELECT websize_path, thumbnail_path FROM photo WHERE id = ?",
+    )
+    .bind(photo_id)
+    .fetch_one(app.pool())
+    .await
+    .unwrap();
+    (PathBuf::from(websize), PathBuf::from(thumbnail))
+}
+
+/// Whether a JPEG contains the given marker (e.g. 0xC2 for progressive, 0xE1 for EXIF)
+fn has_jpeg_marker(bytes: &[u8], marker: u8) -> bool {
+    bytes.windows(2).any(|w| w == [0xFF, marker])
+}
+
+#[tokio::test]
+async fn test_e2e_native_renditions() {
+    let (app, temp_dir) = create_e2e_app().await;
+
+    // A JPEG with an EXIF segment that should not survive into the renditions
+    let source = temp_dir.path().join("generated.jpg");
+    write_generated_photo(&source, 1200, 800, 1);
+    let mut bytes = std::fs::read(&source).unwrap();
+    let exif = b"\xFF\xE1\x00\x16Exif\x00\x00MM\x00\x2A\x00\x00\x00\x08\x00\x00\x00\x00\x00\x00";
+    bytes.splice(2..2, exif.iter().copied());
+    std::fs::write(&source, &bytes).unwrap();
+
+    let photo_id = upload_and_wait(&app, &source).await;
+    let (websize, thumbnail) = rendition_paths(&app, photo_id).await;
+
+    let websize_bytes = std::fs::read(&websize).unwrap();
+    assert!(
+        has_jpeg_marker(&websize_bytes, 0xC2),
+        "websize should be progressive"
+    );
+    assert!(
+        !has_jpeg_marker(&websize_bytes, 0xE1),
+        "websize should have no EXIF"
+    );
+    let websize_img = image::load_from_memory(&websize_bytes).unwrap();
+    assert_eq!((websize_img.width(), websize_img.height()), (1200, 800));
+
+    let thumbnail_bytes = std::fs::read(&thumbnail).unwrap();
+    assert!(
+        has_jpeg_marker(&thumbnail_bytes, 0xC2),
+        "thumbnail should be progressive"
+    );
+    let thumbnail_img = image::load_from_memory(&thumbnail_bytes).unwrap();
+    assert_eq!(thumbnail_img.width(), THUMBNAIL_SIZE);
+    assert!(thumbnail_img.height() < THUMBNAIL_SIZE);
+}
+
+#[tokio::test]
+async fn test_e2e_native_flattens_transparency_onto_white() {
+    let (app, temp_dir) = create_e2e_app().await;
+
+    let source = temp_dir.path().join("transparent.png");
+    let img = image::RgbaImage::from_fn(200, 200, |x, _| {
+        if x < 100 {
+            image::Rgba([0, 0, 0, 0])
+        } else {
+            image::Rgba([200, 0, 0, 255])
+        }
+    });
+    img.save(&source).unwrap();
+
+    let photo_id = upload_and_wait(&app, &source).await;
+    let (websize, _) = rendition_paths(&app, photo_id).await;
+
+    let websize_img = image::open(&websize).unwrap().to_rgb8();
+    let transparent_area = websize_img.get_pixel(20, 100).0;
+    assert!(
+        transparent_area.iter().all(|&c| c > 240),
+        "{transparent_area:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_native_rejects_heic() {
     let (app, _temp_dir) = create_e2e_app().await;
     let router = create_test_router_with_extras(app);
 
-    // Try to upload a fake WebP file
-    let fake_webp = b"RIFFxxxxWEBPfake webp data";
-    let (status, json) = upload_bytes(router, "test.webp", fake_webp).await;
+    let heic_header = b"\x00\x00\x00\x18ftypheic\x00\x00\x00\x00mif1heic";
+    let (status, json) = upload_bytes(router, "IMG_0001.HEIC", heic_header).await;
 
     assert_eq!(status, StatusCode::BAD_REQUEST);
-    assert!(json["error"]
-        .as_str()
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("not supported by the native image processor")
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_magick_backend_upload() {
+    if !has_imagemagick() {
+        eprintln!("Skipping test: ImageMagick not available");
+        return;
+    }
+
+    let processor = Arc::new(MagickProcessor::new().unwrap());
+    let (app, temp_dir) = create_e2e_app_with(processor, DEFAULT_DUPLICATE_THRESHOLD).await;
+
+    let source = temp_dir.path().join("generated.jpg");
+    write_generated_photo(&source, 800, 600, 2);
+    let photo_id = upload_and_wait(&app, &source).await;
+
+    let (websize, thumbnail) = rendition_paths(&app, photo_id).await;
+    let thumbnail_img = image::open(&thumbnail).unwrap();
+    assert!(thumbnail_img.width() <= THUMBNAIL_SIZE && thumbnail_img.height() <= THUMBNAIL_SIZE);
+    assert!(image::open(&websize).is_ok());
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Orientation Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Write a white JPEG with a red square in the top-left corner of the stored pixels, tagged
+/// with the given EXIF orientation. Where the square ends up shows which way it was turned.
+fn write_oriented_photo(dest: &std::path::Path, width: u32, height: u32, orientation: u8) {
+    let img = image::RgbImage::from_fn(width, height, |x, y| {
+        if x < 50 && y < 50 {
+            image::Rgb([255, 0, 0])
+        } else {
+            image::Rgb([255, 255, 255])
+        }
+    });
+    img.save(dest).unwrap();
+
+    // APP1 segment holding a big-endian TIFF header and a single Orientation (0x0112) entry
+    let mut exif = b"\xFF\xE1\x00\x22Exif\x00\x00MM\x00\x2A\x00\x00\x00\x08\x00\x01".to_vec();
+    exif.extend_from_slice(b"\x01\x12\x00\x03\x00\x00\x00\x01\x00");
+    exif.extend_from_slice(&[orientation, 0, 0, 0, 0, 0, 0]);
+    let mut bytes = std::fs::read(dest).unwrap();
+    bytes.splice(2..2, exif);
+    std::fs::write(dest, &bytes).unwrap();
+}
+
+#[tokio::test]
+async fn test_e2e_upload_applies_exif_orientation() {
+    let (app, temp_dir) = create_e2e_app().await;
+
+    // Orientation 6: the camera was held rotated, display by turning 90° clockwise
+    let source = temp_dir.path().join("portrait.jpg");
+    write_oriented_photo(&source, 400, 200, 6);
+    let photo_id = upload_and_wait(&app, &source).await;
+
+    let (websize, thumbnail) = rendition_paths(&app, photo_id).await;
+    let websize_img = image::open(&websize).unwrap().to_rgb8();
+    assert_eq!(websize_img.dimensions(), (200, 400));
+    let [r, g, b] = websize_img.get_pixel(175, 25).0;
+    assert!(
+        r > 200 && g < 60 && b < 60,
+        "top-left corner should now be top-right"
+    );
+    let thumbnail_img = image::open(&thumbnail).unwrap();
+    assert_eq!(
+        (thumbnail_img.width(), thumbnail_img.height()),
+        (160, THUMBNAIL_SIZE)
+    );
+
+    let router = create_test_router_with_extras(app.clone());
+    let (status, photo) = get_json(router, &format!("/api/photos/{photo_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(photo["width"], 200);
+    assert_eq!(photo["height"], 400);
+
+    let orientation = sqlx::query_scalar::<_, i32>("SELECT orientation FROM photo WHERE id = ?")
+        .bind(photo_id)
+        .fetch_one(app.pool())
+        .await
+        .unwrap();
+    assert_eq!(orientation, 6);
+}
+
+#[tokio::test]
+async fn test_e2e_upload_without_orientation_is_unchanged() {
+    let (app, temp_dir) = create_e2e_app().await;
+
+    let source = temp_dir.path().join("landscape.jpg");
+    write_oriented_photo(&source, 400, 200, 1);
+    let photo_id = upload_and_wait(&app, &source).await;
+
+    let (websize, _) = rendition_paths(&app, photo_id).await;
+    let websize_img = image::open(&websize).unwrap().to_rgb8();
+    assert_eq!(websize_img.dimensions(), (400, 200));
+    let [r, g, b] = websize_img.get_pixel(25, 25).0;
+    assert!(r > 200 && g < 60 && b < 60);
+}
+
+#[tokio::test]
+async fn test_e2e_regenerate_renditions_fixes_existing_photos() {
+    let (app, temp_dir) = create_e2e_app().await;
+
+    let source = temp_dir.path().join("portrait.jpg");
+    write_oriented_photo(&source, 400, 200, 6);
+    let rotated_id = upload_and_wait(&app, &source).await;
+
+    let source = temp_dir.path().join("generated.jpg");
+    write_generated_photo(&source, 300, 300, 4);
+    let broken_id = upload_and_wait(&app, &source).await;
+
+    // Put the first photo back how an older import would have left it: sideways renditions
+    // and no dimensions
+    let (websize, thumbnail) = rendition_paths(&app, rotated_id).await;
+    image::RgbImage::new(400, 200).save(&websize).unwrap();
+    image::RgbImage::new(320, 160).save(&thumbnail).unwrap();
+    sqlx::query("UPDATE photo SET width = NULL, height = NULL, orientation = 1 WHERE id = ?")
+        .bind(rotated_id)
+        .execute(app.pool())
+        .await
+        .unwrap();
+
+    // The second photo has lost its original, so it can't be regenerated
+    let fullsize = sqlx::query_scalar::<_, String>("SELECT fullsize_path FROM photo WHERE id = ?")
+        .bind(broken_id)
+        .fetch_one(app.pool())
+        .await
+        .unwrap();
+    std::fs::remove_file(fullsize).unwrap();
+
+    let (done, failed) = app.regenerate_all_renditions().await.unwrap();
+    assert_eq!((done, failed), (1, 1));
+
+    assert_eq!(image::image_dimensions(&websize).unwrap(), (200, 400));
+    assert_eq!(
+        image::image_dimensions(&thumbnail).unwrap(),
+        (160, THUMBNAIL_SIZE)
+    );
+    let dims = sqlx::query_as::<_, (Option<i32>, Option<i32>, i32)>(
+        "SELECT width, height, orientation FROM photo WHERE id = ?",
+    )
+    .bind(rotated_id)
+    .fetch_one(app.pool())
+    .await
+    .unwrap();
+    assert_eq!(dims, (Some(200), Some(400), 6));
+
+    // The failed photo keeps its existing renditions
+    let (websize, _) = rendition_paths(&app, broken_id).await;
+    assert_eq!(image::image_dimensions(&websize).unwrap(), (300, 300));
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Duplicate Detection Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Write a slightly cropped copy of `image` to `dest`, like a re-export from a photo editor.
+fn write_cropped_copy(image: &std::path::Path, dest: &std::path::Path) {
+    let img = image::open(image).unwrap();
+    let (width, height) = (img.width(), img.height());
+    let cropped = img.crop_imm(
+        width / 50,
+        height / 50,
+        width - width / 25,
+        height - height / 25,
+    );
+    cropped.save(dest).unwrap();
+}
+
+#[tokio::test]
+async fn test_e2e_upload_rejects_exact_duplicate() {
+    let images = get_test_images();
+    if images.is_empty() {
+        eprintln!("Skipping test: No test images found in {}", TEST_IMAGES_DIR);
+        return;
+    }
+
+    let (app, temp_dir) = create_e2e_app().await;
+    let photo_id = upload_and_wait(&app, &images[0]).await;
+
+    // Same photo again, under a different name
+    let file_bytes = std::fs::read(&images[0]).unwrap();
+    let router = create_test_router_with_extras(app.clone());
+    let (status, json) = upload_bytes(router, "copy.jpg", &file_bytes).await;
+
+    assert_eq!(
+        status,
+        StatusCode::CONFLICT,
+        "Expected 409, got: {:?}",
+        json
+    );
+    assert_eq!(json["existing"], photo_id);
+    assert_eq!(json["distance"], 0);
+    assert!(json["error"].as_str().unwrap().contains("duplicate"));
+
+    // Nothing was queued or stored
+    let router = create_test_router_with_extras(app.clone());
+    let (_, jobs) = get_json(router, "/api/jobs").await;
+    assert_eq!(jobs.as_array().unwrap().len(), 1);
+    let router = create_test_router_with_extras(app.clone());
+    let (_, photos) = get_json(router, "/api/photos").await;
+    assert_eq!(photos["photos"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        std::fs::read_dir(temp_dir.path().join("staging"))
+            .unwrap()
+            .count(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_upload_rejects_near_duplicate() {
+    let images = get_test_images();
+    if images.is_empty() {
+        eprintln!("Skipping test: No test images found in {}", TEST_IMAGES_DIR);
+        return;
+    }
+
+    let (app, temp_dir) = create_e2e_app().await;
+    let photo_id = upload_and_wait(&app, &images[0]).await;
+
+    let cropped = temp_dir.path().join("cropped.jpg");
+    write_cropped_copy(&images[0], &cropped);
+
+    let router = create_test_router_with_extras(app.clone());
+    let (status, json) = upload_photo_file(router, &cropped).await;
+
+    assert_eq!(
+        status,
+        StatusCode::CONFLICT,
+        "Expected 409, got: {:?}",
+        json
+    );
+    assert_eq!(json["existing"], photo_id);
+    assert!(json["distance"].as_u64().unwrap() <= DEFAULT_DUPLICATE_THRESHOLD as u64);
+}
+
+#[tokio::test]
+async fn test_e2e_duplicate_queued_before_processing_fails_in_its_job() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let app = App::new(
+        temp_dir.path().to_path_buf(),
+        Arc::new(NativeProcessor::new()),
+    )
+    .await
+    .expect("Failed to create app");
+    let app = Arc::new(app);
+
+    // With no workers running yet, neither copy is in the library when it's uploaded
+    let source = temp_dir.path().join("generated.jpg");
+    write_generated_photo(&source, 400, 300, 1);
+    let mut queued = vec![];
+    for _ in 0..2 {
+        let router = create_test_router_with_extras(app.clone());
+        let (status, json) = upload_photo_file(router, &source).await;
+        assert_eq!(status, StatusCode::ACCEPTED, "{:?}", json);
+        queued.push(json);
+    }
+
+    // A single worker runs them in order, so the second finds the first already imported
+    jobs::spawn_workers(app.clone(), 1)
+        .await
+        .expect("Failed to start job workers");
+    let first = wait_for_job(&app, &queued[0]).await;
+    let second = wait_for_job(&app, &queued[1]).await;
+
+    assert_eq!(first["status"], "done", "{:?}", first);
+    assert_eq!(second["status"], "failed", "{:?}", second);
+    assert_eq!(second["photo"], first["photo"]);
+    assert!(
+        second["error"].as_str().unwrap().contains("duplicate"),
+        "{:?}",
+        second
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_zero_threshold_only_rejects_exact_duplicates() {
+    let images = get_test_images();
+    if images.is_empty() {
+        eprintln!("Skipping test: No test images found in {}", TEST_IMAGES_DIR);
+        return;
+    }
+
+    let (app, temp_dir) = create_e2e_app_with_duplicate_threshold(0).await;
+    upload_and_wait(&app, &images[0]).await;
+
+    let cropped = temp_dir.path().join("cropped.jpg");
+    write_cropped_copy(&images[0], &cropped);
+    let original = image::open(&images[0]).unwrap();
+    let copy = image::open(&cropped).unwrap();
+    let hasher = image_hasher::HasherConfig::new().to_hasher();
+    if hasher.hash_image(&original) == hasher.hash_image(&copy) {
+        eprintln!("Skipping test: cropped copy hashes identically");
+        return;
+    }
+
+    upload_and_wait(&app, &cropped).await;
+
+    let router = create_test_router_with_extras(app);
+    let (_, photos) = get_json(router, "/api/photos").await;
+    assert_eq!(photos["photos"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_e2e_inbox_moves_duplicates_to_fails() {
+    let images = get_test_images();
+    if images.is_empty() {
+        eprintln!("Skipping test: No test images found in {}", TEST_IMAGES_DIR);
+        return;
+    }
+
+    let (app, temp_dir) = create_e2e_app().await;
+    let photo_id = upload_and_wait(&app, &images[0]).await;
+
+    let inbox_dir = temp_dir.path().join("inbox");
+    std::fs::copy(&images[0], inbox_dir.join("again.jpg")).unwrap();
+    app.process_inbox().await.unwrap();
+
+    let fails_dir = temp_dir.path().join("fails");
+    assert!(fails_dir.join("again.jpg").exists());
+    let report = std::fs::read_to_string(fails_dir.join("again.jpg.error.txt")).unwrap();
+    assert!(report.contains(&format!("duplicate of photo {}", photo_id)));
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Inbox Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_e2e_inbox_moves_bad_files_and_keeps_going() {
+    let (app, temp_dir) = create_e2e_app().await;
+    let inbox_dir = temp_dir.path().join("inbox");
+    let fails_dir = temp_dir.path().join("fails");
+
+    std::fs::write(inbox_dir.join("notes.txt"), b"not a photo").unwrap();
+    std::fs::write(inbox_dir.join("broken.jpg"), b"\xFF\xD8\xFF\xE0truncated").unwrap();
+    std::fs::write(inbox_dir.join(".partial.jpg"), b"still copying").unwrap();
+    let images = get_test_images();
+    if let Some(image) = images.first() {
+        std::fs::copy(image, inbox_dir.join(image.file_name().unwrap())).unwrap();
+    }
+
+    app.process_inbox()
+        .await
+        .expect("A bad file should not stop the inbox from being processed");
+
+    // Bad files are moved out of the inbox with an error report alongside
+    assert!(fails_dir.join("notes.txt").exists());
+    assert!(fails_dir.join("notes.txt.error.txt").exists());
+    assert!(fails_dir.join("broken.jpg").exists());
+    let report = std::fs::read_to_string(fails_dir.join("broken.jpg.error.txt")).unwrap();
+    assert!(report.contains("error:"));
+
+    // Hidden files are left alone
+    assert!(inbox_dir.join(".partial.jpg").exists());
+
+    // Good photos are imported despite the earlier failures
+    let router = create_test_router_with_extras(app);
+    let (status, json) = get_json(router, "/api/photos").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        json["photos"].as_array().unwrap().len(),
+        images.len().min(1)
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_inbox_does_not_clobber_earlier_failures() {
+    let (app, temp_dir) = create_e2e_app().await;
+    let inbox_dir = temp_dir.path().join("inbox");
+    let fails_dir = temp_dir.path().join("fails");
+
+    std::fs::write(inbox_dir.join("notes.txt"), b"first").unwrap();
+    app.process_inbox().await.unwrap();
+    std::fs::write(inbox_dir.join("notes.txt"), b"second").unwrap();
+    app.process_inbox().await.unwrap();
+
+    assert_eq!(
+        std::fs::read(fails_dir.join("notes.txt")).unwrap(),
+        b"first"
+    );
+    assert_eq!(
+        std::fs::read(fails_dir.join("1-notes.txt")).unwrap(),
+        b"second"
+    );
+    assert!(fails_dir.join("1-notes.txt.error.txt").exists());
+}
+
+#[test]
+fn test_settle_check_waits_for_files_to_stop_changing() {
+    let temp_dir = TempDir::new().unwrap();
+    let copying = temp_dir.path().join("copying.jpg");
+    let done = temp_dir.path().join("done.jpg");
+    std::fs::write(&copying, b"part").unwrap();
+    std::fs::write(&done, b"whole").unwrap();
+    let files = || vec![copying.clone(), done.clone()];
+    let start = tokio::time::Instant::now();
+    let at = |secs| start + Duration::from_secs(secs);
+
+    // Nothing is settled the first time a file is seen, or until it's been still long enough
+    let mut settle = SettleCheck::new(Duration::from_secs(2));
+    assert_eq!(settle.check(files(), at(0)), (vec![], 2));
+    assert_eq!(settle.check(files(), at(1)), (vec![], 2));
+
+    std::fs::write(&copying, b"part and more").unwrap();
+    assert_eq!(settle.check(files(), at(2)), (vec![done.clone()], 1));
+    assert_eq!(settle.check(files(), at(3)), (vec![done.clone()], 1));
+    assert_eq!(
+        settle.check(files(), at(4)),
+        (vec![copying.clone(), done.clone()], 0)
+    );
+
+    // Files that have gone are forgotten, so one coming back starts over
+    std::fs::remove_file(&done).unwrap();
+    assert_eq!(
+        settle.check(vec![copying.clone()], at(5)),
+        (vec![copying.clone()], 0)
+    );
+    std::fs::write(&done, b"whole").unwrap();
+    assert_eq!(settle.check(files(), at(6)), (vec![copying.clone()], 1));
+}
+
+#[test]
+fn test_debounce_waits_for_events_to_stop() {
+    let quiet = Duration::from_secs(2);
+    let start = tokio::time::Instant::now();
+    let mut events = Debounce::new(quiet);
+    assert_eq!(events.deadline(), None);
+    assert!(!events.take_due(start + quiet));
+
+    // Every event pushes the deadline back
+    events.event(start);
+    events.event(start + Duration::from_secs(1));
+    assert_eq!(events.deadline(), Some(start + Duration::from_secs(3)));
+    assert!(!events.take_due(start + quiet));
+
+    assert!(events.take_due(start + Duration::from_secs(3)));
+    assert_eq!(events.deadline(), None);
+    assert!(!events.take_due(start + Duration::from_secs(4)));
+}
+
+#[tokio::test]
+async fn test_e2e_inbox_watcher_imports_files_once_copied() {
+    let (app, temp_dir) = create_e2e_app().await;
+    let inbox_dir = temp_dir.path().join("inbox");
+    let fails_dir = temp_dir.path().join("fails");
+
+    // Already waiting when the watcher starts
+    let early = inbox_dir.join("early.jpg");
+    write_generated_photo(&early, 400, 300, 1);
+    // Mirror part of it so the duplicate check tells them apart
+    let mut mirrored = image::open(&early).unwrap();
+    let strip = mirrored.fliph().crop_imm(0, 0, 100, 300);
+    image::imageops::overlay(&mut mirrored, &strip, 300, 0);
+    let source = temp_dir.path().join("late.jpg");
+    mirrored.save(&source).unwrap();
+    let bytes = std::fs::read(&source).unwrap();
+
+    let watcher = InboxWatcher::new(app.clone(), Duration::from_millis(200))
+        .with_settle_time(Duration::from_millis(500));
+    let watcher = tokio::spawn(watcher.run());
+
+    // A copy that takes a while, which a scan mustn't mistake for a broken photo
+    let late = inbox_dir.join("late.jpg");
+    let mut copied = Vec::new();
+    for chunk in bytes.chunks(bytes.len().div_ceil(30)) {
+        copied.extend_from_slice(chunk);
+        std::fs::write(&late, &copied).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(late.exists(), "picked up mid-copy");
+    }
+
+    let router = create_test_router_with_extras(app);
+    let mut total = 0;
+    for _ in 0..100 {
+        let (_, json) = get_json(router.clone(), "/api/photos").await;
+        total = json["total"].as_u64().unwrap();
+        if total == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    watcher.abort();
+
+    assert_eq!(total, 2);
+    assert_eq!(std::fs::read_dir(&inbox_dir).unwrap().count(), 0);
+    assert!(!fails_dir.exists() || std::fs::read_dir(&fails_dir).unwrap().count() == 0);
+}
+
+#[tokio::test]
+async fn test_e2e_upload_queues_classification() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let classifier = StubClassifier::new(vec![
+        Classification::new("beach", SuggestionKind::Scene, 0.9),
+        Classification::new("person", SuggestionKind::Face, 0.7),
+    ]);
+    let app = App::new(
+        temp_dir.path().to_path_buf(),
+        Arc::new(NativeProcessor::new()),
+    )
+    .await
+    .expect("Failed to create app")
+    .with_classifier(Arc::new(classifier));
+    let app = Arc::new(app);
+    jobs::spawn_workers(app.clone(), 2)
+        .await
+        .expect("Failed to start job workers");
+
+    let source = temp_dir.path().join("generated.jpg");
+    write_generated_photo(&source, 400, 300, 1);
+    let photo_id = upload_and_wait(&app, &source).await;
+
+    // The classify job is queued behind the upload and run by the same workers
+    let (_, jobs) = get_json(create_test_router_with_extras(app.clone()), "/api/jobs").await;
+    let classify = jobs
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|job| job["kind"] == "classify")
+        .expect("Upload should queue a classify job")
+        .clone();
+    assert_eq!(classify["photo"], photo_id);
+    let classify = wait_for_job(&app, &classify).await;
+    assert_eq!(classify["status"], "done", "{:?}", classify);
+
+    let router = create_test_router_with_extras(app.clone());
+    let (status, json) = get_json(router, &format!("/api/suggestions?photo={}", photo_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let labels: Vec<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["label"].as_str().unwrap())
+        .collect();
+    assert_eq!(labels, vec!["beach", "person"]);
+    assert_eq!(json[1]["kind"], "face");
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Trash Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[tokio::test]
+async fn test_e2e_trash_keeps_files_until_purged() {
+    let (app, temp_dir) = create_e2e_app().await;
+    let source = temp_dir.path().join("generated.jpg");
+    write_generated_photo(&source, 400, 300, 1);
+    let photo_id = upload_and_wait(&app, &source).await;
+    let (websize, thumbnail) = rendition_paths(&app, photo_id).await;
+
+    let router = create_test_router_with_extras(app.clone());
+    let (status, _) = delete(router, &format!("/api/photos/{}", photo_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(websize.exists() && thumbnail.exists());
+
+    // The trash view still shows the thumbnail
+    let router = create_test_router_with_extras(app.clone());
+    let (status, _) = get_bytes(
+        router,
+        &format!("/api/images/{}?variant=thumbnail", photo_id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Uploading it again says where the existing copy is
+    let router = create_test_router_with_extras(app.clone());
+    let (status, json) = upload_photo_file(router, &source).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(
+        json["error"].as_str().unwrap().contains("in the trash"),
+        "{:?}",
+        json
+    );
+
+    set_deleted_days_ago(&app, photo_id, DEFAULT_TRASH_RETENTION_DAYS + 1).await;
+    assert_eq!(app.purge_expired_trash().await.unwrap(), 1);
+    assert!(!websize.exists() && !thumbnail.exists());
+    assert!(!websize.parent().unwrap().exists());
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Backup Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Fill an app with two photos in an album, one of them in the trash.
+/// Returns the IDs of the photo in the library, the trashed photo and the album.
+async fn populate_for_backup(app: &Arc<App>, temp_dir: &TempDir) -> (i32, i32, i32) {
+    let first = temp_dir.path().join("generated.jpg");
+    write_generated_photo(&first, 400, 300, 1);
+    // Generated photos all look alike to the duplicate check, unless part of one is mirrored
+    let second = temp_dir.path().join("generated-mirrored.jpg");
+    let mut mirrored = image::open(&first).unwrap();
+    let strip = mirrored.fliph().crop_imm(0, 0, 100, 300);
+    image::imageops::overlay(&mut mirrored, &strip, 300, 0);
+    mirrored.save(&second).unwrap();
+
+    let mut photo_ids = vec![];
+    for source in [&first, &second] {
+        photo_ids.push(upload_and_wait(app, source).await);
+    }
+
+    let router = create_test_router_with_extras(app.clone());
+    let (status, json) = post_json(
+        router,
+        "/api/albums",
+        &CreateAlbumRequest {
+            name: "Holidays".to_string(),
+            notes: None,
+            virtual_album: None,
+            smart_rule: None,
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let album_id = json["id"].as_i64().unwrap() as i32;
+    for photo_id in &photo_ids {
+        let router = create_test_router_with_extras(app.clone());
+        let (status, _) = post_empty(
+            router,
+            &format!("/api/albums/{}/photos/{}", album_id, photo_id),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let router = create_test_router_with_extras(app.clone());
+    let (status, _) = delete(router, &format!("/api/photos/{}", photo_ids[1])).await;
+    assert_eq!(status, StatusCode::OK);
+
+    (photo_ids[0], photo_ids[1], album_id)
+}
+
+/// Download a backup and save it next to the data dir
+async fn download_backup(app: &Arc<App>, uri: &str, dest_dir: &TempDir) -> PathBuf {
+    let router = create_test_router_with_extras(app.clone());
+    let request = Request::builder()
+        .method("GET")
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/x-tar");
+    let disposition = response.headers()["content-disposition"].to_str().unwrap();
+    assert!(
+        disposition.starts_with("attachment; filename=\"pictureframe-backup-"),
+        "{}",
+        disposition
+    );
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let archive = dest_dir.path().join("backup.tar");
+    std::fs::write(&archive, &body).unwrap();
+    archive
+}
+
+/// Open the library in `data_dir`, as the server would after a restore
+async fn open_restored(data_dir: &TempDir) -> Arc<App> {
+    let app = App::new(
+        data_dir.path().to_path_buf(),
+        Arc::new(NativeProcessor::new()),
+    )
+    .await
+    .expect("Failed to open restored library");
+    Arc::new(app)
+}
+
+/// Write an archive holding only a manifest, as a backup from another version might
+fn write_archive_with_manifest(dest: &std::path::Path, manifest: &Value) {
+    let manifest = serde_json::to_vec(manifest).unwrap();
+    let mut builder = tar::Builder::new(std::fs::File::create(dest).unwrap());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, "manifest.json", manifest.as_slice())
+        .unwrap();
+    builder.finish().unwrap();
+}
+
+#[tokio::test]
+async fn test_e2e_backup_and_restore() {
+    let (app, temp_dir) = create_e2e_app().await;
+    let (photo_id, trashed_id, album_id) = populate_for_backup(&app, &temp_dir).await;
+
+    let downloads = TempDir::new().unwrap();
+    let archive = download_backup(&app, "/api/backup", &downloads).await;
+
+    let restored_dir = TempDir::new().unwrap();
+    let manifest = backup::restore(&archive, restored_dir.path(), false)
+        .await
+        .unwrap();
+    assert_eq!(manifest.photos, 2);
+    assert_eq!(manifest.schema_version, backup::schema_version());
+    assert!(!manifest.originals_only);
+
+    let restored = open_restored(&restored_dir).await;
+    // Every rendition came along, so there's nothing to rebuild
+    assert_eq!(
+        restored.regenerate_missing_renditions().await.unwrap(),
+        (0, 0)
+    );
+
+    let router = create_test_router_with_extras(restored.clone());
+    let (status, json) = get_json(router, &format!("/api/albums/{}", album_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["name"], "Holidays");
+    assert_eq!(json["photos"].as_array().unwrap().len(), 1);
+
+    let router = create_test_router_with_extras(restored.clone());
+    let (_, json) = get_json(router, "/api/trash").await;
+    assert_eq!(json[0]["photo"]["id"], trashed_id);
+
+    // Files are served from the new data dir, byte for byte what the old one had
+    for variant in ["thumbnail", "web", "full"] {
+        let uri = format!("/api/images/{}?variant={}", photo_id, variant);
+        let (status, original) = get_bytes(create_test_router_with_extras(app.clone()), &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, copy) =
+            get_bytes(create_test_router_with_extras(restored.clone()), &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(original, copy, "{} rendition differs", variant);
+    }
+    let (websize, _) = rendition_paths(&restored, photo_id).await;
+    assert!(websize.starts_with(restored_dir.path()), "{:?}", websize);
+
+    // Restoring the trash means it can still be emptied
+    let router = create_test_router_with_extras(restored.clone());
+    let (status, _) = delete(router, "/api/trash").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_e2e_backup_of_originals_rebuilds_renditions_on_restore() {
+    let (app, temp_dir) = create_e2e_app().await;
+    let (photo_id, _, _) = populate_for_backup(&app, &temp_dir).await;
+
+    let downloads = TempDir::new().unwrap();
+    let archive = download_backup(&app, "/api/backup?originals_only=true", &downloads).await;
+
+    let restored_dir = TempDir::new().unwrap();
+    let manifest = backup::restore(&archive, restored_dir.path(), false)
+        .await
+        .unwrap();
+    assert!(manifest.originals_only);
+
+    let restored = open_restored(&restored_dir).await;
+    let (websize, thumbnail) = rendition_paths(&restored, photo_id).await;
+    assert!(!websize.exists() && !thumbnail.exists());
+
+    assert_eq!(
+        restored.regenerate_missing_renditions().await.unwrap(),
+        (2, 0)
+    );
+    assert!(websize.exists() && thumbnail.exists());
+    let router = create_test_router_with_extras(restored.clone());
+    let (status, bytes) = get_bytes(
+        router,
+        &format!("/api/images/{}?variant=thumbnail", photo_id),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let thumb = image::load_from_memory(&bytes).unwrap();
+    assert!(thumb.width() <= THUMBNAIL_SIZE && thumb.height() <= THUMBNAIL_SIZE);
+}
+
+#[tokio::test]
+async fn test_e2e_restore_refuses_to_replace_a_library_unless_asked() {
+    let (app, temp_dir) = create_e2e_app().await;
+    let (photo_id, _, _) = populate_for_backup(&app, &temp_dir).await;
+    let downloads = TempDir::new().unwrap();
+    let archive = download_backup(&app, "/api/backup", &downloads).await;
+
+    // A library that's been used since
+    let (other, other_dir) = create_e2e_app().await;
+    let source = other_dir.path().join("other.jpg");
+    write_generated_photo(&source, 400, 300, 5);
+    upload_and_wait(&other, &source).await;
+    drop(other);
+
+    let err = backup::restore(&archive, other_dir.path(), false)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("already has a library"), "{err:#}");
+
+    backup::restore(&archive, other_dir.path(), true)
+        .await
+        .unwrap();
+    let restored = open_restored(&other_dir).await;
+    let router = create_test_router_with_extras(restored);
+    let (_, json) = get_json(router, "/api/photos").await;
+    let ids: Vec<i64> = json["photos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![photo_id as i64]);
+}
+
+#[tokio::test]
+async fn test_e2e_restore_refuses_other_database_versions() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("future.tar");
+    write_archive_with_manifest(
+        &archive,
+        &serde_json::json!({
+            "format_version": backup::FORMAT_VERSION,
+            "app_version": "99.0.0",
+            "schema_version": backup::schema_version() + 1,
+            "created_at": "2026-01-01T00:00:00",
+            "photos": 0,
+            "originals_only": false,
+        }),
+    );
+
+    let data_dir = TempDir::new().unwrap();
+    let err = backup::restore(&archive, data_dir.path(), false)
+        .await
+        .unwrap_err();
+    let msg = err.to_string();
+    assert!(
+        msg.contains("database version") && msg.contains("99.0.0"),
+        "{msg}"
+    );
+    // Nothing was restored
+    assert_eq!(std::fs::read_dir(data_dir.path()).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_e2e_restore_refuses_truncated_backups() {
+    let (app, temp_dir) = create_e2e_app().await;
+    populate_for_backup(&app, &temp_dir).await;
+    let downloads = TempDir::new().unwrap();
+    let archive = download_backup(&app, "/api/backup", &downloads).await;
+
+    // Cut off at an entry boundary, so only the missing manifest gives it away
+    let bytes = std::fs::read(&archive).unwrap();
+    let mut entries = tar::Archive::new(bytes.as_slice());
+    let manifest_offset = entries
+        .entries()
+        .unwrap()
+        .map(|entry| entry.unwrap())
+        .find(|entry| entry.path().unwrap().to_str() == Some("manifest.json"))
+        .map(|entry| entry.raw_header_position())
+        .unwrap();
+    std::fs::write(&archive, &bytes[..manifest_offset as usize]).unwrap();
+
+    let data_dir = TempDir::new().unwrap();
+    let err = backup::restore(&archive, data_dir.path(), false)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("no manifest"), "{err:#}");
+    assert_eq!(std::fs::read_dir(data_dir.path()).unwrap().count(), 0);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Album Export Tests
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Download an album export. Returns the `Content-Disposition` header and the zip.
+async fn export_album(
+    app: &Arc<App>,
+    album_id: i32,
+) -> (String, zip::ZipArchive<std::io::Cursor<Vec<u8>>>) {
+    let router = create_test_router_with_extras(app.clone());
+    let uri = format!("/api/albums/{}/export", album_id);
+    let request = Request::builder()
+        .method("GET")
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/zip");
+    let disposition = response.headers()["content-disposition"]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let zip = zip::ZipArchive::new(std::io::Cursor::new(body.to_vec()))
+        .expect("Export should be a valid zip");
+    (disposition, zip)
+}
+
+/// Read one file out of a zip
+fn zip_file(zip: &mut zip::ZipArchive<std::io::Cursor<Vec<u8>>>, name: &str) -> Vec<u8> {
+    let mut bytes = vec![];
+    std::io::Read::read_to_end(&mut zip.by_name(name).unwrap(), &mut bytes).unwrap();
+    bytes
+}
+
+#[tokio::test]
+async fn test_e2e_export_album() {
+    let (app, temp_dir) = create_e2e_app().await;
+    let (titled_id, dated_id, album_id) = populate_for_backup(&app, &temp_dir).await;
+
+    // Bring the trashed photo back and move it to the front, dated but without the title it got
+    // from its file name
+    let router = create_test_router_with_extras(app.clone());
+    let (status, _) = post_empty(router, &format!("/api/trash/{}/restore", dated_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let taken = chrono::NaiveDate::from_ymd_opt(2019, 7, 14)
         .unwrap()
-        .contains("Only JPEG files are supported"));
+        .and_hms_opt(16, 30, 0)
+        .unwrap();
+    let router = create_test_router_with_extras(app.clone());
+    let (status, _) = put_json(
+        router,
+        &format!("/api/photos/{}", dated_id),
+        &UpdatePhotoRequest {
+            title: Some(Update::Remove),
+            date_taken: Some(Update::Set(taken)),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let router = create_test_router_with_extras(app.clone());
+    let (status, _) = put_json(
+        router,
+        &format!("/api/photos/{}", titled_id),
+        &UpdatePhotoRequest {
+            title: Some(Update::Set("Beach: at dusk".to_string())),
+            artist: Some(Update::Set("Gran".to_string())),
+            notes: Some(Update::Set("Low tide".to_string())),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let router = create_test_router_with_extras(app.clone());
+    let (status, _) = put_json(
+        router,
+        &format!("/api/albums/{}/order", album_id),
+        &ReorderAlbumRequest {
+            photos: vec![PhotoID(dated_id)],
+        },
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (disposition, mut zip) = export_album(&app, album_id).await;
+    assert!(
+        disposition.contains("filename=\"Holidays.zip\""),
+        "{}",
+        disposition
+    );
+    let mut names: Vec<String> = zip.file_names().map(String::from).collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![
+            "001 - 2019-07-14 16.30.00.jpg",
+            "002 - Beach_ at dusk.jpg",
+            "manifest.json"
+        ]
+    );
+
+    // The originals, untouched
+    let router = create_test_router_with_extras(app.clone());
+    let (_, original) = get_bytes(router, &format!("/api/images/{}?variant=full", titled_id)).await;
+    assert_eq!(zip_file(&mut zip, "002 - Beach_ at dusk.jpg"), original);
+
+    let manifest: Value = serde_json::from_slice(&zip_file(&mut zip, "manifest.json")).unwrap();
+    assert_eq!(manifest["album"], "Holidays");
+    let photos = manifest["photos"].as_array().unwrap();
+    assert_eq!(photos[0]["file"], "001 - 2019-07-14 16.30.00.jpg");
+    assert_eq!(photos[0]["date_taken"], "2019-07-14T16:30:00");
+    assert_eq!(photos[0]["title"], Value::Null);
+    assert_eq!(photos[1]["file"], "002 - Beach_ at dusk.jpg");
+    assert_eq!(photos[1]["title"], "Beach: at dusk");
+    assert_eq!(photos[1]["artist"], "Gran");
+    assert_eq!(photos[1]["copyright"], Value::Null);
+    assert_eq!(photos[1]["notes"], "Low tide");
+}
+
+#[tokio::test]
+async fn test_e2e_export_album_leaves_out_trashed_photos() {
+    let (app, temp_dir) = create_e2e_app().await;
+    let (_, _, album_id) = populate_for_backup(&app, &temp_dir).await;
+
+    let (_, mut zip) = export_album(&app, album_id).await;
+    assert_eq!(zip.len(), 2);
+    let manifest: Value = serde_json::from_slice(&zip_file(&mut zip, "manifest.json")).unwrap();
+    let files: Vec<&str> = manifest["photos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["file"].as_str().unwrap())
+        .collect();
+    assert_eq!(files, vec!["001 - generated.jpg"]);
+
+    let router = create_test_router_with_extras(app.clone());
+    let (status, json) = get_json(router, "/api/albums/999/export").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(json["error"], "Album 999 not found");
 }